@@ -18,6 +18,7 @@ object ConfigToml {
             appendLine("poll_interval = ${cfg.pollIntervalSec}")
             appendLine("verbose = ${cfg.verbose}")
             appendLine("multi_record = \"${cfg.multiRecord}\"")
+            appendLine("allow_create = true")
         }
         file.writeText(content)
         return file
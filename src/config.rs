@@ -2,21 +2,52 @@
 //!
 //! This module handles loading and validating configuration from files and environment variables.
 
+use std::collections::BTreeMap;
 use std::env;
-use std::path::PathBuf;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use anyhow::{Context as _, Result};
 use zeroize::ZeroizeOnDrop;
 
 use crate::constants::{
-    DEFAULT_POLL_INTERVAL_SECS, DEFAULT_TIMEOUT_SECS, ENV_ALLOW_LOOPBACK, ENV_API_TOKEN,
-    ENV_HEALTH_PORT, ENV_MULTI_RECORD, ENV_PROVIDER_TYPE, ENV_RECORD_NAME, ENV_ZONE_ID,
-    MAX_POLL_INTERVAL_SECS, MAX_TIMEOUT_SECS, MAX_ZONE_ID_LENGTH, MIN_API_TOKEN_LENGTH,
-    MIN_POLL_INTERVAL_SECS, MIN_TIMEOUT_SECS, MIN_ZONE_ID_LENGTH,
+    CLOUDFLARE_API_BASE, CLOUDFLARE_USER_AGENT, CLOUDFLARE_ZONE_ID_LENGTH,
+    DEFAULT_ADDRESS_RECORD_LIMIT, DEFAULT_API_TOKEN_CREDENTIAL, DEFAULT_DELETE_COOLDOWN_SECS,
+    DEFAULT_EXTERNAL_CHECK_INTERVAL_SECS, DEFAULT_EXTERNAL_IP_URL, DEFAULT_HOOK_TIMEOUT_SECS,
+    DEFAULT_MAX_CONCURRENT_UPDATES, DEFAULT_MAX_ERROR_BODY_LEN, DEFAULT_MAX_RETRIES,
+    DEFAULT_NETLINK_MAX_DRAIN, DEFAULT_POLL_INTERVAL_SECS, DEFAULT_PROPAGATION_RESOLVER,
+    DEFAULT_SYSLOG_FACILITY, DEFAULT_TIMEOUT_SECS, DEFAULT_VERIFY_DELAY_SECS, ENV_ALLOW_CREATE,
+    ENV_ALLOW_LOOPBACK, ENV_ALLOW_PRIVATE_IPV4, ENV_API_TOKEN, ENV_API_TOKEN_FILE,
+    ENV_CLOUDFLARE_API_TOKEN, ENV_CLOUDFLARE_API_TOKEN_FILE, ENV_CLOUDFLARE_RECORD_NAME,
+    ENV_CLOUDFLARE_ZONE_ID, ENV_CLOUDFLARE_ZONE_ID_FILE, ENV_CREDENTIALS_DIRECTORY,
+    ENV_DELETE_ON_SHUTDOWN, ENV_ENABLE_IPV4, ENV_HEALTH_PORT, ENV_LOG_FORMAT, ENV_MULTI_RECORD,
+    ENV_ON_REMOVAL, ENV_PROVIDER_TYPE, ENV_READ_ONLY, ENV_RECORD_NAME, ENV_RECORD_TYPE,
+    ENV_SECRET_KEY, ENV_SECRET_KEY_FILE, ENV_STRICT_MODE, ENV_ZONE_ID, ENV_ZONE_ID_FILE,
+    MAX_ADDRESS_RECORD_LIMIT, MAX_ERROR_THRESHOLD, MAX_EXTERNAL_CHECK_INTERVAL_SECS,
+    MAX_FORCE_UPDATE_INTERVAL_SECS, MAX_HOOK_TIMEOUT_SECS, MAX_MAX_CONCURRENT_UPDATES,
+    MAX_MAX_ERROR_BODY_LEN, MAX_MAX_RETRIES, MAX_NETLINK_MAX_DRAIN, MAX_POLL_INTERVAL_SECS,
+    MAX_POLL_JITTER, MAX_REMOVAL_GRACE_SECS, MAX_STARTUP_JITTER_SECS, MAX_TIMEOUT_SECS,
+    MAX_VERIFY_DELAY_SECS, MAX_ZONE_ID_LENGTH, MIN_API_TOKEN_LENGTH,
+    MIN_EXTERNAL_CHECK_INTERVAL_SECS, MIN_FORCE_UPDATE_INTERVAL_SECS, MIN_MAX_ERROR_BODY_LEN,
+    MIN_POLL_INTERVAL_SECS, MIN_TIMEOUT_SECS, MIN_ZONE_ID_LENGTH, SYSLOG_FACILITIES,
 };
-use crate::dns_provider::MultiRecordPolicy;
-use crate::validation::validate_record_name;
+use crate::dns_provider::{MultiRecordPolicy, ProviderType, ReconcileFields, RecordType};
+use crate::duckdns::duckdns_subdomain;
+use crate::hooks::{validate_command, validate_hook_command, HookCommand};
+use crate::namecheap::host as namecheap_host;
+use crate::netlink::{
+    parse_address_preference, parse_address_selection, parse_prefix, parse_scope, AddressSelection,
+    RoutingContext, SelectionPolicy, RT_SCOPE_LINK, RT_SCOPE_UNIVERSE,
+};
+use crate::porkbun::subdomain as porkbun_subdomain;
+use crate::resolver::parse_resolver;
+use crate::validation::{to_ascii_name, validate_record_name, Ipv6Filter};
+use crate::vultr::subdomain as vultr_subdomain;
+use crate::webhook::validate_webhook_url;
+
+/// `--config` value that reads the config from standard input
+const STDIN_CONFIG_PATH: &str = "-";
 
 //==============================================================================
 // Config
@@ -33,14 +64,78 @@ use crate::validation::validate_record_name;
 ///
 /// - `api_token`: Cloudflare API token with DNS edit permissions
 /// - `zone_id`: Cloudflare zone ID for the domain
-/// - `record`: DNS record name to update (e.g., "example.com")
+/// - `zone_name`: Cloudflare zone name, resolved to the zone ID at startup
+/// - `strict_zone_id`: Require the zone ID to be 32 hex digits, lowercasing it
+/// - `api_token_credential`: systemd credential name to read the API token from
+/// - `records`: DNS record names to update (e.g., "example.com")
 /// - `timeout`: HTTP request timeout in seconds
+/// - `connect_timeout`: Time allowed to establish a connection, in seconds
+/// - `user_agent_suffix`: Text appended to the User-Agent of API requests
+/// - `http_proxy`, `https_proxy`: Proxies for Cloudflare API requests
+/// - `api_base`: Base URL of the Cloudflare API (or a compatible server)
+/// - `ca_cert_file`: PEM file with extra CA certificates for the API
 /// - `poll_interval`: Polling interval in seconds (fallback when netlink unavailable)
+/// - `poll_jitter`: Random variation of the polling interval, as a fraction
+/// - `netlink_max_drain`: Netlink messages read per wakeup
+/// - `startup_jitter`: Maximum random delay before the initial sync
+/// - `force_update_interval`: Time after which unchanged records are written again
 /// - `verbose`: Enable verbose logging
+/// - `log_format`: Log output format (text or JSON)
+/// - `log_target`: Where logs go (stdout, a file or syslog)
+/// - `log_file`: File to write logs to instead of stdout
+/// - `log_rotation`: When the log file is rotated
+/// - `syslog_facility`: Facility of messages sent to syslog
 /// - `multi_record`: Policy for handling multiple AAAA records
-/// - `allow_loopback`: Allow loopback IPv6 (::1) as a valid address
-/// - `provider_type`: DNS provider type (default: "cloudflare")
+/// - `sticky_record`: Keep updating the same record under `multi_record = "first"`
+/// - `reconcile_fields`: Record fields that trigger an update when they differ
+/// - `stamp_comment`: Set the record comment to the time of the update
+/// - `ipv6_filter`: Reserved IPv6 ranges that may be published (incl. `allow_loopback`)
+/// - `source`: Where the address to publish comes from (netlink, an external service or a command)
+/// - `external_ip_url`: HTTPS service queried with `source = "external"`
+/// - `detect_command`: Command printing the address with `source = "command"`
+/// - `accepted_scopes`: Address scopes that may be published
+/// - `netns`: Network namespace to detect addresses in
+/// - `selection`: How one address is chosen when several are available
+/// - `allow_create`: Allow creating the record when it does not exist yet
+/// - `enable_ipv4`: Also publish the global IPv4 address as an A record
+/// - `allow_private_ipv4`: Accept private and CGNAT IPv4 addresses
+/// - `record_type`: Record types published under each name (`AAAA`, `A` or `both`)
+/// - `required_families`: Address families that must be synced for the daemon to be healthy
+/// - `strict_mode`: Report polling fallback as degraded monitoring
+/// - `read_only`: Detect and report the address without ever calling the provider
+/// - `pause_file`: File whose presence pauses all provider writes
+/// - `lock_file`: File locked at startup so only one instance runs
+/// - `ready_on`: When the daemon reports itself ready (at startup or after the first sync)
+/// - `ready_file`: File created once the daemon is ready
+/// - `ready_timeout`: Time to wait for the first sync before exiting
+/// - `on_removal`: What to do with the record when the address disappears
+/// - `delete_cooldown`: Minimum age of a created record before it may be deleted
+/// - `removal_grace`: Wait after the address disappears before acting on it
+/// - `delete_on_shutdown`: Delete the records when the daemon receives SIGTERM
+/// - `verify_after_update`: Read records back after an update to confirm it took effect
+/// - `verify_delay`: Wait before that read
+/// - `verify_propagation`: Resolve updated AAAA records over DNS to confirm they propagated
+/// - `propagation_resolver`: Resolver those lookups are sent to
+/// - `error_threshold`: Consecutive failed syncs before a record is reported as failing
+/// - `max_retries`: Retries of a Cloudflare request after a transient failure
+/// - `max_error_body_len`: Characters of an API response kept in error messages
+/// - `max_concurrent_updates`: Records synced at once
+/// - `max_consecutive_errors`: Consecutive failed syncs of a record before the daemon exits
+/// - `max_sync_age`: Age of the last sync past which the health check reports stale
+/// - `external_resolver`: Public resolver the published AAAA records are checked against
+/// - `external_check_interval`: Time between those checks
+/// - `address_record_template`: Naming template for publishing each address to its own record
+/// - `address_records_only`: Publish only the per-address records
+/// - `address_record_limit`: Maximum number of per-address records per name
+/// - `heartbeat_txt`: TXT record set to the time of each successful sync
+/// - `provider_type`: DNS provider (default: Cloudflare)
 /// - `health_port`: Port for health check endpoint (0 = disabled)
+/// - `post_update_command`: Command to run after the published address changes
+/// - `hook_shell`: Allow the post-update command to be a shell string
+/// - `hook_timeout`: Time limit for the post-update command
+/// - `webhook_url`: URL to POST a JSON notification to after the address changes
+/// - `metrics_textfile`: File the metrics are written to for the node_exporter textfile collector
+/// - `state_file`: File the synced records are kept in across restarts
 ///
 /// # Configuration Loading Priority
 ///
@@ -53,56 +148,505 @@ pub struct Config {
     /// Cloudflare API token with DNS edit permissions
     ///
     /// This token should have the `Zone:DNS:Edit` permission.
-    /// It can be set via the `CLOUDFLARE_API_TOKEN` environment variable.
+    /// It can be set via the `IPV6DDNS_API_TOKEN` (or legacy `CLOUDFLARE_API_TOKEN`)
+    /// environment variable, or read from the file named by `api_token_file` /
+    /// `IPV6DDNS_API_TOKEN_FILE` (alias `CLOUDFLARE_API_TOKEN_FILE`). If none of
+    /// these are set, it is read from the systemd credential named by
+    /// `api_token_credential`.
     #[zeroize(skip)]
     pub api_token: zeroize::Zeroizing<String>,
     /// Cloudflare zone ID for the domain
     ///
     /// The zone ID can be found in the Cloudflare dashboard under your domain's DNS settings.
-    /// It can be set via the `CLOUDFLARE_ZONE_ID` environment variable.
+    /// It can be set via the `IPV6DDNS_ZONE_ID` (or legacy `CLOUDFLARE_ZONE_ID`)
+    /// environment variable, or read from the file named by `zone_id_file` /
+    /// `IPV6DDNS_ZONE_ID_FILE` (alias `CLOUDFLARE_ZONE_ID_FILE`).
     #[zeroize(skip)]
     pub zone_id: zeroize::Zeroizing<String>,
-    /// DNS record name to update (e.g., "example.com")
+    /// Cloudflare zone (domain) name to look the zone ID up by, instead of `zone_id`
+    ///
+    /// Default: None
+    /// Resolved once at startup with `GET /zones?name=...`, which needs the
+    /// Zone - Zone - Read permission; the ID found is then stored in `zone_id`
+    /// for the rest of the run. Only one of `zone_id` and `zone_name` may be set.
+    #[zeroize(skip)]
+    pub zone_name: Option<String>,
+    /// Require a Cloudflare zone ID of exactly 32 hexadecimal digits
+    ///
+    /// Default: false (any alphanumeric ID of plausible length)
+    /// Catches copy-paste mistakes the lenient check lets through, such as a
+    /// truncated ID or one with a stray letter. Uppercase hex digits are
+    /// accepted and converted to lowercase.
+    #[zeroize(skip)]
+    pub strict_zone_id: bool,
+    /// Secret key for providers that use a key pair (Porkbun's secret API key)
+    ///
+    /// It can be set via the `IPV6DDNS_SECRET_KEY` environment variable, or read
+    /// from the file named by `secret_key_file` / `IPV6DDNS_SECRET_KEY_FILE`.
+    #[zeroize(skip)]
+    pub secret_key: zeroize::Zeroizing<String>,
+    /// systemd credential name the API token is read from
     ///
-    /// This is the full DNS record name including subdomain if applicable.
-    /// It can be set via the `CLOUDFLARE_RECORD_NAME` environment variable.
+    /// Default: "cloudflare_token"
+    /// Used only when no token is configured otherwise: the token is then read
+    /// from `$CREDENTIALS_DIRECTORY/<name>` if that file exists, which is where
+    /// systemd places files passed with `LoadCredential=`.
     #[zeroize(skip)]
-    pub record: String,
+    pub api_token_credential: String,
+    /// DNS record names to update (e.g., "example.com")
+    ///
+    /// Each entry is the full DNS record name including subdomain if applicable.
+    /// `record_name` accepts a string or an array in TOML. It can be set via the
+    /// `IPV6DDNS_RECORD` (or legacy `CLOUDFLARE_RECORD_NAME`) environment variable
+    /// as a comma-separated list.
+    #[zeroize(skip)]
+    pub records: Vec<String>,
     /// HTTP request timeout in seconds
     ///
     /// Default: 30 seconds
     #[zeroize(skip)]
     pub timeout: Duration,
+    /// Time allowed to establish a connection, in seconds
+    ///
+    /// Default: None (the same as `timeout`; 1-300, at most `timeout`)
+    /// A short value gives up quickly on a link that drops connection attempts,
+    /// while `timeout` still bounds the whole request. See `connect_timeout()`.
+    #[zeroize(skip)]
+    pub connect_timeout: Option<Duration>,
+    /// Text appended to the User-Agent header of API requests
+    ///
+    /// Default: none (the header is just `ipv6ddns/1.0`)
+    /// Sent as `ipv6ddns/1.0 <suffix>`, e.g. to tell hosts apart in a
+    /// provider's audit log. Control characters are rejected.
+    #[zeroize(skip)]
+    pub user_agent_suffix: Option<String>,
+    /// Proxy for plain HTTP requests to the Cloudflare API
+    ///
+    /// Default: none, falling back to the `http_proxy` / `HTTP_PROXY`
+    /// environment variable
+    #[zeroize(skip)]
+    pub http_proxy: Option<String>,
+    /// Proxy for HTTPS requests to the Cloudflare API
+    ///
+    /// Default: none, falling back to the `https_proxy` / `HTTPS_PROXY`
+    /// environment variable
+    /// Hosts listed in `no_proxy` / `NO_PROXY` bypass either proxy. A
+    /// malformed URL is an error when the client is created.
+    #[zeroize(skip)]
+    pub https_proxy: Option<String>,
+    /// Base URL of the Cloudflare API
+    ///
+    /// Default: `https://api.cloudflare.com/client/v4`
+    /// Points the client at an API-compatible server instead, such as a
+    /// gateway in front of Cloudflare or a test server.
+    #[zeroize(skip)]
+    pub api_base: String,
+    /// PEM file with CA certificates to trust for the API, in addition to the
+    /// built-in roots
+    ///
+    /// Default: none
+    #[zeroize(skip)]
+    pub ca_cert_file: Option<PathBuf>,
     /// Polling interval in seconds (fallback when netlink unavailable)
     ///
     /// Default: 60 seconds
     /// This is only used when netlink socket creation fails.
     #[zeroize(skip)]
     pub poll_interval: Duration,
+    /// Random variation of the polling interval, as a fraction of it
+    ///
+    /// Default: 0.0 (poll exactly every `poll_interval`)
+    /// Each poll sleeps `poll_interval ± poll_interval * poll_jitter`, so a fleet
+    /// restarted together doesn't poll in lockstep. Must be between 0.0 and 0.5.
+    #[zeroize(skip)]
+    pub poll_jitter: f64,
+    /// Maximum number of netlink messages read per wakeup
+    ///
+    /// Default: 16
+    /// During a burst of address changes, all queued messages up to this limit
+    /// are read and parsed together. Must be between 1 and 1024.
+    #[zeroize(skip)]
+    pub netlink_max_drain: usize,
+    /// Maximum random delay before the initial sync
+    ///
+    /// Default: 0 (sync immediately)
+    /// Set in TOML as `startup_jitter_secs`. The daemon sleeps a random duration
+    /// in `[0, startup_jitter]` before its first sync, so a fleet started at the
+    /// same time doesn't hit the API all at once. SIGHUP resyncs are not delayed.
+    #[zeroize(skip)]
+    pub startup_jitter: Duration,
+    /// Time after the last write at which the records are written again, even
+    /// if the address hasn't changed
+    ///
+    /// Default: 0 (never)
+    /// A keepalive for providers that age out records. Every write restarts
+    /// the countdown, so the records are only rewritten after this long
+    /// without an address change.
+    #[zeroize(skip)]
+    pub force_update_interval: Duration,
     /// Enable verbose logging
     ///
     /// Default: false
     #[zeroize(skip)]
     pub verbose: bool,
-    /// Policy for handling multiple AAAA records
+    /// Log output format
+    ///
+    /// Default: text
+    /// `json` writes one JSON object per event to stdout, with structured fields
+    /// such as `record` and `sync_state` as keys. Can be set via the
+    /// `IPV6DDNS_LOG_FORMAT` environment variable.
+    #[zeroize(skip)]
+    pub log_format: LogFormat,
+    /// Where logs go
     ///
-    /// Default: `MultiRecordPolicy::Error`
+    /// Default: `LogTarget::File` when `log_file` is set, else `LogTarget::Stdout`
+    /// `LogTarget::Syslog` requires the `syslog` cargo feature.
+    #[zeroize(skip)]
+    pub log_target: LogTarget,
+    /// File to write logs to instead of stdout
+    ///
+    /// Default: None (stdout)
+    /// The file is appended to, and its directory created if needed. Failing
+    /// to open it stops the daemon at startup.
+    #[zeroize(skip)]
+    pub log_file: Option<PathBuf>,
+    /// When the log file is rotated
+    ///
+    /// Default: `LogRotation::Never`
+    /// With `LogRotation::Daily`, the date is appended to the file name
+    /// (`ipv6ddns.log.2026-01-19`) and a new file is started each day (UTC).
+    #[zeroize(skip)]
+    pub log_rotation: LogRotation,
+    /// Facility of messages sent to syslog
+    ///
+    /// Default: `daemon`
+    /// One of `SYSLOG_FACILITIES`, e.g. `local3` to route them to a separate file.
+    #[zeroize(skip)]
+    pub syslog_facility: String,
+    /// Policy for handling multiple AAAA records, if set
+    ///
+    /// Default depends on the provider, see `multi_record()`.
     /// Can be set via the `CLOUDFLARE_MULTI_RECORD` environment variable.
     #[zeroize(skip)]
-    pub multi_record: MultiRecordPolicy,
-    /// Allow loopback IPv6 address (::1) to be used for DDNS updates
+    pub multi_record: Option<MultiRecordPolicy>,
+    /// Keep updating the record picked first under `MultiRecordPolicy::UpdateFirst`
     ///
     /// Default: false
-    /// Can be set via the `IPV6DDNS_ALLOW_LOOPBACK` environment variable.
+    /// Which record is "first" depends on API ordering. With this set, the
+    /// record ID picked on the first sync is reused until that record disappears.
     #[zeroize(skip)]
-    pub allow_loopback: bool,
+    pub sticky_record: bool,
+    /// Record fields that trigger an update when they differ
+    ///
+    /// Default: content only
+    /// Set in TOML as a list of `content`, `ttl`, `proxied`, `comment`; content
+    /// is always compared, the listed fields are added to it.
+    #[zeroize(skip)]
+    pub reconcile_fields: ReconcileFields,
+    /// Set the record comment to "updated by ipv6ddns at <time>" on every write
+    ///
+    /// Default: false (Cloudflare only). The comment is not compared, so a
+    /// record is not rewritten just to refresh the timestamp.
+    pub stamp_comment: bool,
+    /// Reserved IPv6 ranges that may be published
+    ///
+    /// Default: global and unique-local addresses only
+    /// Set with the `allow_loopback`, `allow_link_local`, `allow_unique_local`
+    /// and `allow_documentation` keys, plus `exclude_prefixes`, a list of
+    /// prefixes never to publish. `allow_loopback` can also be set via the
+    /// `IPV6DDNS_ALLOW_LOOPBACK` environment variable.
+    #[zeroize(skip)]
+    pub ipv6_filter: Ipv6Filter,
+    /// Where the address to publish comes from
+    ///
+    /// Default: `AddressSource::Netlink`
+    /// `external` asks `external_ip_url` every `poll_interval` instead, for
+    /// hosts whose public address is not on any interface (e.g. NPTv6).
+    /// `command` runs `detect_command` every `poll_interval`.
+    #[zeroize(skip)]
+    pub source: AddressSource,
+    /// Service answering with the caller's IPv6 address, for `source = "external"`
+    ///
+    /// Default: `https://api6.ipify.org`
+    #[zeroize(skip)]
+    pub external_ip_url: String,
+    /// Command printing the IPv6 address to publish, for `source = "command"`
+    ///
+    /// Default: None
+    /// An argument list run directly, or (with `hook_shell`) a string run
+    /// through `/bin/sh -c`. Its trimmed stdout must be a single publishable
+    /// address; a failed or timed out (`timeout`) run, or any other output,
+    /// counts as no address.
+    #[zeroize(skip)]
+    pub detect_command: Option<HookCommand>,
+    /// Address scopes (`RT_SCOPE_*` values) that may be published
+    ///
+    /// Default: universe only
+    /// Set in TOML as a list of scope names (`universe`, `site`).
+    #[zeroize(skip)]
+    pub accepted_scopes: Vec<u8>,
+    /// Network namespace whose addresses are detected
+    ///
+    /// Default: None (the daemon's own namespace)
+    /// A name under `/run/netns` (as created by `ip netns add`) or a path such
+    /// as `/proc/<pid>/ns/net`. Only the netlink sockets are opened there; API
+    /// requests still use the daemon's own namespace. Requires CAP_SYS_ADMIN.
+    #[zeroize(skip)]
+    pub netns: Option<String>,
+    /// Weighted scoring used to choose one address when several are available
+    ///
+    /// Default: stable over temporary, then longest preferred lifetime
+    /// Set in TOML with `address_selection`, `address_preference`, the `selection_*` keys, the
+    /// `prefix_regions` / `allowed_regions` allowlist, and `route_table` or
+    /// `route_fwmark`.
+    #[zeroize(skip)]
+    pub selection: SelectionPolicy,
+    /// Allow creating the DNS record when none exists
+    ///
+    /// Default: false
+    /// When disabled, the daemon only updates existing records and refuses to
+    /// create a new one, so a typo in the record name can't add junk to the zone.
+    /// Can be set via the `IPV6DDNS_ALLOW_CREATE` environment variable or the
+    /// `--allow-create` command-line flag.
+    #[zeroize(skip)]
+    pub allow_create: bool,
+    /// Also publish the global IPv4 address as an A record under each name
+    ///
+    /// Default: false
+    /// Can be set via the `IPV6DDNS_ENABLE_IPV4` environment variable.
+    #[zeroize(skip)]
+    pub enable_ipv4: bool,
+    /// Publish the IPv6 address as an AAAA record under each name
+    ///
+    /// Default: true
+    /// Only turned off by `record_type = "A"`, which publishes IPv4 alone.
+    #[zeroize(skip)]
+    pub enable_ipv6: bool,
+    /// Accept private (RFC 1918) and carrier-grade NAT (100.64.0.0/10) IPv4 addresses
+    ///
+    /// Default: false
+    /// Can be set via the `IPV6DDNS_ALLOW_PRIVATE_IPV4` environment variable.
+    #[zeroize(skip)]
+    pub allow_private_ipv4: bool,
+    /// Address families whose records must be synced for the health check to
+    /// report healthy
+    ///
+    /// Default: None (every configured family: IPv6, plus IPv4 with `enable_ipv4`)
+    /// Set in TOML as a list of `ipv6` / `ipv4`, e.g. `["ipv6"]` so a host
+    /// without IPv4 connectivity isn't reported unhealthy.
+    #[zeroize(skip)]
+    pub required_families: Option<Vec<RecordType>>,
+    /// Report the polling fallback as degraded monitoring
+    ///
+    /// Default: false
+    /// When enabled and netlink is unavailable at startup, the health endpoint
+    /// reports `monitoring_degraded: true` and a `degraded` status.
+    /// Can be set via the `IPV6DDNS_STRICT_MODE` environment variable or the
+    /// `--strict-mode` command-line flag.
+    #[zeroize(skip)]
+    pub strict_mode: bool,
+    /// Monitor only: detect the address and serve health/metrics, but never
+    /// call the DNS provider
+    ///
+    /// Default: false
+    /// Meant for standby hosts in a setup with a single active writer. The API
+    /// token and zone ID are not required.
+    /// Can be set via the `IPV6DDNS_READ_ONLY` environment variable or the
+    /// `--read-only` command-line flag.
+    #[zeroize(skip)]
+    pub read_only: bool,
+    /// File whose presence pauses all writes to the DNS provider
+    ///
+    /// Default: None (never paused)
+    /// While the file exists, addresses are still detected and reported in
+    /// the health check, but records are neither updated nor deleted. The
+    /// current address is published once the file is removed.
+    #[zeroize(skip)]
+    pub pause_file: Option<PathBuf>,
+    /// File locked with `flock` at startup to keep a second instance from running
+    ///
+    /// Default: None (no guard)
+    /// The daemon exits with an error if another process holds the lock, and
+    /// writes its PID into the file while it runs.
+    #[zeroize(skip)]
+    pub lock_file: Option<PathBuf>,
+    /// When the daemon reports itself ready to systemd and through `ready_file`
+    ///
+    /// Default: `ReadyPolicy::Start`
+    /// With `ReadyPolicy::FirstSync`, readiness waits until every required
+    /// record has been synced once, so dependent services start only after
+    /// DNS points at this host.
+    #[zeroize(skip)]
+    pub ready_on: ReadyPolicy,
+    /// File created once the daemon is ready, and removed when it exits
+    ///
+    /// Default: None
+    #[zeroize(skip)]
+    pub ready_file: Option<PathBuf>,
+    /// Time to wait for the first sync with `ReadyPolicy::FirstSync` before
+    /// exiting with an error
+    ///
+    /// Default: None (wait forever)
+    #[zeroize(skip)]
+    pub ready_timeout: Option<Duration>,
+    /// What to do with the DNS record when the IPv6 address disappears
+    ///
+    /// Default: `RemovalPolicy::Keep`
+    /// Can be set via the `IPV6DDNS_ON_REMOVAL` environment variable.
+    #[zeroize(skip)]
+    pub on_removal: RemovalPolicy,
+    /// Minimum age of a record created by the daemon before it may be deleted
+    ///
+    /// Default: 60 seconds
+    /// Deletions requested earlier are deferred until the cooldown has passed,
+    /// so an address flapping during boot doesn't create and delete the record repeatedly.
+    #[zeroize(skip)]
+    pub delete_cooldown: Duration,
+    /// Wait after the IPv6 address disappears before acting on the removal
+    ///
+    /// Default: 0 (act at once)
+    /// Set in TOML as `removal_grace_secs` (0-3600). When the wait is over,
+    /// the addresses are checked again and the records are only deleted if
+    /// none remains; a new address appearing meanwhile cancels the removal.
+    /// Only matters with `on_removal = "delete"`.
+    #[zeroize(skip)]
+    pub removal_grace: Duration,
+    /// Delete the records when the daemon is stopped with SIGTERM
+    ///
+    /// Default: false
+    /// Keeps stale records from lingering while the host is offline. Deletion
+    /// is bounded by a short timeout so it can't block shutdown.
+    /// Can be set via the `IPV6DDNS_DELETE_ON_SHUTDOWN` environment variable.
+    #[zeroize(skip)]
+    pub delete_on_shutdown: bool,
+    /// Read each record back after an update to confirm the address was stored
+    ///
+    /// Default: false
+    /// A mismatch is logged and counted in the metrics; it costs one extra API
+    /// read per update. Not available with DuckDNS.
+    #[zeroize(skip)]
+    pub verify_after_update: bool,
+    /// Wait between an update and its verification read
+    ///
+    /// Default: 5 seconds
+    /// Set in TOML as `verify_delay_secs` (1-300).
+    #[zeroize(skip)]
+    pub verify_delay: Duration,
+    /// Resolve each updated AAAA record over DNS until it has the new address
+    ///
+    /// Default: false
+    /// Up to 5 lookups 30 seconds apart are sent to `propagation_resolver`; if
+    /// none returns the new address, a warning is logged. The sync still
+    /// counts as successful, since resolvers may serve the old address until
+    /// its TTL expires.
+    #[zeroize(skip)]
+    pub verify_propagation: bool,
+    /// Resolver the `verify_propagation` lookups are sent to
+    ///
+    /// Default: 1.1.1.1 (port 53)
+    /// An IP address with an optional port. One of the zone's authoritative
+    /// nameservers sees an update first, without caching delays.
+    #[zeroize(skip)]
+    pub propagation_resolver: SocketAddr,
+    /// Consecutive failed syncs before a record is reported as failing
+    ///
+    /// Default: 1 (1-100)
+    /// Retries and backoff start with the first failure either way; below the
+    /// threshold the health check keeps reporting the last good state.
+    #[zeroize(skip)]
+    pub error_threshold: u64,
+    /// Retries of a Cloudflare API request after a 5xx answer or network error
+    ///
+    /// Default: 2 (0-10)
+    /// Retries happen within a sync, after a short jittered delay; 4xx answers
+    /// are never retried. Other providers ignore this.
+    #[zeroize(skip)]
+    pub max_retries: u32,
+    /// Characters of a Cloudflare response body kept in error messages
+    ///
+    /// Default: 512 (64-65536)
+    /// Longer bodies are cut after secrets are redacted and end with
+    /// `…(truncated)`. Other providers ignore this.
+    #[zeroize(skip)]
+    pub max_error_body_len: usize,
+    /// Records synced with the provider at once
+    ///
+    /// Default: 1 (one after another, in config order; 1-16)
+    /// Raising it speeds up setups with many `record_name`s at the cost of
+    /// more simultaneous API requests, which may hit provider rate limits.
+    #[zeroize(skip)]
+    pub max_concurrent_updates: usize,
+    /// Consecutive failed syncs of any record before the daemon exits with an error
+    ///
+    /// Default: 0 (never exit)
+    /// Lets a service manager notice a daemon that can't recover, e.g. after
+    /// the token was revoked. A record in the `misconfigured` state (rejected
+    /// credentials) is never retried, so it counts as having reached the limit.
+    #[zeroize(skip)]
+    pub max_consecutive_errors: u64,
+    /// Age of the oldest record sync past which the health check fails
+    ///
+    /// Default: None (no limit)
+    /// The health check then reports `healthy: false` and status `stale`, even
+    /// if that sync succeeded. The provider is only contacted when the address
+    /// changes, so on a host with a stable address the age grows until the
+    /// next change or restart.
+    #[zeroize(skip)]
+    pub max_sync_age: Option<Duration>,
+    /// Public resolver to check the published AAAA records against
+    ///
+    /// Default: None (no check)
+    /// An IP address with an optional port (default 53), e.g. `1.1.1.1`.
+    /// Catches records that resolve differently in practice than at the
+    /// provider's API: proxied records, split-horizon DNS or stuck caches.
+    #[zeroize(skip)]
+    pub external_resolver: Option<SocketAddr>,
+    /// Time between checks against `external_resolver`
+    ///
+    /// Default: 3600 seconds
+    /// Set in TOML as `external_check_interval_secs` (60-86400).
+    #[zeroize(skip)]
+    pub external_check_interval: Duration,
+    /// Naming template for publishing every detected address to its own record
+    ///
+    /// Default: None (only the selected address is published)
+    /// `{label}` is replaced by the first label of each record name and `{n}` by
+    /// the address number, so `"{label}-{n}"` turns `host.example.com` into
+    /// `host-1.example.com`, `host-2.example.com`, ...
+    #[zeroize(skip)]
+    pub address_record_template: Option<String>,
+    /// Publish only the per-address records, not the configured names
+    ///
+    /// Default: false
+    #[zeroize(skip)]
+    pub address_records_only: bool,
+    /// Maximum number of per-address records per record name
+    ///
+    /// Default: 4 (1-32)
+    #[zeroize(skip)]
+    pub address_record_limit: usize,
+    /// TXT record set to the current time after each successful sync
+    ///
+    /// Default: None
+    /// Lets external monitors see through DNS alone that the daemon is alive.
+    /// The record is created if missing. Only supported with Cloudflare.
+    #[zeroize(skip)]
+    pub heartbeat_txt: Option<String>,
     /// DNS provider type
     ///
-    /// Default: "cloudflare"
+    /// Default: Cloudflare
     /// Can be set via the `IPV6DDNS_PROVIDER_TYPE` environment variable.
-    /// Currently supported: "cloudflare"
+    /// Supported: "cloudflare", "duckdns", "route53", "porkbun", "namecheap",
+    /// "vultr".
+    /// DuckDNS needs no zone ID; the api_token is the DuckDNS token and each
+    /// record_name a DuckDNS domain.
+    /// Route 53 takes the hosted zone ID and reads credentials from the standard
+    /// `AWS_*` environment variables instead of api_token. Porkbun takes the
+    /// domain as zone ID, the API key as api_token, and needs `secret_key`.
+    /// Namecheap takes the domain as zone ID and its dynamic DNS password as
+    /// api_token. Vultr takes the domain as zone ID and the API key as api_token.
     #[zeroize(skip)]
-    pub provider_type: String,
+    pub provider_type: ProviderType,
     /// Port for health check endpoint
     ///
     /// Default: 0 (disabled)
@@ -110,6 +654,98 @@ pub struct Config {
     /// Set to 0 to disable the health check endpoint.
     #[zeroize(skip)]
     pub health_port: u16,
+    /// Command to run after a sync changes the published address
+    ///
+    /// Default: None (disabled)
+    /// Either an argument list run directly, or (with `hook_shell`) a string run
+    /// through `/bin/sh -c`. It runs without blocking the daemon and receives the
+    /// new address, record name, and previous address in the `IPV6DDNS_IP`,
+    /// `IPV6DDNS_RECORD`, and `IPV6DDNS_OLD_IP` environment variables.
+    /// Also accepted as `on_change_command` in TOML.
+    #[zeroize(skip)]
+    pub post_update_command: Option<HookCommand>,
+    /// Allow `post_update_command` and `detect_command` to be shell strings
+    ///
+    /// Default: false
+    /// Shell strings are rejected unless this is set, so hooks don't go through
+    /// a shell by accident.
+    #[zeroize(skip)]
+    pub hook_shell: bool,
+    /// Time limit for the post-update command
+    ///
+    /// Default: 30 seconds
+    /// A command still running after this long is killed. Must be between 1
+    /// and 3600 seconds.
+    #[zeroize(skip)]
+    pub hook_timeout: Duration,
+    /// Webhook URL notified after a sync changes the published address
+    ///
+    /// Default: None (disabled)
+    /// The daemon POSTs `{ "record", "old_ip", "new_ip", "timestamp" }` as JSON,
+    /// using the configured `timeout`. Failures are logged and never affect sync state.
+    #[zeroize(skip)]
+    pub webhook_url: Option<String>,
+    /// File the metrics are written to for the node_exporter textfile collector
+    ///
+    /// Default: None (disabled)
+    /// Rewritten atomically after every sync of the AAAA records and when the
+    /// daemon exits, so the outcome of the last run can be scraped even when
+    /// nothing serves `/metrics`.
+    #[zeroize(skip)]
+    pub metrics_textfile: Option<PathBuf>,
+    /// File the address and ID of every synced record are kept in across restarts
+    ///
+    /// Default: None (disabled)
+    /// Read at startup so records still holding the detected address aren't
+    /// written again; rewritten atomically whenever a record's state changes.
+    /// A missing or corrupt file is ignored with a warning.
+    #[zeroize(skip)]
+    pub state_file: Option<PathBuf>,
+}
+
+/// Command-line overrides for configuration values
+///
+/// These take precedence over both environment variables and the config file.
+/// The API token is deliberately not available as a flag, since command lines
+/// are visible to other users through `ps`.
+#[derive(Debug, Default, clap::Args)]
+pub struct CliOverrides {
+    /// DNS record name(s) to update, comma-separated
+    #[arg(long, value_name = "NAME")]
+    pub record: Option<String>,
+    /// Zone ID of the domain
+    #[arg(long)]
+    pub zone_id: Option<String>,
+    /// HTTP request timeout in seconds (1-300)
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<u64>,
+    /// Polling interval in seconds when netlink is unavailable (10-3600)
+    #[arg(long, value_name = "SECS")]
+    pub poll_interval: Option<u64>,
+    /// Enable verbose logging
+    #[arg(long)]
+    pub verbose: bool,
+    /// Policy when several AAAA records exist (error|first|all)
+    #[arg(long, value_name = "POLICY")]
+    pub multi_record: Option<String>,
+    /// Port for the health check endpoint (0 = disabled)
+    #[arg(long, value_name = "PORT")]
+    pub health_port: Option<u16>,
+    /// Allow loopback (::1) as a valid address
+    #[arg(long)]
+    pub allow_loopback: bool,
+    /// DNS provider type
+    #[arg(long, value_name = "TYPE")]
+    pub provider_type: Option<String>,
+    /// Allow creating the DNS record if it does not exist yet
+    #[arg(long)]
+    pub allow_create: bool,
+    /// Report the polling fallback as degraded monitoring in the health check
+    #[arg(long)]
+    pub strict_mode: bool,
+    /// Detect and report the address without ever calling the DNS provider
+    #[arg(long)]
+    pub read_only: bool,
 }
 
 impl Config {
@@ -118,11 +754,14 @@ impl Config {
     /// This method loads configuration in the following order:
     /// 1. Loads from the specified config file (if provided and exists)
     /// 2. Overrides with environment variables (if set)
-    /// 3. Validates the final configuration
+    /// 3. Overrides with command-line flags (if given)
+    /// 4. Converts internationalized record names to punycode
+    /// 5. Validates the final configuration
     ///
     /// # Arguments
     ///
     /// * `config_path` - Optional path to a TOML config file
+    /// * `cli` - Values given as command-line flags
     ///
     /// # Returns
     ///
@@ -134,14 +773,45 @@ impl Config {
     /// # Environment Variables
     ///
     /// The following environment variables can override config file values:
-    /// - `CLOUDFLARE_API_TOKEN` - Cloudflare API token
-    /// - `CLOUDFLARE_ZONE_ID` - Cloudflare zone ID
-    /// - `CLOUDFLARE_RECORD_NAME` - DNS record name
+    /// - `IPV6DDNS_API_TOKEN` (alias `CLOUDFLARE_API_TOKEN`) - Provider API token
+    /// - `IPV6DDNS_ZONE_ID` (alias `CLOUDFLARE_ZONE_ID`) - Provider zone ID
+    /// - `IPV6DDNS_RECORD` (alias `CLOUDFLARE_RECORD_NAME`) - DNS record name(s), comma-separated
     /// - `CLOUDFLARE_MULTI_RECORD` - Multi-record policy (error|first|all)
-    pub fn load(config_path: Option<PathBuf>) -> Result<Self> {
+    /// - `CREDENTIALS_DIRECTORY` - systemd credentials directory, used for the
+    ///   API token when it is not set any other way
+    pub fn load(config_path: Option<PathBuf>, cli: &CliOverrides) -> Result<Self> {
+        Self::load_checked(config_path, cli, true)
+    }
+
+    /// Loads the configuration for address detection only
+    ///
+    /// Like `load`, but provider credentials and zone IDs aren't required,
+    /// since nothing is sent to the provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `config_path` - Optional path to a TOML config file
+    /// * `cli` - Values given as command-line flags
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the loaded `Config` or an error as for `load`
+    pub fn load_for_detection(config_path: Option<PathBuf>, cli: &CliOverrides) -> Result<Self> {
+        Self::load_checked(config_path, cli, false)
+    }
+
+    fn load_checked(
+        config_path: Option<PathBuf>,
+        cli: &CliOverrides,
+        needs_provider: bool,
+    ) -> Result<Self> {
         let mut config = Self::load_from_file(config_path)?;
         Self::override_with_env(&mut config)?;
-        Self::validate(&config)?;
+        Self::override_with_cli(&mut config, cli)?;
+        Self::load_credentials(&mut config)?;
+        Self::convert_idn_names(&mut config)?;
+        Self::normalize_zone_id(&mut config);
+        Self::validate(&config, needs_provider)?;
         Ok(config)
     }
 
@@ -156,58 +826,445 @@ impl Config {
     /// Returns a `Result` containing the loaded `Config` with default values
     /// for any missing fields.
     fn load_from_file(config_path: Option<PathBuf>) -> Result<Self> {
-        let mut api_token = String::new();
-        let mut zone_id = String::new();
-        let mut record = String::new();
+        let mut api_token = zeroize::Zeroizing::new(String::new());
+        let mut zone_id = zeroize::Zeroizing::new(String::new());
+        let mut zone_name = None;
+        let mut strict_zone_id = false;
+        let mut secret_key = zeroize::Zeroizing::new(String::new());
+        let mut api_token_credential = DEFAULT_API_TOKEN_CREDENTIAL.to_string();
+        let mut records = Vec::new();
         let mut timeout = DEFAULT_TIMEOUT_SECS;
+        let mut connect_timeout = None;
+        let mut user_agent_suffix = None;
+        let mut http_proxy = None;
+        let mut https_proxy = None;
+        let mut api_base = CLOUDFLARE_API_BASE.to_string();
+        let mut ca_cert_file = None;
         let mut poll_interval = DEFAULT_POLL_INTERVAL_SECS;
+        let mut poll_jitter = 0.0;
+        let mut netlink_max_drain = DEFAULT_NETLINK_MAX_DRAIN;
+        let mut startup_jitter = 0;
+        let mut force_update_interval = 0;
         let mut verbose = false;
-        let mut multi_record = MultiRecordPolicy::Error;
-        let mut allow_loopback = false;
-        let mut provider_type = "cloudflare".to_string();
+        let mut log_format = LogFormat::Text;
+        let mut log_target = None;
+        let mut log_file = None;
+        let mut log_rotation = LogRotation::Never;
+        let mut syslog_facility = DEFAULT_SYSLOG_FACILITY.to_string();
+        let mut multi_record = None;
+        let mut sticky_record = false;
+        let mut reconcile_fields = ReconcileFields::default();
+        let mut stamp_comment = false;
+        let mut ipv6_filter = Ipv6Filter::default();
+        let mut source = AddressSource::Netlink;
+        let mut external_ip_url = DEFAULT_EXTERNAL_IP_URL.to_string();
+        let mut accepted_scopes = vec![RT_SCOPE_UNIVERSE];
+        let mut netns = None;
+        let mut selection = SelectionPolicy::default();
+        let mut allow_create = false;
+        let mut enable_ipv4 = false;
+        let mut enable_ipv6 = true;
+        let mut required_families = None;
+        let mut allow_private_ipv4 = false;
+        let mut strict_mode = false;
+        let mut read_only = false;
+        let mut pause_file = None;
+        let mut lock_file = None;
+        let mut ready_on = ReadyPolicy::Start;
+        let mut ready_file = None;
+        let mut ready_timeout = None;
+        let mut on_removal = RemovalPolicy::Keep;
+        let mut delete_cooldown = DEFAULT_DELETE_COOLDOWN_SECS;
+        let mut removal_grace_secs = 0;
+        let mut delete_on_shutdown = false;
+        let mut verify_after_update = false;
+        let mut verify_delay = DEFAULT_VERIFY_DELAY_SECS;
+        let mut verify_propagation = false;
+        let mut propagation_resolver = parse_resolver(DEFAULT_PROPAGATION_RESOLVER)?;
+        let mut error_threshold = 1;
+        let mut max_retries = DEFAULT_MAX_RETRIES;
+        let mut max_error_body_len = DEFAULT_MAX_ERROR_BODY_LEN;
+        let mut max_concurrent_updates = DEFAULT_MAX_CONCURRENT_UPDATES;
+        let mut max_consecutive_errors = 0;
+        let mut max_sync_age = None;
+        let mut external_resolver = None;
+        let mut external_check_interval = DEFAULT_EXTERNAL_CHECK_INTERVAL_SECS;
+        let mut address_record_template = None;
+        let mut address_records_only = false;
+        let mut address_record_limit = DEFAULT_ADDRESS_RECORD_LIMIT;
+        let mut heartbeat_txt = None;
+        let mut provider_type = ProviderType::Cloudflare;
         let mut health_port: u16 = 0;
+        let mut post_update_command = None;
+        let mut detect_command = None;
+        let mut hook_shell = false;
+        let mut hook_timeout = DEFAULT_HOOK_TIMEOUT_SECS;
+        let mut webhook_url = None;
+        let mut metrics_textfile = None;
+        let mut state_file = None;
 
         if let Some(path) = config_path {
-            if path.exists() {
-                let content = std::fs::read_to_string(&path)
-                    .with_context(|| format!("Failed to read config: {}", path.display()))?;
-                let toml_config: TomlConfig =
-                    toml::from_str(&content).with_context(|| "Failed to parse config file")?;
-
-                api_token = toml_config.api_token.unwrap_or_default();
-                zone_id = toml_config.zone_id.unwrap_or_default();
-                record = toml_config.record_name.unwrap_or_default();
-                timeout = toml_config.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS);
-                poll_interval = toml_config
+            if let Some(content) = read_config_source(&path)? {
+                let file_config = parse_config_file(&path, &content)?;
+
+                if let Some(v) = secret_value(
+                    file_config.api_token,
+                    file_config.api_token_file.as_deref(),
+                    "api_token",
+                    "api_token_file",
+                )? {
+                    api_token = v;
+                }
+                if let Some(v) = secret_value(
+                    file_config.zone_id,
+                    file_config.zone_id_file.as_deref(),
+                    "zone_id",
+                    "zone_id_file",
+                )? {
+                    zone_id = v;
+                }
+                zone_name = file_config
+                    .zone_name
+                    .map(|v| v.trim().trim_end_matches('.').to_ascii_lowercase());
+                if let Some(v) = file_config.strict_zone_id {
+                    strict_zone_id = v;
+                }
+                if let Some(v) = secret_value(
+                    file_config.secret_key,
+                    file_config.secret_key_file.as_deref(),
+                    "secret_key",
+                    "secret_key_file",
+                )? {
+                    secret_key = v;
+                }
+                if let Some(v) = file_config.api_token_credential {
+                    api_token_credential = v;
+                }
+                records = file_config
+                    .record_name
+                    .map(RecordNames::into_vec)
+                    .unwrap_or_default();
+                timeout = file_config.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS);
+                connect_timeout = file_config.connect_timeout;
+                user_agent_suffix = file_config.user_agent_suffix;
+                http_proxy = file_config.http_proxy;
+                https_proxy = file_config.https_proxy;
+                if let Some(v) = file_config.api_base {
+                    api_base = v;
+                }
+                ca_cert_file = file_config.ca_cert_file;
+                poll_interval = file_config
                     .poll_interval
                     .unwrap_or(DEFAULT_POLL_INTERVAL_SECS);
-                verbose = toml_config.verbose.unwrap_or(false);
-                if let Some(v) = toml_config.multi_record.as_deref() {
-                    multi_record = parse_multi_record(v)?;
+                poll_jitter = file_config.poll_jitter.unwrap_or(0.0);
+                netlink_max_drain = file_config
+                    .netlink_max_drain
+                    .unwrap_or(DEFAULT_NETLINK_MAX_DRAIN);
+                startup_jitter = file_config.startup_jitter_secs.unwrap_or(0);
+                force_update_interval = file_config.force_update_interval.unwrap_or(0);
+                verbose = file_config.verbose.unwrap_or(false);
+                if let Some(v) = file_config.log_format.as_deref() {
+                    log_format = parse_log_format(v)?;
+                }
+                if let Some(v) = file_config.log_target.as_deref() {
+                    log_target = Some(parse_log_target(v)?);
+                }
+                log_file = file_config.log_file;
+                if let Some(v) = file_config.log_rotation.as_deref() {
+                    log_rotation = parse_log_rotation(v)?;
+                }
+                if let Some(v) = file_config.syslog_facility {
+                    syslog_facility = v.trim().to_ascii_lowercase();
+                }
+                if let Some(v) = file_config.multi_record.as_deref() {
+                    multi_record = Some(parse_multi_record(v)?);
+                }
+                sticky_record = file_config.sticky_record.unwrap_or(false);
+                if let Some(v) = file_config.reconcile_fields.as_deref() {
+                    reconcile_fields = parse_reconcile_fields(v)?;
+                }
+                stamp_comment = file_config.stamp_comment.unwrap_or(false);
+                if let Some(v) = file_config.allow_loopback {
+                    ipv6_filter.allow_loopback = v;
+                }
+                if let Some(v) = file_config.allow_link_local {
+                    ipv6_filter.allow_link_local = v;
+                }
+                if let Some(v) = file_config.allow_unique_local {
+                    ipv6_filter.allow_unique_local = v;
+                }
+                if let Some(v) = file_config.allow_documentation {
+                    ipv6_filter.allow_documentation = v;
+                }
+                if let Some(v) = file_config.exclude_prefixes {
+                    ipv6_filter.exclude_prefixes = v
+                        .iter()
+                        .map(|prefix| {
+                            parse_prefix(prefix).context("Invalid exclude_prefixes value")
+                        })
+                        .collect::<Result<_>>()?;
+                }
+                if let Some(v) = file_config.source.as_deref() {
+                    source = parse_address_source(v)?;
+                }
+                if let Some(v) = file_config.external_ip_url {
+                    external_ip_url = v;
+                }
+                detect_command = file_config.detect_command.filter(|cmd| !cmd.is_empty());
+                if let Some(v) = file_config.accepted_scopes {
+                    accepted_scopes = v
+                        .iter()
+                        .map(|name| parse_scope(name))
+                        .collect::<Result<_>>()?;
+                }
+                netns = file_config.netns;
+                if let Some(v) = file_config.address_selection.as_deref() {
+                    selection.mode = parse_address_selection(v)?;
+                }
+                if let Some(v) = file_config.address_preference.as_deref() {
+                    selection.preference = parse_address_preference(v)?;
+                }
+                if let Some(v) = file_config.selection_stable_weight {
+                    selection.stable_weight = v;
+                }
+                if let Some(v) = file_config.selection_lifetime_weight {
+                    selection.lifetime_weight = v;
+                }
+                if let Some(v) = file_config.selection_interfaces {
+                    selection.interfaces = v;
+                }
+                if let Some(v) = file_config.selection_interface_weight {
+                    selection.interface_weight = v;
+                }
+                if let Some(v) = file_config.selection_prefix.as_deref() {
+                    selection.prefix =
+                        Some(parse_prefix(v).context("Invalid selection_prefix value")?);
                 }
-                if let Some(v) = toml_config.allow_loopback {
-                    allow_loopback = v;
+                if let Some(v) = file_config.selection_prefix_weight {
+                    selection.prefix_weight = v;
                 }
-                if let Some(v) = toml_config.provider_type {
-                    provider_type = v;
+                if let Some(v) = file_config.prefix_regions {
+                    selection.regions = v
+                        .iter()
+                        .map(|(prefix, region)| {
+                            let (network, len) =
+                                parse_prefix(prefix).context("Invalid prefix_regions value")?;
+                            Ok((network, len, region.trim().to_string()))
+                        })
+                        .collect::<Result<_>>()?;
                 }
-                if let Some(v) = toml_config.health_port {
+                if let Some(v) = file_config.allowed_regions {
+                    selection.allowed_regions = v;
+                }
+                selection.routing = match (file_config.route_table, file_config.route_fwmark) {
+                    (None, None) => RoutingContext::Any,
+                    (Some(table), None) => RoutingContext::Table(table),
+                    (None, Some(mark)) => RoutingContext::Fwmark(mark),
+                    (Some(_), Some(_)) => {
+                        return Err(anyhow::anyhow!(
+                            "Set only one of route_table and route_fwmark"
+                        ))
+                    }
+                };
+                if let Some(v) = file_config.allow_create {
+                    allow_create = v;
+                }
+                if let Some(v) = file_config.enable_ipv4 {
+                    enable_ipv4 = v;
+                }
+                if let Some(v) = file_config.record_type.as_deref() {
+                    if file_config.enable_ipv4.is_some() {
+                        return Err(anyhow::anyhow!(
+                            "record_type and enable_ipv4 can't both be set; use record_type"
+                        ));
+                    }
+                    (enable_ipv6, enable_ipv4) = parse_record_types(v)?;
+                }
+                if let Some(v) = file_config.allow_private_ipv4 {
+                    allow_private_ipv4 = v;
+                }
+                if let Some(v) = file_config.required_families {
+                    required_families = Some(
+                        v.iter()
+                            .map(|name| parse_address_family(name))
+                            .collect::<Result<Vec<_>>>()?,
+                    );
+                }
+                if let Some(v) = file_config.strict_mode {
+                    strict_mode = v;
+                }
+                if let Some(v) = file_config.read_only {
+                    read_only = v;
+                }
+                pause_file = file_config.pause_file;
+                lock_file = file_config.lock_file;
+                if let Some(v) = file_config.ready_on.as_deref() {
+                    ready_on = parse_ready_policy(v)?;
+                }
+                ready_file = file_config.ready_file;
+                if let Some(v) = file_config.ready_timeout {
+                    ready_timeout = Some(v);
+                }
+                if let Some(v) = file_config.on_removal.as_deref() {
+                    on_removal = parse_removal_policy(v)?;
+                }
+                if let Some(v) = file_config.delete_cooldown {
+                    delete_cooldown = v;
+                }
+                if let Some(v) = file_config.removal_grace_secs {
+                    removal_grace_secs = v;
+                }
+                if let Some(v) = file_config.delete_on_shutdown {
+                    delete_on_shutdown = v;
+                }
+                verify_after_update = file_config.verify_after_update.unwrap_or(false);
+                verify_delay = file_config
+                    .verify_delay_secs
+                    .unwrap_or(DEFAULT_VERIFY_DELAY_SECS);
+                if let Some(v) = file_config.verify_propagation {
+                    verify_propagation = v;
+                }
+                if let Some(v) = file_config.propagation_resolver.as_deref() {
+                    propagation_resolver =
+                        parse_resolver(v).context("Invalid propagation_resolver value")?;
+                }
+                if let Some(v) = file_config.error_threshold {
+                    error_threshold = v;
+                }
+                if let Some(v) = file_config.max_retries {
+                    max_retries = v;
+                }
+                if let Some(v) = file_config.max_error_body_len {
+                    max_error_body_len = v;
+                }
+                if let Some(v) = file_config.max_concurrent_updates {
+                    max_concurrent_updates = v;
+                }
+                if let Some(v) = file_config.max_consecutive_errors {
+                    max_consecutive_errors = v;
+                }
+                if let Some(v) = file_config.max_sync_age {
+                    max_sync_age = Some(v);
+                }
+                if let Some(v) = file_config.external_resolver.as_deref() {
+                    external_resolver =
+                        Some(parse_resolver(v).context("Invalid external_resolver value")?);
+                }
+                if let Some(v) = file_config.external_check_interval_secs {
+                    external_check_interval = v;
+                }
+                address_record_template = file_config.address_record_template;
+                address_records_only = file_config.address_records_only.unwrap_or(false);
+                if let Some(v) = file_config.address_record_limit {
+                    address_record_limit = v;
+                }
+                heartbeat_txt = file_config.heartbeat_txt;
+                if let Some(v) = file_config.provider_type.as_deref() {
+                    provider_type = parse_provider_type(v)?;
+                }
+                if let Some(v) = file_config.health_port {
                     health_port = v;
                 }
+                post_update_command = file_config
+                    .post_update_command
+                    .filter(|cmd| !cmd.is_empty());
+                if let Some(v) = file_config.hook_shell {
+                    hook_shell = v;
+                }
+                if let Some(v) = file_config.hook_timeout {
+                    hook_timeout = v;
+                }
+                webhook_url = file_config.webhook_url.filter(|url| !url.trim().is_empty());
+                metrics_textfile = file_config.metrics_textfile;
+                state_file = file_config.state_file;
             }
         }
+        // Link-local addresses are only reported with link scope
+        if ipv6_filter.allow_link_local && !accepted_scopes.contains(&RT_SCOPE_LINK) {
+            accepted_scopes.push(RT_SCOPE_LINK);
+        }
 
         Ok(Self {
-            api_token: zeroize::Zeroizing::new(api_token),
-            zone_id: zeroize::Zeroizing::new(zone_id),
-            record,
+            api_token,
+            zone_id,
+            zone_name,
+            strict_zone_id,
+            secret_key,
+            api_token_credential,
+            records,
             timeout: Duration::from_secs(timeout),
+            connect_timeout: connect_timeout.map(Duration::from_secs),
+            user_agent_suffix,
+            http_proxy,
+            https_proxy,
+            api_base,
+            ca_cert_file,
             poll_interval: Duration::from_secs(poll_interval),
+            poll_jitter,
+            netlink_max_drain,
+            startup_jitter: Duration::from_secs(startup_jitter),
+            force_update_interval: Duration::from_secs(force_update_interval),
             verbose,
+            log_format,
+            log_target: log_target.unwrap_or(if log_file.is_some() {
+                LogTarget::File
+            } else {
+                LogTarget::Stdout
+            }),
+            log_file,
+            log_rotation,
+            syslog_facility,
             multi_record,
-            allow_loopback,
+            sticky_record,
+            reconcile_fields,
+            stamp_comment,
+            ipv6_filter,
+            source,
+            external_ip_url,
+            detect_command,
+            accepted_scopes,
+            netns,
+            selection,
+            allow_create,
+            enable_ipv4,
+            enable_ipv6,
+            allow_private_ipv4,
+            required_families,
+            strict_mode,
+            read_only,
+            pause_file,
+            lock_file,
+            ready_on,
+            ready_file,
+            ready_timeout: ready_timeout.map(Duration::from_secs),
+            on_removal,
+            delete_cooldown: Duration::from_secs(delete_cooldown),
+            removal_grace: Duration::from_secs(removal_grace_secs),
+            delete_on_shutdown,
+            verify_after_update,
+            verify_delay: Duration::from_secs(verify_delay),
+            verify_propagation,
+            propagation_resolver,
+            error_threshold,
+            max_retries,
+            max_error_body_len,
+            max_concurrent_updates,
+            max_consecutive_errors,
+            max_sync_age: max_sync_age.map(Duration::from_secs),
+            external_resolver,
+            external_check_interval: Duration::from_secs(external_check_interval),
+            address_record_template,
+            address_records_only,
+            address_record_limit,
+            heartbeat_txt,
             provider_type,
             health_port,
+            post_update_command,
+            hook_shell,
+            hook_timeout: Duration::from_secs(hook_timeout),
+            webhook_url,
+            metrics_textfile,
+            state_file,
         })
     }
 
@@ -224,35 +1281,107 @@ impl Config {
     ///
     /// Returns `Ok(())` or an error if the multi-record policy is invalid.
     fn override_with_env(config: &mut Self) -> Result<()> {
-        if let Ok(v) = env::var(ENV_API_TOKEN) {
+        if let Some(v) = secret_value(
+            env_with_alias(ENV_API_TOKEN, ENV_CLOUDFLARE_API_TOKEN),
+            env_with_alias(ENV_API_TOKEN_FILE, ENV_CLOUDFLARE_API_TOKEN_FILE)
+                .map(PathBuf::from)
+                .as_deref(),
+            ENV_API_TOKEN,
+            ENV_API_TOKEN_FILE,
+        )? {
+            config.api_token = v;
+        }
+        if let Some(v) = secret_value(
+            env_with_alias(ENV_ZONE_ID, ENV_CLOUDFLARE_ZONE_ID),
+            env_with_alias(ENV_ZONE_ID_FILE, ENV_CLOUDFLARE_ZONE_ID_FILE)
+                .map(PathBuf::from)
+                .as_deref(),
+            ENV_ZONE_ID,
+            ENV_ZONE_ID_FILE,
+        )? {
+            config.zone_id = v;
+        }
+        if let Some(v) = secret_value(
+            env::var(ENV_SECRET_KEY).ok(),
+            env::var(ENV_SECRET_KEY_FILE)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+                .as_deref(),
+            ENV_SECRET_KEY,
+            ENV_SECRET_KEY_FILE,
+        )? {
+            config.secret_key = v;
+        }
+        if let Some(v) = env_with_alias(ENV_RECORD_NAME, ENV_CLOUDFLARE_RECORD_NAME) {
+            config.records = parse_record_list(&v);
+        }
+        if let Ok(v) = env::var(ENV_MULTI_RECORD) {
             if !v.is_empty() {
-                config.api_token = zeroize::Zeroizing::new(v);
+                config.multi_record = Some(parse_multi_record(&v)?);
             }
         }
-        if let Ok(v) = env::var(ENV_ZONE_ID) {
+        if let Ok(v) = env::var(ENV_ALLOW_LOOPBACK) {
             if !v.is_empty() {
-                config.zone_id = zeroize::Zeroizing::new(v);
+                config.ipv6_filter.allow_loopback =
+                    parse_bool_env(&v).context("Invalid IPV6DDNS_ALLOW_LOOPBACK value")?;
             }
         }
-        if let Ok(v) = env::var(ENV_RECORD_NAME) {
+        if let Ok(v) = env::var(ENV_ALLOW_CREATE) {
             if !v.is_empty() {
-                config.record = v;
+                config.allow_create =
+                    parse_bool_env(&v).context("Invalid IPV6DDNS_ALLOW_CREATE value")?;
             }
         }
-        if let Ok(v) = env::var(ENV_MULTI_RECORD) {
+        if let Ok(v) = env::var(ENV_ENABLE_IPV4) {
             if !v.is_empty() {
-                config.multi_record = parse_multi_record(&v)?;
+                config.enable_ipv4 =
+                    parse_bool_env(&v).context("Invalid IPV6DDNS_ENABLE_IPV4 value")?;
             }
         }
-        if let Ok(v) = env::var(ENV_ALLOW_LOOPBACK) {
+        if let Ok(v) = env::var(ENV_RECORD_TYPE) {
             if !v.is_empty() {
-                config.allow_loopback =
-                    parse_bool_env(&v).context("Invalid IPV6DDNS_ALLOW_LOOPBACK value")?;
+                (config.enable_ipv6, config.enable_ipv4) =
+                    parse_record_types(&v).context("Invalid IPV6DDNS_RECORD_TYPE value")?;
+            }
+        }
+        if let Ok(v) = env::var(ENV_ALLOW_PRIVATE_IPV4) {
+            if !v.is_empty() {
+                config.allow_private_ipv4 =
+                    parse_bool_env(&v).context("Invalid IPV6DDNS_ALLOW_PRIVATE_IPV4 value")?;
+            }
+        }
+        if let Ok(v) = env::var(ENV_STRICT_MODE) {
+            if !v.is_empty() {
+                config.strict_mode =
+                    parse_bool_env(&v).context("Invalid IPV6DDNS_STRICT_MODE value")?;
+            }
+        }
+        if let Ok(v) = env::var(ENV_READ_ONLY) {
+            if !v.is_empty() {
+                config.read_only =
+                    parse_bool_env(&v).context("Invalid IPV6DDNS_READ_ONLY value")?;
+            }
+        }
+        if let Ok(v) = env::var(ENV_LOG_FORMAT) {
+            if !v.is_empty() {
+                config.log_format = parse_log_format(&v)?;
+            }
+        }
+        if let Ok(v) = env::var(ENV_ON_REMOVAL) {
+            if !v.is_empty() {
+                config.on_removal = parse_removal_policy(&v)?;
+            }
+        }
+        if let Ok(v) = env::var(ENV_DELETE_ON_SHUTDOWN) {
+            if !v.is_empty() {
+                config.delete_on_shutdown =
+                    parse_bool_env(&v).context("Invalid IPV6DDNS_DELETE_ON_SHUTDOWN value")?;
             }
         }
         if let Ok(v) = env::var(ENV_PROVIDER_TYPE) {
             if !v.is_empty() {
-                config.provider_type = v;
+                config.provider_type = parse_provider_type(&v)?;
             }
         }
         if let Ok(v) = env::var(ENV_HEALTH_PORT) {
@@ -263,66 +1392,282 @@ impl Config {
         Ok(())
     }
 
-    /// Validates the configuration
+    /// Overrides configuration values with command-line flags
     ///
-    /// Ensures that all required fields are present and valid.
+    /// Boolean flags can only turn a setting on.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Mutable reference to the config to update
+    /// * `cli` - Values given as command-line flags
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` or an error if:
+    /// Returns `Ok(())` or an error if the multi-record policy is invalid.
+    fn override_with_cli(config: &mut Self, cli: &CliOverrides) -> Result<()> {
+        if let Some(v) = &cli.record {
+            config.records = parse_record_list(v);
+        }
+        if let Some(v) = cli.zone_id.as_ref().filter(|v| !v.is_empty()) {
+            config.zone_id = zeroize::Zeroizing::new(v.clone());
+        }
+        if let Some(v) = cli.timeout {
+            config.timeout = Duration::from_secs(v);
+        }
+        if let Some(v) = cli.poll_interval {
+            config.poll_interval = Duration::from_secs(v);
+        }
+        if let Some(v) = &cli.multi_record {
+            config.multi_record = Some(parse_multi_record(v)?);
+        }
+        if let Some(v) = cli.health_port {
+            config.health_port = v;
+        }
+        if let Some(v) = &cli.provider_type {
+            config.provider_type = parse_provider_type(v)?;
+        }
+        config.verbose |= cli.verbose;
+        config.ipv6_filter.allow_loopback |= cli.allow_loopback;
+        config.allow_create |= cli.allow_create;
+        config.strict_mode |= cli.strict_mode;
+        config.read_only |= cli.read_only;
+        Ok(())
+    }
+
+    /// Loads the API token from the systemd credentials directory
+    ///
+    /// Only applies when no token was configured inline, via a file, or via the
+    /// environment, and `$CREDENTIALS_DIRECTORY/<api_token_credential>` exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Mutable reference to the config to update
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` or an error if the credential exists but cannot be read.
+    fn load_credentials(config: &mut Self) -> Result<()> {
+        if !config.api_token.is_empty() {
+            return Ok(());
+        }
+        let Some(dir) = env::var_os(ENV_CREDENTIALS_DIRECTORY).filter(|v| !v.is_empty()) else {
+            return Ok(());
+        };
+        let path = Path::new(&dir).join(&config.api_token_credential);
+        if path.exists() {
+            config.api_token = read_secret_file(&path, "API token credential")?;
+        }
+        Ok(())
+    }
+
+    /// Lowercases a Cloudflare zone ID when `strict_zone_id` is set
+    ///
+    /// Cloudflare shows zone IDs in lowercase hex; an uppercase copy names the
+    /// same zone but would not match the API's form.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Mutable reference to the config to update
+    fn normalize_zone_id(config: &mut Self) {
+        if config.strict_zone_id && config.provider_type == ProviderType::Cloudflare {
+            let zone_id = config.zone_id.to_ascii_lowercase();
+            config.zone_id = zeroize::Zeroizing::new(zone_id);
+        }
+    }
+
+    /// Converts record names with non-ASCII labels to their punycode form
+    ///
+    /// Providers only accept the ASCII (`xn--`) form, so `müller.example` is
+    /// published as `xn--mller-kva.example`. The `heartbeat_txt` name, and with
+    /// Porkbun, Namecheap and Vultr the zone ID (a domain), are converted the
+    /// same way.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Mutable reference to the config to update
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` or an error if a name cannot be converted.
+    fn convert_idn_names(config: &mut Self) -> Result<()> {
+        for record in &mut config.records {
+            *record = to_ascii_name(record)
+                .with_context(|| format!("Invalid record name: {}", record))?;
+        }
+        if matches!(
+            config.provider_type,
+            ProviderType::Porkbun | ProviderType::Namecheap | ProviderType::Vultr
+        ) {
+            let zone_id = to_ascii_name(config.zone_id.as_str()).with_context(|| {
+                format!(
+                    "{} must be the {} domain",
+                    ENV_ZONE_ID, config.provider_type
+                )
+            })?;
+            config.zone_id = zeroize::Zeroizing::new(zone_id);
+        }
+        if let Some(name) = &mut config.heartbeat_txt {
+            *name = to_ascii_name(name).context("Invalid heartbeat_txt")?;
+        }
+        if let Some(name) = &mut config.zone_name {
+            *name = to_ascii_name(name).context("Invalid zone_name")?;
+        }
+        Ok(())
+    }
+
+    /// Validates the configuration
+    ///
+    /// Ensures that all required fields are present and valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `needs_provider` - Whether provider credentials are required (unless `read_only`)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` or an error if:
     /// - API token is missing or too short
     /// - Zone ID is missing or invalid format
     /// - Record name is missing
     /// - Record name is invalid
     /// - Timeout is out of valid range
     /// - Poll interval is out of valid range
-    fn validate(&self) -> Result<()> {
-        if self.api_token.as_str().is_empty() {
-            return Err(anyhow::anyhow!("Missing {}", ENV_API_TOKEN));
+    fn validate(&self, needs_provider: bool) -> Result<()> {
+        let needs_provider = needs_provider && !self.read_only;
+        // Credentials are only needed to talk to the provider; Route 53 reads
+        // them from the AWS environment
+        if needs_provider && self.provider_type != ProviderType::Route53 {
+            if self.api_token.as_str().is_empty() {
+                return Err(anyhow::anyhow!("Missing {}", ENV_API_TOKEN));
+            }
+            // Cloudflare API tokens are typically 40+ characters
+            if self.api_token.as_str().len() < MIN_API_TOKEN_LENGTH {
+                return Err(anyhow::anyhow!(
+                    "{} is too short ({} chars, minimum {})",
+                    ENV_API_TOKEN,
+                    self.api_token.as_str().len(),
+                    MIN_API_TOKEN_LENGTH
+                ));
+            }
         }
-        // Cloudflare API tokens are typically 40+ characters
-        if self.api_token.as_str().len() < MIN_API_TOKEN_LENGTH {
-            return Err(anyhow::anyhow!(
-                "{} is too short ({} chars, minimum {})",
-                ENV_API_TOKEN,
-                self.api_token.as_str().len(),
-                MIN_API_TOKEN_LENGTH
-            ));
+        // zone_id and zone_name are mutually exclusive
+        if let Some(name) = &self.zone_name {
+            if !self.zone_id.as_str().is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Set only one of {} (zone_id) and zone_name",
+                    ENV_ZONE_ID
+                ));
+            }
+            validate_record_name(name).context("Invalid zone_name")?;
+        }
+        // DuckDNS takes the domain from the record name instead of a zone
+        if needs_provider
+            && self.provider_type == ProviderType::Cloudflare
+            && self.zone_name.is_none()
+        {
+            if self.zone_id.as_str().is_empty() {
+                return Err(anyhow::anyhow!("Missing {} (or zone_name)", ENV_ZONE_ID));
+            }
+            // Zone IDs are alphanumeric and typically 32 characters
+            if !self.zone_id.as_str().chars().all(|c| c.is_alphanumeric()) {
+                return Err(anyhow::anyhow!(
+                    "{} must be alphanumeric, got: {}",
+                    ENV_ZONE_ID,
+                    self.zone_id.as_str()
+                ));
+            }
+            if self.zone_id.as_str().len() < MIN_ZONE_ID_LENGTH
+                || self.zone_id.as_str().len() > MAX_ZONE_ID_LENGTH
+            {
+                return Err(anyhow::anyhow!(
+                    "{} has invalid length ({} chars, expected {}-{})",
+                    ENV_ZONE_ID,
+                    self.zone_id.as_str().len(),
+                    MIN_ZONE_ID_LENGTH,
+                    MAX_ZONE_ID_LENGTH
+                ));
+            }
+            if self.strict_zone_id
+                && (self.zone_id.len() != CLOUDFLARE_ZONE_ID_LENGTH
+                    || !self.zone_id.chars().all(|c| c.is_ascii_hexdigit()))
+            {
+                return Err(anyhow::anyhow!(
+                    "{} must be {} hexadecimal characters (strict_zone_id), got: {}",
+                    ENV_ZONE_ID,
+                    CLOUDFLARE_ZONE_ID_LENGTH,
+                    self.zone_id.as_str()
+                ));
+            }
+        }
+        if needs_provider && self.provider_type == ProviderType::Porkbun {
+            if self.secret_key.as_str().is_empty() {
+                return Err(anyhow::anyhow!("Missing {}", ENV_SECRET_KEY));
+            }
+            if self.zone_id.as_str().is_empty() {
+                return Err(anyhow::anyhow!("Missing {}", ENV_ZONE_ID));
+            }
+            validate_record_name(self.zone_id.as_str())
+                .with_context(|| format!("{} must be the Porkbun domain", ENV_ZONE_ID))?;
+            for record in &self.records {
+                porkbun_subdomain(self.zone_id.as_str(), record)?;
+            }
         }
-        if self.zone_id.as_str().is_empty() {
-            return Err(anyhow::anyhow!("Missing {}", ENV_ZONE_ID));
+        if needs_provider && self.provider_type == ProviderType::Namecheap {
+            if self.zone_id.as_str().is_empty() {
+                return Err(anyhow::anyhow!("Missing {}", ENV_ZONE_ID));
+            }
+            validate_record_name(self.zone_id.as_str())
+                .with_context(|| format!("{} must be the Namecheap domain", ENV_ZONE_ID))?;
+            for record in &self.records {
+                namecheap_host(self.zone_id.as_str(), record)?;
+            }
         }
-        // Zone IDs are alphanumeric and typically 32 characters
-        if !self.zone_id.as_str().chars().all(|c| c.is_alphanumeric()) {
-            return Err(anyhow::anyhow!(
-                "{} must be alphanumeric, got: {}",
-                ENV_ZONE_ID,
-                self.zone_id.as_str()
-            ));
+        if needs_provider && self.provider_type == ProviderType::Vultr {
+            if self.zone_id.as_str().is_empty() {
+                return Err(anyhow::anyhow!("Missing {}", ENV_ZONE_ID));
+            }
+            validate_record_name(self.zone_id.as_str())
+                .with_context(|| format!("{} must be the Vultr domain", ENV_ZONE_ID))?;
+            for record in &self.records {
+                vultr_subdomain(self.zone_id.as_str(), record)?;
+            }
         }
-        if self.zone_id.as_str().len() < MIN_ZONE_ID_LENGTH
-            || self.zone_id.as_str().len() > MAX_ZONE_ID_LENGTH
+        // Namecheap's dynamic DNS can only set a host's address
+        if self.provider_type == ProviderType::Namecheap
+            && (self.on_removal == RemovalPolicy::Delete || self.delete_on_shutdown)
         {
             return Err(anyhow::anyhow!(
-                "{} has invalid length ({} chars, expected {}-{})",
-                ENV_ZONE_ID,
-                self.zone_id.as_str().len(),
-                MIN_ZONE_ID_LENGTH,
-                MAX_ZONE_ID_LENGTH
+                "on_removal = \"delete\" and delete_on_shutdown are not supported with Namecheap, \
+                 which can't delete records"
             ));
         }
-        if self.record.is_empty() {
+        if needs_provider && self.provider_type == ProviderType::Route53 {
+            let zone_id = self.zone_id.as_str().trim_start_matches("/hostedzone/");
+            if zone_id.is_empty() {
+                return Err(anyhow::anyhow!("Missing {}", ENV_ZONE_ID));
+            }
+            if !zone_id.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(anyhow::anyhow!(
+                    "{} must be a Route 53 hosted zone ID, got: {}",
+                    ENV_ZONE_ID,
+                    self.zone_id.as_str()
+                ));
+            }
+        }
+        if self.records.is_empty() {
             return Err(anyhow::anyhow!("Missing {}", ENV_RECORD_NAME));
         }
-        validate_record_name(&self.record)?;
+        for (i, record) in self.records.iter().enumerate() {
+            validate_record_name(record)?;
+            if self.records[..i].contains(record) {
+                return Err(anyhow::anyhow!("Duplicate record name: {}", record));
+            }
+        }
 
-        let provider = self.provider_type.trim().to_ascii_lowercase();
-        if provider != "cloudflare" {
-            return Err(anyhow::anyhow!(
-                "{} must be \"cloudflare\" (only provider supported), got: {}",
-                ENV_PROVIDER_TYPE,
-                self.provider_type
-            ));
+        if self.provider_type == ProviderType::DuckDns {
+            for record in &self.records {
+                duckdns_subdomain(record)?;
+            }
         }
 
         let timeout_secs = self.timeout.as_secs();
@@ -334,6 +1679,58 @@ impl Config {
                 timeout_secs
             ));
         }
+        if let Some(connect_timeout) = self.connect_timeout {
+            let connect_secs = connect_timeout.as_secs();
+            if !(MIN_TIMEOUT_SECS..=MAX_TIMEOUT_SECS).contains(&connect_secs) {
+                return Err(anyhow::anyhow!(
+                    "connect_timeout must be between {} and {} seconds, got {}",
+                    MIN_TIMEOUT_SECS,
+                    MAX_TIMEOUT_SECS,
+                    connect_secs
+                ));
+            }
+            if connect_timeout > self.timeout {
+                return Err(anyhow::anyhow!(
+                    "connect_timeout ({}s) must not be longer than timeout ({}s)",
+                    connect_secs,
+                    timeout_secs
+                ));
+            }
+        }
+        if self.log_rotation != LogRotation::Never && self.log_file.is_none() {
+            return Err(anyhow::anyhow!("log_rotation requires log_file"));
+        }
+        if (self.log_target == LogTarget::File) != self.log_file.is_some() {
+            return Err(anyhow::anyhow!(
+                "log_file must be set exactly when log_target = \"file\""
+            ));
+        }
+        if !SYSLOG_FACILITIES.contains(&self.syslog_facility.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Invalid syslog_facility: '{}'. Use one of: {}",
+                self.syslog_facility,
+                SYSLOG_FACILITIES.join(", ")
+            ));
+        }
+        if let Some(path) = &self.log_file {
+            if path.file_name().is_none() {
+                return Err(anyhow::anyhow!(
+                    "log_file must name a file, got: {}",
+                    path.display()
+                ));
+            }
+        }
+        if let Some(suffix) = &self.user_agent_suffix {
+            if suffix.trim().is_empty() {
+                return Err(anyhow::anyhow!("user_agent_suffix must not be empty"));
+            }
+            if suffix.chars().any(char::is_control) {
+                return Err(anyhow::anyhow!(
+                    "user_agent_suffix must not contain control characters or newlines, got: {:?}",
+                    suffix
+                ));
+            }
+        }
 
         let poll_interval_secs = self.poll_interval.as_secs();
         if !(MIN_POLL_INTERVAL_SECS..=MAX_POLL_INTERVAL_SECS).contains(&poll_interval_secs) {
@@ -345,474 +1742,3819 @@ impl Config {
             ));
         }
 
-        Ok(())
-    }
-}
-
-/// Parses a boolean value from an environment variable
-///
-/// This function accepts multiple string representations of boolean values:
-/// - `true`: "1", "true", "yes", "on"
-/// - `false`: "0", "false", "no", "off"
-///
-/// # Arguments
-///
-/// * `value` - The string value to parse
-///
-/// # Returns
-///
-/// Returns a `Result` containing the parsed boolean or an error if invalid
-fn parse_bool_env(value: &str) -> Result<bool> {
-    match value.trim().to_ascii_lowercase().as_str() {
-        "1" | "true" | "yes" | "on" => Ok(true),
-        "0" | "false" | "no" | "off" => Ok(false),
-        _ => Err(anyhow::anyhow!(
-            "expected boolean (true/false/1/0/yes/no/on/off)"
-        )),
-    }
-}
+        if self.startup_jitter.as_secs() > MAX_STARTUP_JITTER_SECS {
+            return Err(anyhow::anyhow!(
+                "startup_jitter_secs must be at most {} seconds, got {}",
+                MAX_STARTUP_JITTER_SECS,
+                self.startup_jitter.as_secs()
+            ));
+        }
 
-/// TOML configuration file structure
-#[derive(Debug, serde::Deserialize)]
-struct TomlConfig {
-    api_token: Option<String>,
-    zone_id: Option<String>,
-    #[serde(rename = "record_name")]
-    record_name: Option<String>,
-    timeout: Option<u64>,
-    #[serde(rename = "poll_interval")]
-    poll_interval: Option<u64>,
-    verbose: Option<bool>,
-    multi_record: Option<String>,
-    allow_loopback: Option<bool>,
-    provider_type: Option<String>,
-    health_port: Option<u16>,
-}
+        let force_update_secs = self.force_update_interval.as_secs();
+        if force_update_secs != 0
+            && !(MIN_FORCE_UPDATE_INTERVAL_SECS..=MAX_FORCE_UPDATE_INTERVAL_SECS)
+                .contains(&force_update_secs)
+        {
+            return Err(anyhow::anyhow!(
+                "force_update_interval must be 0 (never) or between {} and {} seconds, got {}",
+                MIN_FORCE_UPDATE_INTERVAL_SECS,
+                MAX_FORCE_UPDATE_INTERVAL_SECS,
+                force_update_secs
+            ));
+        }
 
-/// Parses a multi-record policy string into a `MultiRecordPolicy` enum
-///
-/// This function accepts multiple aliases for each policy type:
-/// - `Error`: "error", "fail", "reject"
-/// - `UpdateFirst`: "first", "update_first", "updatefirst"
-/// - `UpdateAll`: "all", "update_all", "updateall"
-///
-/// # Arguments
-///
-/// * `value` - The policy string to parse
-///
-/// # Returns
-///
-/// Returns a `Result` containing the parsed `MultiRecordPolicy` or an error
-/// if the value is invalid.
-pub fn parse_multi_record(value: &str) -> Result<MultiRecordPolicy> {
-    let normalized = value.trim().to_ascii_lowercase();
-    match normalized.as_str() {
-        "error" | "fail" | "reject" => Ok(MultiRecordPolicy::Error),
-        "first" | "update_first" | "updatefirst" => Ok(MultiRecordPolicy::UpdateFirst),
-        "all" | "update_all" | "updateall" => Ok(MultiRecordPolicy::UpdateAll),
-        _ => Err(anyhow::anyhow!(
-            "Invalid multi_record policy: '{}'. Use: error|first|all",
-            value
-        )),
-    }
-}
+        if !(0.0..=MAX_POLL_JITTER).contains(&self.poll_jitter) {
+            return Err(anyhow::anyhow!(
+                "poll_jitter must be between 0.0 and {}, got {}",
+                MAX_POLL_JITTER,
+                self.poll_jitter
+            ));
+        }
 
-//==============================================================================
-// Tests
-//==============================================================================
+        if self.selection.mode == AddressSelection::PreferPrefix && self.selection.prefix.is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "address_selection = \"prefer_prefix\" requires selection_prefix"
+            ));
+        }
+        if self.selection.routing == RoutingContext::Table(0) {
+            return Err(anyhow::anyhow!("route_table must not be 0"));
+        }
+        for region in &self.selection.allowed_regions {
+            if !self.selection.regions.iter().any(|(_, _, r)| r == region) {
+                return Err(anyhow::anyhow!(
+                    "allowed_regions entry '{}' is not a region in prefix_regions",
+                    region
+                ));
+            }
+        }
+        if !(1..=MAX_NETLINK_MAX_DRAIN).contains(&self.netlink_max_drain) {
+            return Err(anyhow::anyhow!(
+                "netlink_max_drain must be between 1 and {}, got {}",
+                MAX_NETLINK_MAX_DRAIN,
+                self.netlink_max_drain
+            ));
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serial_test::serial;
-    use tempfile::TempDir;
+        if self.accepted_scopes.is_empty() {
+            return Err(anyhow::anyhow!("accepted_scopes must not be empty"));
+        }
 
-    struct EnvGuard {
-        saved: Vec<(&'static str, Option<String>)>,
-    }
+        if self.netns.as_deref().is_some_and(|n| n.trim().is_empty()) {
+            return Err(anyhow::anyhow!("netns must not be empty"));
+        }
 
-    impl EnvGuard {
-        fn new() -> Self {
-            let keys = [
-                ENV_API_TOKEN,
-                ENV_ZONE_ID,
-                ENV_RECORD_NAME,
-                ENV_MULTI_RECORD,
-                ENV_ALLOW_LOOPBACK,
-            ];
-            let mut saved = Vec::with_capacity(keys.len());
-            for key in keys {
-                saved.push((key, std::env::var(key).ok()));
-                std::env::remove_var(key);
+        if let Some(families) = &self.required_families {
+            if families.is_empty() {
+                return Err(anyhow::anyhow!("required_families must not be empty"));
+            }
+            if families.contains(&RecordType::A) && !self.enable_ipv4 {
+                return Err(anyhow::anyhow!(
+                    "required_families includes ipv4, which needs enable_ipv4 = true"
+                ));
+            }
+            if families.contains(&RecordType::Aaaa) && !self.enable_ipv6 {
+                return Err(anyhow::anyhow!(
+                    "required_families includes ipv6, which record_type = \"A\" doesn't publish"
+                ));
             }
-            Self { saved }
         }
-    }
-
-    impl Drop for EnvGuard {
-        fn drop(&mut self) {
-            for (key, value) in self.saved.drain(..) {
-                if let Some(val) = value {
-                    std::env::set_var(key, val);
-                } else {
-                    std::env::remove_var(key);
-                }
+        if !self.enable_ipv6 {
+            if !self.enable_ipv4 {
+                return Err(anyhow::anyhow!(
+                    "record_type = \"A\" publishes IPv4, but IPV6DDNS_ENABLE_IPV4 turns it off"
+                ));
+            }
+            if self.address_record_template.is_some() {
+                return Err(anyhow::anyhow!(
+                    "address_record_template publishes AAAA records, which record_type = \"A\" \
+                     doesn't"
+                ));
             }
         }
-    }
 
-    fn write_config(contents: &str) -> (TempDir, PathBuf) {
-        let dir = TempDir::new().expect("temp dir");
-        let path = dir.path().join("config.toml");
-        std::fs::write(&path, contents).expect("write config");
-        (dir, path)
+        if self.removal_grace.as_secs() > MAX_REMOVAL_GRACE_SECS {
+            return Err(anyhow::anyhow!(
+                "removal_grace_secs must be at most {} seconds, got {}",
+                MAX_REMOVAL_GRACE_SECS,
+                self.removal_grace.as_secs()
+            ));
+        }
+
+        let verify_delay_secs = self.verify_delay.as_secs();
+        if !(1..=MAX_VERIFY_DELAY_SECS).contains(&verify_delay_secs) {
+            return Err(anyhow::anyhow!(
+                "verify_delay_secs must be between 1 and {} seconds, got {}",
+                MAX_VERIFY_DELAY_SECS,
+                verify_delay_secs
+            ));
+        }
+        if self.verify_after_update && self.provider_type == ProviderType::DuckDns {
+            return Err(anyhow::anyhow!(
+                "verify_after_update is not supported with DuckDNS, which can't list records"
+            ));
+        }
+        if self.verify_after_update && self.provider_type == ProviderType::Namecheap {
+            return Err(anyhow::anyhow!(
+                "verify_after_update is not supported with Namecheap, which can't list records"
+            ));
+        }
+
+        if !(1..=MAX_ERROR_THRESHOLD).contains(&self.error_threshold) {
+            return Err(anyhow::anyhow!(
+                "error_threshold must be between 1 and {}, got {}",
+                MAX_ERROR_THRESHOLD,
+                self.error_threshold
+            ));
+        }
+
+        if self.max_retries > MAX_MAX_RETRIES {
+            return Err(anyhow::anyhow!(
+                "max_retries must be between 0 and {}, got {}",
+                MAX_MAX_RETRIES,
+                self.max_retries
+            ));
+        }
+        if !(MIN_MAX_ERROR_BODY_LEN..=MAX_MAX_ERROR_BODY_LEN).contains(&self.max_error_body_len) {
+            return Err(anyhow::anyhow!(
+                "max_error_body_len must be between {} and {}, got {}",
+                MIN_MAX_ERROR_BODY_LEN,
+                MAX_MAX_ERROR_BODY_LEN,
+                self.max_error_body_len
+            ));
+        }
+        if !(1..=MAX_MAX_CONCURRENT_UPDATES).contains(&self.max_concurrent_updates) {
+            return Err(anyhow::anyhow!(
+                "max_concurrent_updates must be between 1 and {}, got {}",
+                MAX_MAX_CONCURRENT_UPDATES,
+                self.max_concurrent_updates
+            ));
+        }
+
+        if let Some(timeout) = self.ready_timeout {
+            if self.ready_on != ReadyPolicy::FirstSync {
+                return Err(anyhow::anyhow!(
+                    "ready_timeout only applies with ready_on = \"first_sync\""
+                ));
+            }
+            if timeout.is_zero() {
+                return Err(anyhow::anyhow!(
+                    "ready_timeout must be at least 1 second; leave it unset to wait forever"
+                ));
+            }
+        }
+
+        if self.max_sync_age == Some(Duration::ZERO) {
+            return Err(anyhow::anyhow!(
+                "max_sync_age must be at least 1 second; leave it unset for no limit"
+            ));
+        }
+
+        let external_check_interval_secs = self.external_check_interval.as_secs();
+        if !(MIN_EXTERNAL_CHECK_INTERVAL_SECS..=MAX_EXTERNAL_CHECK_INTERVAL_SECS)
+            .contains(&external_check_interval_secs)
+        {
+            return Err(anyhow::anyhow!(
+                "external_check_interval_secs must be between {} and {} seconds, got {}",
+                MIN_EXTERNAL_CHECK_INTERVAL_SECS,
+                MAX_EXTERNAL_CHECK_INTERVAL_SECS,
+                external_check_interval_secs
+            ));
+        }
+
+        if self.external_resolver.is_some() && self.read_only {
+            return Err(anyhow::anyhow!(
+                "external_resolver checks published records, so it can't be used with read_only"
+            ));
+        }
+
+        if let Some(template) = &self.address_record_template {
+            if !template.contains("{n}") {
+                return Err(anyhow::anyhow!(
+                    "address_record_template must contain {{n}}, got: {}",
+                    template
+                ));
+            }
+            for record in &self.records {
+                let name = address_record_name(template, record, 1);
+                validate_record_name(&name).with_context(|| {
+                    format!(
+                        "address_record_template gives an invalid name for {}",
+                        record
+                    )
+                })?;
+                if self.provider_type == ProviderType::DuckDns {
+                    duckdns_subdomain(&name)?;
+                }
+                if needs_provider && self.provider_type == ProviderType::Namecheap {
+                    namecheap_host(self.zone_id.as_str(), &name)?;
+                }
+            }
+        } else if self.address_records_only {
+            return Err(anyhow::anyhow!(
+                "address_records_only requires address_record_template"
+            ));
+        }
+        if !(1..=MAX_ADDRESS_RECORD_LIMIT).contains(&self.address_record_limit) {
+            return Err(anyhow::anyhow!(
+                "address_record_limit must be between 1 and {}, got {}",
+                MAX_ADDRESS_RECORD_LIMIT,
+                self.address_record_limit
+            ));
+        }
+        let url = reqwest::Url::parse(&self.api_base)
+            .with_context(|| format!("Invalid api_base: {}", self.api_base))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(anyhow::anyhow!(
+                "api_base must use http or https, got: {}",
+                url.scheme()
+            ));
+        }
+        if self.stamp_comment && self.reconcile_fields.comment {
+            return Err(anyhow::anyhow!(
+                "stamp_comment cannot be combined with \"comment\" in reconcile_fields"
+            ));
+        }
+        if self.provider_type != ProviderType::Cloudflare {
+            let cloudflare_only = [
+                ("http_proxy", self.http_proxy.is_some()),
+                ("https_proxy", self.https_proxy.is_some()),
+                ("api_base", self.api_base != CLOUDFLARE_API_BASE),
+                ("ca_cert_file", self.ca_cert_file.is_some()),
+                ("zone_name", self.zone_name.is_some()),
+                ("stamp_comment", self.stamp_comment),
+            ];
+            if let Some((key, _)) = cloudflare_only.iter().find(|(_, set)| *set) {
+                return Err(anyhow::anyhow!(
+                    "{} is only supported with the cloudflare provider",
+                    key
+                ));
+            }
+        }
+        if let Some(name) = &self.heartbeat_txt {
+            validate_record_name(name).context("Invalid heartbeat_txt")?;
+            if self.provider_type != ProviderType::Cloudflare {
+                return Err(anyhow::anyhow!(
+                    "heartbeat_txt is only supported with the cloudflare provider"
+                ));
+            }
+        }
+
+        if let Some(command) = &self.post_update_command {
+            validate_hook_command(command, self.hook_shell)?;
+        }
+
+        let hook_timeout_secs = self.hook_timeout.as_secs();
+        if !(1..=MAX_HOOK_TIMEOUT_SECS).contains(&hook_timeout_secs) {
+            return Err(anyhow::anyhow!(
+                "hook_timeout must be between 1 and {} seconds, got {}",
+                MAX_HOOK_TIMEOUT_SECS,
+                hook_timeout_secs
+            ));
+        }
+
+        if let Some(url) = &self.webhook_url {
+            validate_webhook_url(url)?;
+        }
+        match (&self.detect_command, self.source) {
+            (Some(command), AddressSource::Command) => {
+                validate_command("detect_command", command, self.hook_shell)?;
+            }
+            (None, AddressSource::Command) => {
+                return Err(anyhow::anyhow!(
+                    "source = \"command\" requires detect_command"
+                ));
+            }
+            (Some(_), _) => {
+                return Err(anyhow::anyhow!(
+                    "detect_command requires source = \"command\""
+                ));
+            }
+            (None, _) => {}
+        }
+        if self.source == AddressSource::Command && self.address_record_template.is_some() {
+            return Err(anyhow::anyhow!(
+                "address_record_template needs every local address, so it can't be used \
+                 with source = \"command\""
+            ));
+        }
+        if self.source == AddressSource::External {
+            let url = reqwest::Url::parse(&self.external_ip_url)
+                .with_context(|| format!("Invalid external_ip_url: {}", self.external_ip_url))?;
+            if url.scheme() != "https" {
+                return Err(anyhow::anyhow!(
+                    "external_ip_url must use https, got: {}",
+                    url.scheme()
+                ));
+            }
+            if self.address_record_template.is_some() {
+                return Err(anyhow::anyhow!(
+                    "address_record_template needs every local address, so it can't be used \
+                     with source = \"external\""
+                ));
+            }
+        }
+        if let Some(path) = &self.metrics_textfile {
+            if path.file_name().is_none() || path.parent().map_or(true, |p| !p.is_dir()) {
+                return Err(anyhow::anyhow!(
+                    "metrics_textfile must be a file in an existing directory, got: {}",
+                    path.display()
+                ));
+            }
+        }
+        if let Some(path) = &self.state_file {
+            if path.file_name().is_none() || path.parent().map_or(true, |p| !p.is_dir()) {
+                return Err(anyhow::anyhow!(
+                    "state_file must be a file in an existing directory, got: {}",
+                    path.display()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the time allowed to establish a connection: `connect_timeout`,
+    /// or `timeout` if unset
+    pub fn connect_timeout(&self) -> Duration {
+        self.connect_timeout.unwrap_or(self.timeout)
+    }
+
+    /// Returns the policy for names holding several records: `multi_record`,
+    /// or if unset `all` for Route 53, whose UPSERT replaces the whole record
+    /// set anyway, and `error` for every other provider
+    pub fn multi_record(&self) -> MultiRecordPolicy {
+        self.multi_record.unwrap_or(match self.provider_type {
+            ProviderType::Route53 => MultiRecordPolicy::UpdateAll,
+            _ => MultiRecordPolicy::Error,
+        })
+    }
+
+    /// Returns the record types published under each name (`record_type`)
+    pub fn record_types(&self) -> Vec<RecordType> {
+        let mut types = Vec::new();
+        if self.enable_ipv6 {
+            types.push(RecordType::Aaaa);
+        }
+        if self.enable_ipv4 {
+            types.push(RecordType::A);
+        }
+        types
+    }
+
+    /// Returns the address families that must be synced for the daemon to be
+    /// healthy: `required_families`, or every configured family if unset
+    pub fn required_record_types(&self) -> Vec<RecordType> {
+        match &self.required_families {
+            Some(families) => families.clone(),
+            None => self.record_types(),
+        }
+    }
+
+    /// Returns the User-Agent sent to DNS providers: `ipv6ddns/1.0`, followed
+    /// by `user_agent_suffix` if set
+    pub fn user_agent(&self) -> String {
+        match &self.user_agent_suffix {
+            Some(suffix) => format!("{} {}", CLOUDFLARE_USER_AGENT, suffix.trim()),
+            None => CLOUDFLARE_USER_AGENT.to_string(),
+        }
+    }
+}
+
+/// Reads an environment variable, falling back to a legacy alias
+///
+/// The provider-neutral name wins when both are set; empty values are ignored.
+///
+/// # Arguments
+///
+/// * `name` - The preferred environment variable name
+/// * `alias` - The legacy name checked when `name` is unset or empty
+///
+/// # Returns
+///
+/// Returns the first non-empty value, or `None` if neither is set
+fn env_with_alias(name: &str, alias: &str) -> Option<String> {
+    [name, alias]
+        .into_iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|v| !v.is_empty())
+}
+
+/// Resolves a secret given inline or as a file path
+///
+/// Setting both is an error rather than silently preferring one. File contents
+/// have trailing newlines trimmed, so files written by `echo` or secret
+/// managers work as-is.
+///
+/// # Arguments
+///
+/// * `inline` - The inline value, if set (empty counts as unset)
+/// * `file` - Path to a file containing the value, if set
+/// * `inline_name` - Name of the inline setting, for error messages
+/// * `file_name` - Name of the file setting, for error messages
+///
+/// # Returns
+///
+/// Returns the secret, `None` if neither source is set, or an error if both
+/// are set or the file cannot be read
+fn secret_value(
+    inline: Option<String>,
+    file: Option<&Path>,
+    inline_name: &str,
+    file_name: &str,
+) -> Result<Option<zeroize::Zeroizing<String>>> {
+    let inline = inline
+        .filter(|v| !v.is_empty())
+        .map(zeroize::Zeroizing::new);
+    match (inline, file) {
+        (Some(_), Some(_)) => Err(anyhow::anyhow!(
+            "Both {} and {} are set; use only one",
+            inline_name,
+            file_name
+        )),
+        (Some(v), None) => Ok(Some(v)),
+        (None, Some(path)) => read_secret_file(path, file_name).map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Reads a secret from a file, trimming trailing newlines
+///
+/// # Arguments
+///
+/// * `path` - Path to the file
+/// * `name` - Name of the setting, for error messages
+///
+/// # Returns
+///
+/// Returns the secret or an error if the file cannot be read
+fn read_secret_file(path: &Path, name: &str) -> Result<zeroize::Zeroizing<String>> {
+    let mut content = zeroize::Zeroizing::new(
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}: {}", name, path.display()))?,
+    );
+    let trimmed_len = content.trim_end_matches(['\n', '\r']).len();
+    content.truncate(trimmed_len);
+    Ok(content)
+}
+
+/// Parses a boolean value from an environment variable
+///
+/// This function accepts multiple string representations of boolean values:
+/// - `true`: "1", "true", "yes", "on"
+/// - `false`: "0", "false", "no", "off"
+///
+/// # Arguments
+///
+/// * `value` - The string value to parse
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed boolean or an error if invalid
+fn parse_bool_env(value: &str) -> Result<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(anyhow::anyhow!(
+            "expected boolean (true/false/1/0/yes/no/on/off)"
+        )),
+    }
+}
+
+/// Reads the config file at `path`, or standard input if `path` is `-`
+///
+/// The contents are returned in a `Zeroizing` buffer, since they may hold
+/// the API token.
+///
+/// # Arguments
+///
+/// * `path` - Path given with `--config`
+///
+/// # Returns
+///
+/// Returns the contents, `None` if the file does not exist, or an error if
+/// it cannot be read
+fn read_config_source(path: &Path) -> Result<Option<zeroize::Zeroizing<String>>> {
+    if path == Path::new(STDIN_CONFIG_PATH) {
+        return read_config(std::io::stdin().lock())
+            .context("Failed to read config from stdin")
+            .map(Some);
+    }
+    if !path.exists() {
+        return Ok(None);
+    }
+    std::fs::File::open(path)
+        .map_err(anyhow::Error::from)
+        .and_then(read_config)
+        .with_context(|| format!("Failed to read config: {}", path.display()))
+        .map(Some)
+}
+
+/// Reads a whole config from `reader` into a `Zeroizing` buffer
+fn read_config(mut reader: impl std::io::Read) -> Result<zeroize::Zeroizing<String>> {
+    let mut content = zeroize::Zeroizing::new(String::new());
+    reader.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Parses a config file, choosing the format from its extension
+///
+/// `.json` is parsed as JSON, `.yaml`/`.yml` as YAML, and anything else
+/// (including `.toml` and a config read from stdin) as TOML.
+///
+/// # Arguments
+///
+/// * `path` - Path of the config file, used to pick the format
+/// * `content` - The file contents
+///
+/// # Returns
+///
+/// Returns the parsed `FileConfig` or an error if the contents don't match the format
+fn parse_config_file(path: &Path, content: &str) -> Result<FileConfig> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("toml") => toml::from_str(content).context("Failed to parse TOML config file"),
+        Some("json") => serde_json::from_str(content).context("Failed to parse JSON config file"),
+        Some("yaml" | "yml") => {
+            serde_yaml::from_str(content).context("Failed to parse YAML config file")
+        }
+        None if path == Path::new(STDIN_CONFIG_PATH) => {
+            toml::from_str(content).context("Failed to parse TOML config from stdin")
+        }
+        _ => toml::from_str(content).with_context(|| {
+            format!(
+                "Failed to parse config file {} as TOML (supported formats: .toml, .json, .yaml, .yml)",
+                path.display()
+            )
+        }),
+    }
+}
+
+/// Config file structure, shared by the TOML, JSON and YAML formats
+#[derive(Debug, serde::Deserialize)]
+struct FileConfig {
+    api_token: Option<String>,
+    api_token_file: Option<PathBuf>,
+    zone_id: Option<String>,
+    zone_id_file: Option<PathBuf>,
+    zone_name: Option<String>,
+    strict_zone_id: Option<bool>,
+    secret_key: Option<String>,
+    secret_key_file: Option<PathBuf>,
+    api_token_credential: Option<String>,
+    #[serde(rename = "record_name")]
+    record_name: Option<RecordNames>,
+    timeout: Option<u64>,
+    connect_timeout: Option<u64>,
+    user_agent_suffix: Option<String>,
+    http_proxy: Option<String>,
+    https_proxy: Option<String>,
+    api_base: Option<String>,
+    ca_cert_file: Option<PathBuf>,
+    #[serde(rename = "poll_interval")]
+    poll_interval: Option<u64>,
+    poll_jitter: Option<f64>,
+    netlink_max_drain: Option<usize>,
+    startup_jitter_secs: Option<u64>,
+    force_update_interval: Option<u64>,
+    verbose: Option<bool>,
+    log_format: Option<String>,
+    log_target: Option<String>,
+    log_file: Option<PathBuf>,
+    log_rotation: Option<String>,
+    syslog_facility: Option<String>,
+    multi_record: Option<String>,
+    sticky_record: Option<bool>,
+    reconcile_fields: Option<Vec<String>>,
+    stamp_comment: Option<bool>,
+    allow_loopback: Option<bool>,
+    allow_link_local: Option<bool>,
+    allow_unique_local: Option<bool>,
+    allow_documentation: Option<bool>,
+    exclude_prefixes: Option<Vec<String>>,
+    source: Option<String>,
+    external_ip_url: Option<String>,
+    detect_command: Option<HookCommand>,
+    accepted_scopes: Option<Vec<String>>,
+    netns: Option<String>,
+    address_selection: Option<String>,
+    address_preference: Option<String>,
+    selection_stable_weight: Option<i64>,
+    selection_lifetime_weight: Option<i64>,
+    selection_interfaces: Option<Vec<String>>,
+    selection_interface_weight: Option<i64>,
+    selection_prefix: Option<String>,
+    selection_prefix_weight: Option<i64>,
+    prefix_regions: Option<BTreeMap<String, String>>,
+    allowed_regions: Option<Vec<String>>,
+    route_table: Option<u32>,
+    route_fwmark: Option<u32>,
+    allow_create: Option<bool>,
+    enable_ipv4: Option<bool>,
+    record_type: Option<String>,
+    required_families: Option<Vec<String>>,
+    allow_private_ipv4: Option<bool>,
+    strict_mode: Option<bool>,
+    read_only: Option<bool>,
+    pause_file: Option<PathBuf>,
+    lock_file: Option<PathBuf>,
+    ready_on: Option<String>,
+    ready_file: Option<PathBuf>,
+    ready_timeout: Option<u64>,
+    on_removal: Option<String>,
+    delete_cooldown: Option<u64>,
+    removal_grace_secs: Option<u64>,
+    delete_on_shutdown: Option<bool>,
+    verify_after_update: Option<bool>,
+    verify_delay_secs: Option<u64>,
+    verify_propagation: Option<bool>,
+    propagation_resolver: Option<String>,
+    error_threshold: Option<u64>,
+    max_retries: Option<u32>,
+    max_error_body_len: Option<usize>,
+    max_concurrent_updates: Option<usize>,
+    max_consecutive_errors: Option<u64>,
+    max_sync_age: Option<u64>,
+    external_resolver: Option<String>,
+    external_check_interval_secs: Option<u64>,
+    address_record_template: Option<String>,
+    address_records_only: Option<bool>,
+    address_record_limit: Option<usize>,
+    heartbeat_txt: Option<String>,
+    provider_type: Option<String>,
+    health_port: Option<u16>,
+    #[serde(alias = "on_change_command")]
+    post_update_command: Option<HookCommand>,
+    hook_shell: Option<bool>,
+    hook_timeout: Option<u64>,
+    webhook_url: Option<String>,
+    metrics_textfile: Option<PathBuf>,
+    state_file: Option<PathBuf>,
+}
+
+/// `record_name` value in the TOML file: a single name or a list of names
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum RecordNames {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl RecordNames {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::One(name) => parse_record_list(&name),
+            Self::Many(names) => names
+                .into_iter()
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// Splits a comma-separated list of record names, dropping empty entries
+///
+/// # Arguments
+///
+/// * `value` - The list to parse (e.g., "home.example.com,nas.example.com")
+///
+/// # Returns
+///
+/// Returns the trimmed record names in their original order
+pub fn parse_record_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Builds the name of a per-address record from `address_record_template`
+///
+/// The template replaces the first label of the record name: `{label}` stands
+/// for that label and `{n}` for the address number.
+///
+/// # Arguments
+///
+/// * `template` - The naming template (e.g., "{label}-{n}")
+/// * `record` - The configured record name (e.g., "host.example.com")
+/// * `n` - The 1-based address number
+///
+/// # Returns
+///
+/// Returns the record name (e.g., "host-1.example.com")
+pub fn address_record_name(template: &str, record: &str, n: usize) -> String {
+    let (label, rest) = match record.split_once('.') {
+        Some((label, rest)) => (label, Some(rest)),
+        None => (record, None),
+    };
+    let name = template
+        .replace("{label}", label)
+        .replace("{n}", &n.to_string());
+    match rest {
+        Some(rest) => format!("{}.{}", name, rest),
+        None => name,
+    }
+}
+
+/// Parses a multi-record policy string into a `MultiRecordPolicy` enum
+///
+/// This function accepts multiple aliases for each policy type:
+/// - `Error`: "error", "fail", "reject"
+/// - `UpdateFirst`: "first", "update_first", "updatefirst"
+/// - `UpdateAll`: "all", "update_all", "updateall"
+///
+/// # Arguments
+///
+/// * `value` - The policy string to parse
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `MultiRecordPolicy` or an error
+/// if the value is invalid.
+pub fn parse_multi_record(value: &str) -> Result<MultiRecordPolicy> {
+    let normalized = value.trim().to_ascii_lowercase();
+    match normalized.as_str() {
+        "error" | "fail" | "reject" => Ok(MultiRecordPolicy::Error),
+        "first" | "update_first" | "updatefirst" => Ok(MultiRecordPolicy::UpdateFirst),
+        "all" | "update_all" | "updateall" => Ok(MultiRecordPolicy::UpdateAll),
+        _ => Err(anyhow::anyhow!(
+            "Invalid multi_record policy: '{}'. Use: error|first|all",
+            value
+        )),
+    }
+}
+
+/// Parses `record_type` into whether AAAA and A records are published
+///
+/// # Arguments
+///
+/// * `value` - `AAAA`, `A` or `both` (case-insensitive)
+///
+/// # Returns
+///
+/// Returns `(enable_ipv6, enable_ipv4)`, or an error if the value is unknown
+pub fn parse_record_types(value: &str) -> Result<(bool, bool)> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "aaaa" => Ok((true, false)),
+        "a" => Ok((false, true)),
+        "both" => Ok((true, true)),
+        _ => Err(anyhow::anyhow!(
+            "Invalid record_type: '{}'. Use: AAAA|A|both",
+            value
+        )),
+    }
+}
+
+/// Parses a `required_families` entry
+///
+/// # Arguments
+///
+/// * `value` - `ipv6` or `ipv4` (case-insensitive)
+///
+/// # Returns
+///
+/// Returns the record type of the family, or an error if the name is unknown
+pub fn parse_address_family(value: &str) -> Result<RecordType> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "ipv6" => Ok(RecordType::Aaaa),
+        "ipv4" => Ok(RecordType::A),
+        _ => Err(anyhow::anyhow!(
+            "Invalid required_families entry: '{}'. Use: ipv6|ipv4",
+            value
+        )),
+    }
+}
+
+/// Parses the `reconcile_fields` list into a `ReconcileFields` set
+///
+/// `content` is always enabled; each listed field (`content`, `ttl`,
+/// `proxied`, `comment`) is added to it.
+///
+/// # Arguments
+///
+/// * `values` - The field names to enable
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `ReconcileFields` or an error
+/// if a field name is unknown.
+pub fn parse_reconcile_fields(values: &[String]) -> Result<ReconcileFields> {
+    let mut fields = ReconcileFields::default();
+    for value in values {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "content" => fields.content = true,
+            "ttl" => fields.ttl = true,
+            "proxied" => fields.proxied = true,
+            "comment" => fields.comment = true,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Invalid reconcile_fields entry: '{}'. Use: content|ttl|proxied|comment",
+                    value
+                ))
+            }
+        }
+    }
+    Ok(fields)
+}
+
+/// What the daemon does with the DNS record when the IPv6 address disappears
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalPolicy {
+    /// Leave the record pointing at the last published address (default)
+    Keep,
+    /// Delete the record once no global IPv6 address remains
+    Delete,
+}
+
+/// Parses a removal policy string into a `RemovalPolicy` enum
+///
+/// # Arguments
+///
+/// * `value` - The policy string to parse ("keep" or "delete")
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `RemovalPolicy` or an error
+/// if the value is invalid.
+pub fn parse_removal_policy(value: &str) -> Result<RemovalPolicy> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "keep" => Ok(RemovalPolicy::Keep),
+        "delete" => Ok(RemovalPolicy::Delete),
+        _ => Err(anyhow::anyhow!(
+            "Invalid on_removal policy: '{}'. Use: keep|delete",
+            value
+        )),
+    }
+}
+
+/// When the daemon reports itself ready
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadyPolicy {
+    /// Once the initial sync has been attempted, whatever its result (default)
+    Start,
+    /// Once every required record has been synced
+    FirstSync,
+}
+
+/// Parses a readiness policy string into a `ReadyPolicy` enum
+///
+/// # Arguments
+///
+/// * `value` - The policy string to parse ("start" or "first_sync")
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `ReadyPolicy` or an error
+pub fn parse_ready_policy(value: &str) -> Result<ReadyPolicy> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "start" => Ok(ReadyPolicy::Start),
+        "first_sync" => Ok(ReadyPolicy::FirstSync),
+        _ => Err(anyhow::anyhow!(
+            "Invalid ready_on policy: '{}'. Use: start|first_sync",
+            value
+        )),
+    }
+}
+
+/// Parses a provider name into a `ProviderType` enum
+///
+/// # Arguments
+///
+/// * `value` - The provider name to parse (one of `ProviderType::ALL`)
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `ProviderType` or an error
+/// listing the supported providers.
+pub fn parse_provider_type(value: &str) -> Result<ProviderType> {
+    ProviderType::from_name(value).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid {}: '{}'. Use: {}",
+            ENV_PROVIDER_TYPE,
+            value,
+            ProviderType::supported()
+        )
+    })
+}
+
+/// Where the address to publish comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressSource {
+    /// The host's interfaces, watched via netlink or polled (default)
+    Netlink,
+    /// An HTTPS service answering with the caller's address, polled
+    External,
+    /// A command printing the address (`detect_command`), polled
+    Command,
+}
+
+/// Parses an address source string into an `AddressSource` enum
+///
+/// # Arguments
+///
+/// * `value` - The source string to parse ("netlink", "external" or "command")
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `AddressSource` or an error
+/// if the value is invalid.
+pub fn parse_address_source(value: &str) -> Result<AddressSource> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "netlink" => Ok(AddressSource::Netlink),
+        "external" => Ok(AddressSource::External),
+        "command" => Ok(AddressSource::Command),
+        _ => Err(anyhow::anyhow!(
+            "Invalid source: '{}'. Use: netlink|external|command",
+            value
+        )),
+    }
+}
+
+/// Log output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable text (default)
+    Text,
+    /// One JSON object per event
+    Json,
+}
+
+/// Parses a log format string into a `LogFormat` enum
+///
+/// # Arguments
+///
+/// * `value` - The format string to parse ("text" or "json")
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `LogFormat` or an error
+/// if the value is invalid.
+pub fn parse_log_format(value: &str) -> Result<LogFormat> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "text" => Ok(LogFormat::Text),
+        "json" => Ok(LogFormat::Json),
+        _ => Err(anyhow::anyhow!(
+            "Invalid log_format: '{}'. Use: text|json",
+            value
+        )),
+    }
+}
+
+/// Where logs go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogTarget {
+    /// Standard output, or the journal with the `journald` feature
+    Stdout,
+    /// The file named by `log_file`
+    File,
+    /// The local syslog daemon
+    Syslog,
+}
+
+/// Parses a log target string into a `LogTarget` enum
+///
+/// # Arguments
+///
+/// * `value` - The target string to parse ("stdout", "file" or "syslog")
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `LogTarget` or an error
+/// if the value is invalid.
+pub fn parse_log_target(value: &str) -> Result<LogTarget> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "stdout" => Ok(LogTarget::Stdout),
+        "file" => Ok(LogTarget::File),
+        "syslog" => Ok(LogTarget::Syslog),
+        _ => Err(anyhow::anyhow!(
+            "Invalid log_target: '{}'. Use: stdout|file|syslog",
+            value
+        )),
+    }
+}
+
+/// When the log file is rotated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRotation {
+    /// Always write to the same file (default)
+    Never,
+    /// Start a new, dated file every day
+    Daily,
+}
+
+/// Parses a log rotation string into a `LogRotation` enum
+///
+/// # Arguments
+///
+/// * `value` - The rotation string to parse ("never" or "daily")
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `LogRotation` or an error
+/// if the value is invalid.
+pub fn parse_log_rotation(value: &str) -> Result<LogRotation> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "never" => Ok(LogRotation::Never),
+        "daily" => Ok(LogRotation::Daily),
+        _ => Err(anyhow::anyhow!(
+            "Invalid log_rotation: '{}'. Use: daily|never",
+            value
+        )),
+    }
+}
+
+//==============================================================================
+// Example Config
+//==============================================================================
+
+/// Fully commented example config.toml documenting every key
+pub const EXAMPLE_CONFIG: &str = include_str!("../etc/config.toml");
+
+/// Writes the example config for `--generate-config`
+///
+/// # Arguments
+///
+/// * `output` - Path to write to (must not exist yet), or `None` for stdout
+///
+/// # Returns
+///
+/// Returns `Ok(())` or an error if the file already exists or cannot be written
+pub fn write_example_config(output: Option<&Path>) -> Result<()> {
+    use std::io::Write as _;
+
+    match output {
+        Some(out) => {
+            use std::os::unix::fs::OpenOptionsExt as _;
+
+            // 0600 because the user is expected to add the API token to it
+            let mut file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o600)
+                .open(out)
+                .with_context(|| format!("Failed to create {}", out.display()))?;
+            file.write_all(EXAMPLE_CONFIG.as_bytes())
+                .with_context(|| format!("Failed to write {}", out.display()))?;
+            eprintln!("Wrote {}", out.display());
+        }
+        None => print!("{}", EXAMPLE_CONFIG),
+    }
+    Ok(())
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    struct EnvGuard {
+        saved: Vec<(&'static str, Option<String>)>,
+    }
+
+    impl EnvGuard {
+        fn new() -> Self {
+            let keys = [
+                ENV_API_TOKEN,
+                ENV_ZONE_ID,
+                ENV_RECORD_NAME,
+                ENV_CLOUDFLARE_API_TOKEN,
+                ENV_CLOUDFLARE_ZONE_ID,
+                ENV_CLOUDFLARE_RECORD_NAME,
+                ENV_API_TOKEN_FILE,
+                ENV_ZONE_ID_FILE,
+                ENV_SECRET_KEY,
+                ENV_SECRET_KEY_FILE,
+                ENV_CLOUDFLARE_API_TOKEN_FILE,
+                ENV_CLOUDFLARE_ZONE_ID_FILE,
+                ENV_CREDENTIALS_DIRECTORY,
+                ENV_MULTI_RECORD,
+                ENV_ALLOW_LOOPBACK,
+                ENV_ALLOW_CREATE,
+                ENV_ENABLE_IPV4,
+                ENV_RECORD_TYPE,
+                ENV_ALLOW_PRIVATE_IPV4,
+                ENV_STRICT_MODE,
+                ENV_READ_ONLY,
+                ENV_ON_REMOVAL,
+                ENV_DELETE_ON_SHUTDOWN,
+                ENV_LOG_FORMAT,
+            ];
+            let mut saved = Vec::with_capacity(keys.len());
+            for key in keys {
+                saved.push((key, std::env::var(key).ok()));
+                std::env::remove_var(key);
+            }
+            Self { saved }
+        }
+    }
+
+    impl Drop for EnvGuard {
+        fn drop(&mut self) {
+            for (key, value) in self.saved.drain(..) {
+                if let Some(val) = value {
+                    std::env::set_var(key, val);
+                } else {
+                    std::env::remove_var(key);
+                }
+            }
+        }
+    }
+
+    fn write_config(contents: &str) -> (TempDir, PathBuf) {
+        write_config_named("config.toml", contents)
+    }
+
+    fn write_config_named(name: &str, contents: &str) -> (TempDir, PathBuf) {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join(name);
+        std::fs::write(&path, contents).expect("write config");
+        (dir, path)
+    }
+
+    #[test]
+    #[serial]
+    fn config_load_from_file() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "file_token_123456789012345678901234567890"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+timeout = 45
+poll_interval = 90
+verbose = true
+multi_record = "all"
+allow_loopback = true
+allow_create = true
+"#,
+        );
+
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.api_token.as_str(),
+            "file_token_123456789012345678901234567890"
+        );
+        assert_eq!(cfg.zone_id.as_str(), "0123456789abcdef0123456789abcdef");
+        assert_eq!(cfg.records, vec!["example.com"]);
+        assert_eq!(cfg.timeout, Duration::from_secs(45));
+        assert_eq!(cfg.poll_interval, Duration::from_secs(90));
+        assert!(cfg.verbose);
+        assert!(matches!(cfg.multi_record(), MultiRecordPolicy::UpdateAll));
+        assert!(cfg.ipv6_filter.allow_loopback);
+        assert!(cfg.allow_create);
+    }
+
+    #[test]
+    #[serial]
+    fn config_env_overrides_file() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "file_token_123456789012345678901234567890"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+allow_loopback = false
+"#,
+        );
+
+        std::env::set_var(ENV_API_TOKEN, "env_token_123456789012345678901234567890");
+        std::env::set_var(ENV_ZONE_ID, "envzone0123456789abcdef0123456789ab");
+        std::env::set_var(ENV_RECORD_NAME, "example.com");
+        std::env::set_var(ENV_ALLOW_LOOPBACK, "true");
+
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.api_token.as_str(),
+            "env_token_123456789012345678901234567890"
+        );
+        assert_eq!(cfg.zone_id.as_str(), "envzone0123456789abcdef0123456789ab");
+        assert_eq!(cfg.records, vec!["example.com"]);
+        assert!(cfg.ipv6_filter.allow_loopback);
+    }
+
+    #[test]
+    #[serial]
+    fn config_missing_required_fields() {
+        let _env = EnvGuard::new();
+        let err = Config::load(None, &CliOverrides::default()).expect_err("missing required");
+        let msg = format!("{err}");
+        assert!(
+            msg.starts_with("Missing ")
+                || msg.contains("Missing required")
+                || msg.contains("missing required")
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn config_api_token_too_short() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "short"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("token too short");
+        let msg = format!("{err}");
+        assert!(msg.contains("too short"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_zone_id_invalid_format() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "invalid-zone-id!"
+record_name = "example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("zone id invalid");
+        let msg = format!("{err}");
+        assert!(msg.contains("alphanumeric"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_zone_id_invalid_length() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "short"
+record_name = "example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("zone id length");
+        let msg = format!("{err}");
+        assert!(msg.contains("invalid length"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_strict_zone_id() {
+        let _env = EnvGuard::new();
+        let load = |zone_id: &str, strict: bool| {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "{zone_id}"
+record_name = "example.com"
+strict_zone_id = {strict}
+"#
+            ));
+            Config::load(Some(path), &CliOverrides::default())
+        };
+
+        let cfg = load("0123456789abcdef0123456789abcdef", true).expect("valid");
+        assert_eq!(cfg.zone_id.as_str(), "0123456789abcdef0123456789abcdef");
+        // Uppercase hex is the same zone
+        let cfg = load("0123456789ABCDEF0123456789ABCDEF", true).expect("uppercase");
+        assert_eq!(cfg.zone_id.as_str(), "0123456789abcdef0123456789abcdef");
+
+        // Not hex, too short, too long
+        for zone_id in [
+            "0123456789abcdefg123456789abcdef",
+            "0123456789abcdef0123456789abcde",
+            "0123456789abcdef0123456789abcdef0",
+        ] {
+            let err = load(zone_id, true).expect_err(zone_id);
+            assert!(format!("{err}").contains("strict_zone_id"), "{zone_id}");
+        }
+
+        // The lenient default takes any plausible alphanumeric ID as given
+        let cfg = load("0123456789ABCDEFg123456789abcdef0", false).expect("lenient");
+        assert_eq!(cfg.zone_id.as_str(), "0123456789ABCDEFg123456789abcdef0");
+    }
+
+    #[test]
+    #[serial]
+    fn config_zone_name() {
+        let _env = EnvGuard::new();
+        let load = |extra: &str| {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+record_name = "home.example.com"
+{extra}
+"#
+            ));
+            Config::load(Some(path), &CliOverrides::default())
+        };
+
+        let cfg = load(r#"zone_name = "Example.COM.""#).expect("zone_name");
+        assert_eq!(cfg.zone_name.as_deref(), Some("example.com"));
+        assert!(cfg.zone_id.as_str().is_empty());
+        // The ID checks don't apply to a name
+        assert!(load("zone_name = \"example.com\"\nstrict_zone_id = true").is_ok());
+
+        let err = load(
+            r#"zone_name = "example.com"
+zone_id = "0123456789abcdef0123456789abcdef""#,
+        )
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("only one of"));
+
+        let err = load("").unwrap_err();
+        assert!(format!("{err:#}").contains("zone_name"));
+
+        let err = load(r#"zone_name = "not a domain""#).unwrap_err();
+        assert!(format!("{err:#}").contains("zone_name"));
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "duckdns"
+api_token = "01234567-89ab-cdef-0123-456789abcdef"
+record_name = "home.duckdns.org"
+zone_name = "example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("only supported with the cloudflare provider"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_stamp_comment() {
+        let _env = EnvGuard::new();
+        let load = |extra: &str| {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "home.example.com"
+{extra}
+"#
+            ));
+            Config::load(Some(path), &CliOverrides::default())
+        };
+
+        assert!(!load("").unwrap().stamp_comment);
+        assert!(load("stamp_comment = true").unwrap().stamp_comment);
+
+        let err = load("stamp_comment = true\nreconcile_fields = [\"comment\"]").unwrap_err();
+        assert!(format!("{err:#}").contains("stamp_comment"));
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "duckdns"
+api_token = "01234567-89ab-cdef-0123-456789abcdef"
+record_name = "home.duckdns.org"
+stamp_comment = true
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("only supported with the cloudflare provider"));
+    }
+
+    #[test]
+    fn parse_multi_record_valid_and_invalid() {
+        assert!(matches!(
+            parse_multi_record("first").unwrap(),
+            MultiRecordPolicy::UpdateFirst
+        ));
+        assert!(parse_multi_record("bogus").is_err());
+    }
+
+    // Additional edge case tests for config parsing
+
+    #[test]
+    #[serial]
+    fn config_timeout_boundary_values() {
+        let _env = EnvGuard::new();
+        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
+        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
+        std::env::set_var(ENV_RECORD_NAME, "example.com");
+
+        // Test minimum timeout via config file
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+timeout = 1
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.timeout, Duration::from_secs(1));
+
+        // Test maximum timeout via config file
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+timeout = 300
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.timeout, Duration::from_secs(300));
+
+        // Test timeout below minimum
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+timeout = 0
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("timeout too low");
+        assert!(format!("{err}").contains("timeout"));
+
+        // Test timeout above maximum
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+timeout = 301
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("timeout too high");
+        assert!(format!("{err}").contains("timeout"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_connect_timeout() {
+        let _env = EnvGuard::new();
+        let load = |extra: &str| {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+timeout = 60
+{extra}
+"#
+            ));
+            Config::load(Some(path), &CliOverrides::default())
+        };
+
+        let cfg = load("").unwrap();
+        assert_eq!(cfg.connect_timeout, None);
+        assert_eq!(cfg.connect_timeout(), Duration::from_secs(60));
+
+        let cfg = load("connect_timeout = 5").unwrap();
+        assert_eq!(cfg.connect_timeout(), Duration::from_secs(5));
+        assert_eq!(cfg.timeout, Duration::from_secs(60));
+
+        for bad in ["0", "61"] {
+            let err = load(&format!("connect_timeout = {bad}")).unwrap_err();
+            assert!(format!("{err}").contains("connect_timeout"));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn config_user_agent_suffix() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.user_agent(), "ipv6ddns/1.0");
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+user_agent_suffix = "(host=router1)"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.user_agent(), "ipv6ddns/1.0 (host=router1)");
+
+        // A newline would let the suffix inject extra request headers
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+user_agent_suffix = "router1\r\nX-Injected: 1"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("control chars");
+        assert!(format!("{err}").contains("user_agent_suffix"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+user_agent_suffix = " "
+"#,
+        );
+        assert!(Config::load(Some(path), &CliOverrides::default()).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn config_proxy() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+https_proxy = "http://proxy.example.com:3128"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.https_proxy.as_deref(),
+            Some("http://proxy.example.com:3128")
+        );
+        assert!(cfg.http_proxy.is_none());
+        assert_eq!(cfg.api_base, CLOUDFLARE_API_BASE);
+        assert!(cfg.ca_cert_file.is_none());
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+provider_type = "duckdns"
+record_name = "myhost.duckdns.org"
+https_proxy = "http://proxy.example.com:3128"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("provider");
+        assert!(format!("{err}").contains("only supported with the cloudflare provider"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_api_base() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+api_base = "https://dns-gateway.internal/client/v4"
+ca_cert_file = "/etc/ipv6ddns/ca.pem"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.api_base, "https://dns-gateway.internal/client/v4");
+        assert_eq!(
+            cfg.ca_cert_file.as_deref(),
+            Some(Path::new("/etc/ipv6ddns/ca.pem"))
+        );
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+api_base = "ftp://dns-gateway.internal"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("scheme");
+        assert!(format!("{err}").contains("api_base must use http or https"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+provider_type = "duckdns"
+record_name = "myhost.duckdns.org"
+api_base = "https://dns-gateway.internal/client/v4"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("provider");
+        assert!(format!("{err}").contains("api_base is only supported"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_poll_interval_boundary_values() {
+        let _env = EnvGuard::new();
+        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
+        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
+        std::env::set_var(ENV_RECORD_NAME, "example.com");
+
+        // Test minimum poll interval via config file
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+poll_interval = 10
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.poll_interval, Duration::from_secs(10));
+
+        // Test maximum poll interval via config file
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+poll_interval = 3600
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.poll_interval, Duration::from_secs(3600));
+
+        // Test poll interval below minimum
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+poll_interval = 9
+"#,
+        );
+        let err =
+            Config::load(Some(path), &CliOverrides::default()).expect_err("poll interval too low");
+        assert!(format!("{err}").contains("poll_interval"));
+
+        // Test poll interval above maximum
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+poll_interval = 3601
+"#,
+        );
+        let err =
+            Config::load(Some(path), &CliOverrides::default()).expect_err("poll interval too high");
+        assert!(format!("{err}").contains("poll_interval"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_api_token_exact_minimum_length() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "01234567890123456789012345678901"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.api_token.as_str().len(), 32);
+    }
+
+    #[test]
+    #[serial]
+    fn config_zone_id_exact_minimum_length() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.zone_id.as_str().len(), 16);
+    }
+
+    #[test]
+    #[serial]
+    fn config_zone_id_exact_maximum_length() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef01234567"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.zone_id.as_str().len(), 40);
+    }
+
+    #[test]
+    #[serial]
+    fn config_multi_record_policy_variants() {
+        let _env = EnvGuard::new();
+        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
+        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
+        std::env::set_var(ENV_RECORD_NAME, "example.com");
+
+        // Test error policy variants
+        for policy in ["error", "fail", "reject"] {
+            std::env::set_var(ENV_MULTI_RECORD, policy);
+            let cfg = Config::load(None, &CliOverrides::default()).expect("config load");
+            assert!(matches!(cfg.multi_record(), MultiRecordPolicy::Error));
+        }
+
+        // Test first policy variants
+        for policy in ["first", "update_first", "updatefirst"] {
+            std::env::set_var(ENV_MULTI_RECORD, policy);
+            let cfg = Config::load(None, &CliOverrides::default()).expect("config load");
+            assert!(matches!(cfg.multi_record(), MultiRecordPolicy::UpdateFirst));
+        }
+
+        // Test all policy variants
+        for policy in ["all", "update_all", "updateall"] {
+            std::env::set_var(ENV_MULTI_RECORD, policy);
+            let cfg = Config::load(None, &CliOverrides::default()).expect("config load");
+            assert!(matches!(cfg.multi_record(), MultiRecordPolicy::UpdateAll));
+        }
+
+        std::env::remove_var(ENV_MULTI_RECORD);
+    }
+
+    #[test]
+    #[serial]
+    fn config_allow_loopback_variants() {
+        let _env = EnvGuard::new();
+        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
+        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
+        std::env::set_var(ENV_RECORD_NAME, "example.com");
+
+        // Test true variants
+        for value in ["1", "true", "yes", "on"] {
+            std::env::set_var(ENV_ALLOW_LOOPBACK, value);
+            let cfg = Config::load(None, &CliOverrides::default()).expect("config load");
+            assert!(cfg.ipv6_filter.allow_loopback);
+        }
+
+        // Test false variants
+        for value in ["0", "false", "no", "off"] {
+            std::env::set_var(ENV_ALLOW_LOOPBACK, value);
+            let cfg = Config::load(None, &CliOverrides::default()).expect("config load");
+            assert!(!cfg.ipv6_filter.allow_loopback);
+        }
+
+        std::env::remove_var(ENV_ALLOW_LOOPBACK);
+    }
+
+    #[test]
+    #[serial]
+    fn config_ipv6_filter() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.ipv6_filter, Ipv6Filter::default());
+        assert_eq!(cfg.accepted_scopes, vec![RT_SCOPE_UNIVERSE]);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+allow_documentation = true
+allow_unique_local = false
+allow_link_local = true
+exclude_prefixes = ["2a01:4f8:1234::/48", "2a01:4f8:5678::/48"]
+"#,
+        );
+        std::env::set_var(ENV_ALLOW_LOOPBACK, "true");
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.ipv6_filter,
+            Ipv6Filter {
+                allow_loopback: true,
+                allow_link_local: true,
+                allow_unique_local: false,
+                allow_documentation: true,
+                exclude_prefixes: vec![
+                    ("2a01:4f8:1234::".parse().unwrap(), 48),
+                    ("2a01:4f8:5678::".parse().unwrap(), 48),
+                ],
+            }
+        );
+        assert_eq!(cfg.accepted_scopes, vec![RT_SCOPE_UNIVERSE, RT_SCOPE_LINK]);
+    }
+
+    #[test]
+    #[serial]
+    fn config_post_update_command() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+post_update_command = ["/bin/sh", "-c", "true"]
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.post_update_command,
+            Some(HookCommand::Exec(vec![
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                "true".to_string()
+            ]))
+        );
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+post_update_command = ["/nonexistent/hook"]
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("missing program");
+        assert!(format!("{err:#}").contains("does not exist"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+post_update_command = "systemctl reload caddy"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("shell string");
+        assert!(format!("{err}").contains("hook_shell"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+post_update_command = "  "
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert!(cfg.post_update_command.is_none());
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+on_change_command = "ufw reload"
+hook_shell = true
+hook_timeout = 5
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.post_update_command,
+            Some(HookCommand::Shell("ufw reload".to_string()))
+        );
+        assert_eq!(cfg.hook_timeout, Duration::from_secs(5));
+    }
+
+    #[test]
+    #[serial]
+    fn config_metrics_textfile() {
+        let _env = EnvGuard::new();
+        let dir = tempfile::tempdir().unwrap();
+        let textfile = dir.path().join("ipv6ddns.prom");
+        let (_cfg_dir, path) = write_config(&format!(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+metrics_textfile = "{}"
+"#,
+            textfile.display()
+        ));
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.metrics_textfile, Some(textfile));
+
+        let (_cfg_dir, path) = write_config(&format!(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+metrics_textfile = "{}"
+"#,
+            dir.path().join("missing/ipv6ddns.prom").display()
+        ));
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("no dir");
+        assert!(format!("{err}").contains("metrics_textfile"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_state_file() {
+        let _env = EnvGuard::new();
+        let dir = tempfile::tempdir().unwrap();
+        let load = |state_file: &Path| {
+            let (_cfg_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+state_file = "{}"
+"#,
+                state_file.display()
+            ));
+            Config::load(Some(path), &CliOverrides::default())
+        };
+
+        let state_file = dir.path().join("state.json");
+        let cfg = load(&state_file).expect("config load");
+        assert_eq!(cfg.state_file, Some(state_file));
+
+        let err = load(&dir.path().join("missing/state.json")).expect_err("no dir");
+        assert!(format!("{err}").contains("state_file"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_webhook_url() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+webhook_url = "https://hooks.example.com/ddns"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.webhook_url.as_deref(),
+            Some("https://hooks.example.com/ddns")
+        );
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+webhook_url = "ftp://hooks.example.com/ddns"
+"#,
+        );
+        let err =
+            Config::load(Some(path), &CliOverrides::default()).expect_err("invalid webhook scheme");
+        assert!(format!("{err}").contains("webhook_url"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_on_removal_and_cooldown() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.on_removal, RemovalPolicy::Keep);
+        assert_eq!(
+            cfg.delete_cooldown,
+            Duration::from_secs(DEFAULT_DELETE_COOLDOWN_SECS)
+        );
+        assert!(!cfg.delete_on_shutdown);
+        assert_eq!(cfg.removal_grace, Duration::ZERO);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+on_removal = "delete"
+delete_cooldown = 120
+removal_grace_secs = 30
+delete_on_shutdown = true
+"#,
+        );
+        let cfg = Config::load(Some(path.clone()), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.on_removal, RemovalPolicy::Delete);
+        assert_eq!(cfg.delete_cooldown, Duration::from_secs(120));
+        assert_eq!(cfg.removal_grace, Duration::from_secs(30));
+        assert!(cfg.delete_on_shutdown);
+
+        std::env::set_var(ENV_ON_REMOVAL, "keep");
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.on_removal, RemovalPolicy::Keep);
+        assert!(parse_removal_policy("bogus").is_err());
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+removal_grace_secs = 86400
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("too long");
+        assert!(format!("{err}").contains("removal_grace_secs"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_multiple_records() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = ["home.example.com", "nas.example.com", "vpn.example.com"]
+"#,
+        );
+        let cfg = Config::load(Some(path.clone()), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.records,
+            vec!["home.example.com", "nas.example.com", "vpn.example.com"]
+        );
+
+        std::env::set_var(ENV_RECORD_NAME, "home.example.com, nas.example.com");
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.records, vec!["home.example.com", "nas.example.com"]);
+
+        std::env::set_var(ENV_RECORD_NAME, "home.example.com,home.example.com");
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("duplicate record");
+        assert!(format!("{err}").contains("Duplicate record name"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_env_aliases_prefer_neutral_names() {
+        let _env = EnvGuard::new();
+        std::env::set_var(
+            ENV_CLOUDFLARE_API_TOKEN,
+            "cfcfcfcfcfcfcfcfcfcfcfcfcfcfcfcfcfcfcfcf",
+        );
+        std::env::set_var(ENV_CLOUDFLARE_ZONE_ID, "0123456789abcdef0123456789abcdef");
+        std::env::set_var(ENV_CLOUDFLARE_RECORD_NAME, "legacy.example.com");
+        let cfg = Config::load(None, &CliOverrides::default()).expect("legacy names load");
+        assert_eq!(
+            cfg.api_token.as_str(),
+            "cfcfcfcfcfcfcfcfcfcfcfcfcfcfcfcfcfcfcfcf"
+        );
+        assert_eq!(cfg.records, vec!["legacy.example.com"]);
+
+        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
+        std::env::set_var(ENV_RECORD_NAME, "neutral.example.com");
+        std::env::set_var(ENV_ZONE_ID, "");
+        let cfg = Config::load(None, &CliOverrides::default()).expect("neutral names load");
+        assert_eq!(
+            cfg.api_token.as_str(),
+            "0123456789012345678901234567890123456789"
+        );
+        assert_eq!(cfg.records, vec!["neutral.example.com"]);
+        assert_eq!(cfg.zone_id.as_str(), "0123456789abcdef0123456789abcdef");
+    }
+
+    #[test]
+    fn parse_reconcile_fields_valid_and_invalid() {
+        let fields = parse_reconcile_fields(&["ttl".to_string(), "Comment".to_string()]).unwrap();
+        assert!(fields.content);
+        assert!(fields.ttl);
+        assert!(!fields.proxied);
+        assert!(fields.comment);
+        assert_eq!(
+            parse_reconcile_fields(&[]).unwrap(),
+            ReconcileFields::default()
+        );
+        assert!(parse_reconcile_fields(&["bogus".to_string()]).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn config_secrets_from_files() {
+        let _env = EnvGuard::new();
+        let dir = TempDir::new().expect("temp dir");
+        let token_path = dir.path().join("token");
+        let zone_path = dir.path().join("zone");
+        std::fs::write(&token_path, "0123456789012345678901234567890123456789\n").unwrap();
+        std::fs::write(&zone_path, "0123456789abcdef0123456789abcdef\r\n").unwrap();
+
+        let (_dir, path) = write_config(&format!(
+            r#"
+api_token_file = "{}"
+zone_id_file = "{}"
+record_name = "example.com"
+"#,
+            token_path.display(),
+            zone_path.display()
+        ));
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.api_token.as_str(),
+            "0123456789012345678901234567890123456789"
+        );
+        assert_eq!(cfg.zone_id.as_str(), "0123456789abcdef0123456789abcdef");
+
+        let (_dir, path) = write_config(&format!(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+api_token_file = "{}"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+            token_path.display()
+        ));
+        let err = Config::load(Some(path), &CliOverrides::default())
+            .expect_err("inline and file both set");
+        assert!(format!("{err}").contains("use only one"));
+
+        std::env::set_var(ENV_CLOUDFLARE_API_TOKEN_FILE, &token_path);
+        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
+        std::env::set_var(ENV_RECORD_NAME, "example.com");
+        let cfg = Config::load(None, &CliOverrides::default()).expect("token file from env");
+        assert_eq!(
+            cfg.api_token.as_str(),
+            "0123456789012345678901234567890123456789"
+        );
+
+        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
+        let err =
+            Config::load(None, &CliOverrides::default()).expect_err("env inline and file both set");
+        assert!(format!("{err}").contains("use only one"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_api_token_from_credentials_directory() {
+        let _env = EnvGuard::new();
+        let creds = TempDir::new().expect("temp dir");
+        std::fs::write(
+            creds.path().join("cloudflare_token"),
+            "0123456789012345678901234567890123456789\n",
+        )
+        .unwrap();
+        std::fs::write(
+            creds.path().join("ddns"),
+            "abcdefghijabcdefghijabcdefghijabcdefghij",
+        )
+        .unwrap();
+        std::env::set_var(ENV_CREDENTIALS_DIRECTORY, creds.path());
+        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
+        std::env::set_var(ENV_RECORD_NAME, "example.com");
+
+        let cfg =
+            Config::load(None, &CliOverrides::default()).expect("token from default credential");
+        assert_eq!(
+            cfg.api_token.as_str(),
+            "0123456789012345678901234567890123456789"
+        );
+
+        let (_dir, path) = write_config(
+            r#"
+api_token_credential = "ddns"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default())
+            .expect("token from named credential");
+        assert_eq!(
+            cfg.api_token.as_str(),
+            "abcdefghijabcdefghijabcdefghijabcdefghij"
+        );
+
+        std::env::set_var(ENV_API_TOKEN, "zyxwvutsrqzyxwvutsrqzyxwvutsrqzyxwvutsrq");
+        let cfg = Config::load(None, &CliOverrides::default()).expect("env token wins");
+        assert_eq!(
+            cfg.api_token.as_str(),
+            "zyxwvutsrqzyxwvutsrqzyxwvutsrqzyxwvutsrq"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn config_poll_jitter_bounds() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+poll_jitter = 0.2
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.poll_jitter, 0.2);
+        assert_eq!(cfg.startup_jitter, Duration::ZERO);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+startup_jitter_secs = 30
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.startup_jitter, Duration::from_secs(30));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+startup_jitter_secs = 3601
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("jitter too large");
+        assert!(format!("{err}").contains("startup_jitter_secs"));
+
+        for bad in ["-0.1", "0.6"] {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+poll_jitter = {bad}
+"#
+            ));
+            let err = Config::load(Some(path), &CliOverrides::default())
+                .expect_err("jitter out of range");
+            assert!(format!("{err}").contains("poll_jitter"));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn config_duckdns_provider() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "duckdns"
+api_token = "a7c4d0ad-114e-40ef-ba1d-d217904a50f2"
+record_name = ["home.duckdns.org", "nas"]
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.provider_type, ProviderType::DuckDns);
+        assert!(cfg.zone_id.as_str().is_empty());
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "duckdns"
+api_token = "a7c4d0ad-114e-40ef-ba1d-d217904a50f2"
+record_name = "home.example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("not duckdns");
+        assert!(format!("{err}").contains("DuckDNS record name"));
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "gandi"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("unsupported");
+        assert!(format!("{err}").contains("cloudflare|duckdns|route53|porkbun|namecheap|vultr"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_porkbun_provider() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "porkbun"
+api_token = "pk1_0123456789012345678901234567890123456789"
+secret_key = "sk1_0123456789012345678901234567890123456789"
+zone_id = "example.com"
+record_name = ["home.example.com", "example.com"]
+"#,
+        );
+        let cfg = Config::load(Some(path.clone()), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.provider_type, ProviderType::Porkbun);
+        assert_eq!(
+            cfg.secret_key.as_str(),
+            "sk1_0123456789012345678901234567890123456789"
+        );
+
+        std::env::set_var(ENV_SECRET_KEY, "sk1_from_env");
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.secret_key.as_str(), "sk1_from_env");
+        std::env::remove_var(ENV_SECRET_KEY);
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "porkbun"
+api_token = "pk1_0123456789012345678901234567890123456789"
+zone_id = "example.com"
+record_name = "home.example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("no secret");
+        assert!(format!("{err}").contains(ENV_SECRET_KEY));
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "porkbun"
+api_token = "pk1_0123456789012345678901234567890123456789"
+secret_key = "sk1_0123456789012345678901234567890123456789"
+zone_id = "example.com"
+record_name = "home.example.org"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("outside domain");
+        assert!(format!("{err}").contains("not in the Porkbun domain"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_namecheap_provider() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "namecheap"
+api_token = "0123456789abcdef0123456789abcdef"
+zone_id = "example.com"
+record_name = ["home.example.com", "example.com"]
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.provider_type, ProviderType::Namecheap);
+        assert_eq!(cfg.zone_id.as_str(), "example.com");
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "namecheap"
+api_token = "0123456789abcdef0123456789abcdef"
+zone_id = "example.com"
+record_name = "home.example.org"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("outside domain");
+        assert!(format!("{err}").contains("not in the Namecheap domain"));
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "namecheap"
+api_token = "0123456789abcdef0123456789abcdef"
+zone_id = "example.com"
+record_name = "home.example.com"
+on_removal = "delete"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("no delete");
+        assert!(format!("{err}").contains("can't delete records"));
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "namecheap"
+api_token = "0123456789abcdef0123456789abcdef"
+zone_id = "example.com"
+record_name = "home.example.com"
+verify_after_update = true
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("no verify");
+        assert!(format!("{err}").contains("can't list records"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_vultr_provider() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "vultr"
+api_token = "0123456789ABCDEFGHIJ0123456789ABCDEF"
+zone_id = "example.com"
+record_name = ["home.example.com", "example.com"]
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.provider_type, ProviderType::Vultr);
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "vultr"
+api_token = "0123456789ABCDEFGHIJ0123456789ABCDEF"
+record_name = "home.example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("no domain");
+        assert!(format!("{err}").contains(ENV_ZONE_ID));
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "vultr"
+api_token = "0123456789ABCDEFGHIJ0123456789ABCDEF"
+zone_id = "example.com"
+record_name = "home.example.org"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("outside domain");
+        assert!(format!("{err}").contains("not in the Vultr domain"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_route53_provider() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "route53"
+zone_id = "/hostedzone/Z0123456789ABC"
+record_name = "home.example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.provider_type, ProviderType::Route53);
+        assert_eq!(cfg.multi_record(), MultiRecordPolicy::UpdateAll);
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "route53"
+zone_id = "/hostedzone/Z0123456789ABC"
+record_name = "home.example.com"
+multi_record = "error"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.multi_record(), MultiRecordPolicy::Error);
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "route53"
+record_name = "home.example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("no zone");
+        assert!(format!("{err}").contains("Missing"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_error_threshold() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.error_threshold, 1);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+error_threshold = 3
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.error_threshold, 3);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+error_threshold = 0
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("zero");
+        assert!(format!("{err}").contains("error_threshold"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_max_retries() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.max_retries, DEFAULT_MAX_RETRIES);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+max_retries = 0
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.max_retries, 0);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+max_retries = 11
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("too many");
+        assert!(format!("{err}").contains("max_retries"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_force_update_interval() {
+        let _env = EnvGuard::new();
+        let load = |extra: &str| {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+{extra}
+"#
+            ));
+            Config::load(Some(path), &CliOverrides::default())
+        };
+
+        assert!(load("").unwrap().force_update_interval.is_zero());
+        assert_eq!(
+            load("force_update_interval = 86400")
+                .unwrap()
+                .force_update_interval,
+            Duration::from_secs(86400)
+        );
+        for invalid in [30, MAX_FORCE_UPDATE_INTERVAL_SECS + 1] {
+            let err = load(&format!("force_update_interval = {invalid}")).unwrap_err();
+            assert!(format!("{err}").contains("force_update_interval"));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn config_max_error_body_len() {
+        let _env = EnvGuard::new();
+        let load = |extra: &str| {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+{extra}
+"#
+            ));
+            Config::load(Some(path), &CliOverrides::default())
+        };
+
+        assert_eq!(
+            load("").unwrap().max_error_body_len,
+            DEFAULT_MAX_ERROR_BODY_LEN
+        );
+        assert_eq!(
+            load("max_error_body_len = 4096")
+                .unwrap()
+                .max_error_body_len,
+            4096
+        );
+        let err = load("max_error_body_len = 10").unwrap_err();
+        assert!(format!("{err}").contains("max_error_body_len"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_max_concurrent_updates() {
+        let _env = EnvGuard::new();
+        let load = |extra: &str| {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+{extra}
+"#
+            ));
+            Config::load(Some(path), &CliOverrides::default())
+        };
+
+        assert_eq!(
+            load("").unwrap().max_concurrent_updates,
+            DEFAULT_MAX_CONCURRENT_UPDATES
+        );
+        assert_eq!(
+            load("max_concurrent_updates = 4")
+                .unwrap()
+                .max_concurrent_updates,
+            4
+        );
+        for bad in ["0", "17"] {
+            let err = load(&format!("max_concurrent_updates = {bad}")).unwrap_err();
+            assert!(format!("{err}").contains("max_concurrent_updates"));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn config_max_consecutive_errors() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.max_consecutive_errors, 0);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+max_consecutive_errors = 20
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.max_consecutive_errors, 20);
+    }
+
+    #[test]
+    #[serial]
+    fn config_ready_on() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.ready_on, ReadyPolicy::Start);
+        assert_eq!(cfg.ready_file, None);
+        assert_eq!(cfg.ready_timeout, None);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+ready_on = "first_sync"
+ready_file = "/run/ipv6ddns/ready"
+ready_timeout = 300
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.ready_on, ReadyPolicy::FirstSync);
+        assert_eq!(cfg.ready_file, Some(PathBuf::from("/run/ipv6ddns/ready")));
+        assert_eq!(cfg.ready_timeout, Some(Duration::from_secs(300)));
+
+        // A timeout only makes sense while waiting for the first sync
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+ready_timeout = 300
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("no first_sync");
+        assert!(format!("{err}").contains("ready_timeout"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+ready_on = "later"
+"#,
+        );
+        assert!(Config::load(Some(path), &CliOverrides::default()).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn config_heartbeat_txt() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+heartbeat_txt = "_heartbeat.example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.heartbeat_txt.as_deref(), Some("_heartbeat.example.com"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+heartbeat_txt = "bad name.example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("invalid name");
+        assert!(format!("{err:#}").contains("heartbeat_txt"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+provider_type = "duckdns"
+record_name = "myhost.duckdns.org"
+heartbeat_txt = "myhost.duckdns.org"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("provider");
+        assert!(format!("{err}").contains("only supported with the cloudflare provider"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_max_sync_age() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.max_sync_age, None);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+max_sync_age = 86400
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.max_sync_age, Some(Duration::from_secs(86400)));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+max_sync_age = 0
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err}").contains("max_sync_age"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_address_source() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.source, AddressSource::Netlink);
+        assert_eq!(cfg.external_ip_url, "https://api6.ipify.org");
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+source = "external"
+external_ip_url = "https://ipv6.example.net/ip"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.source, AddressSource::External);
+        assert_eq!(cfg.external_ip_url, "https://ipv6.example.net/ip");
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+source = "external"
+external_ip_url = "http://ipv6.example.net/ip"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err}").contains("https"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+source = "upnp"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err}").contains("netlink|external|command"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_detect_command() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+source = "command"
+detect_command = ["/bin/sh", "-c", "echo 2606:4700:4700::1111"]
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.source, AddressSource::Command);
+        assert!(matches!(cfg.detect_command, Some(HookCommand::Exec(_))));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+source = "command"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("no command");
+        assert!(format!("{err}").contains("requires detect_command"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+detect_command = ["/bin/sh", "-c", "echo 2606:4700:4700::1111"]
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("wrong source");
+        assert!(format!("{err}").contains("requires source"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+source = "command"
+detect_command = "upnp-query --ipv6"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("shell string");
+        assert!(format!("{err}").contains("detect_command is a shell string"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_verify_propagation() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert!(!cfg.verify_propagation);
+        assert_eq!(cfg.propagation_resolver, "1.1.1.1:53".parse().unwrap());
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+verify_propagation = true
+propagation_resolver = "108.162.192.1"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert!(cfg.verify_propagation);
+        assert_eq!(
+            cfg.propagation_resolver,
+            "108.162.192.1:53".parse().unwrap()
+        );
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+propagation_resolver = "ns1.example.com"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("propagation_resolver"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_external_resolver() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.external_resolver, None);
+        assert_eq!(cfg.external_check_interval, Duration::from_secs(3600));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+external_resolver = "2606:4700:4700::1111"
+external_check_interval_secs = 600
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.external_resolver,
+            Some("[2606:4700:4700::1111]:53".parse().unwrap())
+        );
+        assert_eq!(cfg.external_check_interval, Duration::from_secs(600));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+external_resolver = "dns.google"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("external_resolver"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+external_resolver = "1.1.1.1"
+external_check_interval_secs = 10
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err}").contains("external_check_interval_secs"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_address_records() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "host.example.com"
+address_record_template = "{label}-{n}"
+address_records_only = true
+address_record_limit = 2
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.address_record_template.as_deref(), Some("{label}-{n}"));
+        assert!(cfg.address_records_only);
+        assert_eq!(cfg.address_record_limit, 2);
+
+        let cases = [
+            ("address_record_template = \"{label}\"", "{n}"),
+            ("address_record_template = \"{label} {n}\"", "invalid name"),
+            (
+                "address_records_only = true",
+                "requires address_record_template",
+            ),
+            (
+                "address_record_template = \"{label}-{n}\"\naddress_record_limit = 0",
+                "address_record_limit",
+            ),
+        ];
+        for (extra, expected) in cases {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "host.example.com"
+{extra}
+"#
+            ));
+            let err = Config::load(Some(path), &CliOverrides::default()).expect_err(extra);
+            assert!(format!("{err:#}").contains(expected), "{err:#}");
+        }
+    }
+
+    #[test]
+    fn test_address_record_name() {
+        assert_eq!(
+            address_record_name("{label}-{n}", "host.example.com", 1),
+            "host-1.example.com"
+        );
+        assert_eq!(
+            address_record_name("addr{n}.{label}", "host.example.com", 12),
+            "addr12.host.example.com"
+        );
+        assert_eq!(address_record_name("{label}-{n}", "home", 2), "home-2");
+    }
+
+    #[test]
+    #[serial]
+    fn config_verify_after_update() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert!(!cfg.verify_after_update);
+        assert_eq!(
+            cfg.verify_delay,
+            Duration::from_secs(DEFAULT_VERIFY_DELAY_SECS)
+        );
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+verify_after_update = true
+verify_delay_secs = 30
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert!(cfg.verify_after_update);
+        assert_eq!(cfg.verify_delay, Duration::from_secs(30));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+verify_delay_secs = 0
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("zero delay");
+        assert!(format!("{err}").contains("verify_delay_secs"));
+
+        let (_dir, path) = write_config(
+            r#"
+provider_type = "duckdns"
+api_token = "a7c4d0ad-114e-40ef-ba1d-d217904a50f2"
+record_name = "home"
+verify_after_update = true
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("duckdns");
+        assert!(format!("{err}").contains("verify_after_update"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_netlink_max_drain() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.netlink_max_drain, DEFAULT_NETLINK_MAX_DRAIN);
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+netlink_max_drain = 64
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.netlink_max_drain, 64);
+
+        for bad in ["0", "1025"] {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+netlink_max_drain = {bad}
+"#
+            ));
+            let err =
+                Config::load(Some(path), &CliOverrides::default()).expect_err("drain out of range");
+            assert!(format!("{err}").contains("netlink_max_drain"));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn example_config_is_valid() {
+        let parsed: FileConfig = toml::from_str(EXAMPLE_CONFIG).expect("example parses");
+        assert!(parsed.record_name.is_some());
+
+        // Commented-out settings must parse once uncommented
+        let settings: Vec<&str> = EXAMPLE_CONFIG
+            .lines()
+            .filter_map(|line| line.strip_prefix("# "))
+            .filter(|line| {
+                line.split_once(" = ")
+                    .is_some_and(|(key, _)| key.chars().all(|c| c.is_ascii_lowercase() || c == '_'))
+            })
+            .collect();
+        for line in &settings {
+            toml::from_str::<FileConfig>(line)
+                .unwrap_or_else(|e| panic!("example line {line:?} does not parse: {e}"));
+        }
+        for key in ["api_token", "zone_id", "provider_type", "health_port"] {
+            assert!(
+                settings
+                    .iter()
+                    .any(|line| line.starts_with(&format!("{key} ="))),
+                "{key} not documented"
+            );
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn config_json_and_yaml_formats() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config_named(
+            "config.json",
+            r#"{
+                "api_token": "0123456789012345678901234567890123456789",
+                "zone_id": "0123456789abcdef0123456789abcdef",
+                "record_name": ["a.example.com", "b.example.com"],
+                "poll_interval": 120
+            }"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("json config load");
+        assert_eq!(cfg.records, vec!["a.example.com", "b.example.com"]);
+        assert_eq!(cfg.poll_interval, Duration::from_secs(120));
+
+        for name in ["config.yaml", "config.yml"] {
+            let (_dir, path) = write_config_named(
+                name,
+                r#"
+api_token: "0123456789012345678901234567890123456789"
+zone_id: "0123456789abcdef0123456789abcdef"
+record_name: example.com
+allow_create: true
+"#,
+            );
+            let cfg = Config::load(Some(path), &CliOverrides::default()).expect("yaml config load");
+            assert_eq!(cfg.records, vec!["example.com"]);
+            assert!(cfg.allow_create);
+        }
+
+        let (_dir, path) = write_config_named("config.conf", "record_name: example.com\n");
+        let err = Config::load(Some(path), &CliOverrides::default())
+            .expect_err("unknown extension, not TOML");
+        assert!(format!("{err}").contains("supported formats"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_idn_record_names() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "file_token_123456789012345678901234567890"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = ["müller.example", "xn--mller-kva.example.org"]
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("load config");
+        assert_eq!(
+            cfg.records,
+            vec!["xn--mller-kva.example", "xn--mller-kva.example.org"]
+        );
+    }
+
+    #[test]
+    fn config_from_stdin() {
+        let content =
+            read_config(&b"record_name = \"example.com\"\nallow_create = true\n"[..]).unwrap();
+        let file_config = parse_config_file(Path::new("-"), &content).unwrap();
+        assert_eq!(file_config.allow_create, Some(true));
+
+        let err = parse_config_file(Path::new("-"), "record_name: example.com\n")
+            .expect_err("stdin is always TOML");
+        assert!(format!("{err}").contains("from stdin"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_cli_overrides_env_and_file() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "file.example.com"
+timeout = 10
+"#,
+        );
+        std::env::set_var(ENV_RECORD_NAME, "env.example.com");
+        std::env::set_var(ENV_MULTI_RECORD, "first");
+
+        let cli = CliOverrides {
+            record: Some("a.example.com,b.example.com".to_string()),
+            zone_id: Some("fedcba9876543210fedcba9876543210".to_string()),
+            timeout: Some(20),
+            multi_record: Some("all".to_string()),
+            health_port: Some(9000),
+            allow_create: true,
+            ..Default::default()
+        };
+        let cfg = Config::load(Some(path.clone()), &cli).expect("config load");
+        assert_eq!(cfg.records, vec!["a.example.com", "b.example.com"]);
+        assert_eq!(cfg.zone_id.as_str(), "fedcba9876543210fedcba9876543210");
+        assert_eq!(cfg.timeout, Duration::from_secs(20));
+        assert_eq!(cfg.multi_record(), MultiRecordPolicy::UpdateAll);
+        assert_eq!(cfg.health_port, 9000);
+        assert!(cfg.allow_create);
+
+        let cli = CliOverrides {
+            timeout: Some(0),
+            ..Default::default()
+        };
+        let err = Config::load(Some(path), &cli).expect_err("CLI values are validated");
+        assert!(format!("{err}").contains("timeout"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_log_format() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+log_format = "json"
+"#,
+        );
+        let cfg = Config::load(Some(path.clone()), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.log_format, LogFormat::Json);
+
+        std::env::set_var(ENV_LOG_FORMAT, "text");
+        let cfg = Config::load(Some(path.clone()), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.log_format, LogFormat::Text);
+
+        std::env::set_var(ENV_LOG_FORMAT, "xml");
+        assert!(Config::load(Some(path), &CliOverrides::default()).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn config_log_target_syslog() {
+        let _env = EnvGuard::new();
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+log_target = "syslog"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.log_target, LogTarget::Syslog);
+        assert_eq!(cfg.syslog_facility, "daemon");
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+log_target = "syslog"
+syslog_facility = "LOCAL3"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.syslog_facility, "local3");
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+log_target = "syslog"
+syslog_facility = "local8"
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("syslog_facility"));
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+log_target = "syslog"
+log_file = "/var/log/ipv6ddns.log"
+"#,
+        );
+        assert!(Config::load(Some(path), &CliOverrides::default()).is_err());
     }
 
     #[test]
     #[serial]
-    fn config_load_from_file() {
+    fn config_log_file() {
         let _env = EnvGuard::new();
         let (_dir, path) = write_config(
             r#"
-api_token = "file_token_123456789012345678901234567890"
+api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
-timeout = 45
-poll_interval = 90
-verbose = true
-multi_record = "all"
-allow_loopback = true
 "#,
         );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.log_target, LogTarget::Stdout);
+        assert_eq!(cfg.log_file, None);
+        assert_eq!(cfg.log_rotation, LogRotation::Never);
 
-        let cfg = Config::load(Some(path)).expect("config load");
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+log_file = "/var/log/ipv6ddns/ipv6ddns.log"
+log_rotation = "Daily"
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
         assert_eq!(
-            cfg.api_token.as_str(),
-            "file_token_123456789012345678901234567890"
+            cfg.log_file,
+            Some(PathBuf::from("/var/log/ipv6ddns/ipv6ddns.log"))
         );
-        assert_eq!(cfg.zone_id.as_str(), "0123456789abcdef0123456789abcdef");
-        assert_eq!(cfg.record, "example.com");
-        assert_eq!(cfg.timeout, Duration::from_secs(45));
-        assert_eq!(cfg.poll_interval, Duration::from_secs(90));
-        assert!(cfg.verbose);
-        assert!(matches!(cfg.multi_record, MultiRecordPolicy::UpdateAll));
-        assert!(cfg.allow_loopback);
-    }
+        assert_eq!(cfg.log_rotation, LogRotation::Daily);
+        assert_eq!(cfg.log_target, LogTarget::File);
 
-    #[test]
-    #[serial]
-    fn config_env_overrides_file() {
-        let _env = EnvGuard::new();
         let (_dir, path) = write_config(
             r#"
-api_token = "file_token_123456789012345678901234567890"
+api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
-allow_loopback = false
+log_rotation = "daily"
 "#,
         );
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("requires log_file"));
 
-        std::env::set_var(ENV_API_TOKEN, "env_token_123456789012345678901234567890");
-        std::env::set_var(ENV_ZONE_ID, "envzone0123456789abcdef0123456789ab");
-        std::env::set_var(ENV_RECORD_NAME, "example.com");
-        std::env::set_var(ENV_ALLOW_LOOPBACK, "true");
-
-        let cfg = Config::load(Some(path)).expect("config load");
-        assert_eq!(
-            cfg.api_token.as_str(),
-            "env_token_123456789012345678901234567890"
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+log_target = "file"
+"#,
         );
-        assert_eq!(cfg.zone_id.as_str(), "envzone0123456789abcdef0123456789ab");
-        assert_eq!(cfg.record, "example.com");
-        assert!(cfg.allow_loopback);
-    }
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("log_file must be set"));
 
-    #[test]
-    #[serial]
-    fn config_missing_required_fields() {
-        let _env = EnvGuard::new();
-        let err = Config::load(None).expect_err("missing required");
-        let msg = format!("{err}");
-        assert!(
-            msg.starts_with("Missing ")
-                || msg.contains("Missing required")
-                || msg.contains("missing required")
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+log_file = "/var/log/ipv6ddns.log"
+log_rotation = "hourly"
+"#,
         );
+        assert!(Config::load(Some(path), &CliOverrides::default()).is_err());
     }
 
     #[test]
     #[serial]
-    fn config_api_token_too_short() {
+    fn config_accepted_scopes() {
         let _env = EnvGuard::new();
         let (_dir, path) = write_config(
             r#"
-api_token = "short"
+api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
 "#,
         );
-        let err = Config::load(Some(path)).expect_err("token too short");
-        let msg = format!("{err}");
-        assert!(msg.contains("too short"));
-    }
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.accepted_scopes, vec![RT_SCOPE_UNIVERSE]);
 
-    #[test]
-    #[serial]
-    fn config_zone_id_invalid_format() {
-        let _env = EnvGuard::new();
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
-zone_id = "invalid-zone-id!"
+zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
+accepted_scopes = ["universe", "site"]
 "#,
         );
-        let err = Config::load(Some(path)).expect_err("zone id invalid");
-        let msg = format!("{err}");
-        assert!(msg.contains("alphanumeric"));
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.accepted_scopes,
+            vec![RT_SCOPE_UNIVERSE, crate::netlink::RT_SCOPE_SITE]
+        );
+
+        for bad in ["[\"link\"]", "[]"] {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+accepted_scopes = {bad}
+"#
+            ));
+            assert!(Config::load(Some(path), &CliOverrides::default()).is_err());
+        }
     }
 
     #[test]
     #[serial]
-    fn config_zone_id_invalid_length() {
+    fn config_netns() {
         let _env = EnvGuard::new();
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
-zone_id = "short"
+zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
+netns = "wan"
 "#,
         );
-        let err = Config::load(Some(path)).expect_err("zone id length");
-        let msg = format!("{err}");
-        assert!(msg.contains("invalid length"));
-    }
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.netns.as_deref(), Some("wan"));
 
-    #[test]
-    fn parse_multi_record_valid_and_invalid() {
-        assert!(matches!(
-            parse_multi_record("first").unwrap(),
-            MultiRecordPolicy::UpdateFirst
-        ));
-        assert!(parse_multi_record("bogus").is_err());
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+netns = ""
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("empty");
+        assert!(format!("{err}").contains("netns"));
     }
 
-    // Additional edge case tests for config parsing
-
     #[test]
     #[serial]
-    fn config_timeout_boundary_values() {
+    fn config_selection_policy() {
         let _env = EnvGuard::new();
-        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
-        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
-        std::env::set_var(ENV_RECORD_NAME, "example.com");
-
-        // Test minimum timeout via config file
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
-timeout = 1
+selection_stable_weight = 0
+selection_interfaces = ["eth0", "wlan0"]
+selection_prefix = "2001:db8::/48"
+selection_prefix_weight = 500
 "#,
         );
-        let cfg = Config::load(Some(path)).expect("config load");
-        assert_eq!(cfg.timeout, Duration::from_secs(1));
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.selection.stable_weight, 0);
+        assert_eq!(cfg.selection.lifetime_weight, 1);
+        assert_eq!(cfg.selection.interfaces, vec!["eth0", "wlan0"]);
+        assert_eq!(
+            cfg.selection.prefix,
+            Some(("2001:db8::".parse().unwrap(), 48))
+        );
+        assert_eq!(cfg.selection.prefix_weight, 500);
 
-        // Test maximum timeout via config file
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
-timeout = 300
+selection_prefix = "2001:db8::/129"
 "#,
         );
-        let cfg = Config::load(Some(path)).expect("config load");
-        assert_eq!(cfg.timeout, Duration::from_secs(300));
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("selection_prefix"));
 
-        // Test timeout below minimum
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
-timeout = 0
+prefix_regions = { "2001:db8:1::/48" = "home", "2001:db8:2::/48" = "vpn" }
+allowed_regions = ["home"]
 "#,
         );
-        let err = Config::load(Some(path)).expect_err("timeout too low");
-        assert!(format!("{err}").contains("timeout"));
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.selection.regions.len(), 2);
+        assert!(cfg
+            .selection
+            .region_allowed("2001:db8:1::1".parse().unwrap()));
+        assert!(!cfg
+            .selection
+            .region_allowed("2001:db8:2::1".parse().unwrap()));
 
-        // Test timeout above maximum
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
-timeout = 301
+prefix_regions = { "2001:db8:1::/48" = "home" }
+allowed_regions = ["office"]
 "#,
         );
-        let err = Config::load(Some(path)).expect_err("timeout too high");
-        assert!(format!("{err}").contains("timeout"));
-    }
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("'office'"));
 
-    #[test]
-    #[serial]
-    fn config_poll_interval_boundary_values() {
-        let _env = EnvGuard::new();
-        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
-        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
-        std::env::set_var(ENV_RECORD_NAME, "example.com");
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+route_fwmark = 0x64
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.selection.routing, RoutingContext::Fwmark(0x64));
 
-        // Test minimum poll interval via config file
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
-poll_interval = 10
+route_table = 100
+route_fwmark = 0x64
 "#,
         );
-        let cfg = Config::load(Some(path)).expect("config load");
-        assert_eq!(cfg.poll_interval, Duration::from_secs(10));
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("route_table"));
 
-        // Test maximum poll interval via config file
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
-poll_interval = 3600
+address_preference = "temporary_only"
 "#,
         );
-        let cfg = Config::load(Some(path)).expect("config load");
-        assert_eq!(cfg.poll_interval, Duration::from_secs(3600));
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(
+            cfg.selection.preference,
+            crate::netlink::AddressPreference::TemporaryOnly
+        );
+        assert_eq!(cfg.selection.mode, AddressSelection::Weighted);
 
-        // Test poll interval below minimum
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
-poll_interval = 9
+address_selection = "longest_lifetime"
 "#,
         );
-        let err = Config::load(Some(path)).expect_err("poll interval too low");
-        assert!(format!("{err}").contains("poll_interval"));
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.selection.mode, AddressSelection::LongestLifetime);
 
-        // Test poll interval above maximum
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
-poll_interval = 3601
+address_selection = "prefer_prefix"
 "#,
         );
-        let err = Config::load(Some(path)).expect_err("poll interval too high");
-        assert!(format!("{err}").contains("poll_interval"));
+        let err = Config::load(Some(path), &CliOverrides::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("selection_prefix"));
     }
 
     #[test]
     #[serial]
-    fn config_api_token_exact_minimum_length() {
+    fn config_allow_create_defaults_off() {
+        let _env = EnvGuard::new();
+        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
+        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
+        std::env::set_var(ENV_RECORD_NAME, "example.com");
+
+        let cfg = Config::load(None, &CliOverrides::default()).expect("config load");
+        assert!(!cfg.allow_create);
+
+        std::env::set_var(ENV_ALLOW_CREATE, "yes");
+        let cfg = Config::load(None, &CliOverrides::default()).expect("config load");
+        assert!(cfg.allow_create);
+
+        std::env::set_var(ENV_ALLOW_CREATE, "maybe");
+        assert!(Config::load(None, &CliOverrides::default()).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn config_enable_ipv4() {
         let _env = EnvGuard::new();
         let (_dir, path) = write_config(
             r#"
-api_token = "01234567890123456789012345678901"
+api_token = "0123456789012345678901234567890123456789"
 zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
+enable_ipv4 = true
 "#,
         );
-        let cfg = Config::load(Some(path)).expect("config load");
-        assert_eq!(cfg.api_token.as_str().len(), 32);
+        let cfg = Config::load(Some(path.clone()), &CliOverrides::default()).expect("config load");
+        assert!(cfg.enable_ipv4);
+        assert!(!cfg.allow_private_ipv4);
+
+        std::env::set_var(ENV_ALLOW_PRIVATE_IPV4, "true");
+        std::env::set_var(ENV_ENABLE_IPV4, "false");
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert!(!cfg.enable_ipv4);
+        assert!(cfg.allow_private_ipv4);
     }
 
     #[test]
     #[serial]
-    fn config_zone_id_exact_minimum_length() {
+    fn config_record_type() {
+        let _env = EnvGuard::new();
+        let load = |extra: &str| {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+{extra}
+"#
+            ));
+            Config::load(Some(path), &CliOverrides::default())
+        };
+
+        let cfg = load("").unwrap();
+        assert_eq!(cfg.record_types(), vec![RecordType::Aaaa]);
+        let cfg = load("record_type = \"both\"").unwrap();
+        assert_eq!(cfg.record_types(), vec![RecordType::Aaaa, RecordType::A]);
+        assert_eq!(
+            cfg.required_record_types(),
+            vec![RecordType::Aaaa, RecordType::A]
+        );
+        let cfg = load("record_type = \"a\"").unwrap();
+        assert!(!cfg.enable_ipv6);
+        assert!(cfg.enable_ipv4);
+        assert_eq!(cfg.required_record_types(), vec![RecordType::A]);
+
+        for bad in [
+            "record_type = \"MX\"",
+            "record_type = \"both\"\nenable_ipv4 = true",
+            "record_type = \"A\"\nrequired_families = [\"ipv6\"]",
+            "record_type = \"A\"\naddress_record_template = \"{label}-{n}\"",
+        ] {
+            let err = load(bad).unwrap_err();
+            assert!(format!("{err:#}").contains("record_type"), "{bad}: {err:#}");
+        }
+
+        std::env::set_var(ENV_RECORD_TYPE, "AAAA");
+        let cfg = load("record_type = \"A\"").unwrap();
+        assert_eq!(cfg.record_types(), vec![RecordType::Aaaa]);
+        std::env::remove_var(ENV_RECORD_TYPE);
+
+        // Nothing would be published
+        std::env::set_var(ENV_ENABLE_IPV4, "false");
+        assert!(load("record_type = \"A\"").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn config_required_families() {
         let _env = EnvGuard::new();
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
-zone_id = "0123456789abcdef"
+zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
+enable_ipv4 = true
 "#,
         );
-        let cfg = Config::load(Some(path)).expect("config load");
-        assert_eq!(cfg.zone_id.as_str().len(), 16);
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.required_families, None);
+        assert_eq!(
+            cfg.required_record_types(),
+            vec![RecordType::Aaaa, RecordType::A]
+        );
+
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+enable_ipv4 = true
+required_families = ["IPv6"]
+"#,
+        );
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert_eq!(cfg.required_record_types(), vec![RecordType::Aaaa]);
+
+        // IPv4 can't be required when it is never published
+        std::env::set_var(ENV_ENABLE_IPV4, "false");
+        let (_dir, path) = write_config(
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+enable_ipv4 = true
+required_families = ["ipv6", "ipv4"]
+"#,
+        );
+        let err = Config::load(Some(path), &CliOverrides::default()).expect_err("no ipv4");
+        assert!(format!("{err}").contains("enable_ipv4"));
+
+        for bad in [r#"[]"#, r#"["ipv5"]"#] {
+            let (_dir, path) = write_config(&format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+required_families = {bad}
+"#
+            ));
+            let err = Config::load(Some(path), &CliOverrides::default()).expect_err(bad);
+            assert!(format!("{err}").contains("required_families"));
+        }
     }
 
     #[test]
     #[serial]
-    fn config_zone_id_exact_maximum_length() {
+    fn config_strict_mode() {
         let _env = EnvGuard::new();
         let (_dir, path) = write_config(
             r#"
 api_token = "0123456789012345678901234567890123456789"
-zone_id = "0123456789abcdef0123456789abcdef01234567"
+zone_id = "0123456789abcdef0123456789abcdef"
 record_name = "example.com"
+strict_mode = true
 "#,
         );
-        let cfg = Config::load(Some(path)).expect("config load");
-        assert_eq!(cfg.zone_id.as_str().len(), 40);
+        let cfg = Config::load(Some(path.clone()), &CliOverrides::default()).expect("config load");
+        assert!(cfg.strict_mode);
+
+        std::env::set_var(ENV_STRICT_MODE, "off");
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
+        assert!(!cfg.strict_mode);
     }
 
     #[test]
     #[serial]
-    fn config_multi_record_policy_variants() {
+    fn config_read_only_needs_no_credentials() {
         let _env = EnvGuard::new();
-        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
-        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
         std::env::set_var(ENV_RECORD_NAME, "example.com");
+        assert!(Config::load(None, &CliOverrides::default()).is_err());
 
-        // Test error policy variants
-        for policy in ["error", "fail", "reject"] {
-            std::env::set_var(ENV_MULTI_RECORD, policy);
-            let cfg = Config::load(None).expect("config load");
-            assert!(matches!(cfg.multi_record, MultiRecordPolicy::Error));
-        }
-
-        // Test first policy variants
-        for policy in ["first", "update_first", "updatefirst"] {
-            std::env::set_var(ENV_MULTI_RECORD, policy);
-            let cfg = Config::load(None).expect("config load");
-            assert!(matches!(cfg.multi_record, MultiRecordPolicy::UpdateFirst));
-        }
-
-        // Test all policy variants
-        for policy in ["all", "update_all", "updateall"] {
-            std::env::set_var(ENV_MULTI_RECORD, policy);
-            let cfg = Config::load(None).expect("config load");
-            assert!(matches!(cfg.multi_record, MultiRecordPolicy::UpdateAll));
-        }
+        std::env::set_var(ENV_READ_ONLY, "true");
+        let cfg = Config::load(None, &CliOverrides::default()).expect("config load");
+        assert!(cfg.read_only);
+        assert!(cfg.api_token.as_str().is_empty());
 
-        std::env::remove_var(ENV_MULTI_RECORD);
+        std::env::set_var(ENV_READ_ONLY, "false");
+        let cli = CliOverrides {
+            read_only: true,
+            ..CliOverrides::default()
+        };
+        assert!(Config::load(None, &cli).expect("config load").read_only);
     }
 
     #[test]
     #[serial]
-    fn config_allow_loopback_variants() {
+    fn config_load_for_detection_needs_no_credentials() {
         let _env = EnvGuard::new();
-        std::env::set_var(ENV_API_TOKEN, "0123456789012345678901234567890123456789");
-        std::env::set_var(ENV_ZONE_ID, "0123456789abcdef0123456789abcdef");
         std::env::set_var(ENV_RECORD_NAME, "example.com");
+        assert!(Config::load(None, &CliOverrides::default()).is_err());
 
-        // Test true variants
-        for value in ["1", "true", "yes", "on"] {
-            std::env::set_var(ENV_ALLOW_LOOPBACK, value);
-            let cfg = Config::load(None).expect("config load");
-            assert!(cfg.allow_loopback);
-        }
-
-        // Test false variants
-        for value in ["0", "false", "no", "off"] {
-            std::env::set_var(ENV_ALLOW_LOOPBACK, value);
-            let cfg = Config::load(None).expect("config load");
-            assert!(!cfg.allow_loopback);
-        }
+        let cfg = Config::load_for_detection(None, &CliOverrides::default()).expect("config load");
+        assert!(!cfg.read_only);
+        assert!(cfg.api_token.as_str().is_empty());
 
-        std::env::remove_var(ENV_ALLOW_LOOPBACK);
+        // Everything else is still validated
+        std::env::set_var(ENV_RECORD_NAME, "bad..name");
+        assert!(Config::load_for_detection(None, &CliOverrides::default()).is_err());
     }
 
     #[test]
@@ -832,13 +5574,13 @@ record_name = "example.com"
         std::env::set_var(ENV_ZONE_ID, "");
         std::env::set_var(ENV_RECORD_NAME, "");
 
-        let cfg = Config::load(Some(path)).expect("config load");
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
         assert_eq!(
             cfg.api_token.as_str(),
             "0123456789012345678901234567890123456789"
         );
         assert_eq!(cfg.zone_id.as_str(), "0123456789abcdef0123456789abcdef");
-        assert_eq!(cfg.record, "example.com");
+        assert_eq!(cfg.records, vec!["example.com"]);
     }
 
     #[test]
@@ -853,7 +5595,7 @@ record_name = "example.com"
 "#,
         );
 
-        let cfg = Config::load(Some(path)).expect("config load");
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
         // Whitespace should not be present in zone_id (alphanumeric check)
         assert!(!cfg.zone_id.as_str().contains(" "));
     }
@@ -870,7 +5612,7 @@ record_name = "example.com"
 "#,
         );
 
-        let cfg = Config::load(Some(path)).expect("config load");
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
         assert_eq!(cfg.zone_id.as_str(), "ABCDEF0123456789abcdef0123456789");
     }
 
@@ -886,7 +5628,7 @@ record_name = "example.com"
 "#,
         );
 
-        let cfg = Config::load(Some(path)).expect("config load");
+        let cfg = Config::load(Some(path), &CliOverrides::default()).expect("config load");
         assert_eq!(
             cfg.api_token.as_str(),
             "0123456789012345678901234567890123456789!@#$%^&*()"
@@ -7,19 +7,121 @@
 /// Cloudflare API base URL
 pub const CLOUDFLARE_API_BASE: &str = "https://api.cloudflare.com/client/v4";
 
+/// Records requested per page when listing DNS records
+pub const CLOUDFLARE_RECORDS_PER_PAGE: u32 = 100;
+
+/// Maximum number of record pages fetched for one name before giving up
+pub const CLOUDFLARE_MAX_RECORD_PAGES: u32 = 50;
+
+/// Delay before the first retry of a failed API request, in milliseconds
+pub const CLOUDFLARE_RETRY_BASE_DELAY_MS: u64 = 250;
+
+/// Upper bound for the delay between retries, in milliseconds
+pub const CLOUDFLARE_RETRY_MAX_DELAY_MS: u64 = 2000;
+
 /// User agent string for Cloudflare API requests
 pub const CLOUDFLARE_USER_AGENT: &str = "ipv6ddns/1.0";
 
 /// DNS record type for IPv6 addresses
 pub const DNS_RECORD_TYPE_AAAA: &str = "AAAA";
 
+/// DNS record type for IPv4 addresses
+pub const DNS_RECORD_TYPE_A: &str = "A";
+
+/// DNS record type for the `heartbeat_txt` record
+pub const DNS_RECORD_TYPE_TXT: &str = "TXT";
+
 /// TTL value for automatic TTL (1 second)
 pub const DNS_TTL_AUTO: u64 = 1;
 
+//==============================================================================
+// Route 53 API Constants
+//==============================================================================
+
+/// Route 53 API endpoint host
+pub const ROUTE53_API_HOST: &str = "route53.amazonaws.com";
+
+/// Route 53 API version, the first path segment of every request
+pub const ROUTE53_API_VERSION: &str = "2013-04-01";
+
+/// Region Route 53 requests are signed for (the service is global)
+pub const ROUTE53_REGION: &str = "us-east-1";
+
+/// Service name Route 53 requests are signed for
+pub const ROUTE53_SERVICE: &str = "route53";
+
+/// TTL of record sets created by the daemon, in seconds
+pub const ROUTE53_TTL_SECS: u64 = 300;
+
+//==============================================================================
+// Porkbun API Constants
+//==============================================================================
+
+/// Porkbun API base URL
+pub const PORKBUN_API_BASE: &str = "https://api.porkbun.com/api/json/v3";
+
+/// TTL of records written by the daemon, in seconds (Porkbun's minimum)
+pub const PORKBUN_TTL_SECS: u64 = 600;
+
+//==============================================================================
+// External Address Detection Constants
+//==============================================================================
+
+/// Default service answering with the caller's IPv6 address (`source = "external"`)
+pub const DEFAULT_EXTERNAL_IP_URL: &str = "https://api6.ipify.org";
+
+//==============================================================================
+// DuckDNS API Constants
+//==============================================================================
+
+/// DuckDNS update endpoint
+pub const DUCKDNS_UPDATE_URL: &str = "https://www.duckdns.org/update";
+
+/// Domain under which all DuckDNS names live
+pub const DUCKDNS_DOMAIN_SUFFIX: &str = ".duckdns.org";
+
+/// TTL DuckDNS serves its records with, in seconds
+pub const DUCKDNS_TTL_SECS: u64 = 60;
+
+//==============================================================================
+// Namecheap API Constants
+//==============================================================================
+
+/// Namecheap dynamic DNS update endpoint
+pub const NAMECHEAP_UPDATE_URL: &str = "https://dynamicdns.park-your-domain.com/update";
+
+/// TTL of Namecheap's "Automatic" setting, which dynamic DNS hosts use, in seconds
+pub const NAMECHEAP_TTL_SECS: u64 = 1800;
+
+//==============================================================================
+// Vultr API Constants
+//==============================================================================
+
+/// Vultr API base URL
+pub const VULTR_API_BASE: &str = "https://api.vultr.com/v2";
+
+/// Records requested per page when listing a domain's records (Vultr's maximum)
+pub const VULTR_RECORDS_PER_PAGE: u32 = 500;
+
+/// Maximum number of record pages fetched for one domain before giving up
+pub const VULTR_MAX_RECORD_PAGES: u32 = 50;
+
+/// TTL of records created by the daemon, in seconds
+pub const VULTR_TTL_SECS: u64 = 300;
+
 //==============================================================================
 // HTTP Status Codes
 //==============================================================================
 
+/// Default length, in characters, a response body is cut to in error messages
+pub const DEFAULT_MAX_ERROR_BODY_LEN: usize = 512;
+
+/// Minimum allowed value for `max_error_body_len`
+pub const MIN_MAX_ERROR_BODY_LEN: usize = 64;
+
+/// Maximum allowed value for `max_error_body_len`
+pub const MAX_MAX_ERROR_BODY_LEN: usize = 65536;
+
 /// HTTP status code for unauthorized requests (401)
 pub const HTTP_STATUS_UNAUTHORIZED: u16 = 401;
 
@@ -57,6 +159,100 @@ pub const MIN_POLL_INTERVAL_SECS: u64 = 10;
 /// Maximum polling interval in seconds
 pub const MAX_POLL_INTERVAL_SECS: u64 = 3600;
 
+/// Maximum polling jitter as a fraction of the interval
+pub const MAX_POLL_JITTER: f64 = 0.5;
+
+/// Maximum random delay before the initial sync, in seconds
+pub const MAX_STARTUP_JITTER_SECS: u64 = 3600;
+
+/// Minimum `force_update_interval` in seconds (other than 0 = never)
+pub const MIN_FORCE_UPDATE_INTERVAL_SECS: u64 = 60;
+
+/// Maximum `force_update_interval` in seconds (30 days)
+pub const MAX_FORCE_UPDATE_INTERVAL_SECS: u64 = 2_592_000;
+
+/// Default number of netlink messages read per wakeup
+pub const DEFAULT_NETLINK_MAX_DRAIN: usize = 16;
+
+/// Maximum number of netlink messages read per wakeup
+pub const MAX_NETLINK_MAX_DRAIN: usize = 1024;
+
+/// Default minimum age of a created record before it may be deleted, in seconds
+pub const DEFAULT_DELETE_COOLDOWN_SECS: u64 = 60;
+
+/// Maximum wait after an address disappears before acting on it, in seconds
+pub const MAX_REMOVAL_GRACE_SECS: u64 = 3600;
+
+/// Default time limit for a hook command, in seconds
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 30;
+
+/// Maximum time limit for a hook command, in seconds
+pub const MAX_HOOK_TIMEOUT_SECS: u64 = 3600;
+
+/// Warn when a new address's preferred lifetime is below this, in seconds
+pub const ADDRESS_EXPIRY_WARN_SECS: u32 = 600;
+
+/// Default wait between an update and its verification read, in seconds
+pub const DEFAULT_VERIFY_DELAY_SECS: u64 = 5;
+
+/// Maximum wait between an update and its verification read, in seconds
+pub const MAX_VERIFY_DELAY_SECS: u64 = 300;
+
+/// Resolver the propagation of an update is checked at by default
+pub const DEFAULT_PROPAGATION_RESOLVER: &str = "1.1.1.1";
+
+/// Lookups made to see an update propagate before giving up
+pub const PROPAGATION_CHECK_ATTEMPTS: u32 = 5;
+
+/// Wait before each propagation lookup, in seconds
+pub const PROPAGATION_CHECK_DELAY_SECS: u64 = 30;
+
+/// Maximum allowed value for `error_threshold` (consecutive failed syncs)
+pub const MAX_ERROR_THRESHOLD: u64 = 100;
+
+/// Default time between checks against `external_resolver`, in seconds
+pub const DEFAULT_EXTERNAL_CHECK_INTERVAL_SECS: u64 = 3600;
+
+/// Minimum time between checks against `external_resolver`, in seconds
+pub const MIN_EXTERNAL_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Maximum time between checks against `external_resolver`, in seconds
+pub const MAX_EXTERNAL_CHECK_INTERVAL_SECS: u64 = 86400;
+
+/// Time for a resolver to answer an external check, in seconds
+pub const EXTERNAL_CHECK_TIMEOUT_SECS: u64 = 5;
+
+/// Wait beyond a record's TTL before a differing external answer counts as a
+/// mismatch, in seconds
+pub const EXTERNAL_CHECK_SETTLE_SECS: u64 = 60;
+
+/// TTL Cloudflare serves records with automatic TTL (`DNS_TTL_AUTO`) with, in seconds
+pub const DNS_TTL_AUTO_SECS: u64 = 300;
+
+/// Default for `max_retries` (retries of a failed API request)
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// Maximum allowed value for `max_retries`
+pub const MAX_MAX_RETRIES: u32 = 10;
+
+/// Default maximum number of per-address records per record name
+pub const DEFAULT_ADDRESS_RECORD_LIMIT: usize = 4;
+
+/// Maximum allowed value for `address_record_limit`
+pub const MAX_ADDRESS_RECORD_LIMIT: usize = 32;
+
+/// Default for `max_concurrent_updates` (records updated one at a time)
+pub const DEFAULT_MAX_CONCURRENT_UPDATES: usize = 1;
+
+/// Maximum allowed value for `max_concurrent_updates`
+pub const MAX_MAX_CONCURRENT_UPDATES: usize = 16;
+
+/// Upper bound on deleting records during shutdown, in seconds
+pub const SHUTDOWN_DELETE_TIMEOUT_SECS: u64 = 10;
+
+/// Default systemd credential name holding the API token
+pub const DEFAULT_API_TOKEN_CREDENTIAL: &str = "cloudflare_token";
+
 //==============================================================================
 // Backoff Constants
 //==============================================================================
@@ -70,6 +266,16 @@ pub const BACKOFF_MAX_SECS: u64 = 600;
 /// Maximum exponent for exponential backoff (capped at 10)
 pub const BACKOFF_MAX_EXPONENT: u64 = 10;
 
+/// Base backoff delay after a network failure in seconds; connection blips
+/// usually clear quickly
+pub const BACKOFF_NETWORK_BASE_SECS: u64 = 2;
+
+/// Maximum backoff delay after a network failure in seconds (1 minute)
+pub const BACKOFF_NETWORK_MAX_SECS: u64 = 60;
+
+/// Base backoff delay after a rate limit without `Retry-After` in seconds
+pub const BACKOFF_RATE_LIMIT_BASE_SECS: u64 = 30;
+
 //==============================================================================
 // Validation Constants
 //==============================================================================
@@ -83,6 +289,9 @@ pub const MIN_ZONE_ID_LENGTH: usize = 16;
 /// Maximum zone ID length in characters
 pub const MAX_ZONE_ID_LENGTH: usize = 64;
 
+/// Length of a Cloudflare zone ID, checked with `strict_zone_id`
+pub const CLOUDFLARE_ZONE_ID_LENGTH: usize = 32;
+
 /// Maximum DNS record name length in characters
 pub const MAX_RECORD_NAME_LENGTH: usize = 253;
 
@@ -93,14 +302,53 @@ pub const MAX_LABEL_LENGTH: usize = 63;
 // Environment Variable Names
 //==============================================================================
 
-/// Environment variable name for Cloudflare API token
-pub const ENV_API_TOKEN: &str = "CLOUDFLARE_API_TOKEN";
+/// Environment variable name for the provider API token
+pub const ENV_API_TOKEN: &str = "IPV6DDNS_API_TOKEN";
+
+/// Environment variable name for the provider zone ID
+pub const ENV_ZONE_ID: &str = "IPV6DDNS_ZONE_ID";
+
+/// Environment variable name for DNS record name(s)
+pub const ENV_RECORD_NAME: &str = "IPV6DDNS_RECORD";
+
+/// Environment variable name for a file containing the API token
+pub const ENV_API_TOKEN_FILE: &str = "IPV6DDNS_API_TOKEN_FILE";
+
+/// Environment variable name for a file containing the zone ID
+pub const ENV_ZONE_ID_FILE: &str = "IPV6DDNS_ZONE_ID_FILE";
+
+/// Environment variable name for the provider secret key (Porkbun)
+pub const ENV_SECRET_KEY: &str = "IPV6DDNS_SECRET_KEY";
 
-/// Environment variable name for Cloudflare zone ID
-pub const ENV_ZONE_ID: &str = "CLOUDFLARE_ZONE_ID";
+/// Environment variable name for a file containing the secret key
+pub const ENV_SECRET_KEY_FILE: &str = "IPV6DDNS_SECRET_KEY_FILE";
 
-/// Environment variable name for DNS record name
-pub const ENV_RECORD_NAME: &str = "CLOUDFLARE_RECORD_NAME";
+/// Legacy alias for `ENV_API_TOKEN` (used when the neutral name is unset)
+pub const ENV_CLOUDFLARE_API_TOKEN: &str = "CLOUDFLARE_API_TOKEN";
+
+/// Legacy alias for `ENV_ZONE_ID` (used when the neutral name is unset)
+pub const ENV_CLOUDFLARE_ZONE_ID: &str = "CLOUDFLARE_ZONE_ID";
+
+/// Legacy alias for `ENV_RECORD_NAME` (used when the neutral name is unset)
+pub const ENV_CLOUDFLARE_RECORD_NAME: &str = "CLOUDFLARE_RECORD_NAME";
+
+/// Legacy alias for `ENV_API_TOKEN_FILE` (used when the neutral name is unset)
+pub const ENV_CLOUDFLARE_API_TOKEN_FILE: &str = "CLOUDFLARE_API_TOKEN_FILE";
+
+/// Legacy alias for `ENV_ZONE_ID_FILE` (used when the neutral name is unset)
+pub const ENV_CLOUDFLARE_ZONE_ID_FILE: &str = "CLOUDFLARE_ZONE_ID_FILE";
+
+/// AWS access key ID (Route 53)
+pub const ENV_AWS_ACCESS_KEY_ID: &str = "AWS_ACCESS_KEY_ID";
+
+/// AWS secret access key (Route 53)
+pub const ENV_AWS_SECRET_ACCESS_KEY: &str = "AWS_SECRET_ACCESS_KEY";
+
+/// AWS session token of temporary credentials (Route 53)
+pub const ENV_AWS_SESSION_TOKEN: &str = "AWS_SESSION_TOKEN";
+
+/// Directory systemd exposes `LoadCredential=` files in
+pub const ENV_CREDENTIALS_DIRECTORY: &str = "CREDENTIALS_DIRECTORY";
 
 /// Environment variable name for multi-record policy
 pub const ENV_MULTI_RECORD: &str = "CLOUDFLARE_MULTI_RECORD";
@@ -108,12 +356,67 @@ pub const ENV_MULTI_RECORD: &str = "CLOUDFLARE_MULTI_RECORD";
 /// Environment variable name to allow loopback IPv6 (::1)
 pub const ENV_ALLOW_LOOPBACK: &str = "IPV6DDNS_ALLOW_LOOPBACK";
 
+/// Environment variable name to allow creating a missing DNS record
+pub const ENV_ALLOW_CREATE: &str = "IPV6DDNS_ALLOW_CREATE";
+
+/// Environment variable name to also publish the IPv4 address as an A record
+pub const ENV_ENABLE_IPV4: &str = "IPV6DDNS_ENABLE_IPV4";
+
+/// Environment variable name to choose the published record types (AAAA, A or both)
+pub const ENV_RECORD_TYPE: &str = "IPV6DDNS_RECORD_TYPE";
+
+/// Environment variable name to accept private and CGNAT IPv4 addresses
+pub const ENV_ALLOW_PRIVATE_IPV4: &str = "IPV6DDNS_ALLOW_PRIVATE_IPV4";
+
+/// Environment variable name to flag degraded (polling) monitoring
+pub const ENV_STRICT_MODE: &str = "IPV6DDNS_STRICT_MODE";
+
+/// Environment variable for read-only (monitoring only) mode
+pub const ENV_READ_ONLY: &str = "IPV6DDNS_READ_ONLY";
+
+/// Environment variable for the removal policy
+pub const ENV_ON_REMOVAL: &str = "IPV6DDNS_ON_REMOVAL";
+
+/// Environment variable for deleting records on shutdown
+pub const ENV_DELETE_ON_SHUTDOWN: &str = "IPV6DDNS_DELETE_ON_SHUTDOWN";
+
 /// Environment variable name for DNS provider type
 pub const ENV_PROVIDER_TYPE: &str = "IPV6DDNS_PROVIDER_TYPE";
 
+/// Environment variable name for the log output format
+pub const ENV_LOG_FORMAT: &str = "IPV6DDNS_LOG_FORMAT";
+
+/// Default facility of messages sent to syslog
+pub const DEFAULT_SYSLOG_FACILITY: &str = "daemon";
+
+/// Syslog facilities accepted for `syslog_facility`
+pub const SYSLOG_FACILITIES: [&str; 20] = [
+    "kern", "user", "mail", "daemon", "auth", "syslog", "lpr", "news", "uucp", "cron", "authpriv",
+    "ftp", "local0", "local1", "local2", "local3", "local4", "local5", "local6", "local7",
+];
+
 /// Environment variable name for health check port
 pub const ENV_HEALTH_PORT: &str = "IPV6DDNS_HEALTH_PORT";
 
+/// Largest health server request read, in bytes; the rest is ignored
+pub const HEALTH_MAX_REQUEST_BYTES: usize = 8192;
+
+/// Time a health server client has to send its request, in seconds
+pub const HEALTH_REQUEST_TIMEOUT_SECS: u64 = 5;
+
+//==============================================================================
+// Hook Environment Variable Names
+//==============================================================================
+
+/// Environment variable passed to hooks with the newly published IP address
+pub const HOOK_ENV_IP: &str = "IPV6DDNS_IP";
+
+/// Environment variable passed to hooks with the DNS record name
+pub const HOOK_ENV_RECORD: &str = "IPV6DDNS_RECORD";
+
+/// Environment variable passed to hooks with the previously published IP address
+pub const HOOK_ENV_OLD_IP: &str = "IPV6DDNS_OLD_IP";
+
 //==============================================================================
 // HTTP Connection Pool Constants
 //==============================================================================
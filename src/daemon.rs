@@ -2,19 +2,43 @@
 //!
 //! This module contains the main daemon implementation for IPv6 DDNS synchronization.
 
+use std::collections::BTreeMap;
+use std::net::Ipv6Addr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use chrono::{DateTime, Utc};
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures_util::future::join_all;
 use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::Semaphore;
 use tracing::{debug, error, info, warn};
 
-use crate::config::Config;
-use crate::constants::{BACKOFF_BASE_SECS, BACKOFF_MAX_EXPONENT, BACKOFF_MAX_SECS};
-use crate::dns_provider::DnsProvider;
+use crate::config::{address_record_name, AddressSource, Config, ReadyPolicy, RemovalPolicy};
+use crate::constants::{
+    ADDRESS_EXPIRY_WARN_SECS, BACKOFF_BASE_SECS, BACKOFF_MAX_EXPONENT, BACKOFF_MAX_SECS,
+    BACKOFF_NETWORK_BASE_SECS, BACKOFF_NETWORK_MAX_SECS, BACKOFF_RATE_LIMIT_BASE_SECS,
+    DNS_TTL_AUTO, DNS_TTL_AUTO_SECS, EXTERNAL_CHECK_SETTLE_SECS, EXTERNAL_CHECK_TIMEOUT_SECS,
+    PROPAGATION_CHECK_ATTEMPTS, PROPAGATION_CHECK_DELAY_SECS, SHUTDOWN_DELETE_TIMEOUT_SECS,
+};
+use crate::detect_command::CommandSource;
+use crate::dns_provider::{
+    build_provider, resolve_zone_name, DnsProvider, ErrorKind, ProviderError, RecordType,
+    UpsertOptions, ZoneNotFound,
+};
+use crate::external_ip::ExternalIpSource;
 use crate::health::HealthServer;
-use crate::netlink::{detect_global_ipv6, NetlinkEvent, NetlinkSocket};
+use crate::hooks::spawn_post_update_hook;
+use crate::metrics::Metrics;
+use crate::netlink::{
+    detect_all_global_ipv6, detect_global_ipv4, detect_global_ipv6, jitter_bounds, random_unit,
+    NetlinkEvent, NetlinkSocket, SelectionPolicy, INFINITY_LIFE_TIME,
+};
+use crate::resolver::resolve_aaaa;
+use crate::state_file::{self, SavedState};
+use crate::systemd;
+use crate::validation::{is_valid_ipv6, normalize_ipv6};
+use crate::webhook::{WebhookNotifier, WebhookPayload};
 
 //==============================================================================
 // State Machine
@@ -22,7 +46,7 @@ use crate::netlink::{detect_global_ipv6, NetlinkEvent, NetlinkSocket};
 
 /// Represents the current state of DNS record synchronization
 ///
-/// This enum tracks the synchronization status of the DNS record with Cloudflare.
+/// This enum tracks the synchronization status of a DNS record with Cloudflare.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RecordState {
     /// Initial state, no record has been synced yet
@@ -31,35 +55,91 @@ pub enum RecordState {
     Synced(String),
     /// Last sync attempt failed, contains the error count
     Error(u64),
+    /// The provider rejected the credentials; the record is not retried until
+    /// the daemon is restarted
+    Misconfigured,
 }
 
-/// Application state for tracking DNS record synchronization
+/// Result of looking up a published record at `external_resolver`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalCheck {
+    /// The resolver answered with the published address
+    Match,
+    /// The resolver answered without the published address
+    Mismatch,
+    /// The lookup failed
+    Error,
+}
+
+impl ExternalCheck {
+    /// Returns the name used in the health check and metrics
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Match => "match",
+            Self::Mismatch => "mismatch",
+            Self::Error => "error",
+        }
+    }
+
+    /// Compares the resolver's answer with the published address
+    ///
+    /// # Arguments
+    ///
+    /// * `published` - The address the record was synced to
+    /// * `resolved` - The addresses the resolver answered with
+    pub fn compare(published: &str, resolved: &[Ipv6Addr]) -> Self {
+        match published.parse::<Ipv6Addr>() {
+            Ok(addr) if resolved.contains(&addr) => Self::Match,
+            _ => Self::Mismatch,
+        }
+    }
+}
+
+/// Synchronization status of a single DNS record
 ///
-/// This struct maintains the state of the DNS record synchronization process,
+/// This struct maintains the state of one record's synchronization process,
 /// including the current sync status, last sync time, error count, and next retry time.
-pub struct AppState {
+pub struct RecordStatus {
     /// Current synchronization state
     pub state: RecordState,
-    /// Timestamp of the last successful sync (UTC)
+    /// Wall-clock time of the last successful sync (UTC), for display only
     pub last_sync: Option<DateTime<Utc>>,
+    /// Monotonic time of the last successful sync, used to measure its age
+    pub last_sync_at: Option<Instant>,
     /// Number of consecutive errors
     pub error_count: u64,
+    /// Consecutive errors of each category, which set that category's backoff
+    pub error_counts: BTreeMap<ErrorKind, u64>,
     /// Next time to retry after an error (if in backoff period)
     pub next_retry: Option<Instant>,
+    /// When the daemon created the record (None if it only updated an existing one)
+    pub created_at: Option<Instant>,
+    /// ID of the record last written
+    pub record_id: Option<String>,
+    /// TTL of the record last written, in seconds
+    pub ttl: Option<u64>,
+    /// Last lookup at `external_resolver` since the record was synced
+    pub external_check: Option<ExternalCheck>,
 }
 
-impl Default for AppState {
+impl Default for RecordStatus {
     fn default() -> Self {
         Self {
             state: RecordState::Unknown,
             last_sync: None,
+            last_sync_at: None,
             error_count: 0,
+            error_counts: BTreeMap::new(),
             next_retry: None,
+            created_at: None,
+            record_id: None,
+            ttl: None,
+            external_check: None,
         }
     }
 }
 
-impl AppState {
+impl RecordStatus {
     /// Marks the record as successfully synced
     ///
     /// This method updates the state to `Synced`, records the sync time,
@@ -71,18 +151,231 @@ impl AppState {
     pub fn mark_synced(&mut self, ip: String) {
         self.state = RecordState::Synced(ip);
         self.last_sync = Some(Utc::now());
+        self.last_sync_at = Some(Instant::now());
         self.error_count = 0;
+        self.error_counts.clear();
         self.next_retry = None;
+        self.external_check = None;
     }
 
     /// Marks the record as having a sync error
     ///
-    /// This method increments the error count, updates the state to `Error`,
-    /// and schedules a retry using exponential backoff.
-    pub fn mark_error(&mut self) {
+    /// This method increments the error count and schedules a retry using
+    /// the backoff of the error's category (see [`backoff_delay_for`]), which
+    /// only counts errors of that category: a burst of rate limits doesn't
+    /// slow down the retry of a later network failure. The state only changes
+    /// to `Error` once `error_threshold` consecutive errors have occurred;
+    /// until then the previous state is kept, so a single transient failure
+    /// doesn't show up in the health check.
+    ///
+    /// # Arguments
+    ///
+    /// * `error_threshold` - Consecutive errors before the state becomes `Error`
+    /// * `kind` - Category of the error
+    /// * `retry_after` - Delay the provider asked for, if any
+    pub fn mark_error(
+        &mut self,
+        error_threshold: u64,
+        kind: ErrorKind,
+        retry_after: Option<Duration>,
+    ) {
+        self.error_count = self.error_count.saturating_add(1);
+        if self.error_count >= error_threshold {
+            self.state = RecordState::Error(self.error_count);
+        }
+        let count = self.error_counts.entry(kind).or_insert(0);
+        *count = count.saturating_add(1);
+        self.next_retry = Some(Instant::now() + backoff_delay_for(kind, *count, retry_after));
+    }
+
+    /// Marks the record as misconfigured after an authentication failure
+    ///
+    /// Unlike `mark_error`, no retry is scheduled and the threshold is not
+    /// applied: rejected credentials won't start working on their own, so the
+    /// record stays in this state until the daemon is restarted.
+    pub fn mark_misconfigured(&mut self) {
         self.error_count = self.error_count.saturating_add(1);
-        self.state = RecordState::Error(self.error_count);
-        self.next_retry = Some(Instant::now() + backoff_delay(self.error_count));
+        self.state = RecordState::Misconfigured;
+        self.next_retry = None;
+    }
+
+    /// Marks the record as deleted
+    ///
+    /// This method resets the state to `Unknown` so the next address is
+    /// synced (and the record re-created) even if it matches the old one.
+    pub fn mark_removed(&mut self) {
+        self.state = RecordState::Unknown;
+        self.created_at = None;
+    }
+
+    /// Returns the time since the last successful sync
+    ///
+    /// Measured on the monotonic clock, so wall-clock adjustments (NTP steps,
+    /// manual changes) don't skew it.
+    ///
+    /// # Returns
+    ///
+    /// Returns the elapsed time, or `None` if the record never synced
+    pub fn since_last_sync(&self) -> Option<Duration> {
+        self.last_sync_at.map(|at| at.elapsed())
+    }
+
+    /// Returns how long deletion must still be deferred for a created record
+    ///
+    /// # Arguments
+    ///
+    /// * `cooldown` - Minimum age of a created record before it may be deleted
+    ///
+    /// # Returns
+    ///
+    /// Returns the remaining cooldown, or `None` if the record may be deleted now
+    pub fn removal_cooldown_remaining(&self, cooldown: Duration) -> Option<Duration> {
+        let age = self.created_at?.elapsed();
+        cooldown.checked_sub(age).filter(|d| !d.is_zero())
+    }
+
+    /// Returns how long after a sync resolvers may still serve the old address
+    ///
+    /// Covers the record's TTL (Cloudflare's automatic TTL counts as 300s)
+    /// plus `EXTERNAL_CHECK_SETTLE_SECS` for the provider to propagate it.
+    pub fn external_check_settle_time(&self) -> Duration {
+        let ttl = match self.ttl {
+            Some(DNS_TTL_AUTO) | None => DNS_TTL_AUTO_SECS,
+            Some(ttl) => ttl,
+        };
+        Duration::from_secs(ttl.saturating_add(EXTERNAL_CHECK_SETTLE_SECS))
+    }
+}
+
+/// Application state for tracking DNS record synchronization
+///
+/// Each configured record is tracked independently, so one record failing
+/// doesn't reset another's success.
+#[derive(Default)]
+pub struct AppState {
+    /// Per-record synchronization status, keyed by record name
+    pub records: BTreeMap<String, RecordStatus>,
+    /// Polling fallback engaged while strict mode is enabled
+    pub monitoring_degraded: bool,
+    /// Most recently detected IPv6 address, whether or not it was published
+    pub detected_ip: Option<String>,
+    /// Per-record status of the A records (only used with `enable_ipv4`)
+    pub a_records: BTreeMap<String, RecordStatus>,
+    /// Number of per-address records published under each record name
+    pub address_record_count: usize,
+    /// Provider writes paused because `pause_file` exists
+    pub paused: bool,
+    /// Every required record has been synced at least once (or, read-only,
+    /// an address was detected); never reset afterwards
+    pub ready: bool,
+    /// Interval address detection polls at, before jitter (None when
+    /// netlink events drive detection)
+    pub poll_interval: Option<Duration>,
+}
+
+impl AppState {
+    /// Creates a state with every record in the `Unknown` state
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - The configured record names
+    pub fn new(records: &[String]) -> Self {
+        Self {
+            records: records
+                .iter()
+                .map(|name| (name.clone(), RecordStatus::default()))
+                .collect(),
+            monitoring_degraded: false,
+            detected_ip: None,
+            a_records: BTreeMap::new(),
+            address_record_count: 0,
+            paused: false,
+            ready: false,
+            poll_interval: None,
+        }
+    }
+
+    /// Records whether provider writes are paused
+    ///
+    /// # Arguments
+    ///
+    /// * `paused` - Whether `pause_file` exists now
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if this pauses or resumes writes, `false` if nothing changed
+    pub fn set_paused(&mut self, paused: bool) -> bool {
+        std::mem::replace(&mut self.paused, paused) != paused
+    }
+
+    /// Returns the status of a record, adding it in the `Unknown` state if missing
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The record name
+    pub fn record_mut(&mut self, record: &str) -> &mut RecordStatus {
+        self.records.entry(record.to_string()).or_default()
+    }
+
+    /// Returns the status of a record of the given type, adding it if missing
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The record name
+    /// * `record_type` - Whether the AAAA or the A record is meant
+    pub fn status_mut(&mut self, record: &str, record_type: RecordType) -> &mut RecordStatus {
+        match record_type {
+            RecordType::Aaaa => self.record_mut(record),
+            RecordType::A => self.a_records.entry(record.to_string()).or_default(),
+        }
+    }
+
+    /// Returns a record that failed at least `limit` consecutive syncs, if any
+    ///
+    /// A misconfigured record is never retried, so it counts as having
+    /// reached any limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Consecutive errors that count as too many
+    pub fn record_over_error_limit(&self, limit: u64) -> Option<&str> {
+        self.records
+            .iter()
+            .chain(&self.a_records)
+            .find(|(_, status)| {
+                status.error_count >= limit || status.state == RecordState::Misconfigured
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns when any record, AAAA or A, was last written
+    pub fn last_write_at(&self) -> Option<Instant> {
+        self.records
+            .values()
+            .chain(self.a_records.values())
+            .filter_map(|status| status.last_sync_at)
+            .max()
+    }
+
+    /// Returns true if every tracked record of each given family is synced
+    ///
+    /// A family without any tracked record (e.g. no IPv4 address was ever
+    /// found) counts as not synced.
+    ///
+    /// # Arguments
+    ///
+    /// * `families` - The record types that must be synced
+    pub fn families_synced(&self, families: &[RecordType]) -> bool {
+        families.iter().all(|family| {
+            let records = match family {
+                RecordType::Aaaa => &self.records,
+                RecordType::A => &self.a_records,
+            };
+            !records.is_empty()
+                && records
+                    .values()
+                    .all(|status| matches!(status.state, RecordState::Synced(_)))
+        })
     }
 }
 
@@ -111,11 +404,52 @@ impl AppState {
 /// assert_eq!(delay, Duration::from_secs(10));
 /// ```
 pub fn backoff_delay(error_count: u64) -> Duration {
+    exponential_backoff(BACKOFF_BASE_SECS, BACKOFF_MAX_SECS, error_count)
+}
+
+/// Calculates the backoff delay for an error category
+///
+/// Each category follows its own schedule:
+/// - `Network`: `min(2 * 2^(n - 1), 60)` seconds, since connection blips
+///   usually clear quickly
+/// - `RateLimit`: the provider's `Retry-After` delay (at most 600 seconds),
+///   or `min(30 * 2^(n - 1), 600)` seconds without one
+/// - `Server`, `Validation` and `Auth`: the default [`backoff_delay`]
+///
+/// # Arguments
+///
+/// * `kind` - Category of the error
+/// * `error_count` - Number of consecutive errors of that category
+/// * `retry_after` - Delay the provider asked for, if any
+///
+/// # Returns
+///
+/// Returns the backoff duration
+pub fn backoff_delay_for(
+    kind: ErrorKind,
+    error_count: u64,
+    retry_after: Option<Duration>,
+) -> Duration {
+    match kind {
+        ErrorKind::Network => exponential_backoff(
+            BACKOFF_NETWORK_BASE_SECS,
+            BACKOFF_NETWORK_MAX_SECS,
+            error_count,
+        ),
+        ErrorKind::RateLimit => match retry_after {
+            Some(delay) => delay.min(Duration::from_secs(BACKOFF_MAX_SECS)),
+            None => {
+                exponential_backoff(BACKOFF_RATE_LIMIT_BASE_SECS, BACKOFF_MAX_SECS, error_count)
+            }
+        },
+        ErrorKind::Server | ErrorKind::Validation | ErrorKind::Auth => backoff_delay(error_count),
+    }
+}
+
+/// Returns `min(base * 2^(error_count - 1), max)` seconds
+fn exponential_backoff(base_secs: u64, max_secs: u64, error_count: u64) -> Duration {
     let exp = error_count.saturating_sub(1).min(BACKOFF_MAX_EXPONENT);
-    let secs = BACKOFF_BASE_SECS
-        .saturating_mul(1u64 << exp)
-        .min(BACKOFF_MAX_SECS);
-    Duration::from_secs(secs)
+    Duration::from_secs(base_secs.saturating_mul(1u64 << exp).min(max_secs))
 }
 
 /// Redacts sensitive data (API tokens and zone IDs) from log messages
@@ -156,6 +490,29 @@ pub fn redact_secrets(message: &str, api_token: &str, zone_id: &str) -> String {
     sanitized
 }
 
+/// Cuts a message down to at most `max_len` characters
+///
+/// A longer message is cut at a character boundary and ends with
+/// `…(truncated)`. Redact secrets with [`redact_secrets`] first: a secret
+/// split by the cut would no longer be found and part of it would leak.
+///
+/// # Arguments
+///
+/// * `message` - The message to shorten
+/// * `max_len` - The number of characters kept
+///
+/// # Returns
+///
+/// Returns the message unchanged if it is short enough, or its first
+/// `max_len` characters followed by the truncation marker
+#[must_use]
+pub fn truncate_message(message: &str, max_len: usize) -> String {
+    match message.char_indices().nth(max_len) {
+        Some((end, _)) => format!("{}…(truncated)", &message[..end]),
+        None => message.to_string(),
+    }
+}
+
 //==============================================================================
 // Daemon
 //==============================================================================
@@ -173,6 +530,18 @@ pub struct Daemon {
     dns_provider: Arc<dyn DnsProvider>,
     /// Netlink socket for IPv6 address monitoring
     netlink: NetlinkSocket,
+    /// Metric counters served by the health server
+    metrics: Arc<Metrics>,
+    /// Webhook notifier for address changes (if configured)
+    webhook: Option<WebhookNotifier>,
+    /// Source asked for the address instead of the local interfaces
+    address_query: Option<AddressQuery>,
+    /// Deferred record deletion waiting for the creation cooldown to pass
+    pending_removal: Option<Instant>,
+    /// End of the `removal_grace` wait after the address disappeared
+    removal_grace_until: Option<Instant>,
+    /// State last read from or written to `state_file`
+    saved_state: Option<SavedState>,
 }
 
 impl Daemon {
@@ -183,13 +552,59 @@ impl Daemon {
     /// * `config` - Configuration for the daemon
     /// * `dns_provider` - DNS provider client (trait object)
     /// * `netlink` - Netlink socket for IPv6 monitoring
-    pub fn new(config: Config, dns_provider: Arc<dyn DnsProvider>, netlink: NetlinkSocket) -> Self {
-        Self {
+    /// * `metrics` - Metric counters, shared with the DNS provider
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the daemon or an error if the webhook
+    /// or external address client cannot be built
+    pub fn new(
+        config: Config,
+        dns_provider: Arc<dyn DnsProvider>,
+        netlink: NetlinkSocket,
+        metrics: Arc<Metrics>,
+    ) -> Result<Self> {
+        let webhook = config
+            .webhook_url
+            .as_deref()
+            .map(|url| WebhookNotifier::new(url, config.timeout, config.connect_timeout()))
+            .transpose()?;
+        let address_query = AddressQuery::from_config(&config)?;
+        let main_records: &[String] = if config.address_records_only || !config.enable_ipv6 {
+            &[]
+        } else {
+            &config.records
+        };
+        let mut state = AppState::new(main_records);
+        state.monitoring_degraded = config.strict_mode
+            && config.source == AddressSource::Netlink
+            && !netlink.is_event_driven();
+        state.poll_interval = (!netlink.is_event_driven()).then_some(config.poll_interval);
+        let saved_state = config.state_file.as_deref().and_then(state_file::load);
+        if let Some(saved) = &saved_state {
+            let a_records: &[String] = if config.enable_ipv4 {
+                &config.records
+            } else {
+                &[]
+            };
+            let restored = saved.restore(&mut state, config.provider_type, main_records, a_records);
+            if restored > 0 {
+                info!("Restored {} synced record(s) from the state file", restored);
+            }
+        }
+
+        Ok(Self {
             config: Arc::new(config),
-            state: Arc::new(tokio::sync::Mutex::new(AppState::default())),
+            state: Arc::new(tokio::sync::Mutex::new(state)),
             dns_provider,
             netlink,
-        }
+            metrics,
+            webhook,
+            address_query,
+            pending_removal: None,
+            removal_grace_until: None,
+            saved_state,
+        })
     }
 
     /// Runs the daemon main loop
@@ -201,22 +616,65 @@ impl Daemon {
     ///    - SIGTERM: Graceful shutdown
     ///    - SIGHUP: Force resync
     ///    - Netlink events: IPv6 address changes
+    ///    - systemd watchdog pings (when enabled)
+    ///    - Lookups at `external_resolver` (when configured)
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` on graceful shutdown or an error if the daemon fails.
+    /// Returns `Ok(())` on graceful shutdown or an error if the daemon fails,
+    /// including when a record reaches `max_consecutive_errors`.
     pub async fn run(&mut self) -> Result<()> {
         info!("Starting ipv6ddns daemon");
-        info!("Record: {}", self.config.record);
-        info!(
-            "Mode: {}",
-            if self.netlink.is_event_driven() {
-                "event-driven (netlink)"
-            } else {
-                "polling"
-            }
-        );
-        info!("Multi-record policy: {:?}", self.config.multi_record);
+        let ready_deadline = self
+            .config
+            .ready_timeout
+            .map(|timeout| tokio::time::Instant::now() + timeout);
+        // A file left behind by a crash must not claim readiness
+        self.remove_ready_file();
+        info!("Records: {}", self.config.records.join(", "));
+        if let Some(query) = &self.address_query {
+            info!(
+                "Mode: {} every {}s",
+                query,
+                self.config.poll_interval.as_secs()
+            );
+        } else {
+            info!(
+                "Mode: {}",
+                if self.netlink.is_event_driven() {
+                    "event-driven (netlink)"
+                } else {
+                    "polling"
+                }
+            );
+        }
+        if let Some(interval) = self.state.lock().await.poll_interval {
+            let (min, max) = jitter_bounds(interval, self.config.poll_jitter);
+            debug!(
+                "Effective poll interval: {:?} (jittered between {:.1?} and {:.1?})",
+                interval, min, max
+            );
+        }
+        if self.config.read_only {
+            info!("Read-only mode: addresses are detected but never published");
+        }
+        if !self.netlink.is_event_driven() && self.address_query.is_none() {
+            warn!(
+                "Degraded monitoring: netlink unavailable, address changes are only noticed every {}s",
+                self.config.poll_interval.as_secs()
+            );
+        }
+        info!("Multi-record policy: {:?}", self.config.multi_record());
+        if self.config.on_removal == RemovalPolicy::Delete {
+            info!(
+                "Records will be deleted when IPv6 disappears (cooldown: {}s, grace: {}s)",
+                self.config.delete_cooldown.as_secs(),
+                self.config.removal_grace.as_secs()
+            );
+        }
+        if !self.config.allow_create {
+            info!("Record creation disabled; only existing records will be updated");
+        }
         debug!(
             "Zone ID: {}",
             redact_secrets(
@@ -228,7 +686,14 @@ impl Daemon {
 
         let mut health_server = if self.config.health_port > 0 {
             let addr = std::net::SocketAddr::from(([127, 0, 0, 1], self.config.health_port));
-            match HealthServer::start(addr, Arc::clone(&self.state)).await {
+            match HealthServer::start(
+                addr,
+                Arc::clone(&self.state),
+                Arc::clone(&self.config),
+                Arc::clone(&self.metrics),
+            )
+            .await
+            {
                 Ok(server) => Some(server),
                 Err(e) => {
                     error!("Health server failed to start: {:#}", e);
@@ -239,44 +704,343 @@ impl Daemon {
             None
         };
 
-        if let Some(ip) = detect_global_ipv6(self.config.allow_loopback) {
-            info!("Initial IPv6: {}", ip);
-            _ = self.sync_record(&ip).await;
+        let mut sigterm = signal(SignalKind::terminate())?;
+        let mut sighup = signal(SignalKind::hangup())?;
+
+        if !self.config.startup_jitter.is_zero() {
+            let delay = self.config.startup_jitter.mul_f64(random_unit());
+            info!(
+                "Delaying initial sync by {:.1}s (startup jitter)",
+                delay.as_secs_f64()
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = sigterm.recv() => {
+                    info!("SIGTERM received during startup delay");
+                    if let Some(server) = health_server.as_mut() {
+                        server.stop().await;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+
+        if !self.config.enable_ipv6 {
+            info!("record_type = \"A\": only IPv4 is published");
+        } else if let Some(ip) = self.detect_ip().await {
+            info!(ip = %ip, "Initial IPv6: {}", ip);
+            _ = self.sync_record(&ip, false).await;
         } else {
             warn!("No IPv6 on startup");
         }
+        // A failure above never keeps the A records from being synced
+        self.sync_ipv4(false).await;
+        let mut ready = false;
+        if self.first_sync_done().await || self.config.ready_on == ReadyPolicy::Start {
+            self.signal_ready();
+            ready = true;
+        } else {
+            info!("Waiting for the first successful sync before reporting ready");
+        }
 
-        let mut sigterm = signal(SignalKind::terminate())?;
-        let mut sighup = signal(SignalKind::hangup())?;
-
+        let mut watchdog = systemd::watchdog_interval().map(|period| {
+            debug!("systemd watchdog enabled; pinging every {:?}", period);
+            tokio::time::interval(period)
+        });
+        // IPv4 changes aren't subscribed to, so the A records are re-checked
+        // every poll interval
+        let mut ipv4_check = (self.config.enable_ipv4 && !self.config.read_only).then(|| {
+            let period = self.config.poll_interval;
+            tokio::time::interval_at(tokio::time::Instant::now() + period, period)
+        });
+        // Removing the pause file raises no event, so it is looked for every
+        // poll interval
+        let mut pause_check = self.config.pause_file.as_ref().map(|_| {
+            let period = self.config.poll_interval;
+            tokio::time::interval_at(tokio::time::Instant::now() + period, period)
+        });
+        let mut external_check = self.config.external_resolver.map(|_| {
+            let period = self.config.external_check_interval;
+            tokio::time::interval_at(tokio::time::Instant::now() + period, period)
+        });
+        // Restarted after every write below, so it only fires once the records
+        // have gone unwritten for a whole interval
+        let mut force_update =
+            (!self.config.force_update_interval.is_zero() && !self.config.read_only).then(|| {
+                let period = self.config.force_update_interval;
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period)
+            });
+        let mut last_write = self.state.lock().await.last_write_at();
+
+        let mut result = Ok(());
         loop {
+            self.save_state().await;
+            if self.first_sync_done().await && !ready {
+                info!("First sync complete; reporting ready");
+                self.signal_ready();
+                ready = true;
+            }
+
+            if self.config.max_consecutive_errors > 0 {
+                let state = self.state.lock().await;
+                if let Some(record) =
+                    state.record_over_error_limit(self.config.max_consecutive_errors)
+                {
+                    error!(
+                        record = %record,
+                        "{} failed {} consecutive syncs (max_consecutive_errors); exiting",
+                        record,
+                        self.config.max_consecutive_errors
+                    );
+                    result = Err(anyhow::anyhow!(
+                        "{} reached max_consecutive_errors ({})",
+                        record,
+                        self.config.max_consecutive_errors
+                    ));
+                    systemd::notify_stopping();
+                    break;
+                }
+            }
+
+            if let Some(timer) = force_update.as_mut() {
+                let latest = self.state.lock().await.last_write_at();
+                if latest != last_write {
+                    last_write = latest;
+                    timer.reset();
+                }
+            }
+
+            let pending_removal = self.pending_removal;
+            let removal_grace_until = self.removal_grace_until;
             tokio::select! {
                 _ = sigterm.recv() => {
                     info!("SIGTERM received");
+                    systemd::notify_stopping();
+                    if self.config.delete_on_shutdown
+                        && !self.config.read_only
+                        && !self.check_paused().await
+                    {
+                        self.delete_records_on_shutdown().await;
+                    }
                     break;
                 }
                 _ = sighup.recv() => {
                     info!("SIGHUP received: forcing resync");
-                    if let Some(ip) = detect_global_ipv6(self.config.allow_loopback) {
-                        if let Err(e) = self.sync_record(&ip).await {
-                            error!("Sync failed: {:#}", e);
+                    if self.config.enable_ipv6 {
+                        match self.detect_ip().await {
+                            Some(ip) => {
+                                if let Err(e) = self.sync_record(&ip, false).await {
+                                    error!("Sync failed: {:#}", e);
+                                }
+                            }
+                            None => warn!("No IPv6 on SIGHUP"),
                         }
-                    } else {
-                        warn!("No IPv6 on SIGHUP");
                     }
+                    self.sync_ipv4(false).await;
                 }
                 event = self.netlink.recv() => {
                     self.handle_event(event).await;
                 }
+                _ = tokio::time::sleep_until(
+                    tokio::time::Instant::from_std(pending_removal.unwrap_or_else(Instant::now))
+                ), if pending_removal.is_some() => {
+                    self.pending_removal = None;
+                    self.handle_removal().await;
+                }
+                _ = tokio::time::sleep_until(
+                    tokio::time::Instant::from_std(removal_grace_until.unwrap_or_else(Instant::now))
+                ), if removal_grace_until.is_some() => {
+                    self.removal_grace_until = None;
+                    debug!("Removal grace period over; checking addresses again");
+                    self.handle_removal().await;
+                }
+                _ = tokio::time::sleep_until(
+                    ready_deadline.unwrap_or_else(tokio::time::Instant::now)
+                ), if !ready && ready_deadline.is_some() => {
+                    let timeout = self.config.ready_timeout.unwrap_or_default();
+                    error!("No successful sync within {}s (ready_timeout); exiting", timeout.as_secs());
+                    result = Err(anyhow::anyhow!(
+                        "first sync did not complete within ready_timeout ({}s)",
+                        timeout.as_secs()
+                    ));
+                    systemd::notify_stopping();
+                    break;
+                }
+                _ = async { watchdog.as_mut().unwrap().tick().await }, if watchdog.is_some() => {
+                    systemd::notify_watchdog();
+                }
+                _ = async { ipv4_check.as_mut().unwrap().tick().await }, if ipv4_check.is_some() => {
+                    self.sync_ipv4(false).await;
+                }
+                _ = async { external_check.as_mut().unwrap().tick().await }, if external_check.is_some() => {
+                    self.spawn_external_check();
+                }
+                _ = async { force_update.as_mut().unwrap().tick().await }, if force_update.is_some() => {
+                    self.force_update().await;
+                }
+                _ = async { pause_check.as_mut().unwrap().tick().await }, if pause_check.is_some() => {
+                    let was_paused = self.state.lock().await.paused;
+                    if was_paused && !self.check_paused().await {
+                        match self.detect_ip().await {
+                            Some(ip) => {
+                                if let Err(e) = self.sync_record(&ip, false).await {
+                                    error!("Sync failed: {:#}", e);
+                                }
+                            }
+                            None if self.config.on_removal == RemovalPolicy::Delete => {
+                                self.handle_removal().await;
+                            }
+                            None => {}
+                        }
+                        self.sync_ipv4(false).await;
+                    }
+                }
             }
         }
 
+        self.write_metrics_textfile();
+        self.save_state().await;
+        self.remove_ready_file();
         info!("Daemon stopped");
         if let Some(server) = health_server.as_mut() {
             server.stop().await;
         }
 
-        Ok(())
+        result
+    }
+
+    /// Writes every record again with the current address (`force_update_interval`)
+    ///
+    /// Records in backoff or with rejected credentials are still skipped, and
+    /// nothing is written while `pause_file` exists.
+    async fn force_update(&self) {
+        info!(
+            "No record written for {}s; writing them again (force_update_interval)",
+            self.config.force_update_interval.as_secs()
+        );
+        if self.config.enable_ipv6 {
+            match self.detect_ip().await {
+                Some(ip) => {
+                    if let Err(e) = self.sync_record(&ip, true).await {
+                        error!("Sync failed: {:#}", e);
+                    }
+                }
+                None => warn!("No IPv6 to write again"),
+            }
+        }
+        self.sync_ipv4(true).await;
+    }
+
+    /// Returns whether every required record has been synced at least once,
+    /// which is what `ReadyPolicy::FirstSync` and `/readyz` wait for
+    ///
+    /// In read-only mode nothing is ever synced, so a detected address is enough.
+    /// Once reached, this is remembered in the state and stays true.
+    async fn first_sync_done(&self) -> bool {
+        let mut state = self.state.lock().await;
+        if !state.ready {
+            state.ready = if self.config.read_only {
+                state.detected_ip.is_some()
+            } else {
+                state.families_synced(&self.config.required_record_types())
+            };
+        }
+        state.ready
+    }
+
+    /// Reports the daemon ready to systemd and by creating `ready_file`
+    fn signal_ready(&self) {
+        systemd::notify_ready();
+        if let Some(path) = &self.config.ready_file {
+            if let Err(e) = std::fs::write(path, b"") {
+                warn!("Ready file {} not written: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Removes `ready_file`, if configured and present
+    fn remove_ready_file(&self) {
+        if let Some(path) = &self.config.ready_file {
+            match std::fs::remove_file(path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => warn!("Ready file {} not removed: {}", path.display(), e),
+            }
+        }
+    }
+
+    /// Writes the metrics to `metrics_textfile`, if configured
+    ///
+    /// Failures are logged; they never affect syncing.
+    fn write_metrics_textfile(&self) {
+        if let Some(path) = &self.config.metrics_textfile {
+            if let Err(e) = self.metrics.write_textfile(path) {
+                warn!("Metrics textfile not written: {:#}", e);
+            }
+        }
+    }
+
+    /// Writes the synced records to `state_file`, if configured and changed
+    ///
+    /// Failures are logged; they never affect syncing.
+    async fn save_state(&mut self) {
+        let Some(path) = &self.config.state_file else {
+            return;
+        };
+        let saved = SavedState::capture(&*self.state.lock().await, self.config.provider_type);
+        if self.saved_state.as_ref() == Some(&saved) {
+            return;
+        }
+        match state_file::save(path, &saved) {
+            Ok(()) => self.saved_state = Some(saved),
+            Err(e) => warn!("State file not written: {:#}", e),
+        }
+    }
+
+    /// Returns whether provider writes are paused by `pause_file`
+    ///
+    /// Logs when writes are paused or resumed, and keeps the health check's
+    /// `paused` flag current.
+    async fn check_paused(&self) -> bool {
+        let Some(path) = &self.config.pause_file else {
+            return false;
+        };
+        let paused = path.exists();
+        if self.state.lock().await.set_paused(paused) {
+            if paused {
+                warn!("{} exists: pausing DNS updates", path.display());
+            } else {
+                info!("{} removed: resuming DNS updates", path.display());
+            }
+        }
+        paused
+    }
+
+    /// Detects every address to publish with `address_record_template`
+    fn detect_all_ips(&self) -> Vec<String> {
+        detect_all_global_ipv6(
+            &self.config.ipv6_filter,
+            &self.config.accepted_scopes,
+            &self.config.selection,
+            self.config.address_record_limit,
+        )
+    }
+
+    /// Detects the current IPv6 address using the configured source
+    async fn detect_ip(&self) -> Option<String> {
+        detect_address(&self.config, self.address_query.as_ref()).await
+    }
+
+    /// Returns the configured record names that get the selected address
+    ///
+    /// Empty with `address_records_only`, where only the per-address records
+    /// are published.
+    fn main_records(&self) -> &[String] {
+        if self.config.address_records_only || !self.config.enable_ipv6 {
+            &[]
+        } else {
+            &self.config.records
+        }
     }
 
     /// Handles a netlink event
@@ -284,59 +1048,485 @@ impl Daemon {
     /// # Arguments
     ///
     /// * `event` - The netlink event to handle
-    async fn handle_event(&self, event: Result<NetlinkEvent>) {
+    async fn handle_event(&mut self, event: Result<NetlinkEvent>) {
+        // Changes may have been missed: act on the addresses present now
+        let event = match event {
+            Ok(NetlinkEvent::Resync) => {
+                info!("Re-reading addresses after netlink events were lost");
+                Ok(match self.detect_ip().await {
+                    Some(ip) => NetlinkEvent::Ipv6Added(ip, None),
+                    None => NetlinkEvent::Ipv6Removed,
+                })
+            }
+            event => event,
+        };
         match event {
-            Ok(NetlinkEvent::Ipv6Added(ip)) => {
-                info!("IPv6 change detected: {}", ip);
-                if let Err(e) = self.sync_record(&ip).await {
+            Ok(NetlinkEvent::Ipv6Added(ip, preferred_lifetime)) => {
+                info!(ip = %ip, "IPv6 change detected: {}", ip);
+                match preferred_lifetime {
+                    Some(INFINITY_LIFE_TIME) | None => {}
+                    Some(secs) if secs < ADDRESS_EXPIRY_WARN_SECS => warn!(
+                        ip = %ip,
+                        preferred_lifetime = secs,
+                        "Address {} stops being preferred in {}s",
+                        ip,
+                        secs
+                    ),
+                    Some(secs) => debug!(
+                        ip = %ip,
+                        preferred_lifetime = secs,
+                        "Preferred lifetime of {}: {}s",
+                        ip,
+                        secs
+                    ),
+                }
+                let region_allowed = ip
+                    .parse()
+                    .map_or(true, |addr| self.config.selection.region_allowed(addr));
+                if !region_allowed {
+                    warn!(
+                        ip = %ip,
+                        "Not publishing {}: its prefix is not in allowed_regions",
+                        ip
+                    );
+                }
+                // The event carries the address that changed; with a custom
+                // policy, publish whichever address now ranks highest instead.
+                // An external or command address is the only candidate there is.
+                let ip = if self.address_query.is_some() {
+                    if !region_allowed {
+                        return;
+                    }
+                    ip
+                } else if self.config.selection != SelectionPolicy::default() {
+                    match self.detect_ip().await {
+                        Some(selected) => selected,
+                        None => {
+                            debug!("No address matches the selection policy");
+                            return;
+                        }
+                    }
+                } else {
+                    ip
+                };
+                self.pending_removal = None;
+                if self.removal_grace_until.take().is_some() {
+                    info!(ip = %ip, "Address {} appeared within removal_grace; removal cancelled", ip);
+                }
+                if let Err(e) = self.sync_record(&ip, false).await {
                     error!("Sync failed: {:#}", e);
                 }
             }
             Ok(NetlinkEvent::Ipv6Removed) => {
                 warn!("IPv6 address removed");
+                if self.config.read_only || self.check_paused().await {
+                    self.state.lock().await.detected_ip = self.detect_ip().await;
+                    return;
+                }
+                // One of several addresses may be gone; renumber the
+                // per-address records while others remain
+                if self.config.address_record_template.is_some() {
+                    let addrs = self.detect_all_ips();
+                    if !addrs.is_empty() {
+                        self.sync_address_records(&addrs, false).await;
+                    }
+                }
+                if self.config.on_removal == RemovalPolicy::Delete {
+                    let grace = self.config.removal_grace;
+                    if grace.is_zero() {
+                        self.handle_removal().await;
+                    } else if self.removal_grace_until.is_none() {
+                        info!(
+                            "Waiting {}s (removal_grace_secs) before acting on the removal",
+                            grace.as_secs()
+                        );
+                        self.removal_grace_until = Some(Instant::now() + grace);
+                    }
+                }
             }
-            Ok(NetlinkEvent::Unknown) => {}
+            Ok(NetlinkEvent::Unknown | NetlinkEvent::Resync) => {}
             Err(e) => debug!("Netlink error: {:#}", e),
         }
     }
 
-    /// Synchronizes the DNS record with the current IPv6 address
+    /// Deletes the DNS records after the IPv6 address has disappeared
     ///
     /// This method:
-    /// 1. Validates the IPv6 address format
-    /// 2. Checks if the IP has changed (skips if same)
-    /// 3. Checks if backoff is active (skips if in backoff period)
-    /// 4. Calls Cloudflare API to update or create the record
-    /// 5. Updates the application state on success or failure
-    ///
-    /// # Arguments
-    ///
-    /// * `ip` - The IPv6 address to sync
-    ///
-    /// # Returns
-    ///
-    /// Returns `Ok(())` on successful sync or an error if sync fails.
-    async fn sync_record(&self, ip: &str) -> Result<()> {
-        // Validate IPv6 address format before making API calls
-        if ip.parse::<std::net::Ipv6Addr>().is_err() {
-            return Err(anyhow::anyhow!("Invalid IPv6 address format: {}", ip));
+    /// 1. Re-checks for a global IPv6 address and syncs it instead if one remains
+    /// 2. Defers deletion of records the daemon created while within their cooldown
+    /// 3. Deletes the remaining records and resets their sync state
+    async fn handle_removal(&mut self) {
+        if self.check_paused().await {
+            return;
+        }
+        if let Some(ip) = self.detect_ip().await {
+            debug!("Global IPv6 still present ({}); not deleting records", ip);
+            if let Err(e) = self.sync_record(&ip, false).await {
+                error!("Sync failed: {:#}", e);
+            }
+            return;
         }
 
-        {
-            let state = self.state.lock().await;
-            if let RecordState::Synced(current) = &state.state {
-                if current == ip {
-                    debug!("No change: {}", ip);
-                    return Ok(());
-                }
+        let mut deferred: Option<Duration> = None;
+        for record in self.main_records() {
+            let remaining = self
+                .state
+                .lock()
+                .await
+                .record_mut(record)
+                .removal_cooldown_remaining(self.config.delete_cooldown);
+            if let Some(remaining) = remaining {
+                info!(
+                    record = %record,
+                    "Record was created recently; deferring deletion for {}s",
+                    remaining.as_secs()
+                );
+                deferred = Some(deferred.map_or(remaining, |d| d.min(remaining)));
+                continue;
             }
-            if let Some(next_retry) = state.next_retry {
+
+            match self
+                .dns_provider
+                .delete_record(self.config.zone_id.as_str(), record, RecordType::Aaaa)
+                .await
+            {
+                Ok(count) => {
+                    self.state.lock().await.record_mut(record).mark_removed();
+                    info!(
+                        record = %record,
+                        "Deleted {} AAAA record(s) after IPv6 removal",
+                        count
+                    );
+                }
+                Err(e) => {
+                    error!(record = %record, "Record deletion failed: {:#}", e);
+                }
+            }
+        }
+
+        if self.config.address_record_template.is_some() {
+            self.sync_address_records(&[], false).await;
+        }
+
+        if let Some(delay) = deferred {
+            self.pending_removal = Some(Instant::now() + delay);
+        }
+    }
+
+    /// Deletes all configured records before the daemon exits
+    ///
+    /// The whole deletion is bounded by `SHUTDOWN_DELETE_TIMEOUT_SECS` so a
+    /// hanging provider call can't block shutdown.
+    async fn delete_records_on_shutdown(&self) {
+        let mut names = self.main_records().to_vec();
+        if let Some(template) = &self.config.address_record_template {
+            let count = self.state.lock().await.address_record_count;
+            for record in &self.config.records {
+                names.extend((1..=count).map(|n| address_record_name(template, record, n)));
+            }
+        }
+        let delete_all = async {
+            for record in &names {
+                match self
+                    .dns_provider
+                    .delete_record(self.config.zone_id.as_str(), record, RecordType::Aaaa)
+                    .await
+                {
+                    Ok(count) => {
+                        self.state.lock().await.record_mut(record).mark_removed();
+                        info!(
+                            record = %record,
+                            "Deleted {} AAAA record(s) on shutdown",
+                            count
+                        )
+                    }
+                    Err(e) => {
+                        error!(record = %record, "Record deletion on shutdown failed: {:#}", e)
+                    }
+                }
+            }
+        };
+
+        let limit = Duration::from_secs(SHUTDOWN_DELETE_TIMEOUT_SECS);
+        if tokio::time::timeout(limit, delete_all).await.is_err() {
+            warn!(
+                "Record deletion on shutdown timed out after {}s",
+                limit.as_secs()
+            );
+        }
+    }
+
+    /// Synchronizes all configured DNS records with the current IPv6 address
+    ///
+    /// Each record is synced independently; a failure on one record does not
+    /// prevent the others from being updated. In read-only mode, while
+    /// `pause_file` exists, or with `record_type = "A"`, the address is only
+    /// recorded as detected.
+    ///
+    /// # Arguments
+    ///
+    /// * `ip` - The IPv6 address to sync
+    /// * `force` - Write records already synced to `ip` again
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if every record synced, or an error naming how many failed.
+    async fn sync_record(&self, ip: &str, force: bool) -> Result<()> {
+        // Validate IPv6 address format before making API calls
+        let ip = normalize_ipv6(ip)
+            .ok_or_else(|| anyhow::anyhow!("Invalid IPv6 address format: {}", ip))?;
+        let ip = ip.as_str();
+        if !is_valid_ipv6(ip, &self.config.ipv6_filter) {
+            return Err(anyhow::anyhow!(
+                "Refusing to publish non-global address: {}",
+                ip
+            ));
+        }
+
+        let previous = self.state.lock().await.detected_ip.replace(ip.to_string());
+        if !self.config.enable_ipv6 {
+            return Ok(());
+        }
+        if self.config.read_only {
+            if previous.as_deref() != Some(ip) {
+                info!(ip = %ip, "Read-only mode: not publishing {}", ip);
+            }
+            return Ok(());
+        }
+        if self.check_paused().await {
+            if previous.as_deref() != Some(ip) {
+                info!(ip = %ip, "DNS updates paused: not publishing {}", ip);
+            }
+            return Ok(());
+        }
+
+        let mut total = self.main_records().len();
+        let mut failed = self
+            .sync_records(self.main_records(), RecordType::Aaaa, ip, force)
+            .await;
+        if self.config.address_record_template.is_some() {
+            let addrs = self.detect_all_ips();
+            total += addrs.len() * self.config.records.len();
+            failed += self.sync_address_records(&addrs, force).await;
+        }
+        self.write_metrics_textfile();
+        if failed > 0 {
+            return Err(anyhow::anyhow!(
+                "{} of {} records failed to sync",
+                failed,
+                total
+            ));
+        }
+        self.update_heartbeat().await;
+        Ok(())
+    }
+
+    /// Sets the `heartbeat_txt` record to the current time, if configured
+    ///
+    /// Only done while every AAAA record is synced, so a heartbeat always
+    /// means the published address is current. Failures are logged; they
+    /// never affect the sync state of the address records.
+    async fn update_heartbeat(&self) {
+        let Some(name) = &self.config.heartbeat_txt else {
+            return;
+        };
+        let synced = self
+            .state
+            .lock()
+            .await
+            .records
+            .values()
+            .all(|status| matches!(status.state, RecordState::Synced(_)));
+        if !synced {
+            return;
+        }
+        let text = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        match self
+            .dns_provider
+            .upsert_txt_record(self.config.zone_id.as_str(), name, &text)
+            .await
+        {
+            Ok(()) => debug!("Heartbeat {} set to {}", name, text),
+            Err(e) => warn!("Heartbeat {} not updated: {:#}", name, e),
+        }
+    }
+
+    /// Publishes each address to its own record, named by `address_record_template`
+    ///
+    /// Address `n` goes to record `n` under every configured name. Records
+    /// numbered above the current count, left over from a sync with more
+    /// addresses, are deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `addrs` - The addresses to publish, in order
+    /// * `force` - Write records already synced to their address again
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of records that failed to sync
+    async fn sync_address_records(&self, addrs: &[String], force: bool) -> usize {
+        let Some(template) = &self.config.address_record_template else {
+            return 0;
+        };
+        let mut failed = 0;
+        for record in &self.config.records {
+            for (i, ip) in addrs.iter().enumerate() {
+                let name = address_record_name(template, record, i + 1);
+                if self
+                    .sync_one_record(&name, RecordType::Aaaa, ip, force)
+                    .await
+                    .is_err()
+                {
+                    failed += 1;
+                }
+            }
+        }
+
+        let previous = self.state.lock().await.address_record_count;
+        let mut count = addrs.len();
+        for n in addrs.len() + 1..=previous {
+            for record in &self.config.records {
+                let name = address_record_name(template, record, n);
+                match self
+                    .dns_provider
+                    .delete_record(self.config.zone_id.as_str(), &name, RecordType::Aaaa)
+                    .await
+                {
+                    Ok(deleted) => {
+                        self.state.lock().await.records.remove(&name);
+                        info!(
+                            record = %name,
+                            "Deleted {} AAAA record(s) no longer backed by an address",
+                            deleted
+                        );
+                    }
+                    Err(e) => {
+                        error!(record = %name, "Record deletion failed: {:#}", e);
+                        // Retry the deletion on the next sync
+                        count = count.max(n);
+                    }
+                }
+            }
+        }
+        self.state.lock().await.address_record_count = count;
+        failed
+    }
+
+    /// Publishes the current IPv4 address to the A records, if enabled
+    ///
+    /// Failures are logged per record and retried with the same backoff as
+    /// AAAA records. With `force`, records already holding the address are
+    /// written again.
+    async fn sync_ipv4(&self, force: bool) {
+        if !self.config.enable_ipv4 || self.config.read_only || self.check_paused().await {
+            return;
+        }
+        let Some(ip) = detect_global_ipv4(self.config.allow_private_ipv4) else {
+            debug!("No global IPv4 address found");
+            return;
+        };
+        self.sync_records(&self.config.records, RecordType::A, &ip, force)
+            .await;
+    }
+
+    /// Syncs several records to the same address, `max_concurrent_updates` at a time
+    ///
+    /// Permits are handed out in order, so with the default of one the
+    /// records are synced one after another in config order.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of records that failed to sync
+    async fn sync_records(
+        &self,
+        records: &[String],
+        record_type: RecordType,
+        ip: &str,
+        force: bool,
+    ) -> usize {
+        let permits = Semaphore::new(self.config.max_concurrent_updates);
+        let results = join_all(records.iter().map(|record| async {
+            // The semaphore is never closed
+            let _permit = permits.acquire().await.ok();
+            self.sync_one_record(record, record_type, ip, force).await
+        }))
+        .await;
+        results.iter().filter(|result| result.is_err()).count()
+    }
+
+    /// Synchronizes a single DNS record with the current address
+    ///
+    /// This method:
+    /// 1. Checks if the IP has changed (skips if same, unless forced)
+    /// 2. Checks if backoff is active (skips if in backoff period)
+    /// 3. Calls Cloudflare API to update or create the record
+    /// 4. Updates the record's state on success or failure
+    /// 5. Spawns the post-update hook and webhook (if configured) when the IPv6
+    ///    address changed
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The DNS record name
+    /// * `record_type` - Whether the AAAA or the A record is synced
+    /// * `ip` - The address to sync
+    /// * `force` - Write the record even if it was already synced to `ip`
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on successful sync or an error if sync fails.
+    async fn sync_one_record(
+        &self,
+        record: &str,
+        record_type: RecordType,
+        ip: &str,
+        force: bool,
+    ) -> Result<()> {
+        let (old_ip, sticky_id) = {
+            let mut state = self.state.lock().await;
+            let status = state.status_mut(record, record_type);
+            if status.state == RecordState::Misconfigured {
+                debug!(
+                    "Credentials were rejected for {}; not retrying until restart",
+                    record
+                );
+                return Ok(());
+            }
+            if let RecordState::Synced(current) = &status.state {
+                if current == ip && !force {
+                    debug!(
+                        event = "no_change",
+                        record = %record,
+                        record_type = %record_type,
+                        ip = %ip,
+                        "No change for {} {}: {}",
+                        record_type,
+                        record,
+                        ip
+                    );
+                    return Ok(());
+                }
+            }
+            if let Some(next_retry) = status.next_retry {
                 if next_retry > Instant::now() {
-                    debug!("Backoff active; skipping sync until {:?}", next_retry);
+                    debug!(
+                        "Backoff active for {}; skipping sync until {:?}",
+                        record, next_retry
+                    );
                     return Ok(());
                 }
             }
-        }
+            // Taken now: mark_synced replaces it once the update succeeds
+            let old_ip = match &status.state {
+                RecordState::Synced(current) => Some(current.clone()),
+                _ => None,
+            };
+            let sticky_id = status
+                .record_id
+                .clone()
+                .filter(|_| self.config.sticky_record);
+            (old_ip, sticky_id)
+        };
 
         let redacted_zone = redact_secrets(
             self.config.zone_id.as_str(),
@@ -344,35 +1534,655 @@ impl Daemon {
             self.config.zone_id.as_str(),
         );
         info!(
-            "Syncing {} -> {} (zone: {})",
-            self.config.record, ip, redacted_zone
+            record = %record,
+            record_type = %record_type,
+            ip = %ip,
+            zone = %redacted_zone,
+            "Syncing {} {} -> {} (zone: {})",
+            record_type,
+            record,
+            ip,
+            redacted_zone
         );
 
         let result = self
             .dns_provider
-            .upsert_aaaa_record(
+            .upsert_record(
                 self.config.zone_id.as_str(),
-                &self.config.record,
+                record,
+                record_type,
                 ip,
-                self.config.multi_record,
+                UpsertOptions {
+                    policy: self.config.multi_record(),
+                    allow_create: self.config.allow_create,
+                    reconcile: self.config.reconcile_fields,
+                    sticky_id: sticky_id.as_deref(),
+                    stamp_comment: self.config.stamp_comment,
+                },
             )
             .await;
 
         match result {
-            Ok(record) => {
+            Ok(dns_record) => {
                 let mut state = self.state.lock().await;
-                state.mark_synced(ip.to_string());
-                info!("Synced (ID: {})", record.id);
+                let status = state.status_mut(record, record_type);
+                status.mark_synced(ip.to_string());
+                if dns_record.created {
+                    status.created_at = Some(Instant::now());
+                }
+                status.record_id = Some(dns_record.id.clone());
+                status.ttl = Some(dns_record.ttl);
+                info!(
+                    event = "sync_applied",
+                    record = %record,
+                    record_type = %record_type,
+                    ip = %ip,
+                    old_ip = old_ip.as_deref(),
+                    record_id = %dns_record.id,
+                    sync_state = "synced",
+                    "Synced {} {}: {} -> {} (ID: {})",
+                    record_type,
+                    record,
+                    old_ip.as_deref().unwrap_or("none"),
+                    ip,
+                    dns_record.id
+                );
+                if self.config.verify_after_update {
+                    self.spawn_verify(record, record_type, ip);
+                }
+                // A forced write of the same address is no change to report
+                if old_ip.as_deref() == Some(ip) {
+                    return Ok(());
+                }
+                // Hooks and webhooks describe the published IPv6 address
+                if record_type == RecordType::A {
+                    return Ok(());
+                }
+                if self.config.verify_propagation {
+                    self.spawn_propagation_check(record, ip);
+                }
+                if let Some(command) = &self.config.post_update_command {
+                    spawn_post_update_hook(
+                        command,
+                        ip,
+                        record,
+                        old_ip.as_deref(),
+                        self.config.hook_timeout,
+                    );
+                }
+                if let Some(webhook) = &self.webhook {
+                    webhook.spawn_send(WebhookPayload {
+                        record: record.to_string(),
+                        old_ip,
+                        new_ip: ip.to_string(),
+                        timestamp: Utc::now(),
+                    });
+                }
                 Ok(())
             }
             Err(e) => {
+                let kind = ErrorKind::of(&e);
+                self.metrics
+                    .record_dns_error(self.config.provider_type.name(), kind.as_str());
                 let mut state = self.state.lock().await;
-                state.mark_error();
-                error!("Sync failed: {:#}", e);
+                let status = state.status_mut(record, record_type);
+                let sync_state = if kind == ErrorKind::Auth {
+                    status.mark_misconfigured();
+                    "misconfigured"
+                } else {
+                    status.mark_error(
+                        self.config.error_threshold,
+                        kind,
+                        ProviderError::retry_after_of(&e),
+                    );
+                    "error"
+                };
+                let message = redact_secrets(
+                    &format!("{:#}", e),
+                    self.config.api_token.as_str(),
+                    self.config.zone_id.as_str(),
+                );
+                error!(
+                    record = %record,
+                    ip = %ip,
+                    sync_state = sync_state,
+                    error_type = %kind,
+                    error_count = status.error_count,
+                    error = %message,
+                    "Sync failed ({}): {}",
+                    kind,
+                    message
+                );
+                if kind == ErrorKind::Auth {
+                    error!(
+                        record = %record,
+                        "Credentials were rejected; {} is not retried until the daemon is restarted",
+                        record
+                    );
+                }
                 Err(e)
             }
         }
     }
+
+    /// Looks up the synced AAAA records at `external_resolver`
+    ///
+    /// Runs in the background. Records synced too recently for resolvers to
+    /// have dropped the old address (see
+    /// [`RecordStatus::external_check_settle_time`]) are skipped until the next
+    /// check. A mismatch or failed lookup is logged, counted in the metrics and
+    /// shown in the health check; the record is not re-synced.
+    fn spawn_external_check(&self) {
+        let Some(resolver) = self.config.external_resolver else {
+            return;
+        };
+        let state = Arc::clone(&self.state);
+        let metrics = Arc::clone(&self.metrics);
+        tokio::spawn(async move {
+            let due: Vec<(String, String)> = state
+                .lock()
+                .await
+                .records
+                .iter()
+                .filter_map(|(record, status)| match &status.state {
+                    RecordState::Synced(ip)
+                        if status
+                            .since_last_sync()
+                            .is_some_and(|age| age >= status.external_check_settle_time()) =>
+                    {
+                        Some((record.clone(), ip.clone()))
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            for (record, ip) in due {
+                let timeout = Duration::from_secs(EXTERNAL_CHECK_TIMEOUT_SECS);
+                let check = match resolve_aaaa(resolver, &record, timeout).await {
+                    Ok(resolved) => {
+                        let check = ExternalCheck::compare(&ip, &resolved);
+                        if check == ExternalCheck::Mismatch {
+                            let found: Vec<String> =
+                                resolved.iter().map(|addr| addr.to_string()).collect();
+                            warn!(
+                                record = %record,
+                                ip = %ip,
+                                "{} resolves to [{}] at {}, but {} was published",
+                                record,
+                                found.join(", "),
+                                resolver,
+                                ip
+                            );
+                        } else {
+                            debug!("{} resolves to {} at {}", record, ip, resolver);
+                        }
+                        check
+                    }
+                    Err(e) => {
+                        warn!(record = %record, "Could not look up {} at {}: {:#}", record, resolver, e);
+                        ExternalCheck::Error
+                    }
+                };
+                metrics.record_external_check(check.as_str());
+
+                // The address may have changed during the lookup
+                let mut state = state.lock().await;
+                if let Some(status) = state.records.get_mut(&record) {
+                    if status.state == RecordState::Synced(ip) {
+                        status.external_check = Some(check);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Reads a record back after `verify_delay` to confirm the update took effect
+    ///
+    /// Runs in the background. A mismatch or failed read is logged and counted
+    /// in the metrics; the record state is left as is.
+    fn spawn_verify(&self, record: &str, record_type: RecordType, ip: &str) {
+        let config = Arc::clone(&self.config);
+        let dns_provider = Arc::clone(&self.dns_provider);
+        let metrics = Arc::clone(&self.metrics);
+        let record = record.to_string();
+        let ip = ip.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(config.verify_delay).await;
+            let result = dns_provider
+                .get_records(config.zone_id.as_str(), &record, record_type)
+                .await;
+            match result {
+                Ok(records) if records.iter().any(|r| r.content == ip) => {
+                    metrics.record_update_verification("ok");
+                    debug!("Verified {} {} -> {}", record_type, record, ip);
+                }
+                Ok(records) => {
+                    metrics.record_update_verification("mismatch");
+                    let found: Vec<&str> = records.iter().map(|r| r.content.as_str()).collect();
+                    warn!(
+                        record = %record,
+                        ip = %ip,
+                        "Update of {} {} did not take effect: expected {}, found [{}]",
+                        record_type,
+                        record,
+                        ip,
+                        found.join(", ")
+                    );
+                }
+                Err(e) => {
+                    metrics.record_update_verification("error");
+                    let message = redact_secrets(
+                        &format!("{:#}", e),
+                        config.api_token.as_str(),
+                        config.zone_id.as_str(),
+                    );
+                    warn!(record = %record, "Could not verify update of {}: {}", record, message);
+                }
+            }
+        });
+    }
+
+    /// Resolves an updated AAAA record until it returns the new address
+    ///
+    /// Runs in the background: up to `PROPAGATION_CHECK_ATTEMPTS` lookups at
+    /// `propagation_resolver`, `PROPAGATION_CHECK_DELAY_SECS` apart. If none
+    /// returns the address, a warning is logged; the sync is not failed, since
+    /// resolvers may serve the old address until its TTL expires.
+    fn spawn_propagation_check(&self, record: &str, ip: &str) {
+        let resolver = self.config.propagation_resolver;
+        let record = record.to_string();
+        let ip = ip.to_string();
+        tokio::spawn(async move {
+            let timeout = Duration::from_secs(EXTERNAL_CHECK_TIMEOUT_SECS);
+            let mut last_answer = String::new();
+            for attempt in 1..=PROPAGATION_CHECK_ATTEMPTS {
+                tokio::time::sleep(Duration::from_secs(PROPAGATION_CHECK_DELAY_SECS)).await;
+                match resolve_aaaa(resolver, &record, timeout).await {
+                    Ok(addrs) if ExternalCheck::compare(&ip, &addrs) == ExternalCheck::Match => {
+                        debug!(
+                            record = %record,
+                            "{} resolves to {} at {} (lookup {})",
+                            record,
+                            ip,
+                            resolver,
+                            attempt
+                        );
+                        return;
+                    }
+                    Ok(addrs) => {
+                        let found: Vec<String> = addrs.iter().map(Ipv6Addr::to_string).collect();
+                        last_answer = format!("found [{}]", found.join(", "));
+                    }
+                    Err(e) => last_answer = format!("{:#}", e),
+                }
+            }
+            warn!(
+                record = %record,
+                ip = %ip,
+                "{} does not resolve to {} at {} after {} lookups ({}); check the zone's \
+                 nameservers if this persists",
+                record,
+                ip,
+                resolver,
+                PROPAGATION_CHECK_ATTEMPTS,
+                last_answer
+            );
+        });
+    }
+}
+
+//==============================================================================
+// Status
+//==============================================================================
+
+/// Source asked for the address instead of the local interfaces
+enum AddressQuery {
+    /// The external address service (`source = "external"`)
+    External(ExternalIpSource),
+    /// The address command (`source = "command"`)
+    Command(CommandSource),
+}
+
+impl AddressQuery {
+    /// Creates the query for the configured `source`
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` with `source = "netlink"`, or an error if the external
+    /// address client can't be created
+    fn from_config(config: &Config) -> Result<Option<Self>> {
+        Ok(match config.source {
+            AddressSource::Netlink => None,
+            AddressSource::External => Some(Self::External(ExternalIpSource::new(
+                &config.external_ip_url,
+                config.timeout,
+                config.connect_timeout(),
+                config.ipv6_filter.clone(),
+            )?)),
+            AddressSource::Command => config.detect_command.clone().map(|command| {
+                Self::Command(CommandSource::new(
+                    command,
+                    config.timeout,
+                    config.ipv6_filter.clone(),
+                ))
+            }),
+        })
+    }
+
+    /// Asks the source for the address
+    ///
+    /// A failed external request is logged as a warning; a failed command is
+    /// only logged at debug level, since it is how the command says "no address".
+    async fn detect(&self) -> Option<String> {
+        match self {
+            Self::External(source) => source
+                .detect()
+                .await
+                .map_err(|e| warn!("External address detection failed: {:#}", e))
+                .ok(),
+            Self::Command(source) => source
+                .detect()
+                .await
+                .map_err(|e| debug!("No address from detect_command: {:#}", e))
+                .ok(),
+        }
+    }
+}
+
+impl std::fmt::Display for AddressQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::External(source) => write!(f, "external ({})", source.url()),
+            Self::Command(_) => f.write_str("command (detect_command)"),
+        }
+    }
+}
+
+/// Detects the address to publish
+///
+/// Asks the external service or runs the address command if one is given,
+/// otherwise reads the local interfaces with the configured scopes and
+/// selection policy. An address from either outside `allowed_regions` is
+/// refused like a local one.
+///
+/// # Arguments
+///
+/// * `config` - The loaded configuration
+/// * `query` - The source asked instead of the interfaces, if any
+///
+/// # Returns
+///
+/// Returns the address, or `None` if none is found or the source failed
+async fn detect_address(config: &Config, query: Option<&AddressQuery>) -> Option<String> {
+    let Some(query) = query else {
+        return detect_global_ipv6(
+            &config.ipv6_filter,
+            &config.accepted_scopes,
+            &config.selection,
+        );
+    };
+    let ip = query.detect().await?;
+    match ip.parse() {
+        Ok(addr) if config.selection.region_allowed(addr) => Some(ip),
+        _ => {
+            warn!(
+                ip = %ip,
+                "Not publishing {}: its prefix is not in allowed_regions",
+                ip
+            );
+            None
+        }
+    }
+}
+
+/// Prints what the provider has for each record next to the detected address
+///
+/// Runs the `status` subcommand: nothing is changed at the provider.
+///
+/// # Arguments
+///
+/// * `config` - The loaded configuration
+/// * `dns_provider` - The provider to query
+///
+/// # Returns
+///
+/// Returns `Ok(())` if every record holds the detected address, or an error
+/// naming how many don't (or if no address is detected)
+pub async fn print_status(config: &Config, dns_provider: &dyn DnsProvider) -> Result<()> {
+    let detected = detect_address_once(config).await?;
+    println!(
+        "Detected address: {}",
+        detected.as_deref().unwrap_or("none")
+    );
+
+    let mut differing = 0;
+    for record in &config.records {
+        let line = match dns_provider
+            .list_aaaa_records(config.zone_id.as_str(), record)
+            .await
+        {
+            Ok(records) => {
+                let contents: Vec<&str> = records.iter().map(|r| r.content.as_str()).collect();
+                let (line, in_sync) = status_line(record, detected.as_deref(), &contents);
+                if !in_sync {
+                    differing += 1;
+                }
+                line
+            }
+            Err(e) => {
+                differing += 1;
+                let message = redact_secrets(
+                    &format!("{:#}", e),
+                    config.api_token.as_str(),
+                    config.zone_id.as_str(),
+                );
+                format!("{}: lookup failed: {}", record, message)
+            }
+        };
+        println!("{}", line);
+    }
+
+    if detected.is_none() {
+        return Err(anyhow::anyhow!("No IPv6 address detected"));
+    }
+    if differing > 0 {
+        return Err(anyhow::anyhow!(
+            "{} of {} records differ from the detected address",
+            differing,
+            config.records.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Reports whether the provider's records have drifted from the detected address
+///
+/// Runs the `diff` subcommand. Unlike `status`, a failed lookup is an error
+/// rather than a differing record, so a monitoring job can tell drift from a
+/// broken check. Nothing is changed at the provider.
+///
+/// # Arguments
+///
+/// * `config` - The loaded configuration
+/// * `dns_provider` - The provider to query
+///
+/// # Returns
+///
+/// Returns `true` if every record holds only the detected address, `false` if
+/// any has drifted, or an error if no address is detected or a lookup fails
+pub async fn print_diff(config: &Config, dns_provider: &dyn DnsProvider) -> Result<bool> {
+    let detected = detect_address_once(config)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("No IPv6 address detected"))?;
+
+    let mut drifted = 0;
+    for record in &config.records {
+        let records = dns_provider
+            .list_aaaa_records(config.zone_id.as_str(), record)
+            .await
+            .map_err(|e| {
+                let message = redact_secrets(
+                    &format!("{:#}", e),
+                    config.api_token.as_str(),
+                    config.zone_id.as_str(),
+                );
+                anyhow::anyhow!("{}: lookup failed: {}", record, message)
+            })?;
+        let contents: Vec<&str> = records.iter().map(|r| r.content.as_str()).collect();
+        let (line, in_sync) = status_line(record, Some(&detected), &contents);
+        if !in_sync {
+            drifted += 1;
+            println!("{} (detected {})", line, detected);
+        } else {
+            println!("{}", line);
+        }
+    }
+    Ok(drifted == 0)
+}
+
+/// What a failed `probe` lookup says about the configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProbeFailure {
+    /// The provider rejected the credentials (401/403)
+    Token,
+    /// The credentials were accepted, but the zone doesn't exist for them
+    Zone,
+    /// Anything else: network trouble, a provider outage, an unsupported lookup
+    Other,
+}
+
+impl ProbeFailure {
+    /// Classifies an error returned by a provider lookup
+    fn of(err: &anyhow::Error) -> Self {
+        if err.downcast_ref::<ZoneNotFound>().is_some() {
+            Self::Zone
+        } else if ErrorKind::of(err) == ErrorKind::Auth {
+            Self::Token
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// Prints the diagnostic lines for a failed `probe` lookup
+///
+/// # Returns
+///
+/// Returns the error to exit with, its message redacted
+fn report_probe_failure(config: &Config, err: &anyhow::Error, record: &str) -> anyhow::Error {
+    let message = redact_secrets(
+        &format!("{:#}", err),
+        config.api_token.as_str(),
+        config.zone_id.as_str(),
+    );
+    match ProbeFailure::of(err) {
+        ProbeFailure::Token => {
+            println!("Token: rejected");
+            anyhow::anyhow!("Check the API token and its permissions: {}", message)
+        }
+        ProbeFailure::Zone => {
+            println!("Token: authenticated");
+            println!("Zone: not found");
+            anyhow::anyhow!("Check zone_id (or zone_name): {}", message)
+        }
+        ProbeFailure::Other => anyhow::anyhow!("{}: lookup failed: {}", record, message),
+    }
+}
+
+/// Checks the credentials, zone and record names against the provider
+///
+/// Runs the `probe` subcommand: resolves `zone_name` if set, then looks up
+/// the AAAA records of each name once. Rejected credentials and a missing
+/// zone are reported as such instead of as raw API errors. Nothing is changed
+/// at the provider, and no address needs to be detected.
+///
+/// # Arguments
+///
+/// * `config` - The loaded configuration; `zone_id` is filled in from `zone_name`
+/// * `metrics` - Counters updated for every API request
+///
+/// # Returns
+///
+/// Returns `Ok(())` if every lookup succeeded, or an error describing the first failure
+pub async fn print_probe(config: &mut Config, metrics: Arc<Metrics>) -> Result<()> {
+    println!(
+        "Config: OK ({}, {} record(s))",
+        config.provider_type,
+        config.records.len()
+    );
+    if let Err(e) = resolve_zone_name(config, Arc::clone(&metrics)).await {
+        let zone_name = config.zone_name.clone().unwrap_or_default();
+        return Err(report_probe_failure(config, &e, &zone_name));
+    }
+    let dns_provider = build_provider(config, metrics)
+        .with_context(|| format!("{} client failed", config.provider_type))?;
+
+    for (i, record) in config.records.iter().enumerate() {
+        let records = dns_provider
+            .list_aaaa_records(config.zone_id.as_str(), record)
+            .await
+            .map_err(|e| report_probe_failure(config, &e, record))?;
+        if i == 0 {
+            println!("Token: authenticated");
+            println!("Zone: found");
+        }
+        println!("{}: {} AAAA record(s)", record, records.len());
+    }
+    Ok(())
+}
+
+/// Prints the address the daemon would publish, and nothing else
+///
+/// Runs the `print-ip` subcommand, applying the same filters and selection
+/// policy as the daemon.
+///
+/// # Arguments
+///
+/// * `config` - The loaded configuration
+///
+/// # Returns
+///
+/// Returns `Ok(())` after printing the address, or an error if none is detected
+pub async fn print_ip(config: &Config) -> Result<()> {
+    let detected = detect_address_once(config)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("No IPv6 address detected"))?;
+    println!("{}", detected);
+    Ok(())
+}
+
+/// Detects the address once, for the `status`, `diff` and `print-ip` subcommands
+///
+/// # Returns
+///
+/// Returns the address or `None` if none is found, or an error if the external
+/// address client can't be created
+async fn detect_address_once(config: &Config) -> Result<Option<String>> {
+    let query = AddressQuery::from_config(config)?;
+    Ok(detect_address(config, query.as_ref()).await)
+}
+
+/// Formats the status of one record
+///
+/// # Arguments
+///
+/// * `record` - The record name
+/// * `detected` - The detected address, if any
+/// * `contents` - The addresses the provider holds for the record
+///
+/// # Returns
+///
+/// Returns the line to print and whether the record holds only the detected address
+fn status_line(record: &str, detected: Option<&str>, contents: &[&str]) -> (String, bool) {
+    if contents.is_empty() {
+        return (format!("{}: no AAAA record", record), false);
+    }
+    let in_sync = detected.is_some_and(|ip| contents.iter().all(|c| *c == ip));
+    let state = if in_sync { "in sync" } else { "out of date" };
+    (
+        format!("{}: {} ({})", record, contents.join(", "), state),
+        in_sync,
+    )
 }
 
 //==============================================================================
@@ -405,9 +2215,56 @@ mod tests {
         assert_eq!(delay, Duration::from_secs(BACKOFF_MAX_SECS));
     }
 
+    #[test]
+    fn test_backoff_delay_for() {
+        let secs = Duration::from_secs;
+        assert_eq!(backoff_delay_for(ErrorKind::Network, 1, None), secs(2));
+        assert_eq!(backoff_delay_for(ErrorKind::Network, 3, None), secs(8));
+        assert_eq!(backoff_delay_for(ErrorKind::Network, 10, None), secs(60));
+
+        assert_eq!(backoff_delay_for(ErrorKind::RateLimit, 1, None), secs(30));
+        assert_eq!(backoff_delay_for(ErrorKind::RateLimit, 2, None), secs(60));
+        assert_eq!(
+            backoff_delay_for(ErrorKind::RateLimit, 1, Some(secs(90))),
+            secs(90)
+        );
+        assert_eq!(
+            backoff_delay_for(ErrorKind::RateLimit, 1, Some(secs(86400))),
+            secs(BACKOFF_MAX_SECS)
+        );
+
+        for kind in [ErrorKind::Server, ErrorKind::Validation] {
+            for n in [1, 2, 5, 20] {
+                assert_eq!(backoff_delay_for(kind, n, None), backoff_delay(n));
+            }
+        }
+    }
+
+    #[test]
+    fn test_status_line() {
+        let ip = Some("2001:db8::1");
+        assert_eq!(
+            status_line("home.example.com", ip, &["2001:db8::1"]),
+            ("home.example.com: 2001:db8::1 (in sync)".to_string(), true)
+        );
+        assert_eq!(
+            status_line("home.example.com", ip, &["2001:db8::2"]),
+            (
+                "home.example.com: 2001:db8::2 (out of date)".to_string(),
+                false
+            )
+        );
+        assert!(!status_line("home.example.com", ip, &["2001:db8::1", "2001:db8::2"]).1);
+        assert!(!status_line("home.example.com", None, &["2001:db8::1"]).1);
+        assert_eq!(
+            status_line("home.example.com", ip, &[]),
+            ("home.example.com: no AAAA record".to_string(), false)
+        );
+    }
+
     #[test]
     fn test_app_state_default() {
-        let state = AppState::default();
+        let state = RecordStatus::default();
         assert_eq!(state.state, RecordState::Unknown);
         assert!(state.last_sync.is_none());
         assert_eq!(state.error_count, 0);
@@ -416,42 +2273,98 @@ mod tests {
 
     #[test]
     fn test_app_state_mark_synced() {
-        let mut state = AppState::default();
+        let mut state = RecordStatus::default();
         state.mark_synced("2001:db8::1".to_string());
 
         assert_eq!(state.state, RecordState::Synced("2001:db8::1".to_string()));
         assert!(state.last_sync.is_some());
+        assert!(state.since_last_sync().is_some());
         assert_eq!(state.error_count, 0);
         assert!(state.next_retry.is_none());
     }
 
+    #[test]
+    fn test_app_state_sync_age_ignores_wall_clock_jumps() {
+        let mut state = RecordStatus::default();
+        assert!(state.since_last_sync().is_none());
+        state.mark_synced("2001:db8::1".to_string());
+
+        // Simulate the wall clock being stepped a day in either direction
+        for jump in [chrono::Duration::days(1), chrono::Duration::days(-1)] {
+            state.last_sync = Some(Utc::now() + jump);
+            let age = state.since_last_sync().expect("synced");
+            assert!(age < Duration::from_secs(5));
+        }
+    }
+
     #[test]
     fn test_app_state_mark_error() {
-        let mut state = AppState::default();
+        let mut state = RecordStatus::default();
         state.mark_synced("2001:db8::1".to_string());
-        state.mark_error();
+        state.mark_error(1, ErrorKind::Server, None);
 
         assert!(matches!(state.state, RecordState::Error(_)));
         assert_eq!(state.error_count, 1);
         assert!(state.next_retry.is_some());
     }
 
+    #[test]
+    fn test_app_state_error_threshold() {
+        let mut state = RecordStatus::default();
+        state.mark_synced("2001:db8::1".to_string());
+
+        state.mark_error(3, ErrorKind::Server, None);
+        state.mark_error(3, ErrorKind::Server, None);
+        assert_eq!(state.state, RecordState::Synced("2001:db8::1".to_string()));
+        assert_eq!(state.error_count, 2);
+        // Backoff applies from the first failure
+        assert!(state.next_retry.is_some());
+
+        state.mark_error(3, ErrorKind::Server, None);
+        assert_eq!(state.state, RecordState::Error(3));
+    }
+
     #[test]
     fn test_app_state_error_backoff_increases() {
-        let mut state = AppState::default();
+        let mut state = RecordStatus::default();
 
-        state.mark_error();
+        state.mark_error(1, ErrorKind::Server, None);
         let retry1 = state.next_retry.unwrap();
-        state.mark_error();
+        state.mark_error(1, ErrorKind::Server, None);
         let retry2 = state.next_retry.unwrap();
 
         assert!(retry2 > retry1);
     }
 
+    #[test]
+    fn test_app_state_backoff_per_error_category() {
+        let mut state = RecordStatus::default();
+        for _ in 0..4 {
+            state.mark_error(1, ErrorKind::RateLimit, None);
+        }
+        assert_eq!(state.error_counts[&ErrorKind::RateLimit], 4);
+        let delay = state.next_retry.unwrap().duration_since(Instant::now());
+        assert!(delay > Duration::from_secs(230));
+
+        // The rate limits don't inflate the backoff of a network failure
+        state.mark_error(1, ErrorKind::Network, None);
+        assert_eq!(state.error_count, 5);
+        assert_eq!(state.error_counts[&ErrorKind::Network], 1);
+        let delay = state.next_retry.unwrap().duration_since(Instant::now());
+        assert!(delay <= Duration::from_secs(2));
+
+        state.mark_error(1, ErrorKind::RateLimit, Some(Duration::from_secs(7)));
+        let delay = state.next_retry.unwrap().duration_since(Instant::now());
+        assert!(delay <= Duration::from_secs(7) && delay > Duration::from_secs(5));
+
+        state.mark_synced("2001:db8::1".to_string());
+        assert!(state.error_counts.is_empty());
+    }
+
     #[test]
     fn test_app_state_sync_resets_error() {
-        let mut state = AppState::default();
-        state.mark_error();
+        let mut state = RecordStatus::default();
+        state.mark_error(1, ErrorKind::Server, None);
         state.mark_synced("2001:db8::1".to_string());
 
         assert_eq!(state.state, RecordState::Synced("2001:db8::1".to_string()));
@@ -459,6 +2372,184 @@ mod tests {
         assert!(state.next_retry.is_none());
     }
 
+    #[test]
+    fn test_app_state_tracks_records_independently() {
+        let records = vec![
+            "home.example.com".to_string(),
+            "nas.example.com".to_string(),
+        ];
+        let mut state = AppState::new(&records);
+        assert!(!state.families_synced(&[RecordType::Aaaa]));
+
+        state
+            .record_mut("home.example.com")
+            .mark_synced("2001:db8::1".to_string());
+        state
+            .record_mut("nas.example.com")
+            .mark_error(1, ErrorKind::Server, None);
+        assert!(!state.families_synced(&[RecordType::Aaaa]));
+        assert_eq!(
+            state.records["home.example.com"].state,
+            RecordState::Synced("2001:db8::1".to_string())
+        );
+
+        state
+            .record_mut("nas.example.com")
+            .mark_synced("2001:db8::1".to_string());
+        assert!(state.families_synced(&[RecordType::Aaaa]));
+        assert!(!AppState::default().families_synced(&[RecordType::Aaaa]));
+    }
+
+    #[test]
+    fn test_app_state_last_write_at() {
+        let mut state = AppState::new(&["home.example.com".to_string()]);
+        assert!(state.last_write_at().is_none());
+
+        state
+            .record_mut("home.example.com")
+            .mark_synced("2001:db8::1".to_string());
+        let first = state.last_write_at().unwrap();
+
+        // A failure is no write; a synced A record is
+        state
+            .record_mut("home.example.com")
+            .mark_error(1, ErrorKind::Server, None);
+        assert_eq!(state.last_write_at(), Some(first));
+        state
+            .status_mut("home.example.com", RecordType::A)
+            .mark_synced("192.0.2.1".to_string());
+        assert!(state.last_write_at().unwrap() >= first);
+        assert_eq!(
+            state.last_write_at(),
+            state.a_records["home.example.com"].last_sync_at
+        );
+    }
+
+    #[test]
+    fn test_app_state_mark_misconfigured() {
+        let mut state = RecordStatus::default();
+        state.mark_error(3, ErrorKind::Server, None);
+        state.mark_misconfigured();
+        assert_eq!(state.state, RecordState::Misconfigured);
+        assert_eq!(state.error_count, 2);
+        assert!(state.next_retry.is_none());
+
+        state.mark_synced("2001:db8::1".to_string());
+        assert_eq!(state.error_count, 0);
+    }
+
+    #[test]
+    fn test_app_state_record_over_error_limit() {
+        let records = vec![
+            "home.example.com".to_string(),
+            "nas.example.com".to_string(),
+        ];
+        let mut state = AppState::new(&records);
+        state
+            .record_mut("nas.example.com")
+            .mark_error(1, ErrorKind::Server, None);
+        state
+            .record_mut("nas.example.com")
+            .mark_error(1, ErrorKind::Server, None);
+        assert_eq!(state.record_over_error_limit(3), None);
+        assert_eq!(state.record_over_error_limit(2), Some("nas.example.com"));
+
+        // A success resets the count
+        state
+            .record_mut("nas.example.com")
+            .mark_synced("2001:db8::1".to_string());
+        assert_eq!(state.record_over_error_limit(1), None);
+
+        state
+            .status_mut("home.example.com", RecordType::A)
+            .mark_misconfigured();
+        assert_eq!(state.record_over_error_limit(10), Some("home.example.com"));
+    }
+
+    #[test]
+    fn test_app_state_families_synced() {
+        let records = vec!["home.example.com".to_string()];
+        let mut state = AppState::new(&records);
+        state
+            .record_mut("home.example.com")
+            .mark_synced("2001:db8::1".to_string());
+        assert!(state.families_synced(&[RecordType::Aaaa]));
+        // No IPv4 address was ever found
+        assert!(!state.families_synced(&[RecordType::Aaaa, RecordType::A]));
+
+        state
+            .status_mut("home.example.com", RecordType::A)
+            .mark_error(1, ErrorKind::Server, None);
+        assert!(!state.families_synced(&[RecordType::A]));
+        state
+            .status_mut("home.example.com", RecordType::A)
+            .mark_synced("192.0.2.1".to_string());
+        assert!(state.families_synced(&[RecordType::Aaaa, RecordType::A]));
+    }
+
+    #[test]
+    fn test_app_state_paused() {
+        let mut state = AppState::new(&["example.com".to_string()]);
+        assert!(!state.paused);
+        assert!(!state.set_paused(false));
+        assert!(state.set_paused(true));
+        assert!(!state.set_paused(true));
+        assert!(state.paused);
+        assert!(state.set_paused(false));
+        assert!(!state.paused);
+    }
+
+    #[test]
+    fn test_app_state_removal_cooldown() {
+        let mut state = RecordStatus::default();
+        let cooldown = Duration::from_secs(60);
+        assert!(state.removal_cooldown_remaining(cooldown).is_none());
+
+        state.created_at = Some(Instant::now());
+        let remaining = state.removal_cooldown_remaining(cooldown).unwrap();
+        assert!(remaining <= cooldown);
+        assert!(remaining > Duration::from_secs(55));
+        assert!(state.removal_cooldown_remaining(Duration::ZERO).is_none());
+
+        state.mark_synced("2001:db8::1".to_string());
+        state.mark_removed();
+        assert_eq!(state.state, RecordState::Unknown);
+        assert!(state.created_at.is_none());
+    }
+
+    #[test]
+    fn test_external_check() {
+        let resolved: Vec<Ipv6Addr> = vec!["2001:db8::1".parse().unwrap()];
+        assert_eq!(
+            ExternalCheck::compare("2001:0db8:0:0::1", &resolved),
+            ExternalCheck::Match
+        );
+        assert_eq!(
+            ExternalCheck::compare("2001:db8::2", &resolved),
+            ExternalCheck::Mismatch
+        );
+        assert_eq!(
+            ExternalCheck::compare("2001:db8::1", &[]),
+            ExternalCheck::Mismatch
+        );
+
+        let mut status = RecordStatus::default();
+        assert_eq!(
+            status.external_check_settle_time(),
+            Duration::from_secs(360)
+        );
+        status.ttl = Some(DNS_TTL_AUTO);
+        assert_eq!(
+            status.external_check_settle_time(),
+            Duration::from_secs(360)
+        );
+        status.ttl = Some(3600);
+        assert_eq!(
+            status.external_check_settle_time(),
+            Duration::from_secs(3660)
+        );
+    }
+
     #[test]
     fn test_redact_secrets() {
         let api_token = "secret_token_123";
@@ -478,11 +2569,41 @@ mod tests {
         assert_eq!(redacted, message);
     }
 
+    #[test]
+    fn test_probe_failure_of() {
+        let rejected = anyhow::Error::new(ProviderError::new(ErrorKind::Auth, "denied"));
+        assert_eq!(ProbeFailure::of(&rejected), ProbeFailure::Token);
+
+        let missing = anyhow::Error::new(ProviderError::new(ErrorKind::Validation, "no zone"))
+            .context(ZoneNotFound);
+        assert_eq!(ProbeFailure::of(&missing), ProbeFailure::Zone);
+
+        let outage = anyhow::Error::new(ProviderError::new(ErrorKind::Server, "502"));
+        assert_eq!(ProbeFailure::of(&outage), ProbeFailure::Other);
+        assert_eq!(
+            ProbeFailure::of(&anyhow::anyhow!("Listing records is not supported")),
+            ProbeFailure::Other
+        );
+    }
+
+    #[test]
+    fn test_truncate_message() {
+        assert_eq!(truncate_message("short", 5), "short");
+        assert_eq!(truncate_message("a longer body", 8), "a longer…(truncated)");
+        // Cut at a character boundary, not a byte offset
+        assert_eq!(truncate_message("ééé", 2), "éé…(truncated)");
+
+        // Redacting first keeps a secret at the cut from leaking in part
+        let message = "error: token secret123 rejected";
+        let clipped = truncate_message(&redact_secrets(message, "secret123", ""), 16);
+        assert_eq!(clipped, "error: token ***…(truncated)");
+    }
+
     // State machine transition tests
 
     #[test]
     fn test_state_machine_unknown_to_synced() {
-        let mut state = AppState::default();
+        let mut state = RecordStatus::default();
         assert_eq!(state.state, RecordState::Unknown);
 
         state.mark_synced("2001:db8::1".to_string());
@@ -494,10 +2615,10 @@ mod tests {
 
     #[test]
     fn test_state_machine_synced_to_error() {
-        let mut state = AppState::default();
+        let mut state = RecordStatus::default();
         state.mark_synced("2001:db8::1".to_string());
 
-        state.mark_error();
+        state.mark_error(1, ErrorKind::Server, None);
         assert!(matches!(state.state, RecordState::Error(1)));
         assert_eq!(state.error_count, 1);
         assert!(state.next_retry.is_some());
@@ -505,9 +2626,9 @@ mod tests {
 
     #[test]
     fn test_state_machine_error_to_synced() {
-        let mut state = AppState::default();
+        let mut state = RecordStatus::default();
         state.mark_synced("2001:db8::1".to_string());
-        state.mark_error();
+        state.mark_error(1, ErrorKind::Server, None);
 
         state.mark_synced("2001:db8::2".to_string());
         assert_eq!(state.state, RecordState::Synced("2001:db8::2".to_string()));
@@ -517,17 +2638,17 @@ mod tests {
 
     #[test]
     fn test_state_machine_multiple_errors_increases_backoff() {
-        let mut state = AppState::default();
+        let mut state = RecordStatus::default();
 
-        state.mark_error();
+        state.mark_error(1, ErrorKind::Server, None);
         let retry1 = state.next_retry.unwrap();
         assert_eq!(state.error_count, 1);
 
-        state.mark_error();
+        state.mark_error(1, ErrorKind::Server, None);
         let retry2 = state.next_retry.unwrap();
         assert_eq!(state.error_count, 2);
 
-        state.mark_error();
+        state.mark_error(1, ErrorKind::Server, None);
         let retry3 = state.next_retry.unwrap();
         assert_eq!(state.error_count, 3);
 
@@ -548,11 +2669,11 @@ mod tests {
 
     #[test]
     fn test_state_machine_backoff_max_limit() {
-        let mut state = AppState::default();
+        let mut state = RecordStatus::default();
 
         // Simulate many errors to hit max backoff
         for _ in 0..20 {
-            state.mark_error();
+            state.mark_error(1, ErrorKind::Server, None);
         }
 
         let retry_time = state.next_retry.unwrap();
@@ -565,7 +2686,7 @@ mod tests {
 
     #[test]
     fn test_state_machine_sync_with_same_ip_no_change() {
-        let mut state = AppState::default();
+        let mut state = RecordStatus::default();
         state.mark_synced("2001:db8::1".to_string());
 
         // Simulate sync with same IP (should be idempotent)
@@ -576,7 +2697,7 @@ mod tests {
 
     #[test]
     fn test_state_machine_sync_with_different_ip_updates() {
-        let mut state = AppState::default();
+        let mut state = RecordStatus::default();
         state.mark_synced("2001:db8::1".to_string());
 
         // Sync with different IP
@@ -589,10 +2710,10 @@ mod tests {
 
     #[test]
     fn test_netlink_event_ipv6_added() {
-        let event = NetlinkEvent::Ipv6Added("2001:db8::1".to_string());
-        assert!(matches!(event, NetlinkEvent::Ipv6Added(_)));
+        let event = NetlinkEvent::Ipv6Added("2001:db8::1".to_string(), None);
+        assert!(matches!(event, NetlinkEvent::Ipv6Added(..)));
 
-        if let NetlinkEvent::Ipv6Added(ip) = event {
+        if let NetlinkEvent::Ipv6Added(ip, _) = event {
             assert_eq!(ip, "2001:db8::1".to_string());
         }
     }
@@ -612,14 +2733,14 @@ mod tests {
     #[test]
     fn test_netlink_event_sequence() {
         let events = [
-            NetlinkEvent::Ipv6Added("2001:db8::1".to_string()),
-            NetlinkEvent::Ipv6Added("2001:db8::2".to_string()),
+            NetlinkEvent::Ipv6Added("2001:db8::1".to_string(), None),
+            NetlinkEvent::Ipv6Added("2001:db8::2".to_string(), None),
             NetlinkEvent::Ipv6Removed,
             NetlinkEvent::Unknown,
         ];
 
-        assert!(matches!(events[0], NetlinkEvent::Ipv6Added(_)));
-        assert!(matches!(events[1], NetlinkEvent::Ipv6Added(_)));
+        assert!(matches!(events[0], NetlinkEvent::Ipv6Added(..)));
+        assert!(matches!(events[1], NetlinkEvent::Ipv6Added(..)));
         assert!(matches!(events[2], NetlinkEvent::Ipv6Removed));
         assert!(matches!(events[3], NetlinkEvent::Unknown));
     }
@@ -634,8 +2755,8 @@ mod tests {
         ];
 
         for ip in valid_ips {
-            let event = NetlinkEvent::Ipv6Added(ip.to_string());
-            assert!(matches!(event, NetlinkEvent::Ipv6Added(_)));
+            let event = NetlinkEvent::Ipv6Added(ip.to_string(), None);
+            assert!(matches!(event, NetlinkEvent::Ipv6Added(..)));
             assert!(ip.parse::<std::net::Ipv6Addr>().is_ok());
         }
     }
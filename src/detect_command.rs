@@ -0,0 +1,220 @@
+//! Address detection through a user-supplied command
+//!
+//! With `source = "command"`, the address to publish is printed by
+//! `detect_command`, e.g. a script asking the router over UPnP or TR-064. This
+//! is an escape hatch for setups where neither the local interfaces nor an
+//! external service know the address to publish.
+//!
+//! The command is run every `poll_interval`. Its stdout, trimmed, must be a
+//! single IPv6 address that `ipv6_filter` accepts (see [`Ipv6Filter`]). A
+//! non-zero exit, a timeout or any other output counts as no address, which
+//! is reported like the address disappearing.
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{bail, Context as _, Result};
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::external_ip::parse_external_ip;
+use crate::hooks::HookCommand;
+use crate::netlink::{jittered_interval, random_unit, Ipv6Monitor, NetlinkEvent};
+use crate::validation::Ipv6Filter;
+
+//==============================================================================
+// Source
+//==============================================================================
+
+/// Runs `detect_command` to learn the address
+#[derive(Debug, Clone)]
+pub struct CommandSource {
+    /// The command printing the address
+    command: HookCommand,
+    /// Time after which the command is killed
+    timeout: Duration,
+    /// Reserved ranges the output may fall in
+    filter: Ipv6Filter,
+}
+
+impl CommandSource {
+    /// Creates a source running `command`
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command printing the address
+    /// * `timeout` - Time after which the command is killed
+    /// * `filter` - Reserved ranges the output may fall in
+    pub fn new(command: HookCommand, timeout: Duration, filter: Ipv6Filter) -> Self {
+        Self {
+            command,
+            timeout,
+            filter,
+        }
+    }
+
+    /// Runs the command and parses its output
+    ///
+    /// # Returns
+    ///
+    /// Returns the address, or an error if the command fails, times out or
+    /// doesn't print a publishable IPv6 address
+    pub async fn detect(&self) -> Result<String> {
+        debug!("Running detect_command: {}", self.command);
+        let output = self
+            .command
+            .to_command()
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(self.timeout, output)
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!("detect_command timed out after {}s", self.timeout.as_secs())
+            })?
+            .with_context(|| format!("Failed to spawn detect_command '{}'", self.command))?;
+        if !output.status.success() {
+            bail!("detect_command exited with {}", output.status);
+        }
+        parse_external_ip(&String::from_utf8_lossy(&output.stdout), &self.filter)
+            .context("Invalid detect_command output")
+    }
+}
+
+//==============================================================================
+// Monitor
+//==============================================================================
+
+/// Runs the command on every poll and reports changes of its output
+///
+/// Unlike an unreachable external service, a command that fails is taken at
+/// its word: the address is reported as removed until it prints one again.
+pub struct CommandMonitor {
+    source: CommandSource,
+    interval: Duration,
+    jitter: f64,
+    last_ip: Option<String>,
+}
+
+impl CommandMonitor {
+    /// Creates a monitor polling `source`
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The command source
+    /// * `interval` - Time between runs
+    /// * `jitter` - Random variation of the interval, as a fraction (0 = none)
+    pub fn new(source: CommandSource, interval: Duration, jitter: f64) -> Self {
+        Self {
+            source,
+            interval,
+            jitter,
+            last_ip: None,
+        }
+    }
+
+    /// Records the result of one run, returning the event to report, if any
+    fn update(&mut self, result: Result<String>) -> Option<NetlinkEvent> {
+        let ip = match result {
+            Ok(ip) => Some(ip),
+            Err(e) => {
+                debug!("No address from detect_command: {:#}", e);
+                None
+            }
+        };
+        if ip == self.last_ip {
+            return None;
+        }
+        self.last_ip = ip.clone();
+        Some(match ip {
+            Some(ip) => NetlinkEvent::Ipv6Added(ip, None),
+            None => NetlinkEvent::Ipv6Removed,
+        })
+    }
+}
+
+#[async_trait]
+impl Ipv6Monitor for CommandMonitor {
+    async fn next_event(&mut self) -> NetlinkEvent {
+        loop {
+            tokio::time::sleep(jittered_interval(self.interval, self.jitter, random_unit())).await;
+
+            let result = self.source.detect().await;
+            if let Some(event) = self.update(result) {
+                return event;
+            }
+        }
+    }
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell(line: &str) -> CommandSource {
+        CommandSource::new(
+            HookCommand::Exec(vec![
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                line.to_string(),
+            ]),
+            Duration::from_secs(5),
+            Ipv6Filter::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_command_source_detect() {
+        assert_eq!(
+            shell("echo '  2606:4700:4700:0:0:0:0:1111 '")
+                .detect()
+                .await
+                .unwrap(),
+            "2606:4700:4700::1111"
+        );
+
+        let err = shell("echo 2606:4700:4700::1111; exit 1")
+            .detect()
+            .await
+            .unwrap_err();
+        assert!(format!("{err}").contains("exited with"));
+        assert!(shell("echo not-an-address").detect().await.is_err());
+        assert!(shell("echo fe80::1").detect().await.is_err());
+
+        let slow = CommandSource::new(
+            HookCommand::Exec(vec!["sleep".to_string(), "5".to_string()]),
+            Duration::from_millis(100),
+            Ipv6Filter::default(),
+        );
+        let err = slow.detect().await.unwrap_err();
+        assert!(format!("{err}").contains("timed out"));
+    }
+
+    #[test]
+    fn test_command_monitor_reports_changes() {
+        let mut monitor = CommandMonitor::new(shell("true"), Duration::from_secs(60), 0.0);
+        let ip = |s: &str| Ok(s.to_string());
+
+        // Failing before any address was seen reports nothing
+        assert!(monitor.update(Err(anyhow::anyhow!("boom"))).is_none());
+        assert!(matches!(
+            monitor.update(ip("2606:4700::1")),
+            Some(NetlinkEvent::Ipv6Added(ref a, None)) if a == "2606:4700::1"
+        ));
+        assert!(monitor.update(ip("2606:4700::1")).is_none());
+        assert!(matches!(
+            monitor.update(ip("2606:4700::2")),
+            Some(NetlinkEvent::Ipv6Added(ref a, None)) if a == "2606:4700::2"
+        ));
+        assert!(matches!(
+            monitor.update(Err(anyhow::anyhow!("exit 1"))),
+            Some(NetlinkEvent::Ipv6Removed)
+        ));
+        assert!(monitor.update(Err(anyhow::anyhow!("exit 1"))).is_none());
+    }
+}
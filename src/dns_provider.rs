@@ -3,9 +3,28 @@
 //! This module defines a trait for DNS provider implementations, allowing
 //! ipv6ddns to support multiple DNS providers beyond Cloudflare.
 
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context as _;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::cloudflare::{CloudflareClient, TransportOptions};
+use crate::config::Config;
+use crate::constants::{
+    DNS_RECORD_TYPE_A, DNS_RECORD_TYPE_AAAA, HTTP_STATUS_FORBIDDEN, HTTP_STATUS_SERVER_ERROR_MAX,
+    HTTP_STATUS_SERVER_ERROR_MIN, HTTP_STATUS_TOO_MANY_REQUESTS, HTTP_STATUS_UNAUTHORIZED,
+};
+use crate::duckdns::DuckDnsClient;
+use crate::metrics::Metrics;
+use crate::namecheap::NamecheapClient;
+use crate::porkbun::PorkbunClient;
+use crate::route53::{AwsCredentials, Route53Client};
+use crate::vultr::VultrClient;
+
 //==============================================================================
 // Types
 //==============================================================================
@@ -29,6 +48,94 @@ pub struct DnsRecord {
     pub proxied: bool,
     /// Time-to-live value in seconds
     pub ttl: u64,
+    /// Whether the record was newly created by the call that returned it
+    #[serde(skip)]
+    pub created: bool,
+}
+
+/// Type of address record managed by the daemon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RecordType {
+    /// IPv6 address record
+    Aaaa,
+    /// IPv4 address record
+    A,
+}
+
+impl RecordType {
+    /// Returns the DNS type name ("AAAA" or "A")
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Aaaa => DNS_RECORD_TYPE_AAAA,
+            Self::A => DNS_RECORD_TYPE_A,
+        }
+    }
+}
+
+impl fmt::Display for RecordType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// DNS provider the daemon publishes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderType {
+    /// Cloudflare API (default)
+    Cloudflare,
+    /// DuckDNS update API
+    DuckDns,
+    /// Amazon Route 53 API
+    Route53,
+    /// Porkbun API
+    Porkbun,
+    /// Namecheap dynamic DNS
+    Namecheap,
+    /// Vultr API
+    Vultr,
+}
+
+impl ProviderType {
+    /// Every supported provider, in the order they are listed to users
+    pub const ALL: [ProviderType; 6] = [
+        ProviderType::Cloudflare,
+        ProviderType::DuckDns,
+        ProviderType::Route53,
+        ProviderType::Porkbun,
+        ProviderType::Namecheap,
+        ProviderType::Vultr,
+    ];
+
+    /// Returns the name used for `provider_type` in the configuration
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cloudflare => "cloudflare",
+            Self::DuckDns => "duckdns",
+            Self::Route53 => "route53",
+            Self::Porkbun => "porkbun",
+            Self::Namecheap => "namecheap",
+            Self::Vultr => "vultr",
+        }
+    }
+
+    /// Looks up a provider by its configuration name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL
+            .into_iter()
+            .find(|p| p.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the supported names for error messages (e.g. "cloudflare|duckdns")
+    pub fn supported() -> String {
+        Self::ALL.map(Self::name).join("|")
+    }
+}
+
+impl fmt::Display for ProviderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 /// Policy for handling multiple records with the same name
@@ -54,6 +161,254 @@ pub enum MultiRecordPolicy {
     UpdateAll,
 }
 
+/// Record fields compared against the desired state to decide whether to update
+///
+/// `content` is always reconciled; the other fields are opt-in so that a TTL,
+/// proxy setting, or comment edited in the provider dashboard doesn't cause an
+/// update unless the user asks for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconcileFields {
+    /// Update when the address differs (always enabled)
+    pub content: bool,
+    /// Update when the TTL differs from automatic
+    pub ttl: bool,
+    /// Update when the record is proxied
+    pub proxied: bool,
+    /// Update when the record has a comment
+    pub comment: bool,
+}
+
+impl Default for ReconcileFields {
+    fn default() -> Self {
+        Self {
+            content: true,
+            ttl: false,
+            proxied: false,
+            comment: false,
+        }
+    }
+}
+
+/// How an upsert treats existing and missing records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpsertOptions<'a> {
+    /// The policy for handling multiple records
+    pub policy: MultiRecordPolicy,
+    /// Whether a missing record may be created
+    pub allow_create: bool,
+    /// Which fields trigger an update when they differ
+    pub reconcile: ReconcileFields,
+    /// Record ID to keep updating under `UpdateFirst`, while it still exists
+    pub sticky_id: Option<&'a str>,
+    /// Whether written records get a comment with the update time
+    /// (Cloudflare only)
+    pub stamp_comment: bool,
+}
+
+/// Category of a failed provider request
+///
+/// Used as the `error_type` metric label and to decide whether retrying can
+/// help: an `Auth` failure never heals on its own, so the daemon stops
+/// retrying the record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ErrorKind {
+    /// The credentials were rejected (401/403)
+    Auth,
+    /// The provider is throttling requests (429)
+    RateLimit,
+    /// The provider failed to handle the request (5xx or malformed response)
+    Server,
+    /// No response arrived (connection, DNS or timeout failure)
+    Network,
+    /// The request was refused as invalid (other 4xx, missing or duplicate record)
+    Validation,
+}
+
+impl ErrorKind {
+    /// Returns the metric label ("auth", "rate_limit", ...)
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::RateLimit => "rate_limit",
+            Self::Server => "server",
+            Self::Network => "network",
+            Self::Validation => "validation",
+        }
+    }
+
+    /// Returns the category of an HTTP error status
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The HTTP status code of a failed request
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            HTTP_STATUS_UNAUTHORIZED | HTTP_STATUS_FORBIDDEN => Self::Auth,
+            HTTP_STATUS_TOO_MANY_REQUESTS => Self::RateLimit,
+            HTTP_STATUS_SERVER_ERROR_MIN..=HTTP_STATUS_SERVER_ERROR_MAX => Self::Server,
+            _ => Self::Validation,
+        }
+    }
+
+    /// Classifies an error returned by a provider
+    ///
+    /// Uses the first `ProviderError` in the error chain; transport errors
+    /// without one count as `Network` (or `Server` for an undecodable
+    /// response), and anything else as `Validation`.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - The error returned by a `DnsProvider` method
+    pub fn of(err: &anyhow::Error) -> Self {
+        for cause in err.chain() {
+            if let Some(e) = cause.downcast_ref::<ProviderError>() {
+                return e.kind;
+            }
+            if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+                return match e.status() {
+                    Some(status) => Self::from_status(status.as_u16()),
+                    None if e.is_decode() => Self::Server,
+                    None => Self::Network,
+                };
+            }
+        }
+        Self::Validation
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error raised by a provider, tagged with its category
+///
+/// Providers return it through `anyhow` (e.g. `bail!(ProviderError::new(..))`);
+/// `ErrorKind::of` finds it again under any added context.
+#[derive(Debug)]
+pub struct ProviderError {
+    /// Category of the failure
+    pub kind: ErrorKind,
+    /// Human-readable description
+    pub message: String,
+    /// How long the provider asked to wait before the next request
+    /// (`Retry-After`), if it did
+    pub retry_after: Option<Duration>,
+}
+
+impl ProviderError {
+    /// Creates an error of the given category
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+
+    /// Sets the delay the provider asked for before the next request
+    pub fn with_retry_after(mut self, retry_after: Option<Duration>) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Returns the `Retry-After` delay of an error returned by a provider
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - The error returned by a `DnsProvider` method
+    pub fn retry_after_of(err: &anyhow::Error) -> Option<Duration> {
+        err.chain()
+            .find_map(|cause| cause.downcast_ref::<ProviderError>())
+            .and_then(|e| e.retry_after)
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+/// Marks a failure caused by a zone the provider doesn't know
+///
+/// Attached as context by providers that can tell a missing zone apart from
+/// other rejected requests, so that the `probe` subcommand can point at the
+/// zone ID rather than the credentials.
+#[derive(Debug)]
+pub struct ZoneNotFound;
+
+impl fmt::Display for ZoneNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("The zone was not found")
+    }
+}
+
+/// Reads the `Retry-After` header of a response
+///
+/// # Arguments
+///
+/// * `headers` - The response headers
+///
+/// # Returns
+///
+/// Returns the delay asked for, or `None` if the header is missing or invalid
+pub fn retry_after_header(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, Utc::now())
+}
+
+/// Returns the part of a record name below a domain ("" for the apex)
+///
+/// Shared by the providers whose zone ID is the domain itself.
+///
+/// # Arguments
+///
+/// * `domain` - The domain, e.g. "example.com"
+/// * `record_name` - The record name, e.g. "home.example.com" or "@"
+///
+/// # Returns
+///
+/// Returns the relative name, or `None` if the record is not in the domain
+pub fn relative_name<'a>(domain: &str, record_name: &'a str) -> Option<&'a str> {
+    let domain = domain.trim().trim_end_matches('.');
+    let name = record_name.trim().trim_end_matches('.');
+    if name == "@" || name.eq_ignore_ascii_case(domain) {
+        return Some("");
+    }
+    let split = name.len().checked_sub(domain.len() + 1)?;
+    (name.as_bytes()[split] == b'.' && name[split + 1..].eq_ignore_ascii_case(domain))
+        .then(|| &name[..split])
+}
+
+/// Parses a `Retry-After` value: delay seconds or an HTTP date (RFC 9110)
+///
+/// # Arguments
+///
+/// * `value` - The header value
+/// * `now` - Current time, to turn a date into a delay
+///
+/// # Returns
+///
+/// Returns the delay (zero for a date in the past), or `None` if the value is
+/// neither form
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        date.with_timezone(&Utc)
+            .signed_duration_since(now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
 //==============================================================================
 // Trait
 //==============================================================================
@@ -65,17 +420,19 @@ pub enum MultiRecordPolicy {
 /// through a common API.
 #[async_trait]
 pub trait DnsProvider: Send + Sync {
-    /// Creates or updates an AAAA record with the given IPv6 address
+    /// Creates or updates an address record (AAAA or A) with the given address
     ///
     /// This method implements an upsert operation: it will create a new record
-    /// if none exists, or update existing records according to the specified policy.
+    /// if none exists (and `allow_create` is set), or update existing records
+    /// according to the specified policy.
     ///
     /// # Arguments
     ///
     /// * `zone_id` - The zone ID for the domain (provider-specific)
     /// * `record_name` - The DNS record name
-    /// * `ipv6_addr` - The IPv6 address to set
-    /// * `policy` - The policy for handling multiple records
+    /// * `record_type` - The record type to manage
+    /// * `content` - The address to set (IPv6 for AAAA, IPv4 for A)
+    /// * `options` - Multi-record policy, record creation, and reconciled fields
     ///
     /// # Returns
     ///
@@ -85,19 +442,229 @@ pub trait DnsProvider: Send + Sync {
     ///
     /// This function will return an error if:
     /// - Multiple records exist and policy is `Error`
+    /// - No record exists and `allow_create` is false
     /// - The HTTP request fails
     /// - The API returns an error response
     /// - Rate limit is exceeded
     /// - Server error occurs
-    async fn upsert_aaaa_record(
+    async fn upsert_record(
         &self,
         zone_id: &str,
         record_name: &str,
-        ipv6_addr: &str,
-        policy: MultiRecordPolicy,
+        record_type: RecordType,
+        content: &str,
+        options: UpsertOptions<'_>,
     ) -> anyhow::Result<DnsRecord>;
 
-    // Future providers can add lookup APIs as needed; keep the trait minimal.
+    /// Deletes all records of the given type and name
+    ///
+    /// # Arguments
+    ///
+    /// * `zone_id` - The zone ID for the domain (provider-specific)
+    /// * `record_name` - The DNS record name
+    /// * `record_type` - The record type to delete
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the number of records deleted (0 if none
+    /// existed) or an error
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The HTTP request fails
+    /// - The API returns an error response
+    async fn delete_record(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+    ) -> anyhow::Result<usize>;
+
+    /// Lists the records of the given type and name
+    ///
+    /// Used to verify that an update took effect. Providers that can't list
+    /// records keep the default, which returns an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `zone_id` - The zone ID for the domain (provider-specific)
+    /// * `record_name` - The DNS record name
+    /// * `record_type` - The record type to list
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the matching records (empty if none exist)
+    async fn get_records(
+        &self,
+        _zone_id: &str,
+        _record_name: &str,
+        _record_type: RecordType,
+    ) -> anyhow::Result<Vec<DnsRecord>> {
+        anyhow::bail!("Listing records is not supported by this provider")
+    }
+
+    /// Creates or updates the TXT record of the given name with `text`
+    ///
+    /// Used for the `heartbeat_txt` record. The record is owned by the daemon:
+    /// it is created if missing, regardless of `allow_create`. Providers that
+    /// can't manage TXT records keep the default, which returns an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `zone_id` - The zone ID for the domain (provider-specific)
+    /// * `record_name` - The DNS record name
+    /// * `text` - The text to publish, without surrounding quotes
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once the record holds `text`, or an error
+    async fn upsert_txt_record(
+        &self,
+        _zone_id: &str,
+        _record_name: &str,
+        _text: &str,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("TXT records are not supported by this provider")
+    }
+
+    /// Lists the AAAA records of the given name without changing anything
+    ///
+    /// A shorthand for `get_records` with `RecordType::Aaaa`, used by
+    /// inspection tooling such as `ipv6ddns status`.
+    ///
+    /// # Arguments
+    ///
+    /// * `zone_id` - The zone ID for the domain (provider-specific)
+    /// * `record_name` - The DNS record name
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the AAAA records (empty if none exist), or
+    /// an error if the provider can't list records
+    async fn list_aaaa_records(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+    ) -> anyhow::Result<Vec<DnsRecord>> {
+        self.get_records(zone_id, record_name, RecordType::Aaaa)
+            .await
+    }
+}
+
+//==============================================================================
+// Factory
+//==============================================================================
+
+/// Builds the DNS provider client selected by `provider_type`
+///
+/// This is the only place that maps a provider to its client; adding a
+/// provider means adding a `ProviderType` variant and an arm here.
+///
+/// # Arguments
+///
+/// * `config` - The validated configuration
+/// * `metrics` - Counters updated for every API request
+///
+/// # Returns
+///
+/// Returns the client, or an error if it cannot be created (e.g. missing
+/// Route 53 credentials)
+pub fn build_provider(
+    config: &Config,
+    metrics: Arc<Metrics>,
+) -> anyhow::Result<Arc<dyn DnsProvider>> {
+    let provider_type = config.provider_type;
+    let user_agent = config.user_agent();
+    let provider: Arc<dyn DnsProvider> = match provider_type {
+        ProviderType::Cloudflare => Arc::new(cloudflare_client(config, metrics)?),
+        ProviderType::DuckDns => Arc::new(DuckDnsClient::new(
+            config.api_token.as_str(),
+            config.timeout,
+            config.connect_timeout(),
+            &user_agent,
+            metrics,
+        )?),
+        ProviderType::Route53 => Arc::new(Route53Client::new(
+            AwsCredentials::from_env().context("Route 53 credentials")?,
+            config.timeout,
+            config.connect_timeout(),
+            &user_agent,
+            metrics,
+        )?),
+        ProviderType::Porkbun => Arc::new(PorkbunClient::new(
+            config.api_token.as_str(),
+            config.secret_key.as_str(),
+            config.timeout,
+            config.connect_timeout(),
+            &user_agent,
+            metrics,
+        )?),
+        ProviderType::Namecheap => Arc::new(NamecheapClient::new(
+            config.api_token.as_str(),
+            config.timeout,
+            config.connect_timeout(),
+            &user_agent,
+            metrics,
+        )?),
+        ProviderType::Vultr => Arc::new(VultrClient::new(
+            config.api_token.as_str(),
+            config.timeout,
+            config.connect_timeout(),
+            &user_agent,
+            metrics,
+        )?),
+    };
+    Ok(provider)
+}
+
+/// Resolves `zone_name` to the zone ID and stores it in `zone_id`
+///
+/// Does nothing unless `zone_name` is set (only allowed with Cloudflare), or
+/// in `read_only` mode, which never talks to the provider.
+///
+/// # Arguments
+///
+/// * `config` - The validated configuration, updated in place
+/// * `metrics` - Counters updated for every API request
+///
+/// # Returns
+///
+/// Returns `Ok(())` or an error if the zone can't be resolved
+pub async fn resolve_zone_name(config: &mut Config, metrics: Arc<Metrics>) -> anyhow::Result<()> {
+    let Some(zone_name) = config.zone_name.clone() else {
+        return Ok(());
+    };
+    if config.read_only {
+        return Ok(());
+    }
+    let zone_id = cloudflare_client(config, metrics)?
+        .resolve_zone_id(&zone_name)
+        .await
+        .with_context(|| format!("Resolving zone_name '{}' failed", zone_name))?;
+    // The zone ID is a secret like the token, so only the name is logged
+    tracing::debug!("Resolved zone_name {}", zone_name);
+    config.zone_id = zeroize::Zeroizing::new(zone_id);
+    Ok(())
+}
+
+/// Builds a Cloudflare client from the configuration
+fn cloudflare_client(config: &Config, metrics: Arc<Metrics>) -> anyhow::Result<CloudflareClient> {
+    CloudflareClient::new(
+        config.api_token.as_str(),
+        config.timeout,
+        config.connect_timeout(),
+        &config.user_agent(),
+        &TransportOptions {
+            api_base: config.api_base.clone(),
+            http_proxy: config.http_proxy.clone(),
+            https_proxy: config.https_proxy.clone(),
+            ca_cert_file: config.ca_cert_file.clone(),
+        },
+        config.max_retries,
+        metrics,
+    )
+    .map(|client| client.with_max_error_body(config.max_error_body_len))
 }
 
 //==============================================================================
@@ -117,6 +684,7 @@ mod tests {
             content: "2001:db8::1".to_string(),
             proxied: false,
             ttl: 1,
+            created: false,
         };
 
         let record2 = DnsRecord {
@@ -126,6 +694,7 @@ mod tests {
             content: "2001:db8::1".to_string(),
             proxied: false,
             ttl: 1,
+            created: false,
         };
 
         assert_eq!(record1, record2);
@@ -140,6 +709,7 @@ mod tests {
             content: "2001:db8::1".to_string(),
             proxied: false,
             ttl: 1,
+            created: false,
         };
 
         let record2 = DnsRecord {
@@ -149,11 +719,95 @@ mod tests {
             content: "2001:db8::1".to_string(),
             proxied: false,
             ttl: 1,
+            created: false,
         };
 
         assert_ne!(record1, record2);
     }
 
+    #[test]
+    fn test_error_kind_from_status() {
+        assert_eq!(ErrorKind::from_status(401), ErrorKind::Auth);
+        assert_eq!(ErrorKind::from_status(403), ErrorKind::Auth);
+        assert_eq!(ErrorKind::from_status(429), ErrorKind::RateLimit);
+        assert_eq!(ErrorKind::from_status(503), ErrorKind::Server);
+        assert_eq!(ErrorKind::from_status(400), ErrorKind::Validation);
+        assert_eq!(ErrorKind::from_status(404), ErrorKind::Validation);
+    }
+
+    #[test]
+    fn test_error_kind_of_finds_provider_error_under_context() {
+        let err = anyhow::Error::new(ProviderError::new(ErrorKind::Auth, "token rejected"))
+            .context("upsert home.example.com");
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Auth);
+        assert_eq!(
+            format!("{:#}", err),
+            "upsert home.example.com: token rejected"
+        );
+
+        let err = anyhow::anyhow!("Multiple AAAA records found");
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Validation);
+        assert_eq!(ErrorKind::RateLimit.to_string(), "rate_limit");
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let err = anyhow::Error::new(
+            ProviderError::new(ErrorKind::RateLimit, "slow down")
+                .with_retry_after(Some(Duration::from_secs(30))),
+        )
+        .context("upsert home.example.com");
+        assert_eq!(
+            ProviderError::retry_after_of(&err),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(ProviderError::retry_after_of(&anyhow::anyhow!("x")), None);
+
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:30:00 GMT", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-5", now), None);
+    }
+
+    #[test]
+    fn test_reconcile_fields_default_is_content_only() {
+        let fields = ReconcileFields::default();
+        assert!(fields.content);
+        assert!(!fields.ttl);
+        assert!(!fields.proxied);
+        assert!(!fields.comment);
+    }
+
+    #[test]
+    fn test_provider_type_names() {
+        for provider in ProviderType::ALL {
+            assert_eq!(ProviderType::from_name(provider.name()), Some(provider));
+        }
+        assert_eq!(
+            ProviderType::from_name(" Route53 "),
+            Some(ProviderType::Route53)
+        );
+        assert_eq!(ProviderType::from_name("gandi"), None);
+        assert_eq!(
+            ProviderType::supported(),
+            "cloudflare|duckdns|route53|porkbun|namecheap|vultr"
+        );
+        assert_eq!(ProviderType::DuckDns.to_string(), "duckdns");
+    }
+
     #[test]
     fn test_multi_record_policy_variants() {
         let policies = [
@@ -167,4 +821,78 @@ mod tests {
         assert!(policies.contains(&MultiRecordPolicy::UpdateFirst));
         assert!(policies.contains(&MultiRecordPolicy::UpdateAll));
     }
+
+    /// A log writer appending to a shared buffer
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(data);
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_resolve_zone_name_does_not_log_zone_id() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/client/v4/zones"))
+            .and(query_param("name", "example.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "success": true,
+                "errors": [],
+                "messages": [],
+                "result": [{ "id": "0123456789abcdef0123456789abcdef", "name": "example.com" }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            format!(
+                r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_name = "example.com"
+record_name = "home.example.com"
+api_base = "{}/client/v4"
+"#,
+                server.uri()
+            ),
+        )
+        .unwrap();
+        let mut config = Config::load(Some(path), &crate::config::CliOverrides::default()).unwrap();
+
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        resolve_zone_name(&mut config, Arc::new(Metrics::new()))
+            .await
+            .unwrap();
+        assert_eq!(config.zone_id.as_str(), "0123456789abcdef0123456789abcdef");
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("Resolved zone_name example.com"), "{}", logs);
+        assert!(
+            !logs.contains("0123456789abcdef0123456789abcdef"),
+            "{}",
+            logs
+        );
+    }
 }
@@ -0,0 +1,295 @@
+//! DuckDNS client for DNS operations
+//!
+//! This module provides a `DnsProvider` backed by the DuckDNS update API, a
+//! single GET request that sets the address of a `<name>.duckdns.org` domain:
+//!
+//! ```text
+//! https://www.duckdns.org/update?domains=<name>&token=<token>&ipv6=<address>
+//! ```
+//!
+//! DuckDNS answers `OK` or `KO` in the response body.
+//!
+//! # Differences from Cloudflare
+//!
+//! - The zone ID is unused; the domain is taken from the record name
+//!   (`home` or `home.duckdns.org`)
+//! - Each domain holds at most one AAAA and one A record, so `MultiRecordPolicy`
+//!   is ignored
+//! - Domains are created in the DuckDNS dashboard, so `allow_create` and
+//!   `reconcile_fields` are ignored
+//! - Deleting clears both the AAAA and the A record of the domain
+//! - Records can't be listed, so `verify_after_update` is not available
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use tracing::debug;
+use zeroize::ZeroizeOnDrop;
+
+use crate::constants::{
+    DUCKDNS_DOMAIN_SUFFIX, DUCKDNS_TTL_SECS, DUCKDNS_UPDATE_URL, HTTP_POOL_IDLE_TIMEOUT_SECS,
+};
+use crate::dns_provider::{
+    DnsProvider, DnsRecord, ErrorKind, ProviderError, RecordType, UpsertOptions,
+};
+use crate::metrics::Metrics;
+
+//==============================================================================
+// Client
+//==============================================================================
+
+/// DuckDNS API client
+///
+/// The token is wrapped in `Zeroizing` to ensure it is securely cleared from
+/// memory when dropped. It is sent as a query parameter, so request URLs are
+/// never logged.
+#[derive(ZeroizeOnDrop)]
+pub struct DuckDnsClient {
+    /// DuckDNS account token
+    #[zeroize(skip)]
+    token: zeroize::Zeroizing<String>,
+    /// HTTP client for making requests
+    #[zeroize(skip)]
+    client: reqwest::Client,
+    /// Counters for API requests
+    #[zeroize(skip)]
+    metrics: Arc<Metrics>,
+}
+
+impl DuckDnsClient {
+    /// Creates a new DuckDNS client
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - DuckDNS account token
+    /// * `timeout` - HTTP request timeout duration
+    /// * `connect_timeout` - Time allowed to establish a connection
+    /// * `user_agent` - User-Agent header sent with every request
+    /// * `metrics` - Counters updated for every API request
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the client or an error if client creation fails
+    pub fn new(
+        token: &str,
+        timeout: Duration,
+        connect_timeout: Duration,
+        user_agent: &str,
+        metrics: Arc<Metrics>,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .user_agent(user_agent)
+            .pool_idle_timeout(Duration::from_secs(HTTP_POOL_IDLE_TIMEOUT_SECS))
+            .build()
+            .context("build reqwest client")?;
+
+        Ok(Self {
+            token: zeroize::Zeroizing::new(token.to_string()),
+            client,
+            metrics,
+        })
+    }
+
+    /// Builds the update URL for a domain
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - The DuckDNS subdomain (without `.duckdns.org`)
+    /// * `params` - Extra query parameters (`ipv6`, `ip` or `clear`)
+    fn update_url(&self, domain: &str, params: &[(&str, &str)]) -> Result<reqwest::Url> {
+        let mut query = vec![("domains", domain), ("token", self.token.as_str())];
+        query.extend_from_slice(params);
+        reqwest::Url::parse_with_params(DUCKDNS_UPDATE_URL, &query)
+            .context("Failed to build DuckDNS update URL")
+    }
+
+    /// Sends an update request and checks for the `OK` answer
+    ///
+    /// # Arguments
+    ///
+    /// * `record_name` - The record name, used in error messages
+    /// * `params` - Extra query parameters (`ipv6`, `ip` or `clear`)
+    async fn update(&self, record_name: &str, params: &[(&str, &str)]) -> Result<()> {
+        let domain = duckdns_subdomain(record_name)?;
+        let url = self.update_url(domain, params)?;
+
+        debug!("GET {} (domain: {})", DUCKDNS_UPDATE_URL, domain);
+        let result = self.client.get(url).send().await;
+        self.metrics.record_provider_request(
+            "GET",
+            result.as_ref().ok().map(|resp| resp.status().as_u16()),
+        );
+        // The error would include the URL, and with it the token
+        let resp = result
+            .map_err(reqwest::Error::without_url)
+            .context("DuckDNS request failed")?;
+
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .context("Failed to read DuckDNS response")?;
+        if !status.is_success() {
+            bail!(ProviderError::new(
+                ErrorKind::from_status(status.as_u16()),
+                format!("DuckDNS returned HTTP {} for {}", status, record_name)
+            ));
+        }
+        check_response(&body).with_context(|| format!("DuckDNS update of {}", record_name))
+    }
+}
+
+#[async_trait]
+impl DnsProvider for DuckDnsClient {
+    async fn upsert_record(
+        &self,
+        _zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+        content: &str,
+        _options: UpsertOptions<'_>,
+    ) -> Result<DnsRecord> {
+        let param = match record_type {
+            RecordType::Aaaa => "ipv6",
+            RecordType::A => "ip",
+        };
+        self.update(record_name, &[(param, content)]).await?;
+
+        Ok(DnsRecord {
+            id: duckdns_subdomain(record_name)?.to_string(),
+            record_type: record_type.as_str().to_string(),
+            name: record_name.to_string(),
+            content: content.to_string(),
+            proxied: false,
+            ttl: DUCKDNS_TTL_SECS,
+            created: false,
+        })
+    }
+
+    async fn delete_record(
+        &self,
+        _zone_id: &str,
+        record_name: &str,
+        _record_type: RecordType,
+    ) -> Result<usize> {
+        self.update(record_name, &[("clear", "true")]).await?;
+        Ok(1)
+    }
+}
+
+//==============================================================================
+// Helpers
+//==============================================================================
+
+/// Returns the DuckDNS subdomain of a record name
+///
+/// Accepts both the bare subdomain (`home`) and the full name
+/// (`home.duckdns.org`).
+///
+/// # Arguments
+///
+/// * `record_name` - The configured record name
+///
+/// # Returns
+///
+/// Returns the subdomain, or an error if the name is not a DuckDNS domain
+pub fn duckdns_subdomain(record_name: &str) -> Result<&str> {
+    let name = record_name.trim().trim_end_matches('.');
+    let lower = name.to_ascii_lowercase();
+    let domain = if lower.ends_with(DUCKDNS_DOMAIN_SUFFIX) {
+        &name[..name.len() - DUCKDNS_DOMAIN_SUFFIX.len()]
+    } else {
+        name
+    };
+    if domain.is_empty()
+        || !domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        bail!(
+            "DuckDNS record name must be <name> or <name>{}, got: {}",
+            DUCKDNS_DOMAIN_SUFFIX,
+            record_name
+        );
+    }
+    Ok(domain)
+}
+
+/// Checks the body of a DuckDNS update response
+///
+/// # Returns
+///
+/// Returns `Ok(())` for `OK`, otherwise an error
+fn check_response(body: &str) -> Result<()> {
+    match body.lines().next().unwrap_or_default().trim() {
+        "OK" => Ok(()),
+        "KO" => bail!(ProviderError::new(
+            ErrorKind::Auth,
+            "DuckDNS rejected the update (KO): check the token and domain"
+        )),
+        other => bail!("Unexpected DuckDNS response: {:?}", other),
+    }
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::CLOUDFLARE_USER_AGENT;
+
+    #[test]
+    fn test_duckdns_subdomain() {
+        assert_eq!(duckdns_subdomain("home").unwrap(), "home");
+        assert_eq!(duckdns_subdomain("home.duckdns.org").unwrap(), "home");
+        assert_eq!(duckdns_subdomain("Home.DuckDNS.org.").unwrap(), "Home");
+        assert_eq!(duckdns_subdomain("my-nas.duckdns.org").unwrap(), "my-nas");
+        assert!(duckdns_subdomain("home.example.com").is_err());
+        assert!(duckdns_subdomain(".duckdns.org").is_err());
+        assert!(duckdns_subdomain("").is_err());
+    }
+
+    #[test]
+    fn test_update_url() {
+        let client = DuckDnsClient::new(
+            "a7c4d0ad-114e-40ef-ba1d-d217904a50f2",
+            Duration::from_secs(5),
+            Duration::from_secs(5),
+            CLOUDFLARE_USER_AGENT,
+            Arc::new(Metrics::new()),
+        )
+        .unwrap();
+        let url = client
+            .update_url("home", &[("ipv6", "2001:db8::1")])
+            .unwrap();
+
+        assert_eq!(url.host_str(), Some("www.duckdns.org"));
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(
+            query,
+            [
+                ("domains".to_string(), "home".to_string()),
+                (
+                    "token".to_string(),
+                    "a7c4d0ad-114e-40ef-ba1d-d217904a50f2".to_string()
+                ),
+                ("ipv6".to_string(), "2001:db8::1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_response() {
+        assert!(check_response("OK").is_ok());
+        assert!(check_response("OK\n2001:db8::1\n").is_ok());
+        let err = check_response("KO").unwrap_err();
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Auth);
+        assert!(check_response("").is_err());
+    }
+}
@@ -0,0 +1,221 @@
+//! Address detection through an external "what is my IP" service
+//!
+//! With `source = "external"`, the address to publish is not read from the
+//! local interfaces but asked from an HTTPS service that answers with the
+//! address the request came from, such as `https://api6.ipify.org`. This is
+//! for hosts whose globally reachable address is not configured on any
+//! interface, e.g. behind NPTv6 prefix translation.
+//!
+//! The service is polled every `poll_interval`; there are no change events to
+//! wait for. The answer must be a single IPv6 address in plain text, and it is
+//! refused if `ipv6_filter` does not accept its range (see [`Ipv6Filter`]).
+
+use std::net::Ipv6Addr;
+use std::time::Duration;
+
+use anyhow::{bail, Context as _, Result};
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+use crate::constants::{CLOUDFLARE_USER_AGENT, HTTP_POOL_IDLE_TIMEOUT_SECS};
+use crate::netlink::{jittered_interval, random_unit, Ipv6Monitor, NetlinkEvent};
+use crate::validation::{is_valid_ipv6, Ipv6Filter};
+
+//==============================================================================
+// Client
+//==============================================================================
+
+/// Client for the external address service
+#[derive(Debug, Clone)]
+pub struct ExternalIpSource {
+    /// Service URL
+    url: String,
+    /// HTTP client for making requests
+    client: reqwest::Client,
+    /// Reserved ranges the answer may fall in
+    filter: Ipv6Filter,
+}
+
+impl ExternalIpSource {
+    /// Creates a new client for the service at `url`
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The service URL (must use HTTPS)
+    /// * `timeout` - HTTP request timeout duration
+    /// * `connect_timeout` - Time allowed to establish a connection
+    /// * `filter` - Reserved ranges the answer may fall in
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the client or an error if client creation fails
+    pub fn new(
+        url: &str,
+        timeout: Duration,
+        connect_timeout: Duration,
+        filter: Ipv6Filter,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .user_agent(CLOUDFLARE_USER_AGENT)
+            .pool_idle_timeout(Duration::from_secs(HTTP_POOL_IDLE_TIMEOUT_SECS))
+            .build()
+            .context("build reqwest client")?;
+
+        Ok(Self {
+            url: url.to_string(),
+            client,
+            filter,
+        })
+    }
+
+    /// Returns the service URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Asks the service for this host's public IPv6 address
+    ///
+    /// # Returns
+    ///
+    /// Returns the address, or an error if the request fails or the answer
+    /// is not a publishable IPv6 address
+    pub async fn detect(&self) -> Result<String> {
+        debug!("GET {}", self.url);
+        let resp = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .with_context(|| format!("Request to {} failed", self.url))?;
+        let status = resp.status();
+        if !status.is_success() {
+            bail!("{} returned HTTP {}", self.url, status);
+        }
+        let body = resp
+            .text()
+            .await
+            .with_context(|| format!("Failed to read the answer of {}", self.url))?;
+        parse_external_ip(&body, &self.filter)
+            .with_context(|| format!("Invalid answer from {}", self.url))
+    }
+}
+
+/// Parses the body of a "what is my IP" answer
+///
+/// # Arguments
+///
+/// * `body` - The response body, a single address with optional whitespace
+/// * `filter` - Reserved ranges the address may fall in
+///
+/// # Returns
+///
+/// Returns the address in canonical form, or an error if it is not an IPv6
+/// address or lies in a range the filter refuses
+pub fn parse_external_ip(body: &str, filter: &Ipv6Filter) -> Result<String> {
+    let body = body.trim();
+    let addr: Ipv6Addr = match body.parse() {
+        Ok(addr) => addr,
+        Err(_) => bail!(
+            "expected an IPv6 address, got: {:?}",
+            body.chars().take(64).collect::<String>()
+        ),
+    };
+    let ip = addr.to_string();
+    if !is_valid_ipv6(&ip, filter) {
+        bail!("{} is in a reserved range and can't be published", ip);
+    }
+    Ok(ip)
+}
+
+//==============================================================================
+// Monitor
+//==============================================================================
+
+/// Polls the external service and reports address changes
+///
+/// A failed request is logged and the last known address kept: the service
+/// being unreachable says nothing about the address, so no removal is ever
+/// reported.
+pub struct ExternalMonitor {
+    source: ExternalIpSource,
+    interval: Duration,
+    jitter: f64,
+    last_ip: Option<String>,
+}
+
+impl ExternalMonitor {
+    /// Creates a monitor polling `source`
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The external address service
+    /// * `interval` - Time between requests
+    /// * `jitter` - Random variation of the interval, as a fraction (0 = none)
+    pub fn new(source: ExternalIpSource, interval: Duration, jitter: f64) -> Self {
+        Self {
+            source,
+            interval,
+            jitter,
+            last_ip: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Ipv6Monitor for ExternalMonitor {
+    async fn next_event(&mut self) -> NetlinkEvent {
+        loop {
+            tokio::time::sleep(jittered_interval(self.interval, self.jitter, random_unit())).await;
+
+            match self.source.detect().await {
+                Ok(ip) if self.last_ip.as_deref() != Some(ip.as_str()) => {
+                    self.last_ip = Some(ip.clone());
+                    return NetlinkEvent::Ipv6Added(ip, None);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("External address detection failed: {:#}", e),
+            }
+        }
+    }
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_external_ip() {
+        let filter = Ipv6Filter::default();
+        assert_eq!(
+            parse_external_ip("2606:4700:4700::1111\n", &filter).unwrap(),
+            "2606:4700:4700::1111"
+        );
+        assert_eq!(
+            parse_external_ip("  2a00:1450:4001:0:0:0:0:200e ", &filter).unwrap(),
+            "2a00:1450:4001::200e"
+        );
+
+        assert!(parse_external_ip("203.0.113.7", &filter).is_err());
+        assert!(parse_external_ip("<html>Too many requests</html>", &filter).is_err());
+        assert!(parse_external_ip("", &filter).is_err());
+        // Reserved ranges are never published
+        assert!(parse_external_ip("::1", &filter).is_err());
+        assert!(parse_external_ip("fe80::1", &filter).is_err());
+        assert!(parse_external_ip("2001:db8::1", &filter).is_err());
+
+        let lab = Ipv6Filter {
+            allow_documentation: true,
+            ..Ipv6Filter::default()
+        };
+        assert_eq!(
+            parse_external_ip("2001:db8::1", &lab).unwrap(),
+            "2001:db8::1"
+        );
+    }
+}
@@ -1,36 +1,106 @@
 //! Health check endpoint for ipv6ddns
 //!
-//! This module provides a lightweight HTTP endpoint for health checks.
+//! This module provides a lightweight HTTP endpoint for health checks, and
+//! serves Prometheus metrics at `/metrics`.
+//!
+//! Besides the JSON status at `/health` (and `/`), it answers Kubernetes-style
+//! probes: `/livez` (alias `/healthz`) returns 200 while the daemon runs, and
+//! `/readyz` returns 200 once the first sync has completed and 503 before.
 
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpListener;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{oneshot, Mutex};
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
-use crate::daemon::{AppState, RecordState};
+use crate::config::Config;
+use crate::constants::{HEALTH_MAX_REQUEST_BYTES, HEALTH_REQUEST_TIMEOUT_SECS};
+use crate::daemon::{AppState, ExternalCheck, RecordState, RecordStatus};
+use crate::dns_provider::RecordType;
+use crate::metrics::Metrics;
+use crate::netlink::jitter_bounds;
 
 //==============================================================================
 // Types
 //==============================================================================
 
 /// Health check response
+///
+/// The top-level fields aggregate all configured records: the daemon is only
+/// healthy when every record of each required address family (see
+/// `required_families`) is synced.
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     /// Overall health status
     pub status: String,
+    /// Aggregate sync state ("synced" only if every record is synced,
+    /// "misconfigured" if the provider rejected the credentials)
+    pub sync_state: String,
+    /// Time since the oldest successful sync (in seconds, or null if any record never synced)
+    pub last_sync_seconds_ago: Option<f64>,
+    /// Configured `max_sync_age` in seconds (null if there is no limit)
+    pub max_sync_age_seconds: Option<u64>,
+    /// Whether the oldest sync is older than `max_sync_age`
+    pub stale: bool,
+    /// Whether any record resolves differently at `external_resolver`
+    pub external_mismatch: bool,
+    /// Highest number of consecutive errors across records
+    pub error_count: u64,
+    /// Whether the daemon is healthy
+    pub healthy: bool,
+    /// IPv6 address currently believed to be published (null until first sync)
+    pub current_ip: Option<String>,
+    /// IPv4 address published in the A records (null unless `enable_ipv4` synced one)
+    pub current_ipv4: Option<String>,
+    /// Whether the daemon fell back to polling (only reported in strict mode)
+    pub monitoring_degraded: bool,
+    /// Whether the daemon runs in read-only mode and never publishes
+    pub read_only: bool,
+    /// Whether DNS updates are paused because `pause_file` exists
+    pub paused: bool,
+    /// Whether the first sync has completed (what `/readyz` reports)
+    pub ready: bool,
+    /// Interval address detection polls at, in seconds (null when netlink
+    /// events drive detection)
+    pub poll_interval_seconds: Option<u64>,
+    /// Shortest and longest wait between polls once `poll_jitter` is applied
+    /// (null when not polling)
+    pub poll_interval_range_seconds: Option<[f64; 2]>,
+    /// Most recently detected IPv6 address (null until one is found)
+    pub detected_ip: Option<String>,
+    /// Aggregate sync state of each published family ("ipv6", "ipv4"), so a
+    /// failing family can be told apart from the other
+    pub families: BTreeMap<String, String>,
+    /// Per-record status of the AAAA records (empty with `record_type = "A"`)
+    pub records: Vec<RecordHealth>,
+    /// Per-record status of the A records (empty unless IPv4 is published)
+    pub a_records: Vec<RecordHealth>,
+}
+
+/// Health details for a single DNS record
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordHealth {
+    /// Configured DNS record name
+    pub record: String,
     /// Current sync state
     pub sync_state: String,
     /// Time since last successful sync (in seconds, or null if never synced)
     pub last_sync_seconds_ago: Option<f64>,
+    /// Wall-clock time of the last successful sync (RFC 3339, or null if never synced)
+    pub last_sync: Option<DateTime<Utc>>,
     /// Number of consecutive errors
     pub error_count: u64,
-    /// Whether the daemon is healthy
-    pub healthy: bool,
+    /// Address currently believed to be published (null until first sync)
+    pub current_ip: Option<String>,
+    /// Last lookup at `external_resolver`: "match", "mismatch" or "error"
+    /// (null if not checked since the last sync)
+    pub external_check: Option<String>,
 }
 
 /// Health check server
@@ -45,7 +115,12 @@ pub struct HealthServer {
 
 impl HealthServer {
     /// Starts the health check server
-    pub async fn start(addr: SocketAddr, state: Arc<Mutex<AppState>>) -> Result<Self> {
+    pub async fn start(
+        addr: SocketAddr,
+        state: Arc<Mutex<AppState>>,
+        config: Arc<Config>,
+        metrics: Arc<Metrics>,
+    ) -> Result<Self> {
         let listener = TcpListener::bind(addr).await?;
         info!("Health check server listening on {}", addr);
 
@@ -61,42 +136,10 @@ impl HealthServer {
                         match accept {
                             Ok((mut socket, _peer)) => {
                                 let state = Arc::clone(&state);
+                                let config = Arc::clone(&config);
+                                let metrics = Arc::clone(&metrics);
                                 tokio::spawn(async move {
-                                    let mut buf = [0u8; 1024];
-                                    let bytes_read = socket.read(&mut buf).await.unwrap_or_default();
-
-                                    let request_text = String::from_utf8_lossy(&buf[..bytes_read]);
-                                    let request_line = request_text.lines().next().unwrap_or("");
-                                    let mut parts = request_line.split_whitespace();
-                                    let method = parts.next().unwrap_or("");
-                                    let path = parts.next().unwrap_or("");
-
-                                    if method == "GET" && path == "/health" {
-                                        let snapshot = state.lock().await;
-                                        let response = build_response(&snapshot);
-                                        let body = match serde_json::to_string(&response) {
-                                            Ok(body) => body,
-                                            Err(_) => "{\"status\":\"error\"}".to_string(),
-                                        };
-
-                                        let reply = format!(
-                                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-                                            body.len(),
-                                            body
-                                        );
-
-                                        if let Err(e) = socket.write_all(reply.as_bytes()).await {
-                                            error!("Health response write failed: {}", e);
-                                        }
-                                    } else {
-                                        let body = "Not Found";
-                                        let reply = format!(
-                                            "HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-                                            body.len(),
-                                            body
-                                        );
-                                        let _ = socket.write_all(reply.as_bytes()).await;
-                                    }
+                                    handle_connection(&mut socket, &state, &config, &metrics).await;
                                     let _ = socket.shutdown().await;
                                 });
                             }
@@ -126,28 +169,331 @@ impl HealthServer {
 // Helpers
 //==============================================================================
 
-fn build_response(state: &AppState) -> HealthResponse {
-    let (sync_state, healthy) = match &state.state {
-        RecordState::Unknown => ("unknown".to_string(), false),
-        RecordState::Synced(_) => ("synced".to_string(), true),
-        RecordState::Error(_) => ("error".to_string(), false),
+/// Reads a request, routes it and writes the response
+///
+/// Only GET is served; other methods get 405 and anything without a
+/// recognizable request line (a port scanner, a TLS handshake) gets 400.
+async fn handle_connection(
+    socket: &mut TcpStream,
+    state: &Mutex<AppState>,
+    config: &Config,
+    metrics: &Metrics,
+) {
+    let request = match tokio::time::timeout(
+        Duration::from_secs(HEALTH_REQUEST_TIMEOUT_SECS),
+        read_request(socket),
+    )
+    .await
+    {
+        Ok(Ok(request)) => request,
+        Ok(Err(e)) => {
+            debug!("Health request read failed: {}", e);
+            return;
+        }
+        Err(_) => {
+            debug!(
+                "Health request not received within {}s",
+                HEALTH_REQUEST_TIMEOUT_SECS
+            );
+            return;
+        }
+    };
+
+    let request_text = String::from_utf8_lossy(&request);
+    let reply = match parse_request_line(request_text.lines().next().unwrap_or("")) {
+        None => text_reply("400 Bad Request", "Bad Request"),
+        Some((method, _)) if method != "GET" => text_reply_with_headers(
+            "405 Method Not Allowed",
+            &[("Allow", "GET")],
+            "Method Not Allowed",
+        ),
+        Some((_, "/health" | "/")) => {
+            let snapshot = state.lock().await;
+            health_reply(&build_response(&snapshot, config))
+        }
+        Some((_, "/metrics")) => {
+            let body = metrics.render();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+        Some((_, "/livez" | "/healthz")) => text_reply("200 OK", "ok"),
+        Some((_, "/readyz")) => {
+            if state.lock().await.ready {
+                text_reply("200 OK", "ready")
+            } else {
+                text_reply("503 Service Unavailable", "not ready")
+            }
+        }
+        Some(_) => text_reply("404 Not Found", "Not Found"),
+    };
+
+    if let Err(e) = socket.write_all(reply.as_bytes()).await {
+        error!("Health response write failed: {}", e);
+    }
+}
+
+/// Reads a request up to the end of its headers
+///
+/// Stops at the blank line ending the headers, when the client closes the
+/// connection, or after `HEALTH_MAX_REQUEST_BYTES`; the request line is all
+/// that is looked at, and no endpoint takes a body.
+async fn read_request<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while request.len() < HEALTH_MAX_REQUEST_BYTES {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+        if request.windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+    }
+    Ok(request)
+}
+
+/// Splits an HTTP request line into its method and path
+///
+/// # Returns
+///
+/// Returns `None` unless the line is `<METHOD> <path> HTTP/<version>` with an
+/// uppercase method and an absolute path
+fn parse_request_line(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let path = parts.next()?;
+    let version = parts.next()?;
+    if parts.next().is_some()
+        || !method.bytes().all(|b| b.is_ascii_uppercase())
+        || !path.starts_with('/')
+        || !version.starts_with("HTTP/")
+    {
+        return None;
+    }
+    // The query string doesn't select anything
+    let path = path.split_once('?').map_or(path, |(path, _)| path);
+    Some((method, path))
+}
+
+fn build_response(state: &AppState, config: &Config) -> HealthResponse {
+    // The configured names in config order (unknown until first synced),
+    // followed by every other tracked record, such as the per-address ones
+    let family_health = |main: &[String], statuses: &BTreeMap<String, RecordStatus>| {
+        let default = RecordStatus::default();
+        main.iter()
+            .map(|name| build_record_health(name, statuses.get(name).unwrap_or(&default)))
+            .chain(
+                statuses
+                    .iter()
+                    .filter(|(name, _)| !main.contains(name))
+                    .map(|(name, status)| build_record_health(name, status)),
+            )
+            .collect::<Vec<_>>()
+    };
+    let main_records: &[String] = if config.address_records_only {
+        &[]
+    } else {
+        &config.records
+    };
+    let records = if config.enable_ipv6 {
+        family_health(main_records, &state.records)
+    } else {
+        Vec::new()
+    };
+    let a_records = if config.enable_ipv4 {
+        family_health(&config.records, &state.a_records)
+    } else {
+        Vec::new()
+    };
+    let mut families = BTreeMap::new();
+    if config.enable_ipv6 {
+        families.insert("ipv6".to_string(), aggregate_state(&records).to_string());
+    }
+    if config.enable_ipv4 {
+        families.insert("ipv4".to_string(), aggregate_state(&a_records).to_string());
+    }
+    // The top-level fields only cover the families that must be synced
+    let required = config.required_record_types();
+    let required_records: Vec<RecordHealth> = records
+        .iter()
+        .filter(|_| required.contains(&RecordType::Aaaa))
+        .chain(
+            a_records
+                .iter()
+                .filter(|_| required.contains(&RecordType::A)),
+        )
+        .cloned()
+        .collect();
+
+    // In read-only mode nothing is ever synced; finding an address is the goal.
+    // Otherwise health covers exactly the records reported below.
+    let healthy = if config.read_only {
+        state.detected_ip.is_some()
+    } else {
+        required.iter().all(|family| {
+            let reported = match family {
+                RecordType::Aaaa => &records,
+                RecordType::A => &a_records,
+            };
+            aggregate_state(reported) == "synced"
+        })
+    };
+    let sync_state = if config.read_only {
+        "read_only"
+    } else if healthy {
+        "synced"
+    } else {
+        match aggregate_state(&required_records) {
+            "synced" => "unknown",
+            state => state,
+        }
     };
 
-    let last_sync_seconds_ago = state.last_sync.map(|ts| {
-        let seconds = (Utc::now() - ts).num_seconds();
-        seconds.max(0) as f64
-    });
+    let last_sync_seconds_ago = required_records
+        .iter()
+        .map(|r| r.last_sync_seconds_ago)
+        .collect::<Option<Vec<f64>>>()
+        .and_then(|ages| ages.into_iter().reduce(f64::max));
+    let stale = !config.read_only && is_stale(last_sync_seconds_ago, config.max_sync_age);
+    let error_count = required_records
+        .iter()
+        .map(|r| r.error_count)
+        .max()
+        .unwrap_or(0);
+    let current_ip = records.iter().find_map(|r| r.current_ip.clone());
+    let external_mismatch = records
+        .iter()
+        .any(|r| r.external_check.as_deref() == Some(ExternalCheck::Mismatch.as_str()));
+    let current_ipv4 = a_records.iter().find_map(|r| r.current_ip.clone());
 
     HealthResponse {
-        status: if healthy {
+        status: if stale {
+            "stale".to_string()
+        } else if healthy && !state.monitoring_degraded {
             "ok".to_string()
         } else {
             "degraded".to_string()
         },
-        sync_state,
+        sync_state: sync_state.to_string(),
+        last_sync_seconds_ago,
+        max_sync_age_seconds: config.max_sync_age.map(|age| age.as_secs()),
+        stale,
+        external_mismatch,
+        error_count,
+        healthy: healthy && !stale,
+        current_ip,
+        current_ipv4,
+        monitoring_degraded: state.monitoring_degraded,
+        read_only: config.read_only,
+        paused: state.paused,
+        ready: state.ready,
+        poll_interval_seconds: state.poll_interval.map(|interval| interval.as_secs()),
+        poll_interval_range_seconds: state.poll_interval.map(|interval| {
+            let (min, max) = jitter_bounds(interval, config.poll_jitter);
+            [min.as_secs_f64(), max.as_secs_f64()]
+        }),
+        detected_ip: state.detected_ip.clone(),
+        families,
+        records,
+        a_records,
+    }
+}
+
+/// Returns the sync state of a group of records: "synced" if all are synced,
+/// else "misconfigured" or "error" if any record is, else "unknown"
+fn aggregate_state(records: &[RecordHealth]) -> &'static str {
+    if !records.is_empty() && records.iter().all(|r| r.sync_state == "synced") {
+        "synced"
+    } else if records.iter().any(|r| r.sync_state == "misconfigured") {
+        "misconfigured"
+    } else if records.iter().any(|r| r.sync_state == "error") {
+        "error"
+    } else {
+        "unknown"
+    }
+}
+
+/// Builds the HTTP response for `/health`
+///
+/// The status is 200 when the daemon is healthy and 503 otherwise, so probes
+/// that only look at the status code see failures too; the JSON body is the
+/// same either way.
+fn health_reply(response: &HealthResponse) -> String {
+    let body = match serde_json::to_string(response) {
+        Ok(body) => body,
+        Err(_) => "{\"status\":\"error\"}".to_string(),
+    };
+    let status = if response.healthy {
+        "200 OK"
+    } else {
+        "503 Service Unavailable"
+    };
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// Builds a plain-text HTTP response with the given status line
+fn text_reply(status: &str, body: &str) -> String {
+    text_reply_with_headers(status, &[], body)
+}
+
+/// Builds a plain-text HTTP response with extra headers before the standard ones
+fn text_reply_with_headers(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+    let extra: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}\r\n", name, value))
+        .collect();
+    format!(
+        "HTTP/1.1 {}\r\n{}Content-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        extra,
+        body.len(),
+        body
+    )
+}
+
+/// Returns whether the oldest sync is older than `max_sync_age`
+///
+/// Records that never synced are already unhealthy, so they don't count as
+/// stale.
+fn is_stale(last_sync_seconds_ago: Option<f64>, max_sync_age: Option<Duration>) -> bool {
+    match (last_sync_seconds_ago, max_sync_age) {
+        (Some(age), Some(max)) => age > max.as_secs_f64(),
+        _ => false,
+    }
+}
+
+fn build_record_health(record: &str, status: &RecordStatus) -> RecordHealth {
+    let sync_state = match &status.state {
+        RecordState::Unknown => "unknown",
+        RecordState::Synced(_) => "synced",
+        RecordState::Error(_) => "error",
+        RecordState::Misconfigured => "misconfigured",
+    };
+
+    let current_ip = match &status.state {
+        RecordState::Synced(ip) => Some(ip.clone()),
+        _ => None,
+    };
+
+    let last_sync_seconds_ago = status.since_last_sync().map(|age| age.as_secs() as f64);
+
+    RecordHealth {
+        record: record.to_string(),
+        sync_state: sync_state.to_string(),
         last_sync_seconds_ago,
-        error_count: state.error_count,
-        healthy,
+        last_sync: status.last_sync,
+        error_count: status.error_count,
+        current_ip,
+        external_check: status.external_check.map(|c| c.as_str().to_string()),
     }
 }
 
@@ -158,6 +504,24 @@ fn build_response(state: &AppState) -> HealthResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::CliOverrides;
+    use crate::dns_provider::ErrorKind;
+    use serial_test::serial;
+
+    fn test_config() -> Config {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            r#"
+api_token = "0123456789012345678901234567890123456789"
+zone_id = "0123456789abcdef0123456789abcdef"
+record_name = "example.com"
+"#,
+        )
+        .unwrap();
+        Config::load(Some(path), &CliOverrides::default()).unwrap()
+    }
 
     #[test]
     fn test_health_response_serialization() {
@@ -165,12 +529,252 @@ mod tests {
             status: "ok".to_string(),
             sync_state: "synced".to_string(),
             last_sync_seconds_ago: Some(0.0),
+            max_sync_age_seconds: None,
+            stale: false,
+            external_mismatch: false,
             error_count: 0,
             healthy: true,
+            current_ip: Some("2001:db8::1".to_string()),
+            current_ipv4: None,
+            monitoring_degraded: false,
+            read_only: false,
+            paused: false,
+            ready: true,
+            poll_interval_seconds: Some(60),
+            poll_interval_range_seconds: Some([54.0, 66.0]),
+            detected_ip: Some("2001:db8::1".to_string()),
+            families: BTreeMap::from([("ipv6".to_string(), "synced".to_string())]),
+            records: vec![RecordHealth {
+                record: "example.com".to_string(),
+                sync_state: "synced".to_string(),
+                last_sync_seconds_ago: Some(0.0),
+                last_sync: None,
+                error_count: 0,
+                current_ip: Some("2001:db8::1".to_string()),
+                external_check: None,
+            }],
+            a_records: Vec::new(),
         };
 
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("\"status\":\"ok\""));
         assert!(json.contains("\"healthy\":true"));
+        assert!(json.contains("\"current_ip\":\"2001:db8::1\""));
+        assert!(json.contains("\"record\":\"example.com\""));
+        assert!(json.contains("\"monitoring_degraded\":false"));
+        assert!(json.contains("\"read_only\":false"));
+        assert!(json.contains("\"paused\":false"));
+        assert!(json.contains("\"ready\":true"));
+        assert!(json.contains("\"poll_interval_seconds\":60"));
+        assert!(json.contains("\"poll_interval_range_seconds\":[54.0,66.0]"));
+        assert!(json.contains("\"max_sync_age_seconds\":null"));
+        assert!(json.contains("\"stale\":false"));
+        assert!(json.contains("\"families\":{\"ipv6\":\"synced\"}"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_health_reply_status() {
+        let config = test_config();
+        let mut state = AppState::new(&config.records);
+        state
+            .record_mut("example.com")
+            .mark_error(1, ErrorKind::Server, None);
+
+        let response = build_response(&state, &config);
+        assert!(!response.healthy);
+        let reply = health_reply(&response);
+        assert!(reply.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        let body = serde_json::to_string(&response).unwrap();
+        assert!(reply.ends_with(&body));
+
+        state
+            .record_mut("example.com")
+            .mark_synced("2001:db8::1".to_string());
+        let reply = health_reply(&build_response(&state, &config));
+        assert!(reply.starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_health_families() {
+        let mut config = test_config();
+        config.enable_ipv4 = true;
+        let mut state = AppState::new(&config.records);
+        state
+            .record_mut("example.com")
+            .mark_synced("2001:db8::1".to_string());
+        state
+            .status_mut("example.com", RecordType::A)
+            .mark_error(1, ErrorKind::Network, None);
+
+        // A failing A record doesn't hide the synced AAAA record
+        let response = build_response(&state, &config);
+        assert!(!response.healthy);
+        assert_eq!(response.sync_state, "error");
+        assert_eq!(response.families["ipv6"], "synced");
+        assert_eq!(response.families["ipv4"], "error");
+        assert_eq!(response.a_records[0].sync_state, "error");
+        assert_eq!(response.current_ip.as_deref(), Some("2001:db8::1"));
+
+        // Only the required families decide health
+        config.required_families = Some(vec![RecordType::Aaaa]);
+        let response = build_response(&state, &config);
+        assert!(response.healthy);
+        assert_eq!(response.sync_state, "synced");
+        assert_eq!(response.error_count, 0);
+
+        // IPv4 only
+        config.required_families = None;
+        config.enable_ipv6 = false;
+        state
+            .status_mut("example.com", RecordType::A)
+            .mark_synced("192.0.2.1".to_string());
+        let response = build_response(&state, &config);
+        assert!(response.healthy);
+        assert!(response.records.is_empty());
+        assert!(!response.families.contains_key("ipv6"));
+        assert_eq!(response.current_ipv4.as_deref(), Some("192.0.2.1"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_health_address_records_only() {
+        let mut config = test_config();
+        config.address_record_template = Some("{label}-{n}".to_string());
+        config.address_records_only = true;
+        let mut state = AppState::new(&[]);
+
+        // Nothing published yet
+        let response = build_response(&state, &config);
+        assert!(!response.healthy);
+        assert!(response.records.is_empty());
+        assert_eq!(response.families["ipv6"], "unknown");
+
+        state
+            .record_mut("example-1.com")
+            .mark_synced("2001:db8::1".to_string());
+        state
+            .record_mut("example-2.com")
+            .mark_error(1, ErrorKind::Server, None);
+        let response = build_response(&state, &config);
+        assert!(!response.healthy);
+        assert_eq!(response.sync_state, "error");
+        let reported: Vec<(&str, &str)> = response
+            .records
+            .iter()
+            .map(|r| (r.record.as_str(), r.sync_state.as_str()))
+            .collect();
+        assert_eq!(
+            reported,
+            [("example-1.com", "synced"), ("example-2.com", "error")]
+        );
+
+        state
+            .record_mut("example-2.com")
+            .mark_synced("2001:db8::2".to_string());
+        let response = build_response(&state, &config);
+        assert!(response.healthy);
+        assert_eq!(response.sync_state, "synced");
+        assert_eq!(response.families["ipv6"], "synced");
+        assert!(response.records.iter().all(|r| r.sync_state == "synced"));
+        assert_eq!(response.current_ip.as_deref(), Some("2001:db8::1"));
+    }
+
+    #[test]
+    fn test_parse_request_line() {
+        assert_eq!(
+            parse_request_line("GET /health HTTP/1.1"),
+            Some(("GET", "/health"))
+        );
+        assert_eq!(
+            parse_request_line("POST /readyz?verbose=1 HTTP/1.0"),
+            Some(("POST", "/readyz"))
+        );
+        assert_eq!(parse_request_line(""), None);
+        assert_eq!(parse_request_line("GET /health"), None);
+        assert_eq!(parse_request_line("GET health HTTP/1.1"), None);
+        assert_eq!(parse_request_line("get /health HTTP/1.1"), None);
+        assert_eq!(parse_request_line("\u{16}\u{3}\u{1} garbage"), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_request() {
+        let mut input: &[u8] = b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let request = read_request(&mut input).await.unwrap();
+        assert!(request.ends_with(b"\r\n\r\n"));
+
+        // A request that never ends is cut off at the cap
+        let huge = vec![b'a'; HEALTH_MAX_REQUEST_BYTES * 2];
+        let mut input: &[u8] = &huge;
+        let request = read_request(&mut input).await.unwrap();
+        assert!(request.len() >= HEALTH_MAX_REQUEST_BYTES);
+        assert!(request.len() < HEALTH_MAX_REQUEST_BYTES + 1024);
+    }
+
+    #[test]
+    fn test_text_reply() {
+        assert_eq!(
+            text_reply("503 Service Unavailable", "not ready"),
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Type: text/plain\r\nContent-Length: 9\r\nConnection: close\r\n\r\nnot ready"
+        );
+        assert_eq!(
+            text_reply_with_headers("405 Method Not Allowed", &[("Allow", "GET")], "Method Not Allowed"),
+            "HTTP/1.1 405 Method Not Allowed\r\nAllow: GET\r\nContent-Type: text/plain\r\nContent-Length: 18\r\nConnection: close\r\n\r\nMethod Not Allowed"
+        );
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let day = Some(Duration::from_secs(86400));
+        assert!(!is_stale(Some(60.0), day));
+        assert!(!is_stale(Some(86400.0), day));
+        assert!(is_stale(Some(86401.0), day));
+        assert!(!is_stale(None, day));
+        assert!(!is_stale(Some(1e9), None));
+    }
+
+    #[test]
+    fn test_record_health_age_survives_clock_step() {
+        let mut status = RecordStatus::default();
+        status.mark_synced("2001:db8::1".to_string());
+        // Wall clock stepped back an hour after the sync
+        status.last_sync = Some(Utc::now() + chrono::Duration::hours(1));
+
+        let health = build_record_health("example.com", &status);
+        assert_eq!(health.last_sync_seconds_ago, Some(0.0));
+        assert_eq!(health.last_sync, status.last_sync);
+    }
+
+    #[test]
+    fn test_record_health_misconfigured() {
+        let mut status = RecordStatus::default();
+        status.mark_misconfigured();
+
+        let health = build_record_health("example.com", &status);
+        assert_eq!(health.sync_state, "misconfigured");
+        assert_eq!(health.error_count, 1);
+        assert_eq!(health.current_ip, None);
+    }
+
+    #[test]
+    fn test_record_health_external_check() {
+        let mut status = RecordStatus::default();
+        status.mark_synced("2001:db8::1".to_string());
+        assert_eq!(
+            build_record_health("example.com", &status).external_check,
+            None
+        );
+
+        status.external_check = Some(ExternalCheck::Mismatch);
+        let health = build_record_health("example.com", &status);
+        assert_eq!(health.external_check.as_deref(), Some("mismatch"));
+
+        // A new address hasn't been checked yet
+        status.mark_synced("2001:db8::2".to_string());
+        assert_eq!(
+            build_record_health("example.com", &status).external_check,
+            None
+        );
     }
 }
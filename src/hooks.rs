@@ -0,0 +1,404 @@
+//! User-defined command hooks for ipv6ddns
+//!
+//! This module runs external commands in response to daemon events, such as
+//! the published address changing. Hooks are spawned in the background so a
+//! slow or hanging command never blocks the main event loop.
+//!
+//! # Command Forms
+//!
+//! A hook is either an argument list (`["systemctl", "reload", "caddy"]`), run
+//! directly, or a string run through `/bin/sh -c`. Shell strings must be
+//! enabled explicitly with `hook_shell = true`, since they make it easy to
+//! build injection-prone commands.
+//!
+//! # Environment
+//!
+//! Hook commands receive the following environment variables:
+//! - `IPV6DDNS_IP`: The newly published IP address
+//! - `IPV6DDNS_RECORD`: The DNS record name
+//! - `IPV6DDNS_OLD_IP`: The previously published IP address (empty on first sync)
+//!
+//! Note that the address is visible to the command (and anything it spawns)
+//! through its environment; nothing is redacted.
+//!
+//! # Output and Timeout
+//!
+//! The command's stdout and stderr are captured and logged. A command that runs
+//! longer than the configured timeout is killed.
+
+use std::os::unix::fs::PermissionsExt as _;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::time::Duration;
+
+use anyhow::{Context as _, Result};
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+use crate::constants::{HOOK_ENV_IP, HOOK_ENV_OLD_IP, HOOK_ENV_RECORD};
+
+//==============================================================================
+// Types
+//==============================================================================
+
+/// A hook command as written in the config file
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(untagged)]
+pub enum HookCommand {
+    /// Program and arguments, run directly without a shell
+    Exec(Vec<String>),
+    /// Command line run through `/bin/sh -c` (requires `hook_shell`)
+    Shell(String),
+}
+
+impl HookCommand {
+    /// Returns true if the command is blank
+    pub fn is_empty(&self) -> bool {
+        match self {
+            HookCommand::Exec(argv) => !argv.first().is_some_and(|p| !p.trim().is_empty()),
+            HookCommand::Shell(line) => line.trim().is_empty(),
+        }
+    }
+
+    /// Builds the process to spawn for this command
+    pub fn to_command(&self) -> Command {
+        match self {
+            HookCommand::Exec(argv) => {
+                let mut command = Command::new(&argv[0]);
+                command.args(&argv[1..]);
+                command
+            }
+            HookCommand::Shell(line) => {
+                let mut command = Command::new("/bin/sh");
+                command.arg("-c").arg(line);
+                command
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for HookCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookCommand::Exec(argv) => write!(f, "{}", argv.join(" ")),
+            HookCommand::Shell(line) => write!(f, "{}", line),
+        }
+    }
+}
+
+//==============================================================================
+// Validation
+//==============================================================================
+
+/// Validates a hook command at config load
+///
+/// Shell strings are rejected unless `allow_shell` is set. For argument lists,
+/// the program must exist and be executable; names without a `/` are looked up
+/// in `PATH`.
+///
+/// # Arguments
+///
+/// * `command` - The configured hook command
+/// * `allow_shell` - Whether shell strings are allowed (`hook_shell`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` or an error describing why the command can't be used
+pub fn validate_hook_command(command: &HookCommand, allow_shell: bool) -> Result<()> {
+    validate_command("post_update_command", command, allow_shell)
+}
+
+/// Validates a configured command, naming its config key in errors
+///
+/// Applies the rules of [`validate_hook_command`] to any command setting,
+/// such as `detect_command`.
+///
+/// # Arguments
+///
+/// * `key` - The config key holding the command
+/// * `command` - The configured command
+/// * `allow_shell` - Whether shell strings are allowed (`hook_shell`)
+///
+/// # Returns
+///
+/// Returns `Ok(())` or an error describing why the command can't be used
+pub fn validate_command(key: &str, command: &HookCommand, allow_shell: bool) -> Result<()> {
+    match command {
+        HookCommand::Shell(_) if !allow_shell => Err(anyhow::anyhow!(
+            "{} is a shell string; use an argument list like \
+             [\"systemctl\", \"reload\", \"caddy\"] or set hook_shell = true",
+            key
+        )),
+        HookCommand::Shell(_) => Ok(()),
+        HookCommand::Exec(argv) => {
+            let program = &argv[0];
+            let path = find_executable(program)
+                .with_context(|| format!("{} program '{}' is not usable", key, program))?;
+            tracing::debug!("Hook program resolved to {}", path.display());
+            Ok(())
+        }
+    }
+}
+
+/// Locates an executable, searching `PATH` for bare names
+///
+/// # Arguments
+///
+/// * `program` - A path or bare program name
+///
+/// # Returns
+///
+/// Returns the path of the executable, or an error if it doesn't exist or
+/// isn't executable
+fn find_executable(program: &str) -> Result<PathBuf> {
+    if program.contains('/') {
+        let path = Path::new(program);
+        return if is_executable(path) {
+            Ok(path.to_path_buf())
+        } else if path.exists() {
+            Err(anyhow::anyhow!(
+                "{} is not an executable file",
+                path.display()
+            ))
+        } else {
+            Err(anyhow::anyhow!("{} does not exist", path.display()))
+        };
+    }
+    let search_path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&search_path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+        .ok_or_else(|| anyhow::anyhow!("not found in PATH"))
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+//==============================================================================
+// Hooks
+//==============================================================================
+
+/// Runs a hook command to completion and returns its exit status and output
+///
+/// # Arguments
+///
+/// * `command` - The command to run
+/// * `ip` - The newly published IP address
+/// * `record` - The DNS record name
+/// * `old_ip` - The previously published IP address, if any
+/// * `timeout` - Time after which the command is killed
+///
+/// # Returns
+///
+/// Returns the captured output of the command, or an error if it could not be
+/// spawned or did not finish within `timeout`
+pub async fn run_hook(
+    command: &HookCommand,
+    ip: &str,
+    record: &str,
+    old_ip: Option<&str>,
+    timeout: Duration,
+) -> Result<Output> {
+    let output = command
+        .to_command()
+        .env(HOOK_ENV_IP, ip)
+        .env(HOOK_ENV_RECORD, record)
+        .env(HOOK_ENV_OLD_IP, old_ip.unwrap_or_default())
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+
+    tokio::time::timeout(timeout, output)
+        .await
+        .map_err(|_| anyhow::anyhow!("Hook command timed out after {}s", timeout.as_secs()))?
+        .with_context(|| format!("Failed to spawn hook command '{}'", command))
+}
+
+/// Spawns the post-update hook in the background
+///
+/// Failures (spawn errors and non-zero exit codes) are logged and otherwise
+/// ignored; they never affect the sync state.
+///
+/// # Arguments
+///
+/// * `command` - The command to run
+/// * `ip` - The newly published IP address
+/// * `record` - The DNS record name
+/// * `old_ip` - The previously published IP address, if any
+/// * `timeout` - Time after which the command is killed
+pub fn spawn_post_update_hook(
+    command: &HookCommand,
+    ip: &str,
+    record: &str,
+    old_ip: Option<&str>,
+    timeout: Duration,
+) {
+    let command = command.clone();
+    let ip = ip.to_string();
+    let record = record.to_string();
+    let old_ip = old_ip.map(str::to_string);
+
+    tokio::spawn(async move {
+        match run_hook(&command, &ip, &record, old_ip.as_deref(), timeout).await {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                if output.status.success() {
+                    debug!("Post-update hook finished successfully");
+                    log_output(&stdout, &stderr, false);
+                } else {
+                    warn!("Post-update hook exited with {}", output.status);
+                    log_output(&stdout, &stderr, true);
+                }
+            }
+            Err(e) => {
+                warn!("Post-update hook failed: {:#}", e);
+            }
+        }
+    });
+}
+
+/// Logs a hook's captured output
+///
+/// Output is logged at debug level, or at warn level when the hook failed.
+fn log_output(stdout: &str, stderr: &str, failed: bool) {
+    for (stream, text) in [("stdout", stdout.trim_end()), ("stderr", stderr.trim_end())] {
+        if text.is_empty() {
+            continue;
+        }
+        if failed {
+            warn!("Post-update hook {}: {}", stream, text);
+        } else {
+            debug!("Post-update hook {}: {}", stream, text);
+        }
+    }
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_hook_passes_environment() {
+        let output = run_hook(
+            &HookCommand::Shell(
+                r#"test "$IPV6DDNS_IP" = "2001:db8::2" && test "$IPV6DDNS_RECORD" = "example.com" && test "$IPV6DDNS_OLD_IP" = "2001:db8::1""#.to_string(),
+            ),
+            "2001:db8::2",
+            "example.com",
+            Some("2001:db8::1"),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert!(output.status.success());
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_empty_old_ip() {
+        let output = run_hook(
+            &HookCommand::Shell(r#"test -z "$IPV6DDNS_OLD_IP""#.to_string()),
+            "2001:db8::2",
+            "example.com",
+            None,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert!(output.status.success());
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_reports_failure() {
+        let output = run_hook(
+            &HookCommand::Shell("echo oops >&2; exit 3".to_string()),
+            "2001:db8::2",
+            "example.com",
+            None,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stderr, b"oops\n");
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_exec_passes_arguments_verbatim() {
+        let output = run_hook(
+            &HookCommand::Exec(vec![
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                r#"test "$1" = "a b; c""#.to_string(),
+                "sh".to_string(),
+                "a b; c".to_string(),
+            ]),
+            "2001:db8::2",
+            "example.com",
+            None,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_hook_command_forms() {
+        let parsed: Vec<HookCommand> =
+            serde_json::from_str(r#"[["systemctl", "reload", "caddy"], "ufw reload"]"#).unwrap();
+        assert_eq!(
+            parsed[0],
+            HookCommand::Exec(vec![
+                "systemctl".to_string(),
+                "reload".to_string(),
+                "caddy".to_string()
+            ])
+        );
+        assert_eq!(parsed[1], HookCommand::Shell("ufw reload".to_string()));
+
+        assert!(validate_hook_command(&parsed[1], false).is_err());
+        assert!(validate_hook_command(&parsed[1], true).is_ok());
+        assert!(HookCommand::Exec(Vec::new()).is_empty());
+        assert!(HookCommand::Shell("  ".to_string()).is_empty());
+    }
+
+    #[test]
+    fn test_validate_hook_command_checks_executable() {
+        let exec = |program: &str| HookCommand::Exec(vec![program.to_string()]);
+        assert!(validate_hook_command(&exec("/bin/sh"), false).is_ok());
+        assert!(validate_hook_command(&exec("sh"), false).is_ok());
+        assert!(validate_hook_command(&exec("/nonexistent/hook"), false).is_err());
+        assert!(validate_hook_command(&exec("no-such-hook-program"), false).is_err());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = dir.path().join("hook.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        let script = script.to_str().unwrap();
+        let err = validate_hook_command(&exec(script), false).unwrap_err();
+        assert!(format!("{err:#}").contains("not an executable"));
+
+        std::fs::set_permissions(script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(validate_hook_command(&exec(script), false).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_times_out() {
+        let err = run_hook(
+            &HookCommand::Exec(vec!["sleep".to_string(), "5".to_string()]),
+            "2001:db8::2",
+            "example.com",
+            None,
+            Duration::from_millis(100),
+        )
+        .await
+        .expect_err("hook should time out");
+        assert!(format!("{err}").contains("timed out"));
+    }
+}
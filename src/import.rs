@@ -0,0 +1,314 @@
+//! Configuration import for ipv6ddns
+//!
+//! This module converts a ddclient configuration (`ddclient.conf`) into an
+//! equivalent ipv6ddns `config.toml`, so users switching tools don't have to
+//! rewrite their setup by hand. Settings with a direct equivalent are mapped;
+//! everything else is reported as a warning.
+//!
+//! # Mapping
+//!
+//! - `password` -> `api_token` (Cloudflare API token)
+//! - `daemon` -> `poll_interval` (only used when netlink is unavailable)
+//! - host names -> `record_name`
+//! - `zone` -> not mapped; ddclient stores the zone *name*, ipv6ddns needs the zone ID
+//!
+//! # Usage
+//!
+//! ```text
+//! ipv6ddns import /etc/ddclient.conf --output /etc/ipv6ddns/config.toml
+//! ```
+
+use std::io::Write as _;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use serde::Serialize;
+
+use crate::constants::{MAX_POLL_INTERVAL_SECS, MIN_POLL_INTERVAL_SECS};
+
+//==============================================================================
+// Types
+//==============================================================================
+
+/// Result of converting a foreign configuration
+#[derive(Debug)]
+pub struct ImportResult {
+    /// Generated ipv6ddns config.toml contents
+    pub toml: String,
+    /// Settings that could not be mapped or need attention
+    pub warnings: Vec<String>,
+}
+
+/// Subset of the ipv6ddns config file written by the importer
+#[derive(Debug, Default, Serialize)]
+struct ImportedConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_token: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    record_name: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    poll_interval: Option<u64>,
+}
+
+//==============================================================================
+// Import
+//==============================================================================
+
+/// Runs the `import` subcommand
+///
+/// Warnings are printed to stderr. The generated config is written to
+/// `output` (created with mode 0600, since it may contain the API token) or
+/// printed to stdout.
+///
+/// # Arguments
+///
+/// * `path` - Path to the ddclient.conf to read
+/// * `output` - Optional path to write the generated config.toml to
+pub fn run(path: &Path, output: Option<&Path>) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let result = import_ddclient(&content)?;
+
+    for warning in &result.warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    match output {
+        Some(out) => {
+            use std::os::unix::fs::OpenOptionsExt as _;
+
+            let mut file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o600)
+                .open(out)
+                .with_context(|| format!("Failed to create {}", out.display()))?;
+            file.write_all(result.toml.as_bytes())
+                .with_context(|| format!("Failed to write {}", out.display()))?;
+            eprintln!("Wrote {}", out.display());
+        }
+        None => print!("{}", result.toml),
+    }
+    Ok(())
+}
+
+/// Converts a ddclient configuration into an ipv6ddns config.toml
+///
+/// # Arguments
+///
+/// * `content` - The ddclient.conf contents
+///
+/// # Returns
+///
+/// Returns an `ImportResult` with the generated TOML and any warnings, or an
+/// error if the TOML cannot be serialized
+pub fn import_ddclient(content: &str) -> Result<ImportResult> {
+    let mut imported = ImportedConfig::default();
+    let mut warnings = Vec::new();
+    let mut zone_name = None;
+
+    for line in logical_lines(content) {
+        for token in line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|t| !t.is_empty())
+        {
+            let Some((key, value)) = token.split_once('=') else {
+                if !imported.record_name.iter().any(|r| r == token) {
+                    imported.record_name.push(token.to_string());
+                }
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = unquote(value.trim());
+
+            match key.as_str() {
+                "password" => imported.api_token = Some(value.to_string()),
+                "zone" => zone_name = Some(value.to_string()),
+                "daemon" => match value.trim_end_matches('s').parse::<u64>() {
+                    Ok(secs) => {
+                        let clamped = secs.clamp(MIN_POLL_INTERVAL_SECS, MAX_POLL_INTERVAL_SECS);
+                        if clamped != secs {
+                            warnings.push(format!(
+                                "daemon={} is outside {}-{} seconds; using poll_interval = {}",
+                                value, MIN_POLL_INTERVAL_SECS, MAX_POLL_INTERVAL_SECS, clamped
+                            ));
+                        }
+                        imported.poll_interval = Some(clamped);
+                    }
+                    Err(_) => warnings.push(format!("Ignoring unparsable daemon={}", value)),
+                },
+                "protocol" => {
+                    if !value.eq_ignore_ascii_case("cloudflare") {
+                        warnings.push(format!(
+                            "protocol={} is not supported; ipv6ddns only supports Cloudflare",
+                            value
+                        ));
+                    }
+                }
+                "login" => {
+                    if value != "token" {
+                        warnings.push(format!(
+                            "login={} looks like a global API key login; ipv6ddns needs an API \
+                             token with Zone:DNS:Edit permission",
+                            value
+                        ));
+                    }
+                }
+                "use" | "usev6" | "web" | "webv6" | "if" | "ifv6" | "cmd" | "cmdv6" => {
+                    warnings.push(format!(
+                        "{}={} ignored; ipv6ddns detects the IPv6 address itself via netlink",
+                        key, value
+                    ));
+                }
+                "ssl" | "syslog" | "pid" | "quiet" | "verbose" | "debug" => {}
+                _ => warnings.push(format!("{}={} has no ipv6ddns equivalent", key, value)),
+            }
+        }
+    }
+
+    if imported.api_token.is_none() {
+        warnings.push("No password found; set api_token or IPV6DDNS_API_TOKEN".to_string());
+    } else {
+        warnings.push(
+            "The generated config contains the API token; consider moving it to \
+             IPV6DDNS_API_TOKEN"
+                .to_string(),
+        );
+    }
+    if imported.record_name.is_empty() {
+        warnings.push("No host names found; set record_name".to_string());
+    }
+    warnings.push(match &zone_name {
+        Some(zone) => format!(
+            "zone={} is a zone name; look up its zone ID in the Cloudflare dashboard and set zone_id",
+            zone
+        ),
+        None => "No zone found; set zone_id".to_string(),
+    });
+
+    let mut toml = String::from("# Imported from ddclient configuration by `ipv6ddns import`\n");
+    match &zone_name {
+        Some(zone) => toml.push_str(&format!(
+            "# zone_id = \"\" # Zone ID for {} (see the Cloudflare dashboard)\n",
+            zone
+        )),
+        None => toml.push_str("# zone_id = \"\" # Zone ID (see the Cloudflare dashboard)\n"),
+    }
+    toml.push_str(&toml::to_string(&imported).context("Failed to serialize imported config")?);
+
+    Ok(ImportResult { toml, warnings })
+}
+
+//==============================================================================
+// Helpers
+//==============================================================================
+
+/// Splits ddclient.conf contents into logical lines
+///
+/// Comments (`#` to end of line) and blank lines are dropped, and lines ending
+/// in a backslash are joined with the following line.
+fn logical_lines(content: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for raw in content.lines() {
+        let line = raw.split('#').next().unwrap_or("").trim_end();
+        match line.strip_suffix('\\') {
+            Some(continued) => {
+                current.push_str(continued);
+                current.push(' ');
+            }
+            None => {
+                current.push_str(line);
+                if !current.trim().is_empty() {
+                    lines.push(std::mem::take(&mut current));
+                }
+                current.clear();
+            }
+        }
+    }
+    if !current.trim().is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Strips matching single or double quotes around a value
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DDCLIENT_CONF: &str = r#"
+# ddclient.conf
+daemon=300
+syslog=yes
+ssl=yes
+usev6=ifv6, ifv6=eth0
+protocol=cloudflare, \
+zone=example.com, \
+ttl=1, \
+login=token, \
+password='0123456789012345678901234567890123456789' \
+home.example.com,nas.example.com
+"#;
+
+    #[test]
+    fn test_import_ddclient_maps_fields() {
+        let result = import_ddclient(DDCLIENT_CONF).unwrap();
+
+        let parsed: toml::Table = toml::from_str(&result.toml).unwrap();
+        assert_eq!(
+            parsed["api_token"].as_str(),
+            Some("0123456789012345678901234567890123456789")
+        );
+        assert_eq!(parsed["poll_interval"].as_integer(), Some(300));
+        let records: Vec<&str> = parsed["record_name"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert_eq!(records, vec!["home.example.com", "nas.example.com"]);
+        assert!(result.toml.contains("# zone_id"));
+    }
+
+    #[test]
+    fn test_import_ddclient_warnings() {
+        let result = import_ddclient(DDCLIENT_CONF).unwrap();
+        let warnings = result.warnings.join("\n");
+        assert!(warnings.contains("ttl=1 has no ipv6ddns equivalent"));
+        assert!(warnings.contains("usev6=ifv6 ignored"));
+        assert!(warnings.contains("zone=example.com is a zone name"));
+        assert!(!warnings.contains("protocol="));
+
+        let result = import_ddclient("protocol=dyndns2\nhome.example.com\n").unwrap();
+        let warnings = result.warnings.join("\n");
+        assert!(warnings.contains("protocol=dyndns2 is not supported"));
+        assert!(warnings.contains("No password found"));
+    }
+
+    #[test]
+    fn test_logical_lines() {
+        let lines = logical_lines("a=1, \\\nb=2 # comment\n\n# only comment\nhost\n");
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("a=1"));
+        assert!(lines[0].contains("b=2"));
+        assert_eq!(lines[1], "host");
+    }
+}
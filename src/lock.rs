@@ -0,0 +1,120 @@
+//! Single-instance guard for ipv6ddns
+//!
+//! With `lock_file` set, the daemon takes an exclusive `flock` on that file at
+//! startup and writes its PID into it. A second copy started against the same
+//! file, e.g. by a duplicated systemd unit, fails to get the lock and exits
+//! instead of fighting the first one over the records.
+//!
+//! The lock belongs to the open file, so the kernel releases it when the
+//! daemon exits, even after a crash; a stale file left behind never blocks a
+//! restart. The file itself is not removed on shutdown, as deleting it could
+//! let two instances lock different files under the same path.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+use anyhow::{bail, Context as _, Result};
+
+//==============================================================================
+// Lock
+//==============================================================================
+
+/// An exclusive lock on `lock_file`, held until dropped
+#[derive(Debug)]
+pub struct InstanceLock {
+    /// The locked file; closing it releases the lock
+    file: File,
+}
+
+impl InstanceLock {
+    /// Takes the lock on `path` and writes this process's PID into it
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The lock file, created if missing
+    ///
+    /// # Returns
+    ///
+    /// Returns the held lock, or an error if the file can't be opened or
+    /// another process holds the lock
+    pub fn acquire(path: &Path) -> Result<Self> {
+        // Not truncated on open: the PID in the file belongs to whoever holds
+        // the lock until we have it
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o644)
+            .open(path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } < 0 {
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+                let mut pid = String::new();
+                let _ = file.read_to_string(&mut pid);
+                let holder = match pid.trim() {
+                    "" => String::new(),
+                    pid => format!(" (PID {})", pid),
+                };
+                bail!(
+                    "Another ipv6ddns instance{} holds {}; refusing to start a second one",
+                    holder,
+                    path.display()
+                );
+            }
+            return Err(err).with_context(|| format!("Failed to lock {}", path.display()));
+        }
+
+        file.set_len(0)
+            .and_then(|_| file.seek(SeekFrom::Start(0)))
+            .and_then(|_| writeln!(file, "{}", std::process::id()))
+            .with_context(|| format!("Failed to write PID to {}", path.display()))?;
+
+        Ok(Self { file })
+    }
+
+    /// Releases the lock
+    ///
+    /// The PID is cleared first so the file no longer names this process.
+    /// Dropping the lock releases it too; this only makes shutdown explicit.
+    pub fn release(self) {
+        let _ = self.file.set_len(0);
+        unsafe { libc::flock(self.file.as_raw_fd(), libc::LOCK_UN) };
+    }
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ipv6ddns.lock");
+
+        let lock = InstanceLock::acquire(&path).unwrap();
+        let pid = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(pid.trim(), std::process::id().to_string());
+
+        // flock locks belong to the open file, so a second open conflicts
+        // even within the same process
+        let err = InstanceLock::acquire(&path).unwrap_err().to_string();
+        assert!(err.contains("Another ipv6ddns instance"));
+        assert!(err.contains(&format!("PID {}", std::process::id())));
+
+        lock.release();
+        assert!(std::fs::read_to_string(&path).unwrap().is_empty());
+        let lock = InstanceLock::acquire(&path).unwrap();
+        drop(lock);
+        assert!(InstanceLock::acquire(&path).is_ok());
+    }
+}
@@ -0,0 +1,327 @@
+//! Logging setup for ipv6ddns
+//!
+//! This module configures the `tracing` subscriber used by the daemon.
+//! Human-readable output on stdout is the default. With `log_format = "json"`,
+//! each event is written to stdout as one JSON object, with structured fields
+//! (`ip`, `record`, `sync_state`, ...) as keys. Otherwise, when built with the
+//! `journald` feature and running under systemd with stderr connected to the
+//! journal, events are sent to the journal natively, so the same fields show
+//! up as typed journal fields in `journalctl -o json`.
+//!
+//! With `log_target = "file"`, events go to `log_file` instead (in either
+//! format), optionally rotated daily by `tracing-appender`. With
+//! `log_target = "syslog"` (built with the `syslog` feature), each event is
+//! sent to the local syslog daemon as `ipv6ddns`, with its level mapped to
+//! the syslog severity.
+//!
+//! Secrets are redacted before they are logged (see `daemon::redact_secrets`),
+//! so the choice of backend does not affect what ends up in the logs.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::EnvFilter;
+
+use crate::config::{Config, LogFormat, LogRotation, LogTarget};
+
+//==============================================================================
+// Setup
+//==============================================================================
+
+/// Initializes the global tracing subscriber
+///
+/// The log level defaults to `info` (or `debug` when `verbose` is set) and
+/// can be overridden with `RUST_LOG`.
+///
+/// # Arguments
+///
+/// * `config` - The loaded configuration
+///
+/// # Returns
+///
+/// Returns `Ok(())` or an error if `log_file` or syslog can't be opened
+pub fn init(config: &Config) -> Result<()> {
+    let filter = env_filter(config);
+
+    if config.log_target == LogTarget::Syslog {
+        #[cfg(feature = "syslog")]
+        {
+            let writer = syslog_writer::SyslogMakeWriter::connect(&config.syslog_facility)?;
+            let builder = tracing_subscriber::fmt()
+                .with_env_filter(filter)
+                .with_writer(writer)
+                .with_ansi(false)
+                .with_level(false)
+                .without_time();
+            if config.log_format == LogFormat::Json {
+                builder.json().init();
+            } else {
+                builder.init();
+            }
+            return Ok(());
+        }
+        #[cfg(not(feature = "syslog"))]
+        anyhow::bail!("log_target = \"syslog\" requires building with the syslog feature");
+    }
+
+    if let (LogTarget::File, Some(path)) = (config.log_target, &config.log_file) {
+        let appender = open_log_file(path, config.log_rotation)?;
+        let builder = tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(appender)
+            .with_ansi(false);
+        if config.log_format == LogFormat::Json {
+            builder.json().init();
+        } else {
+            builder.init();
+        }
+        return Ok(());
+    }
+
+    if config.log_format == LogFormat::Json {
+        tracing_subscriber::fmt()
+            .json()
+            .with_env_filter(filter)
+            .init();
+        return Ok(());
+    }
+
+    #[cfg(feature = "journald")]
+    if journal_connected() {
+        use tracing_subscriber::layer::SubscriberExt as _;
+        use tracing_subscriber::util::SubscriberInitExt as _;
+
+        match tracing_journald::layer() {
+            Ok(layer) => {
+                tracing_subscriber::registry()
+                    .with(filter)
+                    .with(layer.with_syslog_identifier("ipv6ddns".to_string()))
+                    .init();
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("ipv6ddns: journald unavailable ({e}), logging to stdout");
+            }
+        }
+    }
+
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+    Ok(())
+}
+
+/// Initializes a global tracing subscriber writing to stderr
+///
+/// Used by the one-shot subcommands (`status`, `probe`, `diff`, `print-ip`),
+/// whose report goes to stdout: warnings and, with `verbose`, debug output
+/// still reach the terminal without mixing into it. `log_target` and
+/// `log_format` only apply to the daemon.
+///
+/// # Arguments
+///
+/// * `config` - The loaded configuration
+pub fn init_stderr(config: &Config) {
+    tracing_subscriber::fmt()
+        .with_env_filter(env_filter(config))
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Returns the filter for `config`: `RUST_LOG` if set, otherwise `info`, or
+/// `debug` when `verbose` is set
+fn env_filter(config: &Config) -> EnvFilter {
+    EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(if config.verbose { "debug" } else { "info" }))
+}
+
+/// Opens the log file, creating it and its directory if needed
+///
+/// # Arguments
+///
+/// * `path` - The configured `log_file`
+/// * `rotation` - When to start a new file
+///
+/// # Returns
+///
+/// Returns the appender, or an error naming the file if it can't be opened
+fn open_log_file(path: &Path, rotation: LogRotation) -> Result<RollingFileAppender> {
+    let name = path
+        .file_name()
+        .with_context(|| format!("log_file must name a file, got: {}", path.display()))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let rotation = match rotation {
+        LogRotation::Never => Rotation::NEVER,
+        LogRotation::Daily => Rotation::DAILY,
+    };
+    RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(name.to_string_lossy())
+        .build(dir)
+        .with_context(|| format!("Cannot open log file {}", path.display()))
+}
+
+/// A `MakeWriter` sending each formatted event to the local syslog daemon
+#[cfg(feature = "syslog")]
+mod syslog_writer {
+    use std::io::Write;
+    use std::sync::Mutex;
+
+    use anyhow::Result;
+    use syslog::{Facility, Formatter3164, Logger, LoggerBackend};
+    use tracing::{Level, Metadata};
+    use tracing_subscriber::fmt::MakeWriter;
+
+    type SyslogLogger = Logger<LoggerBackend, Formatter3164>;
+
+    pub struct SyslogMakeWriter {
+        logger: Mutex<SyslogLogger>,
+    }
+
+    impl SyslogMakeWriter {
+        /// Connects to the local syslog socket
+        ///
+        /// # Arguments
+        ///
+        /// * `facility` - A facility name validated by the config
+        pub fn connect(facility: &str) -> Result<Self> {
+            let facility: Facility = facility
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid syslog_facility: '{}'", facility))?;
+            let formatter = Formatter3164 {
+                facility,
+                hostname: None,
+                process: "ipv6ddns".to_string(),
+                pid: std::process::id(),
+            };
+            let logger = syslog::unix(formatter)
+                .map_err(|e| anyhow::anyhow!("Cannot connect to syslog: {}", e))?;
+            Ok(Self {
+                logger: Mutex::new(logger),
+            })
+        }
+    }
+
+    /// One event, buffered until it is complete and sent when dropped
+    pub struct SyslogLine<'a> {
+        logger: &'a Mutex<SyslogLogger>,
+        level: Level,
+        buf: Vec<u8>,
+    }
+
+    impl Write for SyslogLine<'_> {
+        fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+            self.buf.extend_from_slice(data);
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Drop for SyslogLine<'_> {
+        fn drop(&mut self) {
+            let line = String::from_utf8_lossy(&self.buf);
+            let line = line.trim_end();
+            if line.is_empty() {
+                return;
+            }
+            let Ok(mut logger) = self.logger.lock() else {
+                return;
+            };
+            // A lost log line can't be reported anywhere else
+            let _ = match self.level {
+                Level::ERROR => logger.err(line),
+                Level::WARN => logger.warning(line),
+                Level::INFO => logger.info(line),
+                Level::DEBUG | Level::TRACE => logger.debug(line),
+            };
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for SyslogMakeWriter {
+        type Writer = SyslogLine<'a>;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            SyslogLine {
+                logger: &self.logger,
+                level: Level::INFO,
+                buf: Vec::new(),
+            }
+        }
+
+        fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+            SyslogLine {
+                logger: &self.logger,
+                level: *meta.level(),
+                buf: Vec::new(),
+            }
+        }
+    }
+}
+
+/// Returns whether stderr is connected to the systemd journal
+///
+/// systemd sets `JOURNAL_STREAM` to `<device>:<inode>` of the stream it
+/// connected to the service; it is only trusted if it matches stderr, so a
+/// variable inherited by a child process run from a terminal is ignored.
+#[cfg(feature = "journald")]
+fn journal_connected() -> bool {
+    let Ok(value) = std::env::var("JOURNAL_STREAM") else {
+        return false;
+    };
+    let Some((dev, ino)) = parse_journal_stream(&value) else {
+        return false;
+    };
+
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstat(libc::STDERR_FILENO, &mut stat) } < 0 {
+        return false;
+    }
+    stat.st_dev as u64 == dev && stat.st_ino as u64 == ino
+}
+
+/// Parses a `JOURNAL_STREAM` value of the form `<device>:<inode>`
+#[cfg_attr(not(feature = "journald"), allow(dead_code))]
+fn parse_journal_stream(value: &str) -> Option<(u64, u64)> {
+    let (dev, ino) = value.trim().split_once(':')?;
+    Some((dev.parse().ok()?, ino.parse().ok()?))
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_journal_stream() {
+        assert_eq!(parse_journal_stream("8:12345"), Some((8, 12345)));
+        assert_eq!(parse_journal_stream(" 8:12345\n"), Some((8, 12345)));
+        assert_eq!(parse_journal_stream("8"), None);
+        assert_eq!(parse_journal_stream("a:b"), None);
+        assert_eq!(parse_journal_stream(""), None);
+    }
+
+    #[test]
+    fn test_open_log_file() {
+        use std::io::Write as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs/ipv6ddns.log");
+        let mut appender = open_log_file(&path, LogRotation::Never).unwrap();
+        appender.write_all(b"line\n").unwrap();
+        appender.flush().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "line\n");
+
+        // A regular file where the directory should be
+        let blocked = dir.path().join("logs/ipv6ddns.log/nested.log");
+        let err = open_log_file(&blocked, LogRotation::Daily).unwrap_err();
+        assert!(format!("{err:#}").contains("Cannot open log file"));
+    }
+}
@@ -7,23 +7,42 @@
 //! - Uses reqwest for HTTP (rustls)
 
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::{Context as _, Result};
-use clap::Parser;
-use tracing_subscriber::EnvFilter;
+use clap::{Parser, Subcommand};
 
 mod cloudflare;
 mod config;
 mod constants;
 mod daemon;
+mod detect_command;
 mod dns_provider;
+mod duckdns;
+mod external_ip;
 mod health;
+mod hooks;
+mod import;
+mod lock;
+mod logging;
+mod metrics;
+mod namecheap;
 mod netlink;
+mod porkbun;
+mod resolver;
+mod route53;
+mod state_file;
+mod systemd;
 mod validation;
+mod vultr;
+mod webhook;
 
-use cloudflare::CloudflareClient;
-use config::Config;
+use config::{AddressSource, CliOverrides, Config};
 use daemon::Daemon;
+use detect_command::{CommandMonitor, CommandSource};
+use external_ip::{ExternalIpSource, ExternalMonitor};
+use lock::InstanceLock;
+use metrics::Metrics;
 use netlink::NetlinkSocket;
 
 /// Application version
@@ -35,29 +54,175 @@ const VERSION: &str = "1.0.0";
 
 #[derive(Debug, Parser)]
 #[command(name = "ipv6ddns")]
-#[command(version = VERSION)]
+#[command(version = VERSION, about, long_about = None)]
 struct Args {
+    /// Path to the config file, or - to read a TOML config from stdin
     #[arg(short, long)]
     config: Option<PathBuf>,
+    #[command(flatten)]
+    overrides: CliOverrides,
+    /// Print a commented example config.toml (or write it to PATH) and exit
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    generate_config: Option<Option<PathBuf>>,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Convert a ddclient configuration into an ipv6ddns config.toml
+    Import {
+        /// Path to the ddclient.conf to read
+        path: PathBuf,
+        /// Write the generated config to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Show the provider's AAAA records next to the detected address, changing nothing
+    Status,
+    /// Check the records for drift from the detected address, changing nothing
+    ///
+    /// Exits 0 when every record is in sync, 1 when any has drifted and 2 on
+    /// errors (no address detected, lookup or config failure).
+    Diff,
+    /// Check that the credentials, zone and record names work, changing nothing
+    ///
+    /// Looks up the AAAA records of each name once and reports whether the
+    /// token was accepted, the zone was found and how many records exist.
+    Probe,
+    /// Print the address that would be published, and nothing else
+    ///
+    /// Exits non-zero when no address is detected. No API token is needed.
+    PrintIp,
+}
+
+/// Exit code of `diff` when a record has drifted
+const EXIT_DIFF_DRIFT: i32 = 1;
+/// Exit code of `diff` when the check itself failed
+const EXIT_DIFF_ERROR: i32 = 2;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
-    let config = Config::load(args.config).context("Config load failed")?;
+    if let Some(Command::Import { path, output }) = &args.command {
+        return import::run(path, output.as_deref());
+    }
+    if let Some(output) = &args.generate_config {
+        return config::write_example_config(output.as_deref());
+    }
+    if let Some(Command::PrintIp) = &args.command {
+        let config = Config::load_for_detection(args.config, &args.overrides)
+            .context("Config load failed")?;
+        logging::init_stderr(&config);
+        if let Some(netns) = &config.netns {
+            netlink::set_network_namespace(netns).context("Network namespace setup failed")?;
+        }
+        return daemon::print_ip(&config).await;
+    }
+    if let Some(Command::Diff) = &args.command {
+        let code = match run_diff(args).await {
+            Ok(true) => 0,
+            Ok(false) => EXIT_DIFF_DRIFT,
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                EXIT_DIFF_ERROR
+            }
+        };
+        std::process::exit(code);
+    }
 
-    let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new(if config.verbose { "debug" } else { "info" }));
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    let mut config = Config::load(args.config, &args.overrides).context("Config load failed")?;
+    // Subcommands print their report on stdout, so they log to stderr
+    if args.command.is_some() {
+        logging::init_stderr(&config);
+    } else {
+        logging::init(&config).context("Logging setup failed")?;
+    }
+    if let Some(netns) = &config.netns {
+        netlink::set_network_namespace(netns).context("Network namespace setup failed")?;
+    }
 
-    let cf_client = CloudflareClient::new(config.api_token.as_str(), config.timeout)
-        .context("Cloudflare client failed")?;
+    let metrics = Arc::new(Metrics::new());
+    if let Some(Command::Probe) = &args.command {
+        return daemon::print_probe(&mut config, metrics).await;
+    }
+    dns_provider::resolve_zone_name(&mut config, Arc::clone(&metrics)).await?;
+    let dns_provider = dns_provider::build_provider(&config, Arc::clone(&metrics))
+        .with_context(|| format!("{} client failed", config.provider_type))?;
+    if let Some(Command::Status) = &args.command {
+        return daemon::print_status(&config, dns_provider.as_ref()).await;
+    }
 
-    let netlink = NetlinkSocket::new(Some(config.poll_interval), config.allow_loopback)
-        .context("Netlink socket failed")?;
+    let lock = config
+        .lock_file
+        .as_deref()
+        .map(InstanceLock::acquire)
+        .transpose()?;
 
-    let mut daemon = Daemon::new(config, std::sync::Arc::new(cf_client), netlink);
-    daemon.run().await?;
+    let netlink = match config.source {
+        AddressSource::Netlink => NetlinkSocket::new(
+            Some(config.poll_interval),
+            config.poll_jitter,
+            config.netlink_max_drain,
+            &config.ipv6_filter,
+            &config.accepted_scopes,
+            &config.selection,
+        )
+        .context("Netlink socket failed")?,
+        AddressSource::External => {
+            let source = ExternalIpSource::new(
+                &config.external_ip_url,
+                config.timeout,
+                config.connect_timeout(),
+                config.ipv6_filter.clone(),
+            )
+            .context("External address client failed")?;
+            NetlinkSocket::from_monitor(Box::new(ExternalMonitor::new(
+                source,
+                config.poll_interval,
+                config.poll_jitter,
+            )))
+        }
+        AddressSource::Command => {
+            let command = config
+                .detect_command
+                .clone()
+                .context("source = \"command\" requires detect_command")?;
+            let source = CommandSource::new(command, config.timeout, config.ipv6_filter.clone());
+            NetlinkSocket::from_monitor(Box::new(CommandMonitor::new(
+                source,
+                config.poll_interval,
+                config.poll_jitter,
+            )))
+        }
+    };
+
+    let mut daemon =
+        Daemon::new(config, dns_provider, netlink, metrics).context("Daemon setup failed")?;
+    let result = daemon.run().await;
+    if let Some(lock) = lock {
+        lock.release();
+    }
+    result
+}
 
-    Ok(())
+/// Runs the `diff` subcommand
+///
+/// Kept apart from `main` so that every failure, including loading the
+/// config, maps to the error exit code rather than the drift one.
+///
+/// # Returns
+///
+/// Returns whether every record is in sync, or an error if the check failed
+async fn run_diff(args: Args) -> Result<bool> {
+    let mut config = Config::load(args.config, &args.overrides).context("Config load failed")?;
+    logging::init_stderr(&config);
+    if let Some(netns) = &config.netns {
+        netlink::set_network_namespace(netns).context("Network namespace setup failed")?;
+    }
+    let metrics = Arc::new(Metrics::new());
+    dns_provider::resolve_zone_name(&mut config, Arc::clone(&metrics)).await?;
+    let dns_provider = dns_provider::build_provider(&config, metrics)
+        .with_context(|| format!("{} client failed", config.provider_type))?;
+    daemon::print_diff(&config, dns_provider.as_ref()).await
 }
@@ -0,0 +1,344 @@
+//! Prometheus metrics for ipv6ddns
+//!
+//! This module keeps a small set of in-process counters and renders them in the
+//! Prometheus text exposition format. They are served at `/metrics` by the
+//! health check server when `health_port` is set, and can be written to a
+//! file for the node_exporter textfile collector (`metrics_textfile`).
+//!
+//! # Metrics
+//!
+//! - `ipv6ddns_provider_requests_total{method,status_class}`: HTTP requests made
+//!   to the DNS provider API, by method and status class (`2xx`, `4xx`, `5xx`,
+//!   or `error` when no response was received)
+//! - `ipv6ddns_update_verifications_total{result}`: reads after an update
+//!   (`verify_after_update`), by result (`ok`, `mismatch`, or `error` when the
+//!   read failed)
+//! - `ipv6ddns_dns_errors_total{provider,error_type}`: failed record updates, by
+//!   provider and category (`auth`, `rate_limit`, `server`, `network`,
+//!   `validation`)
+//! - `ipv6ddns_external_checks_total{result}`: lookups of the published records
+//!   at `external_resolver`, by result (`match`, `mismatch`, or `error` when the
+//!   lookup failed)
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{Context as _, Result};
+
+//==============================================================================
+// Types
+//==============================================================================
+
+/// In-process metric counters
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Provider API requests, keyed by (method, status class)
+    provider_requests: Mutex<BTreeMap<(&'static str, &'static str), u64>>,
+    /// Post-update verification reads, keyed by result
+    update_verifications: Mutex<BTreeMap<&'static str, u64>>,
+    /// Failed record updates, keyed by (provider, error type)
+    dns_errors: Mutex<BTreeMap<(&'static str, &'static str), u64>>,
+    /// Lookups at the external resolver, keyed by result
+    external_checks: Mutex<BTreeMap<&'static str, u64>>,
+}
+
+//==============================================================================
+// Implementation
+//==============================================================================
+
+impl Metrics {
+    /// Creates an empty set of counters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a request made to the DNS provider API
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method (e.g. "GET")
+    /// * `status` - The HTTP status code, or `None` if no response was received
+    pub fn record_provider_request(&self, method: &'static str, status: Option<u16>) {
+        let mut requests = self
+            .provider_requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        *requests.entry((method, status_class(status))).or_insert(0) += 1;
+    }
+
+    /// Counts a verification read made after an update
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - "ok", "mismatch", or "error" if the read failed
+    pub fn record_update_verification(&self, result: &'static str) {
+        let mut verifications = self
+            .update_verifications
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        *verifications.entry(result).or_insert(0) += 1;
+    }
+
+    /// Counts a failed record update
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The DNS provider name (e.g. "cloudflare")
+    /// * `error_type` - The error category (e.g. "auth", "server")
+    pub fn record_dns_error(&self, provider: &'static str, error_type: &'static str) {
+        let mut errors = self.dns_errors.lock().unwrap_or_else(|e| e.into_inner());
+        *errors.entry((provider, error_type)).or_insert(0) += 1;
+    }
+
+    /// Counts a lookup of a published record at the external resolver
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - "match", "mismatch", or "error" if the lookup failed
+    pub fn record_external_check(&self, result: &'static str) {
+        let mut checks = self
+            .external_checks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        *checks.entry(result).or_insert(0) += 1;
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let requests = self
+            .provider_requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let verifications = self
+            .update_verifications
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let dns_errors = self.dns_errors.lock().unwrap_or_else(|e| e.into_inner());
+        let external_checks = self
+            .external_checks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        let mut out = String::new();
+        out.push_str(
+            "# HELP ipv6ddns_provider_requests_total HTTP requests made to the DNS provider API\n",
+        );
+        out.push_str("# TYPE ipv6ddns_provider_requests_total counter\n");
+        for ((method, class), count) in requests.iter() {
+            let _ = writeln!(
+                out,
+                "ipv6ddns_provider_requests_total{{method=\"{}\",status_class=\"{}\"}} {}",
+                method, class, count
+            );
+        }
+        out.push_str(
+            "# HELP ipv6ddns_update_verifications_total Reads made to verify an update took effect\n",
+        );
+        out.push_str("# TYPE ipv6ddns_update_verifications_total counter\n");
+        for (result, count) in verifications.iter() {
+            let _ = writeln!(
+                out,
+                "ipv6ddns_update_verifications_total{{result=\"{}\"}} {}",
+                result, count
+            );
+        }
+        out.push_str("# HELP ipv6ddns_dns_errors_total Failed DNS record updates by error type\n");
+        out.push_str("# TYPE ipv6ddns_dns_errors_total counter\n");
+        for ((provider, error_type), count) in dns_errors.iter() {
+            let _ = writeln!(
+                out,
+                "ipv6ddns_dns_errors_total{{provider=\"{}\",error_type=\"{}\"}} {}",
+                provider, error_type, count
+            );
+        }
+        out.push_str(
+            "# HELP ipv6ddns_external_checks_total Lookups of the published records at the external resolver\n",
+        );
+        out.push_str("# TYPE ipv6ddns_external_checks_total counter\n");
+        for (result, count) in external_checks.iter() {
+            let _ = writeln!(
+                out,
+                "ipv6ddns_external_checks_total{{result=\"{}\"}} {}",
+                result, count
+            );
+        }
+        out
+    }
+
+    /// Writes all metrics to a file for the node_exporter textfile collector
+    ///
+    /// The file is written next to its final path and renamed into place, so
+    /// the collector never reads a partial file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The `.prom` file to write
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` or an error if the file cannot be written
+    pub fn write_textfile(&self, path: &Path) -> Result<()> {
+        let file_name = path
+            .file_name()
+            .with_context(|| format!("Invalid metrics_textfile path: {}", path.display()))?;
+        let mut tmp_name = std::ffi::OsString::from(".");
+        tmp_name.push(file_name);
+        tmp_name.push(".tmp");
+        let tmp = path.with_file_name(tmp_name);
+
+        std::fs::write(&tmp, self.render())
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| {
+            let _ = std::fs::remove_file(&tmp);
+            format!("Failed to move metrics into {}", path.display())
+        })
+    }
+}
+
+//==============================================================================
+// Helpers
+//==============================================================================
+
+/// Maps an HTTP status code to its class label
+///
+/// # Arguments
+///
+/// * `status` - The HTTP status code, or `None` if no response was received
+///
+/// # Returns
+///
+/// Returns "1xx" through "5xx", or "error" for a missing or out-of-range status
+fn status_class(status: Option<u16>) -> &'static str {
+    match status {
+        Some(100..=199) => "1xx",
+        Some(200..=299) => "2xx",
+        Some(300..=399) => "3xx",
+        Some(400..=499) => "4xx",
+        Some(500..=599) => "5xx",
+        _ => "error",
+    }
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_class() {
+        assert_eq!(status_class(Some(200)), "2xx");
+        assert_eq!(status_class(Some(404)), "4xx");
+        assert_eq!(status_class(Some(429)), "4xx");
+        assert_eq!(status_class(Some(503)), "5xx");
+        assert_eq!(status_class(None), "error");
+    }
+
+    #[test]
+    fn test_render_provider_requests() {
+        let metrics = Metrics::new();
+        metrics.record_provider_request("GET", Some(200));
+        metrics.record_provider_request("GET", Some(200));
+        metrics.record_provider_request("PUT", Some(429));
+        metrics.record_provider_request("POST", None);
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE ipv6ddns_provider_requests_total counter"));
+        assert!(text
+            .contains("ipv6ddns_provider_requests_total{method=\"GET\",status_class=\"2xx\"} 2"));
+        assert!(text
+            .contains("ipv6ddns_provider_requests_total{method=\"PUT\",status_class=\"4xx\"} 1"));
+        assert!(text.contains(
+            "ipv6ddns_provider_requests_total{method=\"POST\",status_class=\"error\"} 1"
+        ));
+    }
+
+    #[test]
+    fn test_render_dns_errors() {
+        let metrics = Metrics::new();
+        metrics.record_dns_error("cloudflare", "auth");
+        metrics.record_dns_error("cloudflare", "server");
+        metrics.record_dns_error("cloudflare", "server");
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE ipv6ddns_dns_errors_total counter"));
+        assert!(text
+            .contains("ipv6ddns_dns_errors_total{provider=\"cloudflare\",error_type=\"auth\"} 1"));
+        assert!(text.contains(
+            "ipv6ddns_dns_errors_total{provider=\"cloudflare\",error_type=\"server\"} 2"
+        ));
+    }
+
+    #[test]
+    fn test_write_textfile() {
+        let metrics = Metrics::new();
+        metrics.record_provider_request("GET", Some(200));
+        metrics.record_update_verification("ok");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ipv6ddns.prom");
+        metrics.write_textfile(&path).unwrap();
+        metrics.record_provider_request("GET", Some(200));
+        metrics.write_textfile(&path).unwrap();
+
+        // Only the final file is left behind
+        let names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["ipv6ddns.prom"]);
+
+        // Every line is a HELP/TYPE comment or a `name{labels} value` sample
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.ends_with('\n'));
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                assert!(
+                    comment.starts_with("HELP ipv6ddns_") || comment.starts_with("TYPE ipv6ddns_"),
+                    "{line}"
+                );
+                continue;
+            }
+            let (series, value) = line.rsplit_once(' ').expect(line);
+            assert!(value.parse::<f64>().is_ok(), "{line}");
+            let name = series.split('{').next().unwrap();
+            assert!(
+                name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+                "{line}"
+            );
+            assert!(!series.contains('{') || series.ends_with('}'), "{line}");
+        }
+        assert!(text
+            .contains("ipv6ddns_provider_requests_total{method=\"GET\",status_class=\"2xx\"} 2"));
+    }
+
+    #[test]
+    fn test_render_update_verifications() {
+        let metrics = Metrics::new();
+        metrics.record_update_verification("ok");
+        metrics.record_update_verification("ok");
+        metrics.record_update_verification("mismatch");
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE ipv6ddns_update_verifications_total counter"));
+        assert!(text.contains("ipv6ddns_update_verifications_total{result=\"ok\"} 2"));
+        assert!(text.contains("ipv6ddns_update_verifications_total{result=\"mismatch\"} 1"));
+    }
+
+    #[test]
+    fn test_render_external_checks() {
+        let metrics = Metrics::new();
+        metrics.record_external_check("match");
+        metrics.record_external_check("mismatch");
+        metrics.record_external_check("match");
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE ipv6ddns_external_checks_total counter"));
+        assert!(text.contains("ipv6ddns_external_checks_total{result=\"match\"} 2"));
+        assert!(text.contains("ipv6ddns_external_checks_total{result=\"mismatch\"} 1"));
+    }
+}
@@ -0,0 +1,324 @@
+//! Namecheap dynamic DNS client for DNS operations
+//!
+//! This module provides a `DnsProvider` backed by Namecheap's dynamic DNS
+//! endpoint, a single GET request that sets the address of one host:
+//!
+//! ```text
+//! https://dynamicdns.park-your-domain.com/update?host=<host>&domain=<domain>&password=<password>&ip=<address>
+//! ```
+//!
+//! The zone ID holds the domain (e.g. `example.com`) and `api_token` the
+//! dynamic DNS password shown on the domain's Advanced DNS page. Namecheap
+//! answers with an XML document whose `<ErrCount>` is 0 on success; otherwise
+//! `<Err1>` describes the failure.
+//!
+//! # Differences from Cloudflare
+//!
+//! - The endpoint was built for IPv4. AAAA updates are sent the same way, but
+//!   hosts that don't accept an IPv6 address fail with an error saying so
+//! - Each host holds one address per update, so `MultiRecordPolicy` is ignored
+//! - Hosts are created on the Advanced DNS page, so `allow_create` and
+//!   `reconcile_fields` are ignored
+//! - Records can't be deleted or listed, so `on_removal = "delete"`,
+//!   `delete_on_shutdown` and `verify_after_update` are not available
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use tracing::debug;
+use zeroize::ZeroizeOnDrop;
+
+use crate::constants::{HTTP_POOL_IDLE_TIMEOUT_SECS, NAMECHEAP_TTL_SECS, NAMECHEAP_UPDATE_URL};
+use crate::dns_provider::{
+    relative_name, DnsProvider, DnsRecord, ErrorKind, ProviderError, RecordType, UpsertOptions,
+};
+use crate::metrics::Metrics;
+
+//==============================================================================
+// Client
+//==============================================================================
+
+/// Namecheap dynamic DNS client
+///
+/// The password is wrapped in `Zeroizing` to ensure it is securely cleared
+/// from memory when dropped. It is sent as a query parameter, so request URLs
+/// are never logged.
+#[derive(ZeroizeOnDrop)]
+pub struct NamecheapClient {
+    /// Dynamic DNS password of the domain
+    #[zeroize(skip)]
+    password: zeroize::Zeroizing<String>,
+    /// HTTP client for making requests
+    #[zeroize(skip)]
+    client: reqwest::Client,
+    /// Counters for API requests
+    #[zeroize(skip)]
+    metrics: Arc<Metrics>,
+}
+
+impl NamecheapClient {
+    /// Creates a new Namecheap dynamic DNS client
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - Dynamic DNS password of the domain
+    /// * `timeout` - HTTP request timeout duration
+    /// * `connect_timeout` - Time allowed to establish a connection
+    /// * `user_agent` - User-Agent header sent with every request
+    /// * `metrics` - Counters updated for every API request
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the client or an error if client creation fails
+    pub fn new(
+        password: &str,
+        timeout: Duration,
+        connect_timeout: Duration,
+        user_agent: &str,
+        metrics: Arc<Metrics>,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .user_agent(user_agent)
+            .pool_idle_timeout(Duration::from_secs(HTTP_POOL_IDLE_TIMEOUT_SECS))
+            .build()
+            .context("build reqwest client")?;
+
+        Ok(Self {
+            password: zeroize::Zeroizing::new(password.to_string()),
+            client,
+            metrics,
+        })
+    }
+
+    /// Builds the update URL for a host
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The host below the domain (`@` for the apex)
+    /// * `domain` - The domain
+    /// * `ip` - The address to set
+    fn update_url(&self, host: &str, domain: &str, ip: &str) -> Result<reqwest::Url> {
+        reqwest::Url::parse_with_params(
+            NAMECHEAP_UPDATE_URL,
+            &[
+                ("host", host),
+                ("domain", domain),
+                ("password", self.password.as_str()),
+                ("ip", ip),
+            ],
+        )
+        .context("Failed to build Namecheap update URL")
+    }
+}
+
+#[async_trait]
+impl DnsProvider for NamecheapClient {
+    async fn upsert_record(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+        content: &str,
+        _options: UpsertOptions<'_>,
+    ) -> Result<DnsRecord> {
+        let domain = zone_id.trim().trim_end_matches('.');
+        let host = host(domain, record_name)?;
+        let url = self.update_url(host, domain, content)?;
+
+        debug!(
+            "GET {} (host: {}, domain: {})",
+            NAMECHEAP_UPDATE_URL, host, domain
+        );
+        let result = self.client.get(url).send().await;
+        self.metrics.record_provider_request(
+            "GET",
+            result.as_ref().ok().map(|resp| resp.status().as_u16()),
+        );
+        // The error would include the URL, and with it the password
+        let resp = result
+            .map_err(reqwest::Error::without_url)
+            .context("Namecheap request failed")?;
+
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .context("Failed to read Namecheap response")?;
+        if !status.is_success() {
+            bail!(ProviderError::new(
+                ErrorKind::from_status(status.as_u16()),
+                format!("Namecheap returned HTTP {} for {}", status, record_name)
+            ));
+        }
+        if let Err(e) = check_response(&body) {
+            let e = if record_type == RecordType::Aaaa && ErrorKind::of(&e) != ErrorKind::Auth {
+                e.context(
+                    "Namecheap's dynamic DNS may not accept IPv6 addresses for this host; \
+                     publish the A record instead (record_type = \"A\")",
+                )
+            } else {
+                e
+            };
+            return Err(e.context(format!("Namecheap update of {}", record_name)));
+        }
+
+        Ok(DnsRecord {
+            id: record_name.to_string(),
+            record_type: record_type.as_str().to_string(),
+            name: record_name.to_string(),
+            content: content.to_string(),
+            proxied: false,
+            ttl: NAMECHEAP_TTL_SECS,
+            created: false,
+        })
+    }
+
+    async fn delete_record(
+        &self,
+        _zone_id: &str,
+        _record_name: &str,
+        _record_type: RecordType,
+    ) -> Result<usize> {
+        bail!("Namecheap's dynamic DNS can't delete records")
+    }
+}
+
+//==============================================================================
+// Helpers
+//==============================================================================
+
+/// Returns the Namecheap host of a record name: the part below the domain, or
+/// `@` for the apex
+///
+/// # Arguments
+///
+/// * `domain` - The domain (zone ID), e.g. "example.com"
+/// * `record_name` - The record name, e.g. "home.example.com"
+///
+/// # Returns
+///
+/// Returns the host, or an error if the record is not in the domain
+pub fn host<'a>(domain: &str, record_name: &'a str) -> Result<&'a str> {
+    match relative_name(domain, record_name) {
+        Some("") => Ok("@"),
+        Some(host) => Ok(host),
+        None => bail!(
+            "Record name '{}' is not in the Namecheap domain '{}'",
+            record_name,
+            domain.trim().trim_end_matches('.')
+        ),
+    }
+}
+
+/// Returns the text of the first `<tag>` element in an XML document
+fn xml_text<'a>(body: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = body.find(&open)? + open.len();
+    let end = body[start..].find(&close)? + start;
+    Some(body[start..end].trim())
+}
+
+/// Checks the body of a Namecheap update response
+///
+/// # Returns
+///
+/// Returns `Ok(())` when `<ErrCount>` is 0, otherwise an error with the text
+/// of `<Err1>`; a rejected password is an `Auth` error
+fn check_response(body: &str) -> Result<()> {
+    let count = xml_text(body, "ErrCount")
+        .and_then(|count| count.parse::<u32>().ok())
+        .with_context(|| format!("Unexpected Namecheap response: {:?}", body.trim()))?;
+    if count == 0 {
+        return Ok(());
+    }
+    let message = xml_text(body, "Err1").unwrap_or("unknown error");
+    let kind = if message.to_ascii_lowercase().contains("password") {
+        ErrorKind::Auth
+    } else {
+        ErrorKind::Validation
+    };
+    bail!(ProviderError::new(
+        kind,
+        format!("Namecheap rejected the update: {}", message)
+    ))
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::CLOUDFLARE_USER_AGENT;
+
+    #[test]
+    fn test_host() {
+        assert_eq!(host("example.com", "home.example.com").unwrap(), "home");
+        assert_eq!(host("example.com", "a.b.example.com.").unwrap(), "a.b");
+        assert_eq!(host("example.com", "example.com").unwrap(), "@");
+        assert_eq!(host("example.com", "@").unwrap(), "@");
+        let err = host("example.com", "home.example.org").unwrap_err();
+        assert!(format!("{err}").contains("Namecheap domain"));
+    }
+
+    #[test]
+    fn test_update_url() {
+        let client = NamecheapClient::new(
+            "0123456789abcdef0123456789abcdef",
+            Duration::from_secs(5),
+            Duration::from_secs(5),
+            CLOUDFLARE_USER_AGENT,
+            Arc::new(Metrics::new()),
+        )
+        .unwrap();
+        let url = client
+            .update_url("home", "example.com", "2001:db8::1")
+            .unwrap();
+
+        assert_eq!(url.host_str(), Some("dynamicdns.park-your-domain.com"));
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(
+            query,
+            [
+                ("host".to_string(), "home".to_string()),
+                ("domain".to_string(), "example.com".to_string()),
+                (
+                    "password".to_string(),
+                    "0123456789abcdef0123456789abcdef".to_string()
+                ),
+                ("ip".to_string(), "2001:db8::1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_response() {
+        let ok = r#"<?xml version="1.0" encoding="utf-16"?>
+<interface-response><Command>SETDNSHOST</Command><Language>eng</Language>
+<IP>192.0.2.1</IP><ErrCount>0</ErrCount><errors /><ResponseCount>0</ResponseCount>
+<responses /><Done>true</Done><debug><![CDATA[]]></debug></interface-response>"#;
+        assert!(check_response(ok).is_ok());
+
+        let bad_password = r#"<?xml version="1.0" encoding="utf-16"?>
+<interface-response><Command>SETDNSHOST</Command><Language>eng</Language>
+<ErrCount>1</ErrCount><errors><Err1>Passwords do not match</Err1></errors>
+<Done>true</Done></interface-response>"#;
+        let err = check_response(bad_password).unwrap_err();
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Auth);
+        assert!(format!("{err}").contains("Passwords do not match"));
+
+        let bad_ip = r#"<interface-response><ErrCount>1</ErrCount>
+<errors><Err1>Invalid IP</Err1></errors></interface-response>"#;
+        let err = check_response(bad_ip).unwrap_err();
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Validation);
+
+        assert!(check_response("").is_err());
+        assert!(check_response("<html>Bad gateway</html>").is_err());
+    }
+}
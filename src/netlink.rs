@@ -23,11 +23,12 @@
 //! use ipv6ddns::netlink::NetlinkSocket;
 //! use std::time::Duration;
 //!
-//! let socket = NetlinkSocket::new(Some(Duration::from_secs(60)), false)?;
+//! let socket = NetlinkSocket::new(Some(Duration::from_secs(60)), 0.0, 16, false, &[RT_SCOPE_UNIVERSE], &SelectionPolicy::default())?;
 //! loop {
 //!     match socket.recv().await? {
-//!         NetlinkEvent::Ipv6Added(ip) => println!("IPv6 added: {}", ip),
+//!         NetlinkEvent::Ipv6Added(ip, _) => println!("IPv6 added: {}", ip),
 //!         NetlinkEvent::Ipv6Removed => println!("IPv6 removed"),
+//!         NetlinkEvent::Resync => println!("events lost; re-read the addresses"),
 //!         NetlinkEvent::Unknown => {},
 //!     }
 //! }
@@ -36,26 +37,44 @@
 //! # Address Filtering
 //!
 //! The module automatically filters out:
-//! - Temporary addresses (privacy extensions)
-//! - Tentative addresses (still undergoing DAD)
+//! - Temporary addresses (privacy extensions), unless the address preference favors them
+//! - Tentative addresses (still undergoing DAD); in event mode they are re-checked
+//!   shortly after, so an address is published once DAD completes
 //! - Deprecated addresses
 //! - DAD-failed addresses
 //! - Non-global scope addresses (unless loopback is allowed)
 //!
+//! On policy-routing setups, detection can also be limited to the interfaces
+//! a routing table (or the table an fwmark selects) routes through; see
+//! [`RoutingContext`].
+//!
+//! If the event socket fails, e.g. with ENOBUFS after the kernel overflowed
+//! its receive buffer, it is replaced by a new one and a
+//! [`NetlinkEvent::Resync`] is returned, since changes may have been missed.
+//!
+//! With [`set_network_namespace`], the netlink sockets are opened in another
+//! network namespace, so that namespace's addresses are detected instead.
+//!
 //! # Netlink Protocol
 //!
 //! The module uses the NETLINK_ROUTE protocol to subscribe to RTMGRP_IPV6_ADDR
 //! multicast group, which receives notifications for IPv6 address changes.
 
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hasher};
 use std::io::ErrorKind;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::Duration;
 
 use anyhow::{Context as _, Result};
 use async_trait::async_trait;
 use tokio::io::unix::AsyncFd;
 
-use crate::validation::is_valid_ipv6;
+use crate::validation::{is_valid_ipv4, Ipv6Filter};
 
 //==============================================================================
 // RAII Socket Wrapper
@@ -93,6 +112,94 @@ impl NetlinkFd {
     }
 }
 
+/// Network namespace the netlink sockets are created in, if not the daemon's own
+static NETWORK_NAMESPACE: OnceLock<(PathBuf, OwnedFd)> = OnceLock::new();
+
+/// Makes detection watch another network namespace
+///
+/// Must be called before any netlink socket is created. The namespace is
+/// entered once right away, so a missing capability is reported at startup
+/// rather than on the first detection.
+///
+/// # Arguments
+///
+/// * `netns` - A namespace name (opened as `/run/netns/<name>`, as created by
+///   `ip netns add`) or a path such as `/proc/<pid>/ns/net`
+///
+/// # Returns
+///
+/// Returns `Ok(())` or an error if the namespace can't be opened or entered
+pub fn set_network_namespace(netns: &str) -> Result<()> {
+    let path = network_namespace_path(netns);
+    let file = std::fs::File::open(&path)
+        .with_context(|| format!("Failed to open network namespace {}", path.display()))?;
+    let fd = OwnedFd::from(file);
+    enter_network_namespace(&path, &fd, || Ok(()))?;
+    NETWORK_NAMESPACE
+        .set((path, fd))
+        .map_err(|_| anyhow::anyhow!("Network namespace already set"))
+}
+
+/// Returns the path of a network namespace given by name or path
+///
+/// # Arguments
+///
+/// * `netns` - A name under `/run/netns`, or a path (anything with a `/`)
+fn network_namespace_path(netns: &str) -> PathBuf {
+    if netns.contains('/') {
+        PathBuf::from(netns)
+    } else {
+        Path::new(NETNS_RUN_DIR).join(netns)
+    }
+}
+
+/// Runs `f` in the network namespace set by `set_network_namespace`
+///
+/// Without one, `f` simply runs on the calling thread. `setns` only moves the
+/// calling thread, and the runtime's threads must stay put for the API calls,
+/// so otherwise `f` runs on a short-lived thread that enters the namespace.
+/// That thread exits afterwards, so there is no namespace to restore, whether
+/// `f` succeeds, fails or the namespace can't be entered at all.
+fn in_network_namespace<T: Send>(f: impl FnOnce() -> Result<T> + Send) -> Result<T> {
+    match NETWORK_NAMESPACE.get() {
+        Some((path, fd)) => enter_network_namespace(path, fd, f),
+        None => f(),
+    }
+}
+
+/// Runs `f` on a new thread that has entered the namespace `fd`
+fn enter_network_namespace<T: Send>(
+    path: &Path,
+    fd: &OwnedFd,
+    f: impl FnOnce() -> Result<T> + Send,
+) -> Result<T> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                if unsafe { libc::setns(fd.as_raw_fd(), libc::CLONE_NEWNET) } < 0 {
+                    let err = std::io::Error::last_os_error();
+                    return Err(match err.raw_os_error() {
+                        Some(libc::EPERM) => anyhow::anyhow!(
+                            "Entering network namespace {} requires CAP_SYS_ADMIN: run as root \
+                             or add AmbientCapabilities=CAP_SYS_ADMIN to the service",
+                            path.display()
+                        ),
+                        Some(libc::EINVAL) => {
+                            anyhow::anyhow!("{} is not a network namespace", path.display())
+                        }
+                        _ => anyhow::Error::new(err).context(format!(
+                            "Failed to enter network namespace {}",
+                            path.display()
+                        )),
+                    });
+                }
+                f()
+            })
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Network namespace thread panicked")))
+    })
+}
+
 impl AsRawFd for NetlinkFd {
     fn as_raw_fd(&self) -> i32 {
         self.0
@@ -126,24 +233,48 @@ const NLM_F_DUMP: u16 = 0x0300;
 const RTM_NEWADDR_VAL: u16 = libc::RTM_NEWADDR;
 const RTM_DELADDR_VAL: u16 = libc::RTM_DELADDR;
 const RTM_GETADDR_VAL: u16 = libc::RTM_GETADDR;
+const RTM_NEWROUTE_VAL: u16 = libc::RTM_NEWROUTE;
+const RTM_GETROUTE_VAL: u16 = libc::RTM_GETROUTE;
+const RTM_NEWRULE_VAL: u16 = libc::RTM_NEWRULE;
+const RTM_GETRULE_VAL: u16 = libc::RTM_GETRULE;
+
+// Route and rule attribute types (linux/rtnetlink.h, linux/fib_rules.h)
+const RTA_OIF_VAL: u16 = libc::RTA_OIF;
+const RTA_TABLE_VAL: u16 = libc::RTA_TABLE;
+const FRA_FWMARK: u16 = 10;
+const FRA_TABLE: u16 = 15;
+const FRA_FWMASK: u16 = 16;
+const FR_ACT_TO_TBL: u8 = 1;
 
 // Interface address attribute types
 const IFA_ADDRESS_VAL: u16 = libc::IFA_ADDRESS;
 const IFA_LOCAL_VAL: u16 = libc::IFA_LOCAL;
+const IFA_CACHEINFO_VAL: u16 = libc::IFA_CACHEINFO;
 
 // Netlink message structure constants
 const NLMSG_HDRLEN: usize = 16;
 const IFADDRMSG_LEN: usize = 8;
+// struct rtmsg and struct fib_rule_hdr share this size and field layout
+const RTMSG_LEN: usize = 12;
 const ALIGN_TO: usize = 4;
 
 // Buffer sizes for netlink operations
 const NETLINK_RECV_BUFFER_SIZE: usize = 8192;
 const NETLINK_DUMP_BUFFER_SIZE: usize = 16384;
+// Largest buffer a truncated dump is retried with
+const MAX_NETLINK_DUMP_BUFFER_SIZE: usize = 1 << 20;
 const IPV6_ADDR_BYTES: usize = 16;
+const IPV4_ADDR_BYTES: usize = 4;
 
 // Address family constants
 const AF_INET6: u8 = libc::AF_INET6 as u8;
-const RT_SCOPE_UNIVERSE: u8 = libc::RT_SCOPE_UNIVERSE;
+const AF_INET: u8 = libc::AF_INET as u8;
+/// Global address scope (the default accepted scope)
+pub const RT_SCOPE_UNIVERSE: u8 = libc::RT_SCOPE_UNIVERSE;
+/// Site-local address scope
+pub const RT_SCOPE_SITE: u8 = libc::RT_SCOPE_SITE;
+/// Link-local address scope (only accepted with `allow_link_local`)
+pub const RT_SCOPE_LINK: u8 = libc::RT_SCOPE_LINK;
 
 // Address flag constants
 const IFA_F_TEMPORARY: u32 = libc::IFA_F_TEMPORARY;
@@ -155,12 +286,36 @@ const IFA_F_DEPRECATED: u32 = libc::IFA_F_DEPRECATED;
 const NLMSG_DONE: u16 = libc::NLMSG_DONE as u16;
 const NLMSG_ERROR: u16 = libc::NLMSG_ERROR as u16;
 
+/// Preferred lifetime the kernel reports for addresses that never expire
+pub const INFINITY_LIFE_TIME: u32 = u32::MAX;
+
 // Attribute header size
 const RTA_HEADER_SIZE: usize = 4;
 
+// Directory of the namespaces named by `ip netns add`
+const NETNS_RUN_DIR: &str = "/run/netns";
+
+// Bounds on the work done per buffer. Real buffers stay far below them, but
+// they keep the parser's cost predictable if a buffer is malformed
+const MAX_MESSAGES_PER_BUFFER: usize = 1024;
+const MAX_ATTRIBUTES_PER_MESSAGE: usize = 256;
+// Bound on all messages of one dump (a full BGP table is well below this)
+const MAX_DUMP_MESSAGES: usize = 1 << 20;
+
+// Delay before re-dumping to catch a tentative address that passed DAD, and
+// how many times to try before giving up on it
+const DAD_RECHECK_DELAY: Duration = Duration::from_secs(2);
+const DAD_RECHECK_ATTEMPTS: u32 = 5;
+
+// Wait between attempts to reopen a failed event socket
+const NETLINK_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
 // Default polling interval
 const POLL_INTERVAL_DEFAULT: Duration = Duration::from_secs(60);
 
+// Preferred lifetimes above this count as this much when scoring (one week)
+const MAX_SCORED_LIFETIME_HOURS: i64 = 168;
+
 /// Represents a netlink event related to IPv6 address changes
 ///
 /// This enum describes different types of events that can occur on
@@ -169,18 +324,251 @@ const POLL_INTERVAL_DEFAULT: Duration = Duration::from_secs(60);
 pub enum NetlinkEvent {
     /// An IPv6 address was added or changed
     ///
-    /// Contains the string representation of the IPv6 address
-    Ipv6Added(String),
+    /// Contains the string representation of the IPv6 address and its
+    /// remaining preferred lifetime in seconds, if the kernel reported one
+    /// (`INFINITY_LIFE_TIME` means it never expires)
+    Ipv6Added(String, Option<u32>),
     /// An IPv6 address was removed
     ///
     /// This event does not contain the specific address that was removed
     Ipv6Removed,
+    /// Events may have been lost, e.g. after the receive buffer overflowed
+    ///
+    /// The current addresses must be re-read rather than waiting for the
+    /// next change.
+    Resync,
     /// An unknown or unhandled netlink event
     ///
     /// This is used for events that don't match the above categories
     Unknown,
 }
 
+/// An IPv6 address found on the system, with the details used to rank it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressCandidate {
+    /// The address
+    pub addr: Ipv6Addr,
+    /// Whether this is a temporary (privacy extension) address
+    pub temporary: bool,
+    /// Name of the interface the address is assigned to, if known
+    pub interface: Option<String>,
+    /// Remaining preferred lifetime in seconds, if reported (`u32::MAX` = forever)
+    pub preferred_lifetime: Option<u32>,
+}
+
+/// Which kind of address to publish: stable or temporary (privacy extensions)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddressPreference {
+    /// Prefer stable addresses, falling back to temporary ones
+    #[default]
+    Stable,
+    /// Prefer temporary addresses, falling back to stable ones
+    Temporary,
+    /// Only ever publish stable addresses
+    StableOnly,
+    /// Only ever publish temporary addresses
+    TemporaryOnly,
+}
+
+impl AddressPreference {
+    /// Returns whether temporary addresses are preferred over stable ones
+    fn prefers_temporary(self) -> bool {
+        matches!(self, Self::Temporary | Self::TemporaryOnly)
+    }
+
+    /// Returns whether a candidate of this kind may be published at all
+    fn allows(self, candidate: &AddressCandidate) -> bool {
+        match self {
+            Self::Stable | Self::Temporary => true,
+            Self::StableOnly => !candidate.temporary,
+            Self::TemporaryOnly => candidate.temporary,
+        }
+    }
+}
+
+/// How the remaining candidates are ordered once the policy's filters ran
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddressSelection {
+    /// Highest weighted score first (see `SelectionPolicy`)
+    #[default]
+    Weighted,
+    /// The order the kernel listed the addresses in
+    First,
+    /// Longest remaining preferred lifetime first; unreported lifetimes last
+    LongestLifetime,
+    /// Addresses within the policy's `prefix` first
+    PreferPrefix,
+    /// Numerically smallest address first, whatever order the kernel lists
+    /// them in, so the choice survives a reboot
+    Lowest,
+}
+
+/// Routing context an address must belong to in order to be published
+///
+/// On multi-WAN routers with `ip rule` policy routing, the address to publish
+/// is the one on the uplink a particular routing table uses. With a table (or
+/// an fwmark, resolved to a table through the routing rules), only addresses
+/// on interfaces that table has routes through are considered. Multipath
+/// routes are not followed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoutingContext {
+    /// Ignore routing tables (the default)
+    #[default]
+    Any,
+    /// Only interfaces with IPv6 routes in this table
+    Table(u32),
+    /// Only interfaces with IPv6 routes in the table this fwmark is routed by
+    Fwmark(u32),
+}
+
+/// Weighted scoring used to pick one address when several are available
+///
+/// Candidates ruled out by `preference` are dropped first. Each remaining
+/// candidate scores the sum of:
+/// - `stable_weight` if it is of the preferred kind (stable unless
+///   `preference` favors temporary addresses)
+/// - `lifetime_weight` per hour of remaining preferred lifetime (capped at one week)
+/// - `interface_weight * (n - i)` if its interface is at position `i` of the
+///   `n` entries in `interfaces`
+/// - `prefix_weight` if it lies within `prefix`
+///
+/// With `allowed_regions` set, candidates whose region (looked up in
+/// `regions`) is not listed are dropped as well, before scoring. `routing`
+/// is applied when addresses are detected, since it needs the routing tables.
+///
+/// The highest score wins; ties go to the address the kernel listed first.
+/// The default prefers stable addresses, then the longest preferred lifetime,
+/// in the spirit of RFC 6724 source address selection.
+///
+/// A `mode` other than `Weighted` ignores the weights: candidates of the
+/// preferred kind come first, then the mode decides the order among them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectionPolicy {
+    /// How candidates are ordered
+    pub mode: AddressSelection,
+    /// Which kind of address to publish
+    pub preference: AddressPreference,
+    /// Score for an address of the preferred kind
+    pub stable_weight: i64,
+    /// Score per hour of remaining preferred lifetime
+    pub lifetime_weight: i64,
+    /// Interfaces to prefer, most preferred first
+    pub interfaces: Vec<String>,
+    /// Score per rank in `interfaces`
+    pub interface_weight: i64,
+    /// Prefix to prefer, as (network, length)
+    pub prefix: Option<(Ipv6Addr, u8)>,
+    /// Score for an address within `prefix`
+    pub prefix_weight: i64,
+    /// Region names of address prefixes, as (network, length, region)
+    pub regions: Vec<(Ipv6Addr, u8, String)>,
+    /// Regions whose addresses may be published (empty = no region check)
+    pub allowed_regions: Vec<String>,
+    /// Routing table context addresses must belong to
+    pub routing: RoutingContext,
+}
+
+impl Default for SelectionPolicy {
+    fn default() -> Self {
+        Self {
+            mode: AddressSelection::Weighted,
+            preference: AddressPreference::Stable,
+            stable_weight: 1000,
+            lifetime_weight: 1,
+            interfaces: Vec::new(),
+            interface_weight: 100,
+            prefix: None,
+            prefix_weight: 100,
+            regions: Vec::new(),
+            allowed_regions: Vec::new(),
+            routing: RoutingContext::Any,
+        }
+    }
+}
+
+impl SelectionPolicy {
+    /// Scores a candidate address; higher is better
+    pub fn score(&self, candidate: &AddressCandidate) -> i64 {
+        let mut score = 0i64;
+        if candidate.temporary == self.preference.prefers_temporary() {
+            score = score.saturating_add(self.stable_weight);
+        }
+        if let Some(lifetime) = candidate.preferred_lifetime {
+            let hours = if lifetime == INFINITY_LIFE_TIME {
+                MAX_SCORED_LIFETIME_HOURS
+            } else {
+                (i64::from(lifetime) / 3600).min(MAX_SCORED_LIFETIME_HOURS)
+            };
+            score = score.saturating_add(self.lifetime_weight.saturating_mul(hours));
+        }
+        if let Some(name) = &candidate.interface {
+            if let Some(pos) = self.interfaces.iter().position(|i| i == name) {
+                let rank = (self.interfaces.len() - pos) as i64;
+                score = score.saturating_add(self.interface_weight.saturating_mul(rank));
+            }
+        }
+        if let Some((network, len)) = self.prefix {
+            if prefix_contains(network, len, candidate.addr) {
+                score = score.saturating_add(self.prefix_weight);
+            }
+        }
+        score
+    }
+
+    /// Returns the region of an address: that of the longest matching prefix
+    pub fn region_of(&self, addr: Ipv6Addr) -> Option<&str> {
+        self.regions
+            .iter()
+            .filter(|(network, len, _)| prefix_contains(*network, *len, addr))
+            .max_by_key(|(_, len, _)| *len)
+            .map(|(_, _, region)| region.as_str())
+    }
+
+    /// Returns whether an address passes the region allowlist
+    ///
+    /// Addresses outside every mapped prefix have no region and are only
+    /// allowed when no allowlist is configured.
+    pub fn region_allowed(&self, addr: Ipv6Addr) -> bool {
+        self.allowed_regions.is_empty()
+            || self
+                .region_of(addr)
+                .is_some_and(|r| self.allowed_regions.iter().any(|a| a == r))
+    }
+
+    /// Returns the allowed candidates from best to worst according to `mode`
+    ///
+    /// Ties keep the order the kernel listed them in.
+    pub fn rank<'a>(&self, candidates: &'a [AddressCandidate]) -> Vec<&'a AddressCandidate> {
+        let mut ranked: Vec<&AddressCandidate> = candidates
+            .iter()
+            .filter(|c| self.preference.allows(c) && self.region_allowed(c.addr))
+            .collect();
+        // Sorts are stable, so ties keep the kernel's order
+        let other_kind = |c: &AddressCandidate| c.temporary != self.preference.prefers_temporary();
+        match self.mode {
+            AddressSelection::Weighted => ranked.sort_by_key(|c| std::cmp::Reverse(self.score(c))),
+            AddressSelection::First => ranked.sort_by_key(|c| other_kind(c)),
+            // An unreported lifetime sorts last, INFINITY_LIFE_TIME first
+            AddressSelection::LongestLifetime => {
+                ranked.sort_by_key(|c| (other_kind(c), std::cmp::Reverse(c.preferred_lifetime)))
+            }
+            AddressSelection::PreferPrefix => ranked.sort_by_key(|c| {
+                let inside = self
+                    .prefix
+                    .is_some_and(|(network, len)| prefix_contains(network, len, c.addr));
+                (other_kind(c), !inside)
+            }),
+            AddressSelection::Lowest => ranked.sort_by_key(|c| (other_kind(c), c.addr)),
+        }
+        ranked
+    }
+
+    /// Picks the best allowed candidate, preferring the earliest on ties
+    pub fn select<'a>(&self, candidates: &'a [AddressCandidate]) -> Option<&'a AddressCandidate> {
+        self.rank(candidates).into_iter().next()
+    }
+}
+
 /// Trait for monitoring IPv6 address changes
 ///
 /// This trait defines the interface for both event-driven (netlink) and
@@ -200,11 +588,22 @@ pub trait Ipv6Monitor: Send + Sync {
 
 struct NetlinkImpl {
     fd: AsyncFd<OwnedFd>,
+    accepted_scopes: Vec<u8>,
+    include_temporary: bool,
+    /// Maximum number of messages read per wakeup
+    max_drain: usize,
+    /// Events parsed from the last batch, not yet returned
+    pending_events: VecDeque<NetlinkEvent>,
+    /// Tentative addresses awaiting DAD, with the number of re-dumps so far
+    pending_dad: Vec<(String, u32)>,
+    /// When to re-dump for the pending tentative addresses
+    dad_recheck_at: Option<tokio::time::Instant>,
 }
 
 impl NetlinkImpl {
-    fn new() -> Result<Self> {
-        let socket = NetlinkFd::new()?;
+    fn new(accepted_scopes: &[u8], include_temporary: bool, max_drain: usize) -> Result<Self> {
+        // A socket stays in the namespace it was created in
+        let socket = in_network_namespace(NetlinkFd::new)?;
 
         let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
         addr.nl_family = NETLINK_ROUTE as libc::sa_family_t;
@@ -242,7 +641,15 @@ impl NetlinkImpl {
         let owned_fd = unsafe { OwnedFd::from_raw_fd(socket.as_raw_fd()) };
         std::mem::forget(socket); // Prevent double-close
         let fd = AsyncFd::new(owned_fd).context("AsyncFd")?;
-        Ok(Self { fd })
+        Ok(Self {
+            fd,
+            accepted_scopes: accepted_scopes.to_vec(),
+            include_temporary,
+            max_drain: max_drain.max(1),
+            pending_events: VecDeque::new(),
+            pending_dad: Vec::new(),
+            dad_recheck_at: None,
+        })
     }
 
     fn recv_raw_io(&self) -> std::io::Result<Option<Vec<u8>>> {
@@ -269,45 +676,76 @@ impl NetlinkImpl {
         Ok(Some(buf))
     }
 
-    fn parse_message(data: &[u8]) -> Option<NetlinkEvent> {
-        let mut msg_offset = 0usize;
-
-        while msg_offset + NLMSG_HDRLEN <= data.len() {
-            // Safely extract nlmsg_len with bounds checking
-            let nlmsg_len_bytes = data.get(msg_offset..msg_offset + 4)?;
-            let nlmsg_len = u32::from_ne_bytes(nlmsg_len_bytes.try_into().ok()?) as usize;
-            if nlmsg_len < NLMSG_HDRLEN {
-                break;
-            }
-            if nlmsg_len == 0 {
-                break;
-            }
-
-            // Safely extract nlmsg_type with bounds checking
-            let nlmsg_type_bytes = data.get(msg_offset + 4..msg_offset + 6)?;
-            let nlmsg_type = u16::from_ne_bytes(nlmsg_type_bytes.try_into().ok()?);
+    fn parse_message(
+        data: &[u8],
+        accepted_scopes: &[u8],
+        include_temporary: bool,
+    ) -> Option<NetlinkEvent> {
+        walk_addr_messages(data, |msg_offset, nlmsg_len, nlmsg_type| {
+            extract_ipv6_from_ifaddrmsg(
+                data,
+                msg_offset,
+                nlmsg_len,
+                nlmsg_type,
+                accepted_scopes,
+                include_temporary,
+            )
+        })
+    }
 
-            if nlmsg_type == NLMSG_DONE || nlmsg_type == NLMSG_ERROR {
-                msg_offset += nlmsg_align(nlmsg_len);
-                continue;
+    /// Returns the first address in `data` that is still undergoing DAD
+    fn parse_tentative(
+        data: &[u8],
+        accepted_scopes: &[u8],
+        include_temporary: bool,
+    ) -> Option<String> {
+        walk_addr_messages(data, |msg_offset, nlmsg_len, nlmsg_type| {
+            if nlmsg_type != RTM_NEWADDR_VAL {
+                return None;
             }
+            extract_tentative_ipv6(
+                data,
+                msg_offset,
+                nlmsg_len,
+                accepted_scopes,
+                include_temporary,
+            )
+        })
+    }
 
-            if nlmsg_type != RTM_NEWADDR_VAL && nlmsg_type != RTM_DELADDR_VAL {
-                msg_offset += nlmsg_align(nlmsg_len);
-                continue;
+    /// Re-dumps addresses to see whether pending tentative ones passed DAD
+    ///
+    /// The kernel doesn't always send another RTM_NEWADDR once DAD completes,
+    /// so an address first seen as tentative would otherwise be missed.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `Ipv6Added` event for the first address that is now usable
+    fn recheck_tentative(&mut self) -> Option<NetlinkEvent> {
+        self.dad_recheck_at = None;
+        let candidates = match netlink_dump_ipv6(&self.accepted_scopes) {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::debug!("Re-dump after DAD failed: {:#}", e);
+                Vec::new()
             }
+        };
 
-            // Use the helper function to extract IPv6 address
-            if let Some(event) =
-                extract_ipv6_from_ifaddrmsg(data, msg_offset, nlmsg_len, nlmsg_type)
-            {
-                return Some(event);
+        let mut found = None;
+        self.pending_dad.retain_mut(|(ip, attempts)| {
+            if found.is_none() {
+                if let Some(c) = candidates.iter().find(|c| c.addr.to_string() == *ip) {
+                    found = Some(NetlinkEvent::Ipv6Added(ip.clone(), c.preferred_lifetime));
+                    return false;
+                }
             }
-
-            msg_offset += nlmsg_align(nlmsg_len);
+            *attempts += 1;
+            *attempts < DAD_RECHECK_ATTEMPTS
+        });
+        if !self.pending_dad.is_empty() {
+            self.dad_recheck_at = Some(tokio::time::Instant::now() + DAD_RECHECK_DELAY);
         }
-
-        None
+        found
     }
 }
 
@@ -315,36 +753,157 @@ impl NetlinkImpl {
 impl Ipv6Monitor for NetlinkImpl {
     async fn next_event(&mut self) -> NetlinkEvent {
         loop {
-            let mut guard = match self.fd.readable().await {
-                Ok(g) => g,
-                Err(_) => return NetlinkEvent::Unknown,
-            };
+            if let Some(event) = self.pending_events.pop_front() {
+                return event;
+            }
 
-            let data = match guard.try_io(|_| self.recv_raw_io()) {
-                Ok(Ok(Some(d))) => d,
-                Ok(Ok(None)) => continue,
-                Ok(Err(_)) => return NetlinkEvent::Unknown,
-                Err(_would_block) => continue,
+            let recheck_at = self.dad_recheck_at;
+            let readable = tokio::select! {
+                r = self.fd.readable() => Some(r),
+                _ = tokio::time::sleep_until(recheck_at.unwrap_or_else(tokio::time::Instant::now)),
+                    if recheck_at.is_some() => None,
+            };
+            let mut guard = match readable {
+                Some(Ok(g)) => g,
+                Some(Err(e)) => {
+                    self.reconnect(e).await;
+                    continue;
+                }
+                None => {
+                    if let Some(event) = self.recheck_tentative() {
+                        return event;
+                    }
+                    continue;
+                }
             };
 
-            if let Some(event) = Self::parse_message(&data) {
-                return event;
+            // Read everything queued (up to max_drain) so a burst costs one wakeup
+            let mut batch = Vec::new();
+            let mut error = None;
+            while batch.len() < self.max_drain {
+                match self.recv_raw_io() {
+                    Ok(Some(data)) => batch.push(data),
+                    Ok(None) => {
+                        guard.clear_ready();
+                        break;
+                    }
+                    Err(e) => {
+                        error = Some(e);
+                        break;
+                    }
+                }
+            }
+            drop(guard);
+
+            for data in &batch {
+                self.handle_message(data);
+            }
+            if let Some(e) = error {
+                self.reconnect(e).await;
             }
         }
     }
 }
 
+impl NetlinkImpl {
+    /// Replaces the event socket after a receive error and queues a `Resync`
+    ///
+    /// A socket that returned ENOBUFS dropped events; other errors may leave
+    /// it unusable. Either way a new socket is opened (retrying every
+    /// `NETLINK_RECONNECT_DELAY` until that succeeds), and the daemon re-reads
+    /// the addresses to catch changes made in the meantime.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - The error the old socket returned
+    async fn reconnect(&mut self, err: std::io::Error) {
+        if err.raw_os_error() == Some(libc::ENOBUFS) {
+            tracing::warn!(
+                "Netlink receive buffer overflowed and events were lost; reopening the socket"
+            );
+        } else {
+            tracing::warn!("Netlink socket failed ({}); reopening it", err);
+        }
+
+        loop {
+            match Self::new(
+                &self.accepted_scopes,
+                self.include_temporary,
+                self.max_drain,
+            ) {
+                Ok(socket) => {
+                    self.fd = socket.fd;
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Reopening the netlink socket failed ({:#}); retrying in {}s",
+                        e,
+                        NETLINK_RECONNECT_DELAY.as_secs()
+                    );
+                    tokio::time::sleep(NETLINK_RECONNECT_DELAY).await;
+                }
+            }
+        }
+        queue_event(&mut self.pending_events, NetlinkEvent::Resync);
+    }
+
+    /// Parses one received buffer, queueing its event and tracking tentative addresses
+    ///
+    /// An event identical to the last queued one is dropped, so a burst of
+    /// repeated notifications for the same address syncs only once.
+    fn handle_message(&mut self, data: &[u8]) {
+        if let Some(ip) = Self::parse_tentative(data, &self.accepted_scopes, self.include_temporary)
+        {
+            if !self.pending_dad.iter().any(|(pending, _)| *pending == ip) {
+                tracing::debug!("{} is tentative; re-checking after DAD", ip);
+                self.pending_dad.push((ip, 0));
+            }
+            self.dad_recheck_at
+                .get_or_insert_with(|| tokio::time::Instant::now() + DAD_RECHECK_DELAY);
+        }
+
+        if let Some(event) =
+            Self::parse_message(data, &self.accepted_scopes, self.include_temporary)
+        {
+            if let NetlinkEvent::Ipv6Added(ip, _) = &event {
+                self.pending_dad.retain(|(pending, _)| pending != ip);
+            }
+            queue_event(&mut self.pending_events, event);
+        }
+    }
+}
+
+/// Appends an event to the queue unless it repeats the last one
+fn queue_event(queue: &mut VecDeque<NetlinkEvent>, event: NetlinkEvent) {
+    if queue.back() != Some(&event) {
+        queue.push_back(event);
+    }
+}
+
 struct PollingImpl {
     interval: Duration,
-    allow_loopback: bool,
+    jitter: f64,
+    filter: Ipv6Filter,
+    accepted_scopes: Vec<u8>,
+    selection: SelectionPolicy,
     last_ip: Option<String>,
 }
 
 impl PollingImpl {
-    fn new(interval: Duration, allow_loopback: bool) -> Self {
+    fn new(
+        interval: Duration,
+        jitter: f64,
+        filter: Ipv6Filter,
+        accepted_scopes: &[u8],
+        selection: SelectionPolicy,
+    ) -> Self {
         Self {
             interval,
-            allow_loopback,
+            jitter,
+            filter,
+            accepted_scopes: accepted_scopes.to_vec(),
+            selection,
             last_ip: None,
         }
     }
@@ -354,14 +913,20 @@ impl PollingImpl {
 impl Ipv6Monitor for PollingImpl {
     async fn next_event(&mut self) -> NetlinkEvent {
         loop {
-            tokio::time::sleep(self.interval).await;
+            let sleep = if self.jitter > 0.0 {
+                jittered_interval(self.interval, self.jitter, random_unit())
+            } else {
+                self.interval
+            };
+            tokio::time::sleep(sleep).await;
 
-            let current_ip = detect_global_ipv6(self.allow_loopback);
+            let current_ip =
+                detect_global_ipv6(&self.filter, &self.accepted_scopes, &self.selection);
 
             match (&self.last_ip, &current_ip) {
                 (None, Some(ip)) => {
                     self.last_ip = Some(ip.clone());
-                    return NetlinkEvent::Ipv6Added(ip.clone());
+                    return NetlinkEvent::Ipv6Added(ip.clone(), None);
                 }
                 (Some(_), None) => {
                     self.last_ip = None;
@@ -369,7 +934,7 @@ impl Ipv6Monitor for PollingImpl {
                 }
                 (Some(old), Some(new)) if old != new => {
                     self.last_ip = Some(new.clone());
-                    return NetlinkEvent::Ipv6Added(new.clone());
+                    return NetlinkEvent::Ipv6Added(new.clone(), None);
                 }
                 (Some(old), Some(ip)) if ip == old => {
                     self.last_ip = Some(ip.clone());
@@ -399,6 +964,11 @@ impl NetlinkSocket {
     /// # Arguments
     ///
     /// * `poll_interval` - Optional polling interval. Defaults to 60 seconds if None.
+    /// * `poll_jitter` - Random variation of the polling interval, as a fraction (0 = none)
+    /// * `max_drain` - Maximum number of netlink messages read per wakeup
+    /// * `filter` - Reserved ranges that may be published when polling
+    /// * `accepted_scopes` - Address scopes (`RT_SCOPE_*`) that may be published
+    /// * `selection` - How addresses are ranked when polling
     ///
     /// # Returns
     ///
@@ -408,10 +978,21 @@ impl NetlinkSocket {
     ///
     /// - If netlink is available: Uses event-driven monitoring (zero CPU when idle)
     /// - If netlink is unavailable: Falls back to polling with the specified interval
-    pub fn new(poll_interval: Option<Duration>, allow_loopback: bool) -> Result<Self> {
+    pub fn new(
+        poll_interval: Option<Duration>,
+        poll_jitter: f64,
+        max_drain: usize,
+        filter: &Ipv6Filter,
+        accepted_scopes: &[u8],
+        selection: &SelectionPolicy,
+    ) -> Result<Self> {
         let interval = poll_interval.unwrap_or(POLL_INTERVAL_DEFAULT);
 
-        match NetlinkImpl::new() {
+        match NetlinkImpl::new(
+            accepted_scopes,
+            selection.preference.prefers_temporary(),
+            max_drain,
+        ) {
             Ok(netlink) => {
                 tracing::info!("Using event-driven netlink socket");
                 Ok(Self {
@@ -423,13 +1004,33 @@ impl NetlinkSocket {
                 tracing::warn!("Netlink socket failed ({:#}), falling back to polling", e);
                 tracing::info!("Polling interval: {} seconds", interval.as_secs());
                 Ok(Self {
-                    monitor: Box::new(PollingImpl::new(interval, allow_loopback)),
+                    monitor: Box::new(PollingImpl::new(
+                        interval,
+                        poll_jitter,
+                        filter.clone(),
+                        accepted_scopes,
+                        selection.clone(),
+                    )),
                     is_event_driven: false,
                 })
             }
         }
     }
 
+    /// Wraps another address monitor, e.g. an external address service
+    ///
+    /// The socket reports itself as not event-driven.
+    ///
+    /// # Arguments
+    ///
+    /// * `monitor` - The monitor events are taken from
+    pub fn from_monitor(monitor: Box<dyn Ipv6Monitor>) -> Self {
+        Self {
+            monitor,
+            is_event_driven: false,
+        }
+    }
+
     /// Receives the next IPv6 address change event
     ///
     /// This method is async and will block until a new event is detected.
@@ -454,43 +1055,299 @@ impl NetlinkSocket {
 
 /// Detects the current global IPv6 address on the system
 ///
-/// This function queries the system for global IPv6 addresses, preferring
-/// stable addresses over temporary ones.
+/// This function queries the system for global IPv6 addresses and returns the
+/// one ranked highest by the selection policy.
+///
+/// # Arguments
+///
+/// * `filter` - Reserved ranges that may be returned
+/// * `accepted_scopes` - Address scopes (`RT_SCOPE_*`) that may be returned
+/// * `policy` - How candidate addresses are ranked
 ///
 /// # Returns
 ///
-/// /// Returns `Some(String)` containing the IPv6 address if found, `None` otherwise
+/// Returns `Some(String)` containing the IPv6 address if found, `None` otherwise
 ///
 /// # Behavior
 ///
-/// - Returns stable IPv6 addresses if available
-/// - Falls back to temporary addresses if no stable address exists
+/// - With the default policy, stable addresses win over temporary ones
 /// - Returns `None` if no global IPv6 address is found or an error occurs
 #[must_use]
-pub fn detect_global_ipv6(allow_loopback: bool) -> Option<String> {
-    match netlink_dump_ipv6() {
-        Ok((stable, temporary)) => {
-            // Validate the IPv6 address format
-            stable
-                .and_then(|ip| {
-                    if is_valid_ipv6(&ip, allow_loopback) {
-                        Some(ip)
-                    } else {
-                        None
-                    }
-                })
-                .or_else(|| {
-                    temporary.and_then(|ip| {
-                        if is_valid_ipv6(&ip, allow_loopback) {
-                            Some(ip)
-                        } else {
-                            None
-                        }
-                    })
-                })
+pub fn detect_global_ipv6(
+    filter: &Ipv6Filter,
+    accepted_scopes: &[u8],
+    policy: &SelectionPolicy,
+) -> Option<String> {
+    let candidates = dump_candidates(filter, accepted_scopes, policy.routing).ok()?;
+    policy.select(&candidates).map(|c| c.addr.to_string())
+}
+
+/// Detects every publishable global IPv6 address on the system
+///
+/// The `limit` best addresses by the selection policy are kept and returned in
+/// ascending address order, so a given address keeps its position while the
+/// set of addresses stays the same.
+///
+/// # Arguments
+///
+/// * `filter` - Reserved ranges that may be returned
+/// * `accepted_scopes` - Address scopes (`RT_SCOPE_*`) that may be returned
+/// * `policy` - How candidate addresses are ranked and filtered
+/// * `limit` - Maximum number of addresses returned
+///
+/// # Returns
+///
+/// Returns the addresses, or an empty list if there are none or an error occurs
+#[must_use]
+pub fn detect_all_global_ipv6(
+    filter: &Ipv6Filter,
+    accepted_scopes: &[u8],
+    policy: &SelectionPolicy,
+    limit: usize,
+) -> Vec<String> {
+    let candidates = dump_candidates(filter, accepted_scopes, policy.routing).unwrap_or_default();
+    top_addresses(policy, &candidates, limit)
+        .iter()
+        .map(Ipv6Addr::to_string)
+        .collect()
+}
+
+/// Dumps the publishable IPv6 addresses within the routing context
+///
+/// # Arguments
+///
+/// * `filter` - Reserved ranges that may be returned
+/// * `accepted_scopes` - Address scopes (`RT_SCOPE_*`) that may be returned
+/// * `routing` - Routing table context the addresses must belong to
+///
+/// # Returns
+///
+/// Returns the candidates in kernel order, or an error if a netlink dump fails
+fn dump_candidates(
+    filter: &Ipv6Filter,
+    accepted_scopes: &[u8],
+    routing: RoutingContext,
+) -> Result<Vec<AddressCandidate>> {
+    let mut candidates: Vec<AddressCandidate> = netlink_dump_ipv6(accepted_scopes)?
+        .into_iter()
+        .filter(|c| filter.allows(&c.addr))
+        .collect();
+
+    let table = match routing {
+        RoutingContext::Any => return Ok(candidates),
+        RoutingContext::Table(table) => table,
+        RoutingContext::Fwmark(mark) => match netlink_rule_table(mark)? {
+            Some(table) => table,
+            None => {
+                tracing::debug!("No routing rule for fwmark {:#x}", mark);
+                return Ok(Vec::new());
+            }
+        },
+    };
+    let interfaces: Vec<String> = netlink_route_interfaces(table)?
+        .into_iter()
+        .filter_map(interface_name)
+        .collect();
+    candidates.retain(|c| {
+        c.interface
+            .as_ref()
+            .is_some_and(|name| interfaces.contains(name))
+    });
+    Ok(candidates)
+}
+
+/// Returns the `limit` best distinct addresses, in ascending order
+fn top_addresses(
+    policy: &SelectionPolicy,
+    candidates: &[AddressCandidate],
+    limit: usize,
+) -> Vec<Ipv6Addr> {
+    let mut addrs: Vec<Ipv6Addr> = Vec::new();
+    for candidate in policy.rank(candidates) {
+        if addrs.len() == limit {
+            break;
+        }
+        if !addrs.contains(&candidate.addr) {
+            addrs.push(candidate.addr);
         }
-        Err(_) => None,
     }
+    addrs.sort();
+    addrs
+}
+
+/// Detects the current global IPv4 address on the system
+///
+/// # Arguments
+///
+/// * `allow_private` - Whether RFC 1918 and carrier-grade NAT addresses are accepted
+///
+/// # Returns
+///
+/// Returns `Some(String)` with the first publishable IPv4 address, `None` if
+/// there is none or an error occurs
+#[must_use]
+pub fn detect_global_ipv4(allow_private: bool) -> Option<String> {
+    netlink_dump_ipv4()
+        .ok()?
+        .into_iter()
+        .map(|addr| addr.to_string())
+        .find(|ip| is_valid_ipv4(ip, allow_private))
+}
+
+/// Applies jitter to a polling interval
+///
+/// # Arguments
+///
+/// * `interval` - The base polling interval
+/// * `jitter` - Maximum variation as a fraction of `interval`
+/// * `unit` - A value in `[0, 1)` choosing where in the range the result falls
+///
+/// # Returns
+///
+/// Returns a duration in `interval ± interval * jitter`; exactly `interval`
+/// when `jitter` is 0
+pub fn jittered_interval(interval: Duration, jitter: f64, unit: f64) -> Duration {
+    let offset = jitter * (2.0 * unit - 1.0);
+    interval.mul_f64((1.0 + offset).max(0.0))
+}
+
+/// Returns the shortest and longest interval [`jittered_interval`] can yield
+///
+/// # Arguments
+///
+/// * `interval` - The base polling interval
+/// * `jitter` - Maximum variation as a fraction of `interval`
+pub fn jitter_bounds(interval: Duration, jitter: f64) -> (Duration, Duration) {
+    (
+        jittered_interval(interval, jitter, 0.0),
+        jittered_interval(interval, jitter, 1.0),
+    )
+}
+
+/// Returns a random value in `[0, 1)`
+///
+/// Uses the randomly seeded std hasher, which is plenty for spreading polls
+/// and startup syncs, and avoids pulling in an RNG crate.
+pub fn random_unit() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Parses an address scope name into its `RT_SCOPE_*` value
+///
+/// Only scopes that can meaningfully be published in DNS are accepted:
+/// `universe` (alias `global`) and `site`.
+///
+/// # Arguments
+///
+/// * `name` - The scope name
+///
+/// # Returns
+///
+/// Returns a `Result` containing the scope value or an error if the name is
+/// unknown or not publishable
+pub fn parse_scope(name: &str) -> Result<u8> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "universe" | "global" => Ok(RT_SCOPE_UNIVERSE),
+        "site" => Ok(RT_SCOPE_SITE),
+        "link" | "host" | "nowhere" => Err(anyhow::anyhow!(
+            "Scope '{}' can't be published in DNS. Use: universe|site",
+            name
+        )),
+        _ => Err(anyhow::anyhow!(
+            "Invalid scope: '{}'. Use: universe|site",
+            name
+        )),
+    }
+}
+
+/// Parses an address preference name
+///
+/// # Arguments
+///
+/// * `name` - One of `stable`, `temporary`, `stable_only`, `temporary_only`
+///
+/// # Returns
+///
+/// Returns a `Result` containing the preference or an error if the name is unknown
+pub fn parse_address_preference(name: &str) -> Result<AddressPreference> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "stable" => Ok(AddressPreference::Stable),
+        "temporary" => Ok(AddressPreference::Temporary),
+        "stable_only" => Ok(AddressPreference::StableOnly),
+        "temporary_only" => Ok(AddressPreference::TemporaryOnly),
+        _ => Err(anyhow::anyhow!(
+            "Invalid address_preference: '{}'. Use: stable|temporary|stable_only|temporary_only",
+            name
+        )),
+    }
+}
+
+/// Parses an address selection mode name
+///
+/// # Arguments
+///
+/// * `name` - One of `weighted`, `first`, `longest_lifetime`, `prefer_prefix`, `lowest`
+///
+/// # Returns
+///
+/// Returns a `Result` containing the mode or an error if the name is unknown
+pub fn parse_address_selection(name: &str) -> Result<AddressSelection> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "weighted" => Ok(AddressSelection::Weighted),
+        "first" => Ok(AddressSelection::First),
+        "longest_lifetime" => Ok(AddressSelection::LongestLifetime),
+        "prefer_prefix" => Ok(AddressSelection::PreferPrefix),
+        "lowest" => Ok(AddressSelection::Lowest),
+        _ => Err(anyhow::anyhow!(
+            "Invalid address_selection: '{}'. Use: weighted|first|longest_lifetime|prefer_prefix|lowest",
+            name
+        )),
+    }
+}
+
+/// Parses an IPv6 prefix in CIDR notation (e.g. "2001:db8::/48")
+///
+/// # Arguments
+///
+/// * `s` - The prefix string
+///
+/// # Returns
+///
+/// Returns a `Result` containing the (network, length) pair or an error if the
+/// string is not a valid IPv6 prefix
+pub fn parse_prefix(s: &str) -> Result<(Ipv6Addr, u8)> {
+    let (addr, len) = s
+        .trim()
+        .split_once('/')
+        .ok_or_else(|| anyhow::anyhow!("Invalid prefix '{}': expected address/length", s))?;
+    let addr: Ipv6Addr = addr
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid prefix '{}': bad IPv6 address", s))?;
+    let len: u8 = len
+        .parse()
+        .ok()
+        .filter(|l| *l <= 128)
+        .ok_or_else(|| anyhow::anyhow!("Invalid prefix '{}': length must be 0-128", s))?;
+    Ok((addr, len))
+}
+
+/// Returns whether `addr` lies within the prefix `network/len`
+pub fn prefix_contains(network: Ipv6Addr, len: u8, addr: Ipv6Addr) -> bool {
+    let mask = u128::MAX.checked_shl(128 - u32::from(len)).unwrap_or(0);
+    u128::from(network) & mask == u128::from(addr) & mask
+}
+
+/// Returns the name of the interface with the given index, if it exists
+fn interface_name(index: u32) -> Option<String> {
+    let mut buf = [0u8; libc::IF_NAMESIZE];
+    let ptr = unsafe { libc::if_indextoname(index, buf.as_mut_ptr() as *mut libc::c_char) };
+    if ptr.is_null() {
+        return None;
+    }
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    Some(String::from_utf8_lossy(&buf[..end]).into_owned())
 }
 
 fn nlmsg_align(len: usize) -> usize {
@@ -516,30 +1373,83 @@ fn rta_align(len: usize) -> usize {
 ///
 /// Returns `Some(String)` containing the IPv6 address if found, `None` otherwise
 fn parse_rta_ipv6_address(data: &[u8], msg_offset: usize, msg_end: usize) -> Option<String> {
-    let mut rta_offset = msg_offset + NLMSG_HDRLEN + IFADDRMSG_LEN;
+    let rta_offset = msg_offset + NLMSG_HDRLEN + IFADDRMSG_LEN;
+    walk_attributes(data, rta_offset, msg_end, |rta_type, payload| {
+        // Check for IFA_ADDRESS or IFA_LOCAL attribute with correct payload size
+        if rta_type != IFA_ADDRESS_VAL && rta_type != IFA_LOCAL_VAL {
+            return None;
+        }
+        let addr: [u8; IPV6_ADDR_BYTES] = payload.try_into().ok()?;
+        Some(std::net::Ipv6Addr::from(addr).to_string())
+    })
+}
+
+/// Parses RTA attributes to extract the preferred lifetime from IFA_CACHEINFO
+///
+/// # Arguments
+///
+/// * `data` - The raw netlink message data
+/// * `msg_offset` - Offset to the start of the netlink message
+/// * `msg_end` - End offset of the netlink message
+///
+/// # Returns
+///
+/// Returns `Some(u32)` with the preferred lifetime in seconds if the attribute
+/// is present, `None` otherwise
+fn parse_rta_preferred_lifetime(data: &[u8], msg_offset: usize, msg_end: usize) -> Option<u32> {
+    let rta_offset = msg_offset + NLMSG_HDRLEN + IFADDRMSG_LEN;
+    // struct ifa_cacheinfo starts with the u32 preferred lifetime
+    parse_rta_u32(data, rta_offset, msg_end, IFA_CACHEINFO_VAL)
+}
+
+/// Walks the attributes of a netlink message
+///
+/// Calls `f` with the type and payload of each attribute until it returns
+/// `Some`. Stops at a malformed attribute, and with a warning after
+/// `MAX_ATTRIBUTES_PER_MESSAGE` attributes.
+///
+/// # Arguments
+///
+/// * `data` - The raw netlink message data
+/// * `rta_offset` - Offset of the first attribute
+/// * `msg_end` - End offset of the netlink message
+/// * `f` - Callback taking (rta_type, payload)
+///
+/// # Returns
+///
+/// Returns the first `Some` produced by `f`, or `None`
+fn walk_attributes<T>(
+    data: &[u8],
+    mut rta_offset: usize,
+    msg_end: usize,
+    mut f: impl FnMut(u16, &[u8]) -> Option<T>,
+) -> Option<T> {
+    let msg_end = msg_end.min(data.len());
+    let mut count = 0usize;
     while rta_offset + RTA_HEADER_SIZE <= msg_end {
+        if count == MAX_ATTRIBUTES_PER_MESSAGE {
+            tracing::warn!(
+                "Netlink message has more than {} attributes; ignoring the rest",
+                MAX_ATTRIBUTES_PER_MESSAGE
+            );
+            break;
+        }
+        count += 1;
+
         let rta_len = u16::from_ne_bytes([data[rta_offset], data[rta_offset + 1]]) as usize;
         if rta_len < RTA_HEADER_SIZE {
             break;
         }
         let rta_type = u16::from_ne_bytes([data[rta_offset + 2], data[rta_offset + 3]]);
 
-        let payload_len = rta_len - RTA_HEADER_SIZE;
         let payload_offset = rta_offset + RTA_HEADER_SIZE;
-        if payload_offset + payload_len > msg_end {
+        let payload_end = rta_offset + rta_len;
+        if payload_end > msg_end {
             break;
         }
 
-        // Check for IFA_ADDRESS or IFA_LOCAL attribute with correct payload size
-        if (rta_type == IFA_ADDRESS_VAL || rta_type == IFA_LOCAL_VAL)
-            && payload_len == IPV6_ADDR_BYTES
-        {
-            let addr: [u8; IPV6_ADDR_BYTES] =
-                match data[payload_offset..payload_offset + IPV6_ADDR_BYTES].try_into() {
-                    Ok(a) => a,
-                    Err(_) => return None,
-                };
-            return Some(std::net::Ipv6Addr::from(addr).to_string());
+        if let Some(result) = f(rta_type, &data[payload_offset..payload_end]) {
+            return Some(result);
         }
 
         rta_offset += rta_align(rta_len);
@@ -548,10 +1458,108 @@ fn parse_rta_ipv6_address(data: &[u8], msg_offset: usize, msg_end: usize) -> Opt
     None
 }
 
+/// Walks the interface address messages in a netlink buffer
+///
+/// Calls `f` with the offset, length and type of each RTM_NEWADDR/RTM_DELADDR
+/// message until it returns `Some`.
+///
+/// # Arguments
+///
+/// * `data` - The raw netlink message data
+/// * `f` - Callback taking (msg_offset, nlmsg_len, nlmsg_type)
+///
+/// # Returns
+///
+/// Returns the first `Some` produced by `f`, or `None`
+fn walk_addr_messages<T>(
+    data: &[u8],
+    mut f: impl FnMut(usize, usize, u16) -> Option<T>,
+) -> Option<T> {
+    let mut msg_offset = 0usize;
+    let mut count = 0usize;
+
+    while msg_offset + NLMSG_HDRLEN <= data.len() {
+        if count == MAX_MESSAGES_PER_BUFFER {
+            tracing::warn!(
+                "Netlink buffer has more than {} messages; ignoring the rest",
+                MAX_MESSAGES_PER_BUFFER
+            );
+            break;
+        }
+        count += 1;
+
+        // Safely extract nlmsg_len with bounds checking
+        let nlmsg_len_bytes = data.get(msg_offset..msg_offset + 4)?;
+        let nlmsg_len = u32::from_ne_bytes(nlmsg_len_bytes.try_into().ok()?) as usize;
+        if nlmsg_len < NLMSG_HDRLEN {
+            break;
+        }
+        if nlmsg_len == 0 {
+            break;
+        }
+
+        // Safely extract nlmsg_type with bounds checking
+        let nlmsg_type_bytes = data.get(msg_offset + 4..msg_offset + 6)?;
+        let nlmsg_type = u16::from_ne_bytes(nlmsg_type_bytes.try_into().ok()?);
+
+        if nlmsg_type == RTM_NEWADDR_VAL || nlmsg_type == RTM_DELADDR_VAL {
+            if let Some(result) = f(msg_offset, nlmsg_len, nlmsg_type) {
+                return Some(result);
+            }
+        }
+
+        msg_offset += nlmsg_align(nlmsg_len);
+    }
+
+    None
+}
+
+/// Extracts an address that is still undergoing duplicate address detection
+///
+/// # Arguments
+///
+/// * `data` - The raw netlink message data
+/// * `msg_offset` - Offset to the start of the netlink message
+/// * `nlmsg_len` - Length of the netlink message
+/// * `accepted_scopes` - Address scopes (`RT_SCOPE_*`) to accept
+/// * `include_temporary` - Whether temporary addresses are of interest
+///
+/// # Returns
+///
+/// Returns `Some(String)` if the message carries a tentative address that
+/// would otherwise be accepted, `None` otherwise
+fn extract_tentative_ipv6(
+    data: &[u8],
+    msg_offset: usize,
+    nlmsg_len: usize,
+    accepted_scopes: &[u8],
+    include_temporary: bool,
+) -> Option<String> {
+    let msg_end = (msg_offset + nlmsg_len).min(data.len());
+    if msg_end < msg_offset + NLMSG_HDRLEN + IFADDRMSG_LEN {
+        return None;
+    }
+
+    let ifa_offset = msg_offset + NLMSG_HDRLEN;
+    let ifa_flags = data[ifa_offset + 2] as u32;
+    if data[ifa_offset] != AF_INET6 || !accepted_scopes.contains(&data[ifa_offset + 3]) {
+        return None;
+    }
+    if ifa_flags & IFA_F_TENTATIVE == 0 || ifa_flags & IFA_F_DADFAILED != 0 {
+        return None;
+    }
+    if !include_temporary && ifa_flags & IFA_F_TEMPORARY != 0 {
+        return None;
+    }
+
+    parse_rta_ipv6_address(data, msg_offset, msg_end)
+}
+
 /// Extracts an IPv6 address from a netlink interface address message
 ///
 /// This helper function parses the netlink message to extract IPv6 addresses,
-/// filtering out temporary, tentative, deprecated, and DAD-failed addresses.
+/// filtering out tentative, deprecated, and DAD-failed addresses, and
+/// temporary ones unless `include_temporary` is set.
 ///
 /// # Arguments
 ///
@@ -559,6 +1567,8 @@ fn parse_rta_ipv6_address(data: &[u8], msg_offset: usize, msg_end: usize) -> Opt
 /// * `msg_offset` - Offset to the start of the netlink message
 /// * `nlmsg_len` - Length of the netlink message
 /// * `nlmsg_type` - Type of the netlink message (RTM_NEWADDR or RTM_DELADDR)
+/// * `accepted_scopes` - Address scopes (`RT_SCOPE_*`) to accept
+/// * `include_temporary` - Whether temporary addresses produce events
 ///
 /// # Returns
 ///
@@ -568,6 +1578,8 @@ fn extract_ipv6_from_ifaddrmsg(
     msg_offset: usize,
     nlmsg_len: usize,
     nlmsg_type: u16,
+    accepted_scopes: &[u8],
+    include_temporary: bool,
 ) -> Option<NetlinkEvent> {
     let msg_end = (msg_offset + nlmsg_len).min(data.len());
     if msg_end < msg_offset + NLMSG_HDRLEN + IFADDRMSG_LEN {
@@ -579,14 +1591,14 @@ fn extract_ipv6_from_ifaddrmsg(
     let ifa_flags = data[ifa_offset + 2];
     let ifa_scope = data[ifa_offset + 3];
 
-    // Filter: must be IPv6, global scope, and not tentative/deprecated/DAD-failed
+    // Filter: must be IPv6, an accepted scope, and not tentative/deprecated/DAD-failed
     if ifa_family != AF_INET6 {
         return None;
     }
-    if ifa_scope != RT_SCOPE_UNIVERSE {
+    if !accepted_scopes.contains(&ifa_scope) {
         return None;
     }
-    if (ifa_flags as u32) & IFA_F_TEMPORARY != 0 {
+    if !include_temporary && (ifa_flags as u32) & IFA_F_TEMPORARY != 0 {
         return None;
     }
     if (ifa_flags as u32) & IFA_F_TENTATIVE != 0 {
@@ -602,7 +1614,9 @@ fn extract_ipv6_from_ifaddrmsg(
     // Parse RTA attributes to find the IPv6 address
     if let Some(ip) = parse_rta_ipv6_address(data, msg_offset, msg_end) {
         let event = match nlmsg_type {
-            RTM_NEWADDR_VAL => NetlinkEvent::Ipv6Added(ip),
+            RTM_NEWADDR_VAL => {
+                NetlinkEvent::Ipv6Added(ip, parse_rta_preferred_lifetime(data, msg_offset, msg_end))
+            }
             RTM_DELADDR_VAL => NetlinkEvent::Ipv6Removed,
             _ => NetlinkEvent::Unknown,
         };
@@ -612,26 +1626,28 @@ fn extract_ipv6_from_ifaddrmsg(
     None
 }
 
-/// Extracts IPv6 addresses from a netlink interface address message for dump operations
+/// Extracts an IPv6 address candidate from a netlink interface address message
 ///
-/// This helper function is similar to `extract_ipv6_from_ifaddrmsg` but returns both
-/// stable and temporary addresses separately, which is needed for dump operations.
+/// This helper function is similar to `extract_ipv6_from_ifaddrmsg` but keeps
+/// temporary addresses and returns the details needed to rank them, which is
+/// needed for dump operations.
 ///
 /// # Arguments
 ///
 /// * `data` - The raw netlink message data
 /// * `msg_offset` - Offset to the start of the netlink message
 /// * `nlmsg_len` - Length of the netlink message
+/// * `accepted_scopes` - Address scopes (`RT_SCOPE_*`) to accept
 ///
 /// # Returns
 ///
-/// Returns `Some((stable, temporary))` where each is an Option<String> containing
-/// the IPv6 address, or `None` if no valid address is found
-fn extract_ipv6_addresses_for_dump(
+/// Returns `Some(AddressCandidate)` if a valid address is found, `None` otherwise
+fn extract_ipv6_candidate_for_dump(
     data: &[u8],
     msg_offset: usize,
     nlmsg_len: usize,
-) -> Option<(Option<String>, Option<String>)> {
+    accepted_scopes: &[u8],
+) -> Option<AddressCandidate> {
     let msg_end = (msg_offset + nlmsg_len).min(data.len());
     if msg_end < msg_offset + NLMSG_HDRLEN + IFADDRMSG_LEN {
         return None;
@@ -641,13 +1657,14 @@ fn extract_ipv6_addresses_for_dump(
     let ifa_family = data[ifa_offset];
     let ifa_flags = data[ifa_offset + 2];
     let ifa_scope = data[ifa_offset + 3];
+    let ifa_index = u32::from_ne_bytes(data[ifa_offset + 4..ifa_offset + 8].try_into().ok()?);
 
-    // Filter: must be IPv6, global scope, and not tentative/deprecated/DAD-failed
+    // Filter: must be IPv6, an accepted scope, and not tentative/deprecated/DAD-failed
     // Note: Temporary addresses are NOT filtered out here (unlike in extract_ipv6_from_ifaddrmsg)
     if ifa_family != AF_INET6 {
         return None;
     }
-    if ifa_scope != RT_SCOPE_UNIVERSE {
+    if !accepted_scopes.contains(&ifa_scope) {
         return None;
     }
     if (ifa_flags as u32) & IFA_F_TENTATIVE != 0 {
@@ -660,21 +1677,286 @@ fn extract_ipv6_addresses_for_dump(
         return None;
     }
 
-    let is_temp = (ifa_flags as u32 & IFA_F_TEMPORARY) != 0;
-
     // Parse RTA attributes to find the IPv6 address
-    if let Some(ip) = parse_rta_ipv6_address(data, msg_offset, msg_end) {
-        if is_temp {
-            return Some((None, Some(ip)));
-        } else {
-            return Some((Some(ip), None));
+    let ip = parse_rta_ipv6_address(data, msg_offset, msg_end)?;
+    Some(AddressCandidate {
+        addr: ip.parse().ok()?,
+        temporary: (ifa_flags as u32 & IFA_F_TEMPORARY) != 0,
+        interface: interface_name(ifa_index),
+        preferred_lifetime: parse_rta_preferred_lifetime(data, msg_offset, msg_end),
+    })
+}
+
+/// Extracts a global IPv4 address from a netlink interface address message
+///
+/// Uses IFA_LOCAL, which holds the interface's own address (IFA_ADDRESS is the
+/// peer address on point-to-point links such as tunnels).
+///
+/// # Arguments
+///
+/// * `data` - The raw netlink message data
+/// * `msg_offset` - Offset to the start of the netlink message
+/// * `nlmsg_len` - Length of the netlink message
+///
+/// # Returns
+///
+/// Returns `Some(Ipv4Addr)` for a universe-scope IPv4 address, `None` otherwise
+fn extract_ipv4_for_dump(data: &[u8], msg_offset: usize, nlmsg_len: usize) -> Option<Ipv4Addr> {
+    let msg_end = (msg_offset + nlmsg_len).min(data.len());
+    if msg_end < msg_offset + NLMSG_HDRLEN + IFADDRMSG_LEN {
+        return None;
+    }
+
+    let ifa_offset = msg_offset + NLMSG_HDRLEN;
+    if data[ifa_offset] != AF_INET || data[ifa_offset + 3] != RT_SCOPE_UNIVERSE {
+        return None;
+    }
+
+    let rta_offset = msg_offset + NLMSG_HDRLEN + IFADDRMSG_LEN;
+    walk_attributes(data, rta_offset, msg_end, |rta_type, payload| {
+        if rta_type != IFA_LOCAL_VAL {
+            return None;
+        }
+        let addr: [u8; IPV4_ADDR_BYTES] = payload.try_into().ok()?;
+        Some(Ipv4Addr::from(addr))
+    })
+}
+
+/// Returns the u32 payload of the first attribute of the given type
+///
+/// # Arguments
+///
+/// * `data` - The buffer holding the message
+/// * `rta_offset` - Offset of the first attribute
+/// * `msg_end` - End of the message
+/// * `wanted` - The attribute type to look for
+fn parse_rta_u32(data: &[u8], rta_offset: usize, msg_end: usize, wanted: u16) -> Option<u32> {
+    walk_attributes(data, rta_offset, msg_end, |rta_type, payload| {
+        if rta_type != wanted {
+            return None;
         }
+        let bytes: [u8; 4] = payload.get(..4)?.try_into().ok()?;
+        Some(u32::from_ne_bytes(bytes))
+    })
+}
+
+/// Extracts the output interface of an RTM_NEWROUTE message in `table`
+///
+/// The table comes from `RTA_TABLE` when present, since `rtm_table` only
+/// holds table IDs below 256.
+fn extract_route_oif(data: &[u8], msg_offset: usize, nlmsg_len: usize, table: u32) -> Option<u32> {
+    let msg_end = (msg_offset + nlmsg_len).min(data.len());
+    if msg_end < msg_offset + NLMSG_HDRLEN + RTMSG_LEN {
+        return None;
+    }
+    let rtm_offset = msg_offset + NLMSG_HDRLEN;
+    let attrs = rtm_offset + RTMSG_LEN;
+    let route_table = parse_rta_u32(data, attrs, msg_end, RTA_TABLE_VAL)
+        .unwrap_or(u32::from(data[rtm_offset + 4]));
+    if route_table != table {
+        return None;
     }
+    parse_rta_u32(data, attrs, msg_end, RTA_OIF_VAL)
+}
 
-    None
+/// Extracts the table of an RTM_NEWRULE message if the rule matches `fwmark`
+///
+/// Only rules that select by fwmark and look up a table are considered; a
+/// rule matches when `(fwmark ^ rule mark) & rule mask == 0`.
+fn extract_rule_table(
+    data: &[u8],
+    msg_offset: usize,
+    nlmsg_len: usize,
+    fwmark: u32,
+) -> Option<u32> {
+    let msg_end = (msg_offset + nlmsg_len).min(data.len());
+    if msg_end < msg_offset + NLMSG_HDRLEN + RTMSG_LEN {
+        return None;
+    }
+    let hdr_offset = msg_offset + NLMSG_HDRLEN;
+    if data[hdr_offset + 7] != FR_ACT_TO_TBL {
+        return None;
+    }
+    let attrs = hdr_offset + RTMSG_LEN;
+    let mark = parse_rta_u32(data, attrs, msg_end, FRA_FWMARK)?;
+    let mask = parse_rta_u32(data, attrs, msg_end, FRA_FWMASK).unwrap_or(u32::MAX);
+    if (fwmark ^ mark) & mask != 0 {
+        return None;
+    }
+    Some(parse_rta_u32(data, attrs, msg_end, FRA_TABLE).unwrap_or(u32::from(data[hdr_offset + 4])))
+}
+
+/// Returns the interface indexes the IPv6 routes of a table go out of
+fn netlink_route_interfaces(table: u32) -> Result<Vec<u32>> {
+    let mut rtmsg = [0u8; RTMSG_LEN];
+    rtmsg[0] = AF_INET6;
+    let mut oifs: Vec<u32> = netlink_dump(
+        RTM_GETROUTE_VAL,
+        &rtmsg,
+        RTM_NEWROUTE_VAL,
+        |data, msg_offset, nlmsg_len| extract_route_oif(data, msg_offset, nlmsg_len, table),
+    )?;
+    oifs.sort_unstable();
+    oifs.dedup();
+    Ok(oifs)
+}
+
+/// Returns the table of the first IPv6 rule (by priority) matching `fwmark`
+fn netlink_rule_table(fwmark: u32) -> Result<Option<u32>> {
+    let mut hdr = [0u8; RTMSG_LEN];
+    hdr[0] = AF_INET6;
+    let tables = netlink_dump(
+        RTM_GETRULE_VAL,
+        &hdr,
+        RTM_NEWRULE_VAL,
+        |data, msg_offset, nlmsg_len| extract_rule_table(data, msg_offset, nlmsg_len, fwmark),
+    )?;
+    Ok(tables.first().copied())
+}
+
+fn netlink_dump_ipv6(accepted_scopes: &[u8]) -> Result<Vec<AddressCandidate>> {
+    netlink_dump_addrs(AF_INET6, |data, msg_offset, nlmsg_len| {
+        extract_ipv6_candidate_for_dump(data, msg_offset, nlmsg_len, accepted_scopes)
+    })
+}
+
+fn netlink_dump_ipv4() -> Result<Vec<Ipv4Addr>> {
+    netlink_dump_addrs(AF_INET, extract_ipv4_for_dump)
+}
+
+/// Dumps the system's addresses of one family
+///
+/// # Arguments
+///
+/// * `family` - The address family to request (`AF_INET6` or `AF_INET`)
+/// * `extract` - Called with (data, msg_offset, nlmsg_len) for each RTM_NEWADDR
+///   message; the addresses it returns are collected in kernel order
+///
+/// # Returns
+///
+/// Returns a `Result` containing the extracted addresses or an error if the
+/// netlink request fails
+fn netlink_dump_addrs<T: Send>(
+    family: u8,
+    extract: impl FnMut(&[u8], usize, usize) -> Option<T> + Send,
+) -> Result<Vec<T>> {
+    let mut ifaddrmsg = [0u8; IFADDRMSG_LEN];
+    ifaddrmsg[0] = family;
+    netlink_dump(RTM_GETADDR_VAL, &ifaddrmsg, RTM_NEWADDR_VAL, extract)
+}
+
+/// Sends a netlink dump request and collects what `extract` finds in the replies
+///
+/// # Arguments
+///
+/// * `request_type` - The request message type (e.g. `RTM_GETADDR`)
+/// * `header` - The family header following the netlink header (e.g. `ifaddrmsg`)
+/// * `reply_type` - The reply message type passed to `extract` (e.g. `RTM_NEWADDR`)
+/// * `extract` - Called with (data, msg_offset, nlmsg_len) for each reply message
+///
+/// # Returns
+///
+/// Returns a `Result` containing the extracted values in kernel order or an
+/// error if the netlink request fails
+fn netlink_dump<T: Send>(
+    request_type: u16,
+    header: &[u8],
+    reply_type: u16,
+    extract: impl FnMut(&[u8], usize, usize) -> Option<T> + Send,
+) -> Result<Vec<T>> {
+    // Interface names are resolved while extracting, so the whole dump runs
+    // in the namespace
+    in_network_namespace(|| run_netlink_dump(request_type, header, reply_type, extract))
+}
+
+/// Outcome of reading one netlink dump
+#[derive(Debug)]
+enum DumpOutcome<T> {
+    /// NLMSG_DONE was reached; the values extracted from the replies
+    Complete(Vec<T>),
+    /// A reply did not fit the buffer; its full length in bytes
+    Truncated(usize),
+}
+
+/// Performs a netlink dump in the calling thread's network namespace
+///
+/// A reply that doesn't fit the receive buffer loses its tail, so the dump is
+/// requested again with a buffer large enough for it, up to
+/// `MAX_NETLINK_DUMP_BUFFER_SIZE`. See [`netlink_dump`].
+fn run_netlink_dump<T>(
+    request_type: u16,
+    header: &[u8],
+    reply_type: u16,
+    mut extract: impl FnMut(&[u8], usize, usize) -> Option<T>,
+) -> Result<Vec<T>> {
+    let mut buffer_size = NETLINK_DUMP_BUFFER_SIZE;
+    loop {
+        let socket = send_dump_request(request_type, header)?;
+        let recv = |buf: &mut [u8]| {
+            // MSG_TRUNC makes recv return the full length of a reply that
+            // didn't fit
+            let n = unsafe {
+                libc::recv(
+                    socket.as_raw_fd(),
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    libc::MSG_TRUNC,
+                )
+            };
+            if n < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(n as usize)
+        };
+
+        match read_dump(recv, buffer_size, reply_type, &mut extract)? {
+            DumpOutcome::Complete(values) => return Ok(values),
+            DumpOutcome::Truncated(len) => {
+                buffer_size = grow_dump_buffer(buffer_size, len)?;
+                tracing::debug!(
+                    "Netlink dump reply of {} bytes was truncated; retrying with a {} byte buffer",
+                    len,
+                    buffer_size
+                );
+            }
+        }
+    }
+}
+
+/// Returns the buffer size to retry a dump with after a truncated reply
+///
+/// # Arguments
+///
+/// * `current` - The buffer size the reply was truncated with
+/// * `needed` - The full length of the truncated reply
+///
+/// # Returns
+///
+/// Returns at least double the current size and enough for the reply, or an
+/// error once that exceeds `MAX_NETLINK_DUMP_BUFFER_SIZE`
+fn grow_dump_buffer(current: usize, needed: usize) -> Result<usize> {
+    let size = nlmsg_align(needed).max(current.saturating_mul(2));
+    if needed > MAX_NETLINK_DUMP_BUFFER_SIZE {
+        return Err(anyhow::anyhow!(
+            "netlink dump reply of {} bytes exceeds the {} byte limit",
+            needed,
+            MAX_NETLINK_DUMP_BUFFER_SIZE
+        ));
+    }
+    Ok(size.min(MAX_NETLINK_DUMP_BUFFER_SIZE))
 }
 
-fn netlink_dump_ipv6() -> Result<(Option<String>, Option<String>)> {
+/// Opens a netlink socket and sends a dump request on it
+///
+/// # Arguments
+///
+/// * `request_type` - The request message type (e.g. `RTM_GETADDR`)
+/// * `header` - The family header following the netlink header
+///
+/// # Returns
+///
+/// Returns the socket to read the replies from
+fn send_dump_request(request_type: u16, header: &[u8]) -> Result<NetlinkFd> {
     let socket = NetlinkFd::new()?;
 
     let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
@@ -694,14 +1976,14 @@ fn netlink_dump_ipv6() -> Result<(Option<String>, Option<String>)> {
     }
 
     let seq = 1u32;
-    let mut buf = [0u8; NLMSG_HDRLEN + IFADDRMSG_LEN];
-    let nlmsg_len = (NLMSG_HDRLEN + IFADDRMSG_LEN) as u32;
+    let mut buf = vec![0u8; NLMSG_HDRLEN + header.len()];
+    let nlmsg_len = buf.len() as u32;
     buf[0..4].copy_from_slice(&nlmsg_len.to_ne_bytes());
-    buf[4..6].copy_from_slice(&RTM_GETADDR_VAL.to_ne_bytes());
+    buf[4..6].copy_from_slice(&request_type.to_ne_bytes());
     buf[6..8].copy_from_slice(&(NLM_F_REQUEST | NLM_F_DUMP).to_ne_bytes());
     buf[8..12].copy_from_slice(&seq.to_ne_bytes());
     buf[12..16].copy_from_slice(&0u32.to_ne_bytes());
-    buf[16] = AF_INET6;
+    buf[NLMSG_HDRLEN..].copy_from_slice(header);
 
     let send_res = unsafe {
         libc::send(
@@ -715,75 +1997,132 @@ fn netlink_dump_ipv6() -> Result<(Option<String>, Option<String>)> {
         return Err(std::io::Error::last_os_error()).context("netlink send");
     }
 
-    let mut stable: Option<String> = None;
-    let mut temporary: Option<String> = None;
-    let mut recv_buf = vec![0u8; NETLINK_DUMP_BUFFER_SIZE];
+    Ok(socket)
+}
+
+/// Reads the replies of a netlink dump until NLMSG_DONE
+///
+/// # Arguments
+///
+/// * `recv` - Receives one reply into the buffer, returning its full length
+///   even if it didn't fit (as `recv` with `MSG_TRUNC` does)
+/// * `buffer_size` - Size of the receive buffer
+/// * `reply_type` - The reply message type passed to `extract`
+/// * `extract` - Called with (data, msg_offset, nlmsg_len) for each reply message
+///
+/// # Returns
+///
+/// Returns the extracted values, `DumpOutcome::Truncated` as soon as a reply
+/// doesn't fit the buffer, or an error if receiving or parsing fails
+fn read_dump<T>(
+    mut recv: impl FnMut(&mut [u8]) -> std::io::Result<usize>,
+    buffer_size: usize,
+    reply_type: u16,
+    extract: &mut impl FnMut(&[u8], usize, usize) -> Option<T>,
+) -> Result<DumpOutcome<T>> {
+    let mut candidates = Vec::new();
+    let mut recv_buf = vec![0u8; buffer_size];
+    let mut total_messages = 0usize;
 
     loop {
-        let n = unsafe {
-            libc::recv(
-                socket.as_raw_fd(),
-                recv_buf.as_mut_ptr() as *mut libc::c_void,
-                recv_buf.len(),
-                0,
-            )
-        };
-        if n < 0 {
-            return Err(std::io::Error::last_os_error()).context("netlink recv");
-        }
+        let n = recv(&mut recv_buf).context("netlink recv")?;
         if n == 0 {
             break;
         }
+        if n > recv_buf.len() {
+            return Ok(DumpOutcome::Truncated(n));
+        }
 
-        let data = &recv_buf[..n as usize];
-        let mut msg_offset = 0usize;
-        while msg_offset + NLMSG_HDRLEN <= data.len() {
-            // Safely extract nlmsg_len with bounds checking
-            let nlmsg_len_bytes = data.get(msg_offset..msg_offset + 4);
-            let nlmsg_len = match nlmsg_len_bytes {
-                Some(bytes) => u32::from_ne_bytes(bytes.try_into().unwrap_or([0; 4])) as usize,
-                None => break,
-            };
-            if nlmsg_len < NLMSG_HDRLEN || nlmsg_len == 0 {
-                break;
-            }
+        if parse_dump_buffer(
+            &recv_buf[..n],
+            reply_type,
+            extract,
+            &mut candidates,
+            &mut total_messages,
+        )? {
+            break;
+        }
+    }
 
-            // Safely extract nlmsg_type with bounds checking
-            let nlmsg_type_bytes = data.get(msg_offset + 4..msg_offset + 6);
-            let nlmsg_type = match nlmsg_type_bytes {
-                Some(bytes) => u16::from_ne_bytes(bytes.try_into().unwrap_or([0; 2])),
-                None => break,
-            };
-            if nlmsg_type == NLMSG_DONE {
-                return Ok((stable, temporary));
-            }
-            if nlmsg_type == NLMSG_ERROR {
-                return Err(anyhow::anyhow!("netlink error response"));
-            }
+    Ok(DumpOutcome::Complete(candidates))
+}
 
-            if nlmsg_type == RTM_NEWADDR_VAL {
-                // Use the helper function to extract IPv6 addresses
-                if let Some((addr_stable, addr_temp)) =
-                    extract_ipv6_addresses_for_dump(data, msg_offset, nlmsg_len)
-                {
-                    if let Some(ip) = addr_stable {
-                        if stable.is_none() {
-                            stable = Some(ip);
-                        }
-                    }
-                    if let Some(ip) = addr_temp {
-                        if temporary.is_none() {
-                            temporary = Some(ip);
-                        }
-                    }
-                }
-            }
+/// Parses one reply buffer of a netlink dump
+///
+/// Gives up on the dump with an error after `MAX_MESSAGES_PER_BUFFER` messages
+/// in the buffer or `MAX_DUMP_MESSAGES` in the whole dump: skipping the rest
+/// of a buffer could skip NLMSG_DONE and leave the next recv waiting forever.
+///
+/// # Arguments
+///
+/// * `data` - The received buffer
+/// * `reply_type` - The reply message type passed to `extract`
+/// * `extract` - Called with (data, msg_offset, nlmsg_len) for each reply message
+/// * `candidates` - Collects what `extract` finds
+/// * `total_messages` - Messages parsed so far in this dump, updated
+///
+/// # Returns
+///
+/// Returns `true` once NLMSG_DONE is reached, or an error for a netlink error
+/// response or a dump over the limits
+fn parse_dump_buffer<T>(
+    data: &[u8],
+    reply_type: u16,
+    extract: &mut impl FnMut(&[u8], usize, usize) -> Option<T>,
+    candidates: &mut Vec<T>,
+    total_messages: &mut usize,
+) -> Result<bool> {
+    let mut msg_offset = 0usize;
+    let mut buffer_messages = 0usize;
+    while msg_offset + NLMSG_HDRLEN <= data.len() {
+        buffer_messages += 1;
+        *total_messages += 1;
+        if buffer_messages > MAX_MESSAGES_PER_BUFFER || *total_messages > MAX_DUMP_MESSAGES {
+            tracing::warn!(
+                "Netlink dump exceeded {} messages per buffer or {} in total; giving up",
+                MAX_MESSAGES_PER_BUFFER,
+                MAX_DUMP_MESSAGES
+            );
+            return Err(anyhow::anyhow!("netlink dump too large"));
+        }
+
+        // Safely extract nlmsg_len with bounds checking
+        let nlmsg_len_bytes = data.get(msg_offset..msg_offset + 4);
+        let nlmsg_len = match nlmsg_len_bytes {
+            Some(bytes) => u32::from_ne_bytes(bytes.try_into().unwrap_or([0; 4])) as usize,
+            None => break,
+        };
+        if nlmsg_len < NLMSG_HDRLEN || nlmsg_len == 0 {
+            break;
+        }
+        // Replies hold whole messages; a cut-off one means data was lost
+        if msg_offset + nlmsg_len > data.len() {
+            return Err(anyhow::anyhow!("netlink dump message truncated"));
+        }
+
+        // Safely extract nlmsg_type with bounds checking
+        let nlmsg_type_bytes = data.get(msg_offset + 4..msg_offset + 6);
+        let nlmsg_type = match nlmsg_type_bytes {
+            Some(bytes) => u16::from_ne_bytes(bytes.try_into().unwrap_or([0; 2])),
+            None => break,
+        };
+        if nlmsg_type == NLMSG_DONE {
+            return Ok(true);
+        }
+        if nlmsg_type == NLMSG_ERROR {
+            return Err(anyhow::anyhow!("netlink error response"));
+        }
 
-            msg_offset += nlmsg_align(nlmsg_len);
+        if nlmsg_type == reply_type {
+            if let Some(candidate) = extract(data, msg_offset, nlmsg_len) {
+                candidates.push(candidate);
+            }
         }
+
+        msg_offset += nlmsg_align(nlmsg_len);
     }
 
-    Ok((stable, temporary))
+    Ok(false)
 }
 
 //==============================================================================
@@ -843,11 +2182,11 @@ mod tests {
         // IPv6 address
         let ip_bytes = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
         buf[rta_offset + 4..rta_offset + 20].copy_from_slice(&ip_bytes);
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(
             event,
-            Some(NetlinkEvent::Ipv6Added("2001:db8::1".to_string()))
+            Some(NetlinkEvent::Ipv6Added("2001:db8::1".to_string(), None))
         );
     }
 
@@ -880,7 +2219,7 @@ mod tests {
         // IPv6 address
         let ip_bytes = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
         buf[rta_offset + 4..rta_offset + 20].copy_from_slice(&ip_bytes);
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, Some(NetlinkEvent::Ipv6Removed));
     }
@@ -895,7 +2234,7 @@ mod tests {
         buf[6..8].copy_from_slice(&0u16.to_ne_bytes());
         buf[8..12].copy_from_slice(&1u32.to_ne_bytes());
         buf[12..16].copy_from_slice(&0u32.to_ne_bytes());
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
@@ -911,7 +2250,7 @@ mod tests {
         buf[8..12].copy_from_slice(&1u32.to_ne_bytes());
         buf[12..16].copy_from_slice(&0u32.to_ne_bytes());
         buf[16..20].copy_from_slice(&0xFFFFFFFFu32.to_ne_bytes()); // error code
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
@@ -919,7 +2258,7 @@ mod tests {
     #[test]
     fn test_parse_message_truncated_header() {
         let buf = vec![0u8; 10]; // Less than NLMSG_HDRLEN
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
@@ -930,7 +2269,7 @@ mod tests {
 
         // Invalid nlmsg_len (less than header)
         buf[0..4].copy_from_slice(&8u32.to_ne_bytes());
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
@@ -940,7 +2279,7 @@ mod tests {
         let mut buf = vec![0u8; 16];
 
         buf[0..4].copy_from_slice(&0u32.to_ne_bytes());
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
@@ -961,7 +2300,7 @@ mod tests {
         buf[ifa_offset + 1] = 32;
         buf[ifa_offset + 2] = 0;
         buf[ifa_offset + 3] = RT_SCOPE_UNIVERSE;
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
@@ -982,11 +2321,523 @@ mod tests {
         buf[ifa_offset + 1] = 64;
         buf[ifa_offset + 2] = 0;
         buf[ifa_offset + 3] = libc::RT_SCOPE_LINK; // Link scope, not universe
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
 
+    #[test]
+    fn test_parse_message_site_scope_when_accepted() {
+        let mut buf = vec![0u8; 64];
+
+        let nlmsg_len = 44u32;
+        buf[0..4].copy_from_slice(&nlmsg_len.to_ne_bytes());
+        buf[4..6].copy_from_slice(&RTM_NEWADDR_VAL.to_ne_bytes());
+
+        let ifa_offset = 16;
+        buf[ifa_offset] = AF_INET6;
+        buf[ifa_offset + 1] = 64;
+        buf[ifa_offset + 3] = RT_SCOPE_SITE;
+
+        let rta_offset = ifa_offset + 8;
+        buf[rta_offset..rta_offset + 2].copy_from_slice(&20u16.to_ne_bytes());
+        buf[rta_offset + 2..rta_offset + 4].copy_from_slice(&IFA_ADDRESS_VAL.to_ne_bytes());
+        let ip_bytes = [0xfe, 0xc0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        buf[rta_offset + 4..rta_offset + 20].copy_from_slice(&ip_bytes);
+
+        assert_eq!(
+            NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false),
+            None
+        );
+        assert_eq!(
+            NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE, RT_SCOPE_SITE], false),
+            Some(NetlinkEvent::Ipv6Added("fec0::1".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn test_jittered_interval() {
+        let interval = Duration::from_secs(60);
+        assert_eq!(jittered_interval(interval, 0.0, 0.9), interval);
+        assert_eq!(
+            jittered_interval(interval, 0.5, 0.0),
+            Duration::from_secs(30)
+        );
+        assert_eq!(jittered_interval(interval, 0.5, 0.5), interval);
+        for _ in 0..100 {
+            let unit = random_unit();
+            assert!((0.0..1.0).contains(&unit));
+            let d = jittered_interval(interval, 0.1, unit);
+            assert!(d >= Duration::from_secs(54) && d <= Duration::from_secs(66));
+        }
+        assert_eq!(
+            jitter_bounds(interval, 0.1),
+            (Duration::from_secs(54), Duration::from_secs(66))
+        );
+        assert_eq!(jitter_bounds(interval, 0.0), (interval, interval));
+    }
+
+    #[test]
+    fn test_parse_scope() {
+        assert_eq!(parse_scope("universe").unwrap(), RT_SCOPE_UNIVERSE);
+        assert_eq!(parse_scope("Global").unwrap(), RT_SCOPE_UNIVERSE);
+        assert_eq!(parse_scope("site").unwrap(), RT_SCOPE_SITE);
+        assert!(parse_scope("link").is_err());
+        assert!(parse_scope("bogus").is_err());
+    }
+
+    fn candidate(addr: &str, temporary: bool, interface: &str, lifetime: u32) -> AddressCandidate {
+        AddressCandidate {
+            addr: addr.parse().unwrap(),
+            temporary,
+            interface: Some(interface.to_string()),
+            preferred_lifetime: Some(lifetime),
+        }
+    }
+
+    #[test]
+    fn test_selection_policy_default() {
+        let policy = SelectionPolicy::default();
+        let temp = candidate("2001:db8::a", true, "eth0", INFINITY_LIFE_TIME);
+        let short = candidate("2001:db8::1", false, "eth0", 3600);
+        let long = candidate("2001:db8::2", false, "eth0", 86400);
+
+        // Stable beats temporary, then the longer preferred lifetime wins
+        let candidates = [temp.clone(), short.clone(), long.clone()];
+        assert_eq!(policy.select(&candidates), Some(&long));
+        assert_eq!(policy.select(std::slice::from_ref(&temp)), Some(&temp));
+        assert_eq!(policy.select(&[]), None);
+
+        // Ties keep the kernel's order
+        let twin = candidate("2001:db8::3", false, "eth0", 3600);
+        assert_eq!(policy.select(&[short.clone(), twin]), Some(&short));
+    }
+
+    #[test]
+    fn test_selection_policy_weights() {
+        let eth = candidate("2001:db8:1::1", false, "eth0", 3600);
+        let wlan = candidate("2001:db8:2::1", false, "wlan0", 3600);
+        let temp = candidate("2001:db8:3::1", true, "eth0", 3600);
+
+        let policy = SelectionPolicy {
+            interfaces: vec!["wlan0".to_string(), "eth0".to_string()],
+            ..SelectionPolicy::default()
+        };
+        assert_eq!(policy.select(&[eth.clone(), wlan.clone()]), Some(&wlan));
+
+        let policy = SelectionPolicy {
+            prefix: Some(parse_prefix("2001:db8:3::/48").unwrap()),
+            prefix_weight: 2000,
+            ..SelectionPolicy::default()
+        };
+        assert_eq!(policy.select(&[eth.clone(), temp.clone()]), Some(&temp));
+
+        let policy = SelectionPolicy {
+            stable_weight: -1000,
+            ..SelectionPolicy::default()
+        };
+        assert_eq!(policy.select(&[eth, temp.clone()]), Some(&temp));
+    }
+
+    #[test]
+    fn test_selection_policy_modes() {
+        let temp = candidate("2001:db8:1::a", true, "eth0", INFINITY_LIFE_TIME);
+        let short = candidate("2001:db8:2::1", false, "eth0", 600);
+        let long = candidate("2001:db8:1::1", false, "eth1", 86400 * 30);
+        let forever = candidate("2001:db8:3::1", false, "eth1", INFINITY_LIFE_TIME);
+        let unreported = AddressCandidate {
+            preferred_lifetime: None,
+            ..candidate("2001:db8:4::1", false, "eth0", 0)
+        };
+
+        let policy = SelectionPolicy {
+            mode: AddressSelection::First,
+            ..SelectionPolicy::default()
+        };
+        let candidates = [temp.clone(), short.clone(), long.clone()];
+        assert_eq!(policy.select(&candidates), Some(&short));
+
+        // Weights are ignored: a month beats a week's cap in the weighted mode
+        let policy = SelectionPolicy {
+            mode: AddressSelection::LongestLifetime,
+            ..SelectionPolicy::default()
+        };
+        let candidates = [
+            unreported.clone(),
+            short.clone(),
+            temp.clone(),
+            long.clone(),
+        ];
+        assert_eq!(
+            policy.rank(&candidates),
+            vec![&long, &short, &unreported, &temp]
+        );
+        assert_eq!(
+            policy.select(&[long.clone(), forever.clone()]),
+            Some(&forever)
+        );
+
+        let policy = SelectionPolicy {
+            mode: AddressSelection::PreferPrefix,
+            prefix: Some(parse_prefix("2001:db8:1::/48").unwrap()),
+            ..SelectionPolicy::default()
+        };
+        assert_eq!(policy.select(&[short.clone(), long.clone()]), Some(&long));
+        assert_eq!(policy.select(&[temp, short.clone()]), Some(&short));
+    }
+
+    #[test]
+    fn test_selection_policy_lowest() {
+        let policy = SelectionPolicy {
+            mode: AddressSelection::Lowest,
+            ..SelectionPolicy::default()
+        };
+        let dhcp = candidate("2001:db8:1::1000", false, "eth0", 3600);
+        let slaac = candidate("2001:db8:1:0:211:22ff:fe33:4455", false, "eth0", 86400);
+        let other = candidate("2001:db8:0:ff::1", false, "eth1", INFINITY_LIFE_TIME);
+        let temp = candidate("2001:db8::1", true, "eth0", INFINITY_LIFE_TIME);
+
+        // The kernel's order and the lifetimes don't matter, only the address
+        let orders = [
+            [dhcp.clone(), slaac.clone(), other.clone(), temp.clone()],
+            [slaac.clone(), temp.clone(), dhcp.clone(), other.clone()],
+            [temp.clone(), other.clone(), slaac.clone(), dhcp.clone()],
+        ];
+        for candidates in &orders {
+            assert_eq!(policy.rank(candidates), vec![&other, &dhcp, &slaac, &temp]);
+        }
+        assert_eq!(policy.select(&[slaac.clone(), dhcp.clone()]), Some(&dhcp));
+        assert_eq!(
+            parse_address_selection("lowest").unwrap(),
+            AddressSelection::Lowest
+        );
+    }
+
+    #[test]
+    fn test_parse_address_selection() {
+        assert_eq!(
+            parse_address_selection("Longest_Lifetime").unwrap(),
+            AddressSelection::LongestLifetime
+        );
+        assert_eq!(
+            parse_address_selection("first").unwrap(),
+            AddressSelection::First
+        );
+        assert!(parse_address_selection("random").is_err());
+    }
+
+    #[test]
+    fn test_selection_policy_regions() {
+        let home = candidate("2001:db8:1::1", false, "eth0", 3600);
+        let guest = candidate("2001:db8:1:ff::1", false, "eth0", 3600);
+        let vpn = candidate("2001:db8:2::1", false, "tun0", INFINITY_LIFE_TIME);
+        let unmapped = candidate("2001:db9::1", false, "eth0", INFINITY_LIFE_TIME);
+
+        let region = |p: &str, r: &str| {
+            let (network, len) = parse_prefix(p).unwrap();
+            (network, len, r.to_string())
+        };
+        let policy = SelectionPolicy {
+            regions: vec![
+                region("2001:db8:1::/48", "home"),
+                region("2001:db8:1:ff::/64", "guest"),
+                region("2001:db8:2::/48", "vpn"),
+            ],
+            ..SelectionPolicy::default()
+        };
+        assert_eq!(policy.region_of(home.addr), Some("home"));
+        assert_eq!(policy.region_of(guest.addr), Some("guest"));
+        assert_eq!(policy.region_of(unmapped.addr), None);
+        // Without an allowlist, regions don't filter anything
+        assert!(policy.region_allowed(vpn.addr));
+        assert!(policy.region_allowed(unmapped.addr));
+
+        let policy = SelectionPolicy {
+            allowed_regions: vec!["home".to_string()],
+            ..policy
+        };
+        assert!(policy.region_allowed(home.addr));
+        assert!(!policy.region_allowed(guest.addr));
+        assert!(!policy.region_allowed(unmapped.addr));
+        let all = [vpn.clone(), unmapped.clone(), home.clone()];
+        assert_eq!(policy.select(&all), Some(&home));
+        assert_eq!(policy.select(&[vpn, unmapped, guest]), None);
+    }
+
+    /// Builds a route or rule message: netlink header, 12-byte family header
+    /// with `table` and `action`, then u32 attributes
+    fn routing_message(msg_type: u16, table: u8, action: u8, attrs: &[(u16, u32)]) -> Vec<u8> {
+        let len = NLMSG_HDRLEN + RTMSG_LEN + attrs.len() * 8;
+        let mut buf = vec![0u8; len];
+        buf[0..4].copy_from_slice(&(len as u32).to_ne_bytes());
+        buf[4..6].copy_from_slice(&msg_type.to_ne_bytes());
+        buf[NLMSG_HDRLEN] = AF_INET6;
+        buf[NLMSG_HDRLEN + 4] = table;
+        buf[NLMSG_HDRLEN + 7] = action;
+        for (i, (rta_type, value)) in attrs.iter().enumerate() {
+            let off = NLMSG_HDRLEN + RTMSG_LEN + i * 8;
+            buf[off..off + 2].copy_from_slice(&8u16.to_ne_bytes());
+            buf[off + 2..off + 4].copy_from_slice(&rta_type.to_ne_bytes());
+            buf[off + 4..off + 8].copy_from_slice(&value.to_ne_bytes());
+        }
+        buf
+    }
+
+    #[test]
+    fn test_extract_route_oif() {
+        let route = routing_message(RTM_NEWROUTE_VAL, 100, 1, &[(RTA_OIF_VAL, 3)]);
+        assert_eq!(extract_route_oif(&route, 0, route.len(), 100), Some(3));
+        assert_eq!(extract_route_oif(&route, 0, route.len(), 254), None);
+
+        // Tables above 255 are only carried in RTA_TABLE
+        let route = routing_message(
+            RTM_NEWROUTE_VAL,
+            252,
+            1,
+            &[(RTA_TABLE_VAL, 1000), (RTA_OIF_VAL, 7)],
+        );
+        assert_eq!(extract_route_oif(&route, 0, route.len(), 1000), Some(7));
+        assert_eq!(extract_route_oif(&route, 0, route.len(), 252), None);
+
+        let no_oif = routing_message(RTM_NEWROUTE_VAL, 100, 1, &[]);
+        assert_eq!(extract_route_oif(&no_oif, 0, no_oif.len(), 100), None);
+        assert_eq!(extract_route_oif(&route[..20], 0, route.len(), 1000), None);
+    }
+
+    #[test]
+    fn test_extract_rule_table() {
+        let rule = routing_message(
+            RTM_NEWRULE_VAL,
+            0,
+            FR_ACT_TO_TBL,
+            &[(FRA_FWMARK, 0x64), (FRA_TABLE, 100)],
+        );
+        assert_eq!(extract_rule_table(&rule, 0, rule.len(), 0x64), Some(100));
+        assert_eq!(extract_rule_table(&rule, 0, rule.len(), 0x65), None);
+
+        let masked = routing_message(
+            RTM_NEWRULE_VAL,
+            200,
+            FR_ACT_TO_TBL,
+            &[(FRA_FWMARK, 0x100), (FRA_FWMASK, 0xff00)],
+        );
+        assert_eq!(
+            extract_rule_table(&masked, 0, masked.len(), 0x1ff),
+            Some(200)
+        );
+
+        // Rules that don't select by fwmark, or don't look up a table, are skipped
+        let main = routing_message(RTM_NEWRULE_VAL, 254, FR_ACT_TO_TBL, &[]);
+        assert_eq!(extract_rule_table(&main, 0, main.len(), 0x64), None);
+        let blackhole = routing_message(RTM_NEWRULE_VAL, 0, 6, &[(FRA_FWMARK, 0x64)]);
+        assert_eq!(
+            extract_rule_table(&blackhole, 0, blackhole.len(), 0x64),
+            None
+        );
+    }
+
+    #[test]
+    fn test_top_addresses() {
+        let policy = SelectionPolicy::default();
+        let candidates = [
+            candidate("2001:db8::3", true, "eth0", 3600),
+            candidate("2001:db8::2", false, "eth0", 3600),
+            candidate("2001:db8::2", false, "wlan0", 3600),
+            candidate("2001:db8::1", false, "eth0", 7200),
+        ];
+        let addr = |s: &str| s.parse::<Ipv6Addr>().unwrap();
+        assert_eq!(
+            top_addresses(&policy, &candidates, 4),
+            [
+                addr("2001:db8::1"),
+                addr("2001:db8::2"),
+                addr("2001:db8::3")
+            ]
+        );
+        // The temporary address ranks last and is dropped first
+        assert_eq!(
+            top_addresses(&policy, &candidates, 2),
+            [addr("2001:db8::1"), addr("2001:db8::2")]
+        );
+        assert!(top_addresses(&policy, &[], 2).is_empty());
+    }
+
+    /// Selects between a synthetic (stable, temporary) pair
+    fn pick(
+        preference: AddressPreference,
+        stable: Option<&str>,
+        temporary: Option<&str>,
+    ) -> Option<String> {
+        let policy = SelectionPolicy {
+            preference,
+            ..SelectionPolicy::default()
+        };
+        let candidates: Vec<AddressCandidate> = stable
+            .map(|ip| candidate(ip, false, "eth0", INFINITY_LIFE_TIME))
+            .into_iter()
+            .chain(temporary.map(|ip| candidate(ip, true, "eth0", INFINITY_LIFE_TIME)))
+            .collect();
+        policy.select(&candidates).map(|c| c.addr.to_string())
+    }
+
+    #[test]
+    fn test_address_preference() {
+        use AddressPreference::*;
+        let (s, t) = ("2001:db8::1", "2001:db8::2");
+        let cases = [
+            (Stable, Some(s), Some(t), Some(s)),
+            (Stable, None, Some(t), Some(t)),
+            (Stable, Some(s), None, Some(s)),
+            (Temporary, Some(s), Some(t), Some(t)),
+            (Temporary, Some(s), None, Some(s)),
+            (StableOnly, Some(s), Some(t), Some(s)),
+            (StableOnly, None, Some(t), None),
+            (TemporaryOnly, Some(s), Some(t), Some(t)),
+            (TemporaryOnly, Some(s), None, None),
+            (Stable, None, None, None),
+        ];
+        for (preference, stable, temporary, expected) in cases {
+            assert_eq!(
+                pick(preference, stable, temporary),
+                expected.map(str::to_string),
+                "{preference:?} with ({stable:?}, {temporary:?})"
+            );
+        }
+
+        assert_eq!(
+            parse_address_preference("Temporary_Only").unwrap(),
+            TemporaryOnly
+        );
+        assert!(parse_address_preference("privacy").is_err());
+    }
+
+    #[test]
+    fn test_extract_ipv4_for_dump() {
+        let mut buf = vec![0u8; 48];
+
+        let nlmsg_len = 40u32;
+        buf[0..4].copy_from_slice(&nlmsg_len.to_ne_bytes());
+        buf[4..6].copy_from_slice(&RTM_NEWADDR_VAL.to_ne_bytes());
+
+        let ifa_offset = 16;
+        buf[ifa_offset] = AF_INET;
+        buf[ifa_offset + 1] = 32;
+        buf[ifa_offset + 3] = RT_SCOPE_UNIVERSE;
+
+        // Peer address first (as on a tunnel), then the local address
+        let rta_offset = ifa_offset + 8;
+        buf[rta_offset..rta_offset + 2].copy_from_slice(&8u16.to_ne_bytes());
+        buf[rta_offset + 2..rta_offset + 4].copy_from_slice(&IFA_ADDRESS_VAL.to_ne_bytes());
+        buf[rta_offset + 4..rta_offset + 8].copy_from_slice(&[198, 51, 100, 1]);
+        buf[rta_offset + 8..rta_offset + 10].copy_from_slice(&8u16.to_ne_bytes());
+        buf[rta_offset + 10..rta_offset + 12].copy_from_slice(&IFA_LOCAL_VAL.to_ne_bytes());
+        buf[rta_offset + 12..rta_offset + 16].copy_from_slice(&[93, 184, 216, 34]);
+
+        assert_eq!(
+            extract_ipv4_for_dump(&buf, 0, nlmsg_len as usize),
+            Some(Ipv4Addr::new(93, 184, 216, 34))
+        );
+
+        buf[ifa_offset + 3] = libc::RT_SCOPE_HOST;
+        assert_eq!(extract_ipv4_for_dump(&buf, 0, nlmsg_len as usize), None);
+
+        buf[ifa_offset + 3] = RT_SCOPE_UNIVERSE;
+        buf[ifa_offset] = AF_INET6;
+        assert_eq!(extract_ipv4_for_dump(&buf, 0, nlmsg_len as usize), None);
+    }
+
+    #[test]
+    fn test_parse_prefix() {
+        let (net, len) = parse_prefix("2001:db8::/32").unwrap();
+        assert_eq!(len, 32);
+        assert!(prefix_contains(
+            net,
+            len,
+            "2001:db8:ffff::1".parse().unwrap()
+        ));
+        assert!(!prefix_contains(net, len, "2001:db9::1".parse().unwrap()));
+        assert!(prefix_contains(net, 0, "::1".parse().unwrap()));
+        assert!(parse_prefix("2001:db8::").is_err());
+        assert!(parse_prefix("2001:db8::/129").is_err());
+        assert!(parse_prefix("nope/64").is_err());
+    }
+
+    #[test]
+    fn test_extract_candidate_with_cacheinfo() {
+        let mut buf = vec![0u8; 64];
+
+        let nlmsg_len = 64u32;
+        buf[0..4].copy_from_slice(&nlmsg_len.to_ne_bytes());
+        buf[4..6].copy_from_slice(&RTM_NEWADDR_VAL.to_ne_bytes());
+
+        let ifa_offset = 16;
+        buf[ifa_offset] = AF_INET6;
+        buf[ifa_offset + 1] = 64;
+        buf[ifa_offset + 2] = IFA_F_TEMPORARY as u8;
+        buf[ifa_offset + 3] = RT_SCOPE_UNIVERSE;
+
+        let rta_offset = ifa_offset + 8;
+        buf[rta_offset..rta_offset + 2].copy_from_slice(&20u16.to_ne_bytes());
+        buf[rta_offset + 2..rta_offset + 4].copy_from_slice(&IFA_ADDRESS_VAL.to_ne_bytes());
+        let ip_bytes = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        buf[rta_offset + 4..rta_offset + 20].copy_from_slice(&ip_bytes);
+
+        let cache_offset = rta_offset + 20;
+        buf[cache_offset..cache_offset + 2].copy_from_slice(&20u16.to_ne_bytes());
+        buf[cache_offset + 2..cache_offset + 4].copy_from_slice(&IFA_CACHEINFO_VAL.to_ne_bytes());
+        buf[cache_offset + 4..cache_offset + 8].copy_from_slice(&7200u32.to_ne_bytes());
+        buf[cache_offset + 8..cache_offset + 12].copy_from_slice(&14400u32.to_ne_bytes());
+
+        let candidate =
+            extract_ipv6_candidate_for_dump(&buf, 0, nlmsg_len as usize, &[RT_SCOPE_UNIVERSE])
+                .expect("candidate");
+        assert_eq!(candidate.addr, "2001:db8::1".parse::<Ipv6Addr>().unwrap());
+        assert!(candidate.temporary);
+        assert_eq!(candidate.interface, None);
+        assert_eq!(candidate.preferred_lifetime, Some(7200));
+    }
+
+    #[test]
+    fn test_parse_message_with_cacheinfo() {
+        let mut buf = vec![0u8; 64];
+
+        let nlmsg_len = 64u32;
+        buf[0..4].copy_from_slice(&nlmsg_len.to_ne_bytes());
+        buf[4..6].copy_from_slice(&RTM_NEWADDR_VAL.to_ne_bytes());
+
+        let ifa_offset = 16;
+        buf[ifa_offset] = AF_INET6;
+        buf[ifa_offset + 1] = 64;
+        buf[ifa_offset + 3] = RT_SCOPE_UNIVERSE;
+
+        let rta_offset = ifa_offset + 8;
+        buf[rta_offset..rta_offset + 2].copy_from_slice(&20u16.to_ne_bytes());
+        buf[rta_offset + 2..rta_offset + 4].copy_from_slice(&IFA_ADDRESS_VAL.to_ne_bytes());
+        let ip_bytes = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        buf[rta_offset + 4..rta_offset + 20].copy_from_slice(&ip_bytes);
+
+        let cache_offset = rta_offset + 20;
+        buf[cache_offset..cache_offset + 2].copy_from_slice(&20u16.to_ne_bytes());
+        buf[cache_offset + 2..cache_offset + 4].copy_from_slice(&IFA_CACHEINFO_VAL.to_ne_bytes());
+        buf[cache_offset + 4..cache_offset + 8].copy_from_slice(&300u32.to_ne_bytes());
+        buf[cache_offset + 8..cache_offset + 12].copy_from_slice(&900u32.to_ne_bytes());
+
+        assert_eq!(
+            NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false),
+            Some(NetlinkEvent::Ipv6Added(
+                "2001:db8::1".to_string(),
+                Some(300)
+            ))
+        );
+
+        // A truncated cacheinfo attribute is ignored
+        buf[cache_offset..cache_offset + 2].copy_from_slice(&6u16.to_ne_bytes());
+        assert_eq!(
+            NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false),
+            Some(NetlinkEvent::Ipv6Added("2001:db8::1".to_string(), None))
+        );
+    }
+
     #[test]
     fn test_parse_message_temporary_address() {
         let mut buf = vec![0u8; 64];
@@ -1013,9 +2864,13 @@ mod tests {
 
         let ip_bytes = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
         buf[rta_offset + 4..rta_offset + 20].copy_from_slice(&ip_bytes);
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
+        assert_eq!(
+            NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], true),
+            Some(NetlinkEvent::Ipv6Added("2001:db8::1".to_string(), None))
+        );
     }
 
     #[test]
@@ -1044,9 +2899,24 @@ mod tests {
 
         let ip_bytes = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
         buf[rta_offset + 4..rta_offset + 20].copy_from_slice(&ip_bytes);
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
+
+        // Tentative addresses are tracked so they can be re-checked after DAD
+        assert_eq!(
+            NetlinkImpl::parse_tentative(&buf, &[RT_SCOPE_UNIVERSE], false),
+            Some("2001:db8::1".to_string())
+        );
+        assert_eq!(
+            NetlinkImpl::parse_tentative(&buf, &[RT_SCOPE_SITE], false),
+            None
+        );
+        buf[ifa_offset + 2] = (IFA_F_TENTATIVE | IFA_F_DADFAILED) as u8;
+        assert_eq!(
+            NetlinkImpl::parse_tentative(&buf, &[RT_SCOPE_UNIVERSE], false),
+            None
+        );
     }
 
     #[test]
@@ -1075,7 +2945,7 @@ mod tests {
 
         let ip_bytes = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
         buf[rta_offset + 4..rta_offset + 20].copy_from_slice(&ip_bytes);
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
@@ -1106,7 +2976,7 @@ mod tests {
 
         let ip_bytes = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
         buf[rta_offset + 4..rta_offset + 20].copy_from_slice(&ip_bytes);
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
@@ -1160,12 +3030,12 @@ mod tests {
         buf[rta_offset2 + 2..rta_offset2 + 4].copy_from_slice(&IFA_ADDRESS_VAL.to_ne_bytes());
         let ip_bytes2 = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
         buf[rta_offset2 + 4..rta_offset2 + 20].copy_from_slice(&ip_bytes2);
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         // Should return the first valid event
         assert_eq!(
             event,
-            Some(NetlinkEvent::Ipv6Added("2001:db8::1".to_string()))
+            Some(NetlinkEvent::Ipv6Added("2001:db8::1".to_string(), None))
         );
     }
 
@@ -1189,7 +3059,7 @@ mod tests {
         let rta_offset = ifa_offset + 8;
         // Invalid RTA length (less than header)
         buf[rta_offset..rta_offset + 2].copy_from_slice(&2u16.to_ne_bytes());
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
@@ -1215,7 +3085,7 @@ mod tests {
         let rta_len = 8u16; // Wrong payload length (not 16 bytes for IPv6)
         buf[rta_offset..rta_offset + 2].copy_from_slice(&rta_len.to_ne_bytes());
         buf[rta_offset + 2..rta_offset + 4].copy_from_slice(&IFA_ADDRESS_VAL.to_ne_bytes());
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(event, None);
     }
@@ -1245,11 +3115,240 @@ mod tests {
         buf[rta_offset + 2..rta_offset + 4].copy_from_slice(&IFA_LOCAL_VAL.to_ne_bytes());
         let ip_bytes = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
         buf[rta_offset + 4..rta_offset + 20].copy_from_slice(&ip_bytes);
-        let event = NetlinkImpl::parse_message(&buf);
+        let event = NetlinkImpl::parse_message(&buf, &[RT_SCOPE_UNIVERSE], false);
 
         assert_eq!(
             event,
-            Some(NetlinkEvent::Ipv6Added("2001:db8::1".to_string()))
+            Some(NetlinkEvent::Ipv6Added("2001:db8::1".to_string(), None))
+        );
+    }
+
+    /// `count` empty 16-byte messages of type `msg_type`
+    fn filler_messages(count: usize, msg_type: u16) -> Vec<u8> {
+        let mut buf = vec![0u8; count * NLMSG_HDRLEN];
+        for msg in buf.chunks_mut(NLMSG_HDRLEN) {
+            msg[0..4].copy_from_slice(&(NLMSG_HDRLEN as u32).to_ne_bytes());
+            msg[4..6].copy_from_slice(&msg_type.to_ne_bytes());
+        }
+        buf
+    }
+
+    #[test]
+    fn test_parse_message_caps_message_count() {
+        // An RTM_NEWADDR for 2001:db8::1 after `fillers` unrelated messages
+        let buffer = |fillers: usize| {
+            let mut buf = filler_messages(fillers, libc::RTM_NEWLINK);
+            let mut msg = vec![0u8; 44];
+            msg[0..4].copy_from_slice(&44u32.to_ne_bytes());
+            msg[4..6].copy_from_slice(&RTM_NEWADDR_VAL.to_ne_bytes());
+            msg[16] = AF_INET6;
+            msg[17] = 64;
+            msg[19] = RT_SCOPE_UNIVERSE;
+            msg[24..26].copy_from_slice(&20u16.to_ne_bytes());
+            msg[26..28].copy_from_slice(&IFA_ADDRESS_VAL.to_ne_bytes());
+            msg[28..44].copy_from_slice(&"2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+            buf.extend_from_slice(&msg);
+            buf
+        };
+
+        let found = NetlinkImpl::parse_message(
+            &buffer(MAX_MESSAGES_PER_BUFFER - 1),
+            &[RT_SCOPE_UNIVERSE],
+            false,
+        );
+        assert_eq!(
+            found,
+            Some(NetlinkEvent::Ipv6Added("2001:db8::1".to_string(), None))
+        );
+        let capped = NetlinkImpl::parse_message(
+            &buffer(MAX_MESSAGES_PER_BUFFER),
+            &[RT_SCOPE_UNIVERSE],
+            false,
+        );
+        assert_eq!(capped, None);
+    }
+
+    #[test]
+    fn test_walk_attributes_caps_attribute_count() {
+        // RTA_OIF after `fillers` unrelated attributes
+        let route = |fillers: usize| {
+            let mut attrs = vec![(0xfff0, 0); fillers];
+            attrs.push((RTA_OIF_VAL, 3));
+            routing_message(RTM_NEWROUTE_VAL, 100, 1, &attrs)
+        };
+        let attrs = NLMSG_HDRLEN + RTMSG_LEN;
+
+        let msg = route(MAX_ATTRIBUTES_PER_MESSAGE - 1);
+        assert_eq!(parse_rta_u32(&msg, attrs, msg.len(), RTA_OIF_VAL), Some(3));
+        let msg = route(MAX_ATTRIBUTES_PER_MESSAGE);
+        assert_eq!(parse_rta_u32(&msg, attrs, msg.len(), RTA_OIF_VAL), None);
+
+        // An end past the buffer is clamped rather than read out of bounds
+        let msg = route(0);
+        assert_eq!(
+            parse_rta_u32(&msg, attrs, msg.len() + 64, RTA_OIF_VAL),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_parse_dump_buffer_limits() {
+        let mut extract = |_: &[u8], offset: usize, _: usize| Some(offset);
+
+        let mut buf = filler_messages(3, RTM_NEWADDR_VAL);
+        buf.extend(filler_messages(1, NLMSG_DONE));
+        let (mut found, mut total) = (Vec::new(), 0);
+        let done = parse_dump_buffer(&buf, RTM_NEWADDR_VAL, &mut extract, &mut found, &mut total);
+        assert!(done.unwrap());
+        assert_eq!(found, [0, 16, 32]);
+        assert_eq!(total, 4);
+
+        // More messages than fit in a real buffer
+        let buf = filler_messages(MAX_MESSAGES_PER_BUFFER + 1, RTM_NEWADDR_VAL);
+        let (mut found, mut total) = (Vec::new(), 0);
+        let result = parse_dump_buffer(&buf, RTM_NEWADDR_VAL, &mut extract, &mut found, &mut total);
+        assert!(result.is_err());
+
+        // A dump that never ends
+        let buf = filler_messages(2, RTM_NEWADDR_VAL);
+        let (mut found, mut total) = (Vec::new(), MAX_DUMP_MESSAGES - 1);
+        let result = parse_dump_buffer(&buf, RTM_NEWADDR_VAL, &mut extract, &mut found, &mut total);
+        assert!(result.is_err());
+        assert_eq!(found.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_dump_buffer_cut_off_message() {
+        let mut extract = |_: &[u8], offset: usize, _: usize| Some(offset);
+        // The second message claims more bytes than the buffer holds
+        let mut buf = filler_messages(2, RTM_NEWADDR_VAL);
+        buf[NLMSG_HDRLEN..NLMSG_HDRLEN + 4].copy_from_slice(&64u32.to_ne_bytes());
+        let (mut found, mut total) = (Vec::new(), 0);
+        let result = parse_dump_buffer(&buf, RTM_NEWADDR_VAL, &mut extract, &mut found, &mut total);
+        assert!(result.is_err());
+    }
+
+    /// A `recv` replaying `replies` the way the kernel does with `MSG_TRUNC`
+    fn replay(replies: Vec<Vec<u8>>) -> impl FnMut(&mut [u8]) -> std::io::Result<usize> {
+        let mut replies = replies.into_iter();
+        move |buf: &mut [u8]| {
+            let reply = replies.next().unwrap_or_default();
+            let copied = reply.len().min(buf.len());
+            buf[..copied].copy_from_slice(&reply[..copied]);
+            Ok(reply.len())
+        }
+    }
+
+    #[test]
+    fn test_read_dump_multiple_replies() {
+        let mut extract = |_: &[u8], offset: usize, _: usize| Some(offset);
+        let replies = vec![
+            filler_messages(2, RTM_NEWADDR_VAL),
+            filler_messages(3, RTM_NEWADDR_VAL),
+            filler_messages(1, NLMSG_DONE),
+            // Never read: the dump ended
+            filler_messages(1, RTM_NEWADDR_VAL),
+        ];
+        let outcome = read_dump(replay(replies), 64, RTM_NEWADDR_VAL, &mut extract).unwrap();
+        match outcome {
+            DumpOutcome::Complete(found) => assert_eq!(found, [0, 16, 0, 16, 32]),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+
+        // The socket closing ends the dump too
+        let replies = vec![filler_messages(1, RTM_NEWADDR_VAL)];
+        let outcome = read_dump(replay(replies), 64, RTM_NEWADDR_VAL, &mut extract).unwrap();
+        assert!(matches!(outcome, DumpOutcome::Complete(found) if found == [0]));
+    }
+
+    #[test]
+    fn test_read_dump_truncated_reply() {
+        let mut extract = |_: &[u8], offset: usize, _: usize| Some(offset);
+        let replies = vec![
+            filler_messages(2, RTM_NEWADDR_VAL),
+            filler_messages(5, RTM_NEWADDR_VAL),
+            filler_messages(1, NLMSG_DONE),
+        ];
+        let outcome = read_dump(replay(replies.clone()), 64, RTM_NEWADDR_VAL, &mut extract);
+        assert!(matches!(outcome.unwrap(), DumpOutcome::Truncated(80)));
+
+        // The retry with a grown buffer sees every reply whole
+        let size = grow_dump_buffer(64, 80).unwrap();
+        assert!(size >= 80);
+        let outcome = read_dump(replay(replies), size, RTM_NEWADDR_VAL, &mut extract).unwrap();
+        assert!(matches!(outcome, DumpOutcome::Complete(found) if found.len() == 7));
+    }
+
+    #[test]
+    fn test_grow_dump_buffer() {
+        assert_eq!(grow_dump_buffer(16384, 20000).unwrap(), 32768);
+        assert_eq!(grow_dump_buffer(16384, 40001).unwrap(), 40004);
+        assert_eq!(
+            grow_dump_buffer(MAX_NETLINK_DUMP_BUFFER_SIZE / 2 + 1, 1024).unwrap(),
+            MAX_NETLINK_DUMP_BUFFER_SIZE
+        );
+        assert!(grow_dump_buffer(16384, MAX_NETLINK_DUMP_BUFFER_SIZE + 1).is_err());
+    }
+
+    #[test]
+    fn test_network_namespace_path() {
+        assert_eq!(
+            network_namespace_path("wan"),
+            PathBuf::from("/run/netns/wan")
+        );
+        assert_eq!(
+            network_namespace_path("/proc/1/ns/net"),
+            PathBuf::from("/proc/1/ns/net")
+        );
+
+        assert_eq!(
+            network_namespace_path("/var/run/netns/foo"),
+            PathBuf::from("/var/run/netns/foo")
+        );
+
+        let err = set_network_namespace("/nonexistent/ns/net").unwrap_err();
+        assert!(format!("{err:#}").contains("/nonexistent/ns/net"));
+        assert!(NETWORK_NAMESPACE.get().is_none());
+    }
+
+    #[test]
+    fn test_enter_network_namespace_leaves_caller_in_place() {
+        let own = || std::fs::read_link("/proc/thread-self/ns/net").ok();
+        let before = own();
+
+        // Not a namespace file: setns fails before any capability check
+        let path = Path::new("/dev/null");
+        let fd = OwnedFd::from(std::fs::File::open(path).unwrap());
+        let mut ran = false;
+        let err = enter_network_namespace(path, &fd, || {
+            ran = true;
+            Ok(())
+        })
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("not a network namespace"));
+        assert!(!ran);
+        assert_eq!(own(), before);
+    }
+
+    #[test]
+    fn test_queue_event_collapses_repeats() {
+        let added = |ip: &str| NetlinkEvent::Ipv6Added(ip.to_string(), None);
+        let mut queue = VecDeque::new();
+        queue_event(&mut queue, added("2001:db8::1"));
+        queue_event(&mut queue, added("2001:db8::1"));
+        queue_event(&mut queue, NetlinkEvent::Ipv6Removed);
+        queue_event(&mut queue, NetlinkEvent::Ipv6Removed);
+        queue_event(&mut queue, added("2001:db8::1"));
+        queue_event(&mut queue, added("2001:db8::2"));
+
+        assert_eq!(
+            queue,
+            [
+                added("2001:db8::1"),
+                NetlinkEvent::Ipv6Removed,
+                added("2001:db8::1"),
+                added("2001:db8::2"),
+            ]
         );
     }
 }
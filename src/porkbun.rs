@@ -0,0 +1,490 @@
+//! Porkbun API client for DNS operations
+//!
+//! This module provides a `DnsProvider` backed by the Porkbun JSON API v3. Every
+//! request is a POST whose JSON body carries the API key and secret key:
+//!
+//! ```text
+//! POST https://api.porkbun.com/api/json/v3/dns/retrieveByNameType/<domain>/AAAA/<subdomain>
+//! {"apikey": "pk1_...", "secretapikey": "sk1_..."}
+//! ```
+//!
+//! The zone ID holds the domain (e.g. `example.com`), `api_token` the API key,
+//! and `secret_key` the secret API key. Failures are answered with
+//! `{"status": "ERROR", "message": "..."}`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use tracing::{debug, warn};
+use zeroize::ZeroizeOnDrop;
+
+use crate::constants::{
+    HTTP_POOL_IDLE_TIMEOUT_SECS, HTTP_STATUS_SERVER_ERROR_MAX, HTTP_STATUS_SERVER_ERROR_MIN,
+    HTTP_STATUS_TOO_MANY_REQUESTS, PORKBUN_API_BASE, PORKBUN_TTL_SECS,
+};
+use crate::dns_provider::{
+    relative_name, DnsProvider, DnsRecord, ErrorKind, MultiRecordPolicy, ProviderError, RecordType,
+    UpsertOptions,
+};
+use crate::metrics::Metrics;
+
+//==============================================================================
+// Types
+//==============================================================================
+
+/// A DNS record as returned by Porkbun (all fields are strings)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+struct PorkbunRecord {
+    /// Record ID
+    id: String,
+    /// Fully qualified record name
+    name: String,
+    /// Record type
+    #[serde(rename = "type")]
+    record_type: String,
+    /// Record value
+    content: String,
+    /// Time-to-live in seconds
+    #[serde(default)]
+    ttl: String,
+}
+
+/// Common shape of Porkbun responses
+#[derive(Debug, Deserialize)]
+struct ApiResponse {
+    /// "SUCCESS" or "ERROR"
+    status: String,
+    /// Error description
+    #[serde(default)]
+    message: Option<String>,
+    /// Records, for retrieve calls
+    #[serde(default)]
+    records: Vec<PorkbunRecord>,
+    /// ID of a created record
+    #[serde(default)]
+    id: Option<serde_json::Value>,
+}
+
+//==============================================================================
+// Client
+//==============================================================================
+
+/// Porkbun API client for DNS operations
+///
+/// Both keys are wrapped in `Zeroizing` to ensure they are securely cleared
+/// from memory when dropped.
+#[derive(ZeroizeOnDrop)]
+pub struct PorkbunClient {
+    /// API key
+    #[zeroize(skip)]
+    api_key: zeroize::Zeroizing<String>,
+    /// Secret API key
+    #[zeroize(skip)]
+    secret_key: zeroize::Zeroizing<String>,
+    /// HTTP client for making requests
+    #[zeroize(skip)]
+    client: reqwest::Client,
+    /// Counters for API requests
+    #[zeroize(skip)]
+    metrics: Arc<Metrics>,
+}
+
+impl PorkbunClient {
+    /// Creates a new Porkbun API client
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - Porkbun API key (`pk1_...`)
+    /// * `secret_key` - Porkbun secret API key (`sk1_...`)
+    /// * `timeout` - HTTP request timeout duration
+    /// * `connect_timeout` - Time allowed to establish a connection
+    /// * `user_agent` - User-Agent header sent with every request
+    /// * `metrics` - Counters updated for every API request
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the client or an error if client creation fails
+    pub fn new(
+        api_key: &str,
+        secret_key: &str,
+        timeout: Duration,
+        connect_timeout: Duration,
+        user_agent: &str,
+        metrics: Arc<Metrics>,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .user_agent(user_agent)
+            .pool_idle_timeout(Duration::from_secs(HTTP_POOL_IDLE_TIMEOUT_SECS))
+            .build()
+            .context("build reqwest client")?;
+
+        Ok(Self {
+            api_key: zeroize::Zeroizing::new(api_key.to_string()),
+            secret_key: zeroize::Zeroizing::new(secret_key.to_string()),
+            client,
+            metrics,
+        })
+    }
+
+    /// Sends an authenticated request and checks the response status
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The API path below `/dns/`
+    /// * `fields` - Extra JSON body fields besides the keys
+    /// * `context` - Context message for errors
+    ///
+    /// # Returns
+    ///
+    /// Returns the parsed response, or an error for a failed request or an
+    /// `ERROR` status
+    async fn send(
+        &self,
+        path: &str,
+        fields: serde_json::Value,
+        context: &str,
+    ) -> Result<ApiResponse> {
+        let mut body = serde_json::json!({
+            "apikey": self.api_key.as_str(),
+            "secretapikey": self.secret_key.as_str(),
+        });
+        if let (Some(body), serde_json::Value::Object(fields)) = (body.as_object_mut(), fields) {
+            body.extend(fields);
+        }
+
+        let url = format!("{}/dns/{}", PORKBUN_API_BASE, path);
+        debug!("POST {}", url);
+        let result = self.client.post(&url).json(&body).send().await;
+        self.metrics.record_provider_request(
+            "POST",
+            result.as_ref().ok().map(|resp| resp.status().as_u16()),
+        );
+        let resp = result.with_context(|| format!("POST request failed: {}", context))?;
+        let status = resp.status().as_u16();
+        let text = resp
+            .text()
+            .await
+            .with_context(|| format!("Failed to read Porkbun response: {}", context))?;
+        let parsed: Option<ApiResponse> = serde_json::from_str(&text).ok();
+
+        match parsed {
+            Some(parsed) if parsed.status == "SUCCESS" => Ok(parsed),
+            _ if status == HTTP_STATUS_TOO_MANY_REQUESTS => bail!(ProviderError::new(
+                ErrorKind::RateLimit,
+                format!(
+                    "Rate limited by Porkbun (429): {}. \
+                     The daemon will automatically retry with exponential backoff.",
+                    context
+                )
+            )),
+            _ if (HTTP_STATUS_SERVER_ERROR_MIN..=HTTP_STATUS_SERVER_ERROR_MAX)
+                .contains(&status) =>
+            {
+                bail!(ProviderError::new(
+                    ErrorKind::Server,
+                    format!(
+                        "Porkbun server error ({}): {}. \
+                         The daemon will automatically retry with exponential backoff.",
+                        status, context
+                    )
+                ))
+            }
+            Some(parsed) => bail!(ProviderError::new(
+                ErrorKind::from_status(status),
+                format!(
+                    "API error ({}): {}: {}",
+                    status,
+                    context,
+                    parsed.message.unwrap_or(parsed.status)
+                )
+            )),
+            None => bail!(ProviderError::new(
+                ErrorKind::Server,
+                format!("API error ({}): {}: unexpected response", status, context)
+            )),
+        }
+    }
+
+    /// Fetches the records of the given type and name
+    async fn retrieve(
+        &self,
+        domain: &str,
+        record_name: &str,
+        record_type: RecordType,
+    ) -> Result<Vec<PorkbunRecord>> {
+        let subdomain = subdomain(domain, record_name)?;
+        let path = format!(
+            "retrieveByNameType/{}/{}/{}",
+            domain, record_type, subdomain
+        );
+        let ctx = format!("GET record '{}' in domain '{}'", record_name, domain);
+        Ok(self.send(&path, serde_json::json!({}), &ctx).await?.records)
+    }
+
+    /// Creates a record, returning its ID
+    async fn create(
+        &self,
+        domain: &str,
+        record_name: &str,
+        record_type: RecordType,
+        content: &str,
+    ) -> Result<String> {
+        let fields = record_fields(domain, record_name, record_type, content)?;
+        let ctx = format!("Create record '{}' in domain '{}'", record_name, domain);
+        let resp = self
+            .send(&format!("create/{}", domain), fields, &ctx)
+            .await?;
+        Ok(match resp.id {
+            Some(serde_json::Value::String(id)) => id,
+            Some(id) => id.to_string(),
+            None => String::new(),
+        })
+    }
+
+    /// Points an existing record at a new address
+    async fn edit(
+        &self,
+        domain: &str,
+        id: &str,
+        record_name: &str,
+        record_type: RecordType,
+        content: &str,
+    ) -> Result<()> {
+        let fields = record_fields(domain, record_name, record_type, content)?;
+        let ctx = format!("Update record '{}' in domain '{}'", record_name, domain);
+        self.send(&format!("edit/{}/{}", domain, id), fields, &ctx)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DnsProvider for PorkbunClient {
+    async fn upsert_record(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+        content: &str,
+        options: UpsertOptions<'_>,
+    ) -> Result<DnsRecord> {
+        let domain = zone_id.trim();
+        let mut records = self.retrieve(domain, record_name, record_type).await?;
+        let record = |id: String, ttl: u64, created: bool| DnsRecord {
+            id,
+            record_type: record_type.as_str().to_string(),
+            name: record_name.to_string(),
+            content: content.to_string(),
+            proxied: false,
+            ttl,
+            created,
+        };
+
+        if records.is_empty() {
+            if !options.allow_create {
+                bail!(
+                    "No {} record exists for '{}' and record creation is disabled. \
+                     Verify the record name is correct, then set allow_create = true \
+                     (or pass --allow-create) to create it.",
+                    record_type,
+                    record_name
+                );
+            }
+            let id = self
+                .create(domain, record_name, record_type, content)
+                .await?;
+            return Ok(record(id, PORKBUN_TTL_SECS, true));
+        }
+
+        let targets = match options.policy {
+            MultiRecordPolicy::Error if records.len() > 1 => {
+                warn!("Multiple {} records found for {}", record_type, record_name);
+                bail!(
+                    "Multiple {} records found for {}. Refusing to update.",
+                    record_type,
+                    record_name
+                );
+            }
+            MultiRecordPolicy::Error | MultiRecordPolicy::UpdateFirst => {
+                let pos = options
+                    .sticky_id
+                    .and_then(|id| records.iter().position(|r| r.id == id))
+                    .unwrap_or(0);
+                vec![records.swap_remove(pos)]
+            }
+            MultiRecordPolicy::UpdateAll => records,
+        };
+
+        for target in &targets {
+            if target.content == content {
+                debug!("Record already matches {}", content);
+                continue;
+            }
+            self.edit(domain, &target.id, record_name, record_type, content)
+                .await?;
+        }
+        let first = &targets[0];
+        Ok(record(
+            first.id.clone(),
+            first.ttl.parse().unwrap_or(PORKBUN_TTL_SECS),
+            false,
+        ))
+    }
+
+    async fn delete_record(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+    ) -> Result<usize> {
+        let domain = zone_id.trim();
+        let records = self.retrieve(domain, record_name, record_type).await?;
+        if records.is_empty() {
+            return Ok(0);
+        }
+        let subdomain = subdomain(domain, record_name)?;
+        let path = format!("deleteByNameType/{}/{}/{}", domain, record_type, subdomain);
+        let ctx = format!("DELETE record '{}' in domain '{}'", record_name, domain);
+        self.send(&path, serde_json::json!({}), &ctx).await?;
+        Ok(records.len())
+    }
+
+    async fn get_records(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+    ) -> Result<Vec<DnsRecord>> {
+        let records = self
+            .retrieve(zone_id.trim(), record_name, record_type)
+            .await?;
+        Ok(records
+            .into_iter()
+            .map(|record| DnsRecord {
+                id: record.id,
+                record_type: record.record_type,
+                name: record.name,
+                content: record.content,
+                proxied: false,
+                ttl: record.ttl.parse().unwrap_or(PORKBUN_TTL_SECS),
+                created: false,
+            })
+            .collect())
+    }
+}
+
+//==============================================================================
+// Helpers
+//==============================================================================
+
+/// Returns the part of a record name below the domain ("" for the apex)
+///
+/// # Arguments
+///
+/// * `domain` - The domain (zone ID), e.g. "example.com"
+/// * `record_name` - The record name, e.g. "home.example.com" or "@"
+///
+/// # Returns
+///
+/// Returns the subdomain, or an error if the record is not in the domain
+pub fn subdomain<'a>(domain: &str, record_name: &'a str) -> Result<&'a str> {
+    match relative_name(domain, record_name) {
+        Some(name) => Ok(name),
+        None => bail!(
+            "Record name '{}' is not in the Porkbun domain '{}'",
+            record_name,
+            domain.trim().trim_end_matches('.')
+        ),
+    }
+}
+
+/// Builds the record fields of a create or edit request
+fn record_fields(
+    domain: &str,
+    record_name: &str,
+    record_type: RecordType,
+    content: &str,
+) -> Result<serde_json::Value> {
+    Ok(serde_json::json!({
+        "name": subdomain(domain, record_name)?,
+        "type": record_type.as_str(),
+        "content": content,
+        "ttl": PORKBUN_TTL_SECS.to_string(),
+    }))
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subdomain() {
+        assert_eq!(
+            subdomain("example.com", "home.example.com").unwrap(),
+            "home"
+        );
+        assert_eq!(subdomain("example.com", "a.b.example.com.").unwrap(), "a.b");
+        assert_eq!(subdomain("example.com", "example.com").unwrap(), "");
+        assert_eq!(subdomain("example.com", "@").unwrap(), "");
+        assert_eq!(
+            subdomain("Example.com", "HOME.example.COM").unwrap(),
+            "HOME"
+        );
+        assert!(subdomain("example.com", "home.example.org").is_err());
+        assert!(subdomain("example.com", "homeexample.com").is_err());
+    }
+
+    #[test]
+    fn test_parse_retrieve_response() {
+        let json = r#"{
+            "status": "SUCCESS",
+            "records": [{
+                "id": "106926659",
+                "name": "home.example.com",
+                "type": "AAAA",
+                "content": "2001:db8::1",
+                "ttl": "600",
+                "prio": "0",
+                "notes": ""
+            }]
+        }"#;
+        let resp: ApiResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.status, "SUCCESS");
+        assert_eq!(resp.records.len(), 1);
+        assert_eq!(resp.records[0].id, "106926659");
+        assert_eq!(resp.records[0].content, "2001:db8::1");
+    }
+
+    #[test]
+    fn test_parse_error_response() {
+        let json = r#"{"status": "ERROR", "message": "Invalid API key. (002)"}"#;
+        let resp: ApiResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.status, "ERROR");
+        assert_eq!(resp.message.as_deref(), Some("Invalid API key. (002)"));
+        assert!(resp.records.is_empty());
+    }
+
+    #[test]
+    fn test_record_fields() {
+        let fields = record_fields(
+            "example.com",
+            "home.example.com",
+            RecordType::Aaaa,
+            "2001:db8::1",
+        )
+        .unwrap();
+        assert_eq!(fields["name"], "home");
+        assert_eq!(fields["type"], "AAAA");
+        assert_eq!(fields["content"], "2001:db8::1");
+        assert_eq!(fields["ttl"], "600");
+    }
+}
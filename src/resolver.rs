@@ -0,0 +1,316 @@
+//! Minimal DNS client for checking published records from outside
+//!
+//! This module sends a single AAAA query over UDP to a public resolver (e.g.
+//! `1.1.1.1`) and returns the addresses in its answer. It is used by the
+//! `external_resolver` check, which confirms that a record resolves to the
+//! published address in practice, not just at the provider's API.
+//!
+//! Only what that check needs is implemented: one question, recursion
+//! desired, no EDNS and no TCP fallback. A truncated answer is an error.
+
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use anyhow::{bail, Context as _, Result};
+use tokio::net::UdpSocket;
+
+use crate::netlink::random_unit;
+
+//==============================================================================
+// Constants
+//==============================================================================
+
+/// Port resolvers are queried on when none is given
+const DNS_PORT: u16 = 53;
+
+// Wire format constants (RFC 1035)
+const HEADER_LEN: usize = 12;
+const TYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+const FLAG_QR: u16 = 0x8000;
+const FLAG_TC: u16 = 0x0200;
+const FLAG_RD: u16 = 0x0100;
+const RCODE_MASK: u16 = 0x000f;
+const RCODE_NXDOMAIN: u16 = 3;
+const MAX_LABEL_LEN: usize = 63;
+const MAX_NAME_LEN: usize = 255;
+/// Largest UDP answer accepted without EDNS
+const MAX_UDP_RESPONSE: usize = 512;
+
+//==============================================================================
+// Query
+//==============================================================================
+
+/// Parses a resolver address: an IP address with an optional port
+///
+/// Accepts `1.1.1.1`, `1.1.1.1:53`, `2606:4700:4700::1111` and
+/// `[2606:4700:4700::1111]:53`; the port defaults to 53.
+///
+/// # Arguments
+///
+/// * `value` - The configured resolver
+///
+/// # Returns
+///
+/// Returns the socket address, or an error if it is not an IP address
+pub fn parse_resolver(value: &str) -> Result<SocketAddr> {
+    let value = value.trim();
+    if let Ok(addr) = value.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    value
+        .parse::<IpAddr>()
+        .map(|ip| SocketAddr::new(ip, DNS_PORT))
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "resolver must be an IP address with an optional port, got: {}",
+                value
+            )
+        })
+}
+
+/// Asks a resolver for the AAAA records of a name
+///
+/// # Arguments
+///
+/// * `resolver` - The resolver to query
+/// * `name` - The DNS name to resolve
+/// * `timeout` - How long to wait for the answer
+///
+/// # Returns
+///
+/// Returns the addresses in the answer (empty if the name has none or does not
+/// exist), or an error if the query fails or the answer is malformed
+pub async fn resolve_aaaa(
+    resolver: SocketAddr,
+    name: &str,
+    timeout: Duration,
+) -> Result<Vec<Ipv6Addr>> {
+    let id = (random_unit() * f64::from(u16::MAX)) as u16;
+    let query = build_query(id, name)?;
+
+    let bind: SocketAddr = if resolver.is_ipv4() {
+        (std::net::Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind).await.context("bind DNS socket")?;
+    socket
+        .connect(resolver)
+        .await
+        .with_context(|| format!("connect to resolver {}", resolver))?;
+    socket.send(&query).await.context("send DNS query")?;
+
+    let mut buf = [0u8; MAX_UDP_RESPONSE];
+    tokio::time::timeout(timeout, async {
+        // Answers to other queries (wrong ID) are skipped, not trusted
+        loop {
+            let n = socket.recv(&mut buf).await.context("receive DNS answer")?;
+            if buf[..n].get(0..2) == Some(&id.to_be_bytes()[..]) {
+                return parse_response(id, &buf[..n]);
+            }
+        }
+    })
+    .await
+    .with_context(|| format!("resolver {} did not answer within {:?}", resolver, timeout))?
+}
+
+/// Builds an AAAA query for `name` with recursion desired
+fn build_query(id: u16, name: &str) -> Result<Vec<u8>> {
+    let mut query = Vec::with_capacity(HEADER_LEN + name.len() + 6);
+    query.extend_from_slice(&id.to_be_bytes());
+    query.extend_from_slice(&FLAG_RD.to_be_bytes());
+    // One question, no answer, authority or additional records
+    query.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+
+    let name = name.trim_end_matches('.');
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        bail!("invalid DNS name: {:?}", name);
+    }
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > MAX_LABEL_LEN {
+            bail!("invalid DNS name: {:?}", name);
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&TYPE_AAAA.to_be_bytes());
+    query.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(query)
+}
+
+//==============================================================================
+// Response Parsing
+//==============================================================================
+
+/// Extracts the AAAA addresses from a response to query `id`
+///
+/// CNAME records in the answer are skipped; the addresses of their target are
+/// returned, as a stub resolver would.
+fn parse_response(id: u16, data: &[u8]) -> Result<Vec<Ipv6Addr>> {
+    if data.len() < HEADER_LEN {
+        bail!("DNS answer too short");
+    }
+    let u16_at = |offset: usize| -> Result<u16> {
+        data.get(offset..offset + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .context("DNS answer truncated")
+    };
+
+    if u16_at(0)? != id {
+        bail!("DNS answer ID does not match the query");
+    }
+    let flags = u16_at(2)?;
+    if flags & FLAG_QR == 0 {
+        bail!("DNS answer is not a response");
+    }
+    if flags & FLAG_TC != 0 {
+        bail!("DNS answer truncated (more records than fit in UDP)");
+    }
+    match flags & RCODE_MASK {
+        0 => {}
+        RCODE_NXDOMAIN => return Ok(Vec::new()),
+        rcode => bail!("resolver answered with error code {}", rcode),
+    }
+
+    let questions = u16_at(4)?;
+    let answers = u16_at(6)?;
+    let mut offset = HEADER_LEN;
+    for _ in 0..questions {
+        // Name, type and class
+        offset = skip_name(data, offset)? + 4;
+    }
+
+    let mut addrs = Vec::new();
+    for _ in 0..answers {
+        offset = skip_name(data, offset)?;
+        let record_type = u16_at(offset)?;
+        let class = u16_at(offset + 2)?;
+        let rdlength = usize::from(u16_at(offset + 8)?);
+        let rdata = data
+            .get(offset + 10..offset + 10 + rdlength)
+            .context("DNS answer truncated")?;
+        if record_type == TYPE_AAAA && class == CLASS_IN {
+            let octets: [u8; 16] = rdata.try_into().context("malformed AAAA record")?;
+            addrs.push(Ipv6Addr::from(octets));
+        }
+        offset += 10 + rdlength;
+    }
+    Ok(addrs)
+}
+
+/// Returns the offset just past the (possibly compressed) name at `offset`
+fn skip_name(data: &[u8], mut offset: usize) -> Result<usize> {
+    for _ in 0..=MAX_NAME_LEN {
+        let len = *data.get(offset).context("DNS answer truncated")?;
+        match len {
+            0 => return Ok(offset + 1),
+            // A compression pointer ends the name
+            l if l & 0xc0 == 0xc0 => {
+                data.get(offset + 1).context("DNS answer truncated")?;
+                return Ok(offset + 2);
+            }
+            l if l & 0xc0 != 0 => bail!("malformed DNS name"),
+            l => offset += 1 + usize::from(l),
+        }
+    }
+    bail!("malformed DNS name")
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A response to `query` carrying `answers` as (type, rdata) records whose
+    /// owner is a pointer to the question name
+    fn response(query: &[u8], rcode: u16, answers: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut resp = query.to_vec();
+        resp[2..4].copy_from_slice(&(FLAG_QR | FLAG_RD | 0x0080 | rcode).to_be_bytes());
+        resp[6..8].copy_from_slice(&(answers.len() as u16).to_be_bytes());
+        for (record_type, rdata) in answers {
+            resp.extend_from_slice(&[0xc0, HEADER_LEN as u8]);
+            resp.extend_from_slice(&record_type.to_be_bytes());
+            resp.extend_from_slice(&CLASS_IN.to_be_bytes());
+            resp.extend_from_slice(&300u32.to_be_bytes());
+            resp.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+            resp.extend_from_slice(rdata);
+        }
+        resp
+    }
+
+    #[test]
+    fn test_parse_resolver() {
+        assert_eq!(
+            parse_resolver("1.1.1.1").unwrap(),
+            "1.1.1.1:53".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            parse_resolver("8.8.8.8:5353").unwrap(),
+            "8.8.8.8:5353".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            parse_resolver("2606:4700:4700::1111").unwrap(),
+            "[2606:4700:4700::1111]:53".parse::<SocketAddr>().unwrap()
+        );
+        assert!(parse_resolver("[2606:4700:4700::1111]:853").is_ok());
+        assert!(parse_resolver("one.one.one.one").is_err());
+    }
+
+    #[test]
+    fn test_build_query() {
+        let query = build_query(0x1234, "home.example.com.").unwrap();
+        assert_eq!(&query[..4], &[0x12, 0x34, 0x01, 0x00]);
+        assert_eq!(&query[4..6], &[0, 1]);
+        assert_eq!(&query[HEADER_LEN..HEADER_LEN + 5], b"\x04home");
+        assert_eq!(&query[query.len() - 5..], &[0, 0, 28, 0, 1]);
+
+        assert!(build_query(1, "").is_err());
+        assert!(build_query(1, "a..example.com").is_err());
+        assert!(build_query(1, &format!("{}.com", "a".repeat(64))).is_err());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let query = build_query(7, "home.example.com").unwrap();
+        let addr: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        let cname = b"\x03www\xc0\x0c";
+        let resp = response(
+            &query,
+            0,
+            &[(5, cname.as_slice()), (TYPE_AAAA, &addr.octets())],
+        );
+        assert_eq!(parse_response(7, &resp).unwrap(), [addr]);
+
+        // No such name, or a name without AAAA records
+        let resp = response(&query, RCODE_NXDOMAIN, &[]);
+        assert!(parse_response(7, &resp).unwrap().is_empty());
+        let resp = response(&query, 0, &[]);
+        assert!(parse_response(7, &resp).unwrap().is_empty());
+
+        // SERVFAIL
+        let resp = response(&query, 2, &[]);
+        assert!(parse_response(7, &resp).is_err());
+        // Wrong ID, cut-off record
+        let resp = response(&query, 0, &[(TYPE_AAAA, &addr.octets())]);
+        assert!(parse_response(8, &resp).is_err());
+        assert!(parse_response(7, &resp[..resp.len() - 4]).is_err());
+        // Truncated over UDP
+        let mut resp = response(&query, 0, &[]);
+        resp[2] |= (FLAG_TC >> 8) as u8;
+        assert!(parse_response(7, &resp).is_err());
+    }
+
+    #[test]
+    fn test_skip_name() {
+        let data = b"\x04home\x07example\x03com\x00\xc0\x00";
+        assert_eq!(skip_name(data, 0).unwrap(), 18);
+        assert_eq!(skip_name(data, 18).unwrap(), 20);
+        assert!(skip_name(b"\x04ho", 0).is_err());
+        assert!(skip_name(b"\x40", 0).is_err());
+    }
+}
@@ -0,0 +1,786 @@
+//! Amazon Route 53 client for DNS operations
+//!
+//! This module provides a `DnsProvider` backed by the Route 53 REST API. Records
+//! are written with a `ChangeResourceRecordSets` UPSERT, and requests are signed
+//! with AWS Signature Version 4 over the existing reqwest client.
+//!
+//! # Credentials
+//!
+//! Credentials are read from the standard AWS environment variables:
+//! `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and, for temporary credentials,
+//! `AWS_SESSION_TOKEN`. The zone ID is the hosted zone ID (e.g. `Z0123456789ABC`).
+//!
+//! # Differences from Cloudflare
+//!
+//! Route 53 keeps all addresses of a name in one record set, and an UPSERT
+//! replaces the whole set. So:
+//! - `multi_record` defaults to `UpdateAll` (see `Config::multi_record`)
+//! - `UpdateFirst` and `UpdateAll` both replace the set with the new address
+//! - An explicit `MultiRecordPolicy::Error` refuses to touch a set with
+//!   several values
+//! - The TTL of an existing set is kept unless `reconcile_fields` includes `ttl`
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use ring::{digest, hmac};
+use tracing::{debug, warn};
+use urlencoding::encode;
+use zeroize::ZeroizeOnDrop;
+
+use crate::constants::{
+    ENV_AWS_ACCESS_KEY_ID, ENV_AWS_SECRET_ACCESS_KEY, ENV_AWS_SESSION_TOKEN,
+    HTTP_POOL_IDLE_TIMEOUT_SECS, HTTP_STATUS_FORBIDDEN, HTTP_STATUS_SERVER_ERROR_MAX,
+    HTTP_STATUS_SERVER_ERROR_MIN, ROUTE53_API_HOST, ROUTE53_API_VERSION, ROUTE53_REGION,
+    ROUTE53_SERVICE, ROUTE53_TTL_SECS,
+};
+use crate::dns_provider::{
+    DnsProvider, DnsRecord, ErrorKind, MultiRecordPolicy, ProviderError, RecordType, UpsertOptions,
+    ZoneNotFound,
+};
+use crate::metrics::Metrics;
+
+//==============================================================================
+// Types
+//==============================================================================
+
+/// AWS credentials used to sign requests
+///
+/// The secret key and session token are wrapped in `Zeroizing` to ensure they
+/// are securely cleared from memory when dropped.
+#[derive(ZeroizeOnDrop)]
+pub struct AwsCredentials {
+    /// Access key ID
+    #[zeroize(skip)]
+    access_key_id: String,
+    /// Secret access key
+    #[zeroize(skip)]
+    secret_access_key: zeroize::Zeroizing<String>,
+    /// Session token of temporary credentials
+    #[zeroize(skip)]
+    session_token: Option<zeroize::Zeroizing<String>>,
+}
+
+impl AwsCredentials {
+    /// Reads the credentials from the standard AWS environment variables
+    ///
+    /// # Returns
+    ///
+    /// Returns the credentials, or an error if the key ID or secret is missing
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let access_key_id = var(ENV_AWS_ACCESS_KEY_ID)
+            .with_context(|| format!("Missing {}", ENV_AWS_ACCESS_KEY_ID))?;
+        let secret_access_key = var(ENV_AWS_SECRET_ACCESS_KEY)
+            .with_context(|| format!("Missing {}", ENV_AWS_SECRET_ACCESS_KEY))?;
+
+        Ok(Self {
+            access_key_id,
+            secret_access_key: zeroize::Zeroizing::new(secret_access_key),
+            session_token: var(ENV_AWS_SESSION_TOKEN).map(zeroize::Zeroizing::new),
+        })
+    }
+}
+
+/// A resource record set as stored by Route 53
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RecordSet {
+    /// Fully qualified name, with a trailing dot
+    name: String,
+    /// Record type ("AAAA" or "A")
+    record_type: String,
+    /// Time-to-live in seconds (0 for alias records)
+    ttl: u64,
+    /// Record values (addresses)
+    values: Vec<String>,
+}
+
+/// The parts of a request covered by the SigV4 signature
+struct CanonicalRequest<'a> {
+    /// HTTP method
+    method: &'static str,
+    /// Host the request is sent to
+    host: &'a str,
+    /// Absolute request path
+    path: &'a str,
+    /// Query parameters, unencoded
+    query: &'a [(&'a str, &'a str)],
+    /// Request body
+    payload: &'a [u8],
+}
+
+//==============================================================================
+// Client
+//==============================================================================
+
+/// Route 53 API client for DNS operations
+pub struct Route53Client {
+    /// Credentials used to sign every request
+    credentials: AwsCredentials,
+    /// HTTP client for making requests
+    client: reqwest::Client,
+    /// Counters for API requests
+    metrics: Arc<Metrics>,
+}
+
+impl Route53Client {
+    /// Creates a new Route 53 client
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials` - AWS credentials with `route53:ChangeResourceRecordSets`
+    ///   and `route53:ListResourceRecordSets` permissions
+    /// * `timeout` - HTTP request timeout duration
+    /// * `connect_timeout` - Time allowed to establish a connection
+    /// * `user_agent` - User-Agent header sent with every request
+    /// * `metrics` - Counters updated for every API request
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the client or an error if client creation fails
+    pub fn new(
+        credentials: AwsCredentials,
+        timeout: Duration,
+        connect_timeout: Duration,
+        user_agent: &str,
+        metrics: Arc<Metrics>,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .user_agent(user_agent)
+            .pool_idle_timeout(Duration::from_secs(HTTP_POOL_IDLE_TIMEOUT_SECS))
+            .build()
+            .context("build reqwest client")?;
+
+        Ok(Self {
+            credentials,
+            client,
+            metrics,
+        })
+    }
+
+    /// Sends a signed request and counts it by method and status class
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request to sign and send
+    /// * `context` - Context message for errors
+    ///
+    /// # Returns
+    ///
+    /// Returns the response body, or an error for a failed request or an error status
+    async fn send(&self, request: &CanonicalRequest<'_>, context: &str) -> Result<String> {
+        let now = Utc::now();
+        let authorization =
+            request.authorization(&self.credentials, ROUTE53_REGION, ROUTE53_SERVICE, now);
+        let mut url = format!("https://{}{}", request.host, request.path);
+        if !request.query.is_empty() {
+            url.push('?');
+            url.push_str(&request.canonical_query());
+        }
+
+        let method = reqwest::Method::from_bytes(request.method.as_bytes())
+            .context("Invalid HTTP method")?;
+        let mut builder = self
+            .client
+            .request(method, &url)
+            .header("x-amz-date", amz_date(now))
+            .header("Authorization", authorization)
+            .body(request.payload.to_vec());
+        if let Some(token) = &self.credentials.session_token {
+            builder = builder.header("x-amz-security-token", token.as_str());
+        }
+
+        debug!("{} {}", request.method, url);
+        let result = builder.send().await;
+        self.metrics.record_provider_request(
+            request.method,
+            result.as_ref().ok().map(|resp| resp.status().as_u16()),
+        );
+        let resp =
+            result.with_context(|| format!("{} request failed: {}", request.method, context))?;
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .with_context(|| format!("Failed to read Route 53 response: {}", context))?;
+        check_status(status, &body, context)?;
+        Ok(body)
+    }
+
+    /// Looks up the record set of the given name and type
+    async fn get_record_set(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+    ) -> Result<Option<RecordSet>> {
+        let path = format!(
+            "/{}/hostedzone/{}/rrset",
+            ROUTE53_API_VERSION,
+            hosted_zone_id(zone_id)
+        );
+        let name = fqdn(record_name);
+        let query = [
+            ("maxitems", "1"),
+            ("name", name.as_str()),
+            ("type", record_type.as_str()),
+        ];
+        let request = CanonicalRequest {
+            method: "GET",
+            host: ROUTE53_API_HOST,
+            path: &path,
+            query: &query,
+            payload: b"",
+        };
+        let ctx = format!("GET record '{}' in zone '{}'", record_name, zone_id);
+        let body = self.send(&request, &ctx).await?;
+
+        // The listing starts at the requested name and type but may return the next set
+        Ok(parse_record_sets(&body).into_iter().find(|set| {
+            set.record_type == record_type.as_str() && set.name.eq_ignore_ascii_case(&name)
+        }))
+    }
+
+    /// Applies one change (UPSERT or DELETE) to a record set
+    async fn change_record_set(&self, zone_id: &str, action: &str, set: &RecordSet) -> Result<()> {
+        let path = format!(
+            "/{}/hostedzone/{}/rrset/",
+            ROUTE53_API_VERSION,
+            hosted_zone_id(zone_id)
+        );
+        let payload = change_batch_xml(action, set);
+        let request = CanonicalRequest {
+            method: "POST",
+            host: ROUTE53_API_HOST,
+            path: &path,
+            query: &[],
+            payload: payload.as_bytes(),
+        };
+        let ctx = format!("{} record '{}' in zone '{}'", action, set.name, zone_id);
+        self.send(&request, &ctx).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DnsProvider for Route53Client {
+    async fn upsert_record(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+        content: &str,
+        options: UpsertOptions<'_>,
+    ) -> Result<DnsRecord> {
+        let existing = self
+            .get_record_set(zone_id, record_name, record_type)
+            .await?;
+        let ttl = match &existing {
+            Some(set) if set.ttl > 0 && !options.reconcile.ttl => set.ttl,
+            _ => ROUTE53_TTL_SECS,
+        };
+        let record = |ttl: u64, created: bool| DnsRecord {
+            id: fqdn(record_name),
+            record_type: record_type.as_str().to_string(),
+            name: record_name.to_string(),
+            content: content.to_string(),
+            proxied: false,
+            ttl,
+            created,
+        };
+
+        match &existing {
+            Some(set) if set.values.len() > 1 => {
+                if options.policy == MultiRecordPolicy::Error {
+                    warn!("Multiple {} records found for {}", record_type, record_name);
+                    bail!(
+                        "Multiple {} records found for {}. Refusing to update.",
+                        record_type,
+                        record_name
+                    );
+                }
+                warn!(
+                    "Replacing {} {} values of {} with {}",
+                    set.values.len(),
+                    record_type,
+                    record_name,
+                    content
+                );
+            }
+            Some(set) if set.values == [content] && set.ttl == ttl => {
+                debug!("Record already matches {}", content);
+                return Ok(record(ttl, false));
+            }
+            Some(_) => {}
+            None if !options.allow_create => {
+                bail!(
+                    "No {} record exists for '{}' and record creation is disabled. \
+                     Verify the record name is correct, then set allow_create = true \
+                     (or pass --allow-create) to create it.",
+                    record_type,
+                    record_name
+                );
+            }
+            None => {}
+        }
+
+        let set = RecordSet {
+            name: fqdn(record_name),
+            record_type: record_type.as_str().to_string(),
+            ttl,
+            values: vec![content.to_string()],
+        };
+        self.change_record_set(zone_id, "UPSERT", &set).await?;
+        Ok(record(ttl, existing.is_none()))
+    }
+
+    async fn delete_record(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+    ) -> Result<usize> {
+        // A DELETE has to name the set exactly as it is stored
+        match self
+            .get_record_set(zone_id, record_name, record_type)
+            .await?
+        {
+            Some(set) => {
+                self.change_record_set(zone_id, "DELETE", &set).await?;
+                Ok(set.values.len())
+            }
+            None => Ok(0),
+        }
+    }
+
+    async fn get_records(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+    ) -> Result<Vec<DnsRecord>> {
+        let set = self
+            .get_record_set(zone_id, record_name, record_type)
+            .await?;
+        Ok(set
+            .map(|set| {
+                set.values
+                    .iter()
+                    .map(|value| DnsRecord {
+                        id: set.name.clone(),
+                        record_type: set.record_type.clone(),
+                        name: record_name.to_string(),
+                        content: value.clone(),
+                        proxied: false,
+                        ttl: set.ttl,
+                        created: false,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
+
+//==============================================================================
+// SigV4
+//==============================================================================
+
+impl CanonicalRequest<'_> {
+    /// Returns the query string with sorted, percent-encoded parameters
+    fn canonical_query(&self) -> String {
+        let mut pairs: Vec<(String, String)> = self
+            .query
+            .iter()
+            .map(|(k, v)| (encode(k).into_owned(), encode(v).into_owned()))
+            .collect();
+        pairs.sort();
+        pairs
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// Computes the `Authorization` header value for this request
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials` - The credentials to sign with
+    /// * `region` - The AWS region of the endpoint
+    /// * `service` - The AWS service name
+    /// * `now` - The request time, also sent as `x-amz-date`
+    fn authorization(
+        &self,
+        credentials: &AwsCredentials,
+        region: &str,
+        service: &str,
+        now: DateTime<Utc>,
+    ) -> String {
+        let amz_date = amz_date(now);
+        let date = &amz_date[..8];
+
+        let mut headers = format!("host:{}\nx-amz-date:{}\n", self.host, amz_date);
+        let mut signed_headers = "host;x-amz-date".to_string();
+        if let Some(token) = &credentials.session_token {
+            headers.push_str(&format!("x-amz-security-token:{}\n", token.as_str()));
+            signed_headers.push_str(";x-amz-security-token");
+        }
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            self.method,
+            self.path,
+            self.canonical_query(),
+            headers,
+            signed_headers,
+            hex(digest::digest(&digest::SHA256, self.payload).as_ref())
+        );
+
+        let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(digest::digest(&digest::SHA256, canonical_request.as_bytes()).as_ref())
+        );
+        let key = signing_key(&credentials.secret_access_key, date, region, service);
+        let signature = hex(hmac_sha256(&key, string_to_sign.as_bytes()).as_ref());
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credentials.access_key_id, scope, signed_headers, signature
+        )
+    }
+}
+
+/// Derives the SigV4 signing key for a date, region and service
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+/// Computes HMAC-SHA256 of `data` with `key`
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::sign(&key, data).as_ref().to_vec()
+}
+
+/// Formats a time as an `x-amz-date` timestamp (e.g. 20150830T123600Z)
+fn amz_date(now: DateTime<Utc>) -> String {
+    now.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Encodes bytes as lowercase hex
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+//==============================================================================
+// Helpers
+//==============================================================================
+
+/// Returns the fully qualified form of a record name (with a trailing dot)
+fn fqdn(record_name: &str) -> String {
+    format!("{}.", record_name.trim().trim_end_matches('.'))
+}
+
+/// Strips the `/hostedzone/` prefix the console and CLI show zone IDs with
+fn hosted_zone_id(zone_id: &str) -> &str {
+    zone_id.trim().trim_start_matches("/hostedzone/")
+}
+
+/// Maps a Route 53 error response to a descriptive error
+fn check_status(status: StatusCode, body: &str, context: &str) -> Result<()> {
+    if status.is_success() {
+        return Ok(());
+    }
+    let code = xml_element(body, "Code").unwrap_or_default();
+    let message = xml_unescape(xml_element(body, "Message").unwrap_or_default());
+    match status.as_u16() {
+        _ if code == "Throttling" || code == "PriorRequestNotComplete" => {
+            bail!(ProviderError::new(
+                ErrorKind::RateLimit,
+                format!(
+                    "Rate limited by Route 53 ({}): {}. \
+                 The daemon will automatically retry with exponential backoff.",
+                    code, context
+                )
+            ))
+        }
+        HTTP_STATUS_FORBIDDEN => bail!(ProviderError::new(
+            ErrorKind::Auth,
+            format!(
+                "API error: Permission denied (403): {}. Please verify the AWS credentials \
+                 allow route53:ListResourceRecordSets and route53:ChangeResourceRecordSets. \
+                 Details: {} {}",
+                context, code, message
+            )
+        )),
+        code_num
+            if (HTTP_STATUS_SERVER_ERROR_MIN..=HTTP_STATUS_SERVER_ERROR_MAX)
+                .contains(&code_num) =>
+        {
+            bail!(ProviderError::new(
+                ErrorKind::Server,
+                format!(
+                    "Route 53 server error ({}): {}. \
+                     The daemon will automatically retry with exponential backoff.",
+                    code_num, context
+                )
+            ))
+        }
+        code_num => {
+            let err = anyhow::Error::new(ProviderError::new(
+                ErrorKind::from_status(code_num),
+                format!(
+                    "API error ({}): {}: {} {}",
+                    code_num, context, code, message
+                ),
+            ));
+            Err(if code == "NoSuchHostedZone" {
+                err.context(ZoneNotFound)
+            } else {
+                err
+            })
+        }
+    }
+}
+
+/// Builds a `ChangeResourceRecordSetsRequest` with a single change
+fn change_batch_xml(action: &str, set: &RecordSet) -> String {
+    let values: String = set
+        .values
+        .iter()
+        .map(|v| {
+            format!(
+                "<ResourceRecord><Value>{}</Value></ResourceRecord>",
+                xml_escape(v)
+            )
+        })
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <ChangeResourceRecordSetsRequest xmlns=\"https://route53.amazonaws.com/doc/{}/\">\
+         <ChangeBatch><Changes><Change><Action>{}</Action><ResourceRecordSet>\
+         <Name>{}</Name><Type>{}</Type><TTL>{}</TTL><ResourceRecords>{}</ResourceRecords>\
+         </ResourceRecordSet></Change></Changes></ChangeBatch>\
+         </ChangeResourceRecordSetsRequest>",
+        ROUTE53_API_VERSION,
+        action,
+        xml_escape(&set.name),
+        set.record_type,
+        set.ttl,
+        values
+    )
+}
+
+/// Parses the record sets of a `ListResourceRecordSetsResponse`
+fn parse_record_sets(xml: &str) -> Vec<RecordSet> {
+    xml_elements(xml, "ResourceRecordSet")
+        .into_iter()
+        .map(|set| RecordSet {
+            name: xml_unescape(xml_element(set, "Name").unwrap_or_default()),
+            record_type: xml_element(set, "Type").unwrap_or_default().to_string(),
+            ttl: xml_element(set, "TTL")
+                .and_then(|ttl| ttl.parse().ok())
+                .unwrap_or(0),
+            values: xml_elements(set, "Value")
+                .into_iter()
+                .map(xml_unescape)
+                .collect(),
+        })
+        .collect()
+}
+
+/// Returns the contents of every `<tag>...</tag>` element, in order
+///
+/// This is only meant for the small, attribute-free elements of Route 53
+/// responses; it does not handle nesting of the same tag.
+fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut elements = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        match after.find(&close) {
+            Some(end) => {
+                elements.push(&after[..end]);
+                rest = &after[end + close.len()..];
+            }
+            None => break,
+        }
+    }
+    elements
+}
+
+/// Returns the contents of the first `<tag>...</tag>` element
+fn xml_element<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    xml_elements(xml, tag).into_iter().next()
+}
+
+/// Escapes text for use in an XML element
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Reverses the predefined XML entity escapes
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn example_credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: zeroize::Zeroizing::new(
+                "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            ),
+            session_token: None,
+        }
+    }
+
+    #[test]
+    fn test_signing_key_matches_aws_example() {
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex(&key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_authorization_matches_aws_test_suite() {
+        // "get-vanilla" from the AWS SigV4 test suite
+        let request = CanonicalRequest {
+            method: "GET",
+            host: "example.amazonaws.com",
+            path: "/",
+            query: &[],
+            payload: b"",
+        };
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        assert_eq!(
+            request.authorization(&example_credentials(), "us-east-1", "service", now),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_canonical_query_is_sorted_and_encoded() {
+        let request = CanonicalRequest {
+            method: "GET",
+            host: ROUTE53_API_HOST,
+            path: "/2013-04-01/hostedzone/Z1/rrset",
+            query: &[
+                ("type", "AAAA"),
+                ("name", "*.example.com."),
+                ("maxitems", "1"),
+            ],
+            payload: b"",
+        };
+        assert_eq!(
+            request.canonical_query(),
+            "maxitems=1&name=%2A.example.com.&type=AAAA"
+        );
+    }
+
+    #[test]
+    fn test_parse_record_sets() {
+        let xml = r#"<?xml version="1.0"?>
+<ListResourceRecordSetsResponse xmlns="https://route53.amazonaws.com/doc/2013-04-01/">
+<ResourceRecordSets>
+<ResourceRecordSet><Name>home.example.com.</Name><Type>AAAA</Type><TTL>300</TTL>
+<ResourceRecords><ResourceRecord><Value>2001:db8::1</Value></ResourceRecord>
+<ResourceRecord><Value>2001:db8::2</Value></ResourceRecord></ResourceRecords>
+</ResourceRecordSet>
+</ResourceRecordSets><IsTruncated>false</IsTruncated><MaxItems>1</MaxItems>
+</ListResourceRecordSetsResponse>"#;
+
+        assert_eq!(
+            parse_record_sets(xml),
+            [RecordSet {
+                name: "home.example.com.".to_string(),
+                record_type: "AAAA".to_string(),
+                ttl: 300,
+                values: vec!["2001:db8::1".to_string(), "2001:db8::2".to_string()],
+            }]
+        );
+        assert!(parse_record_sets("<ResourceRecordSets></ResourceRecordSets>").is_empty());
+    }
+
+    #[test]
+    fn test_change_batch_xml() {
+        let set = RecordSet {
+            name: fqdn("home.example.com"),
+            record_type: "AAAA".to_string(),
+            ttl: 300,
+            values: vec!["2001:db8::1".to_string()],
+        };
+        let xml = change_batch_xml("UPSERT", &set);
+        assert!(xml.contains("<Action>UPSERT</Action>"));
+        assert!(xml.contains("<Name>home.example.com.</Name><Type>AAAA</Type><TTL>300</TTL>"));
+        assert!(xml.contains("<ResourceRecord><Value>2001:db8::1</Value></ResourceRecord>"));
+    }
+
+    #[test]
+    fn test_check_status() {
+        assert!(check_status(StatusCode::OK, "", "ctx").is_ok());
+
+        let throttled = "<ErrorResponse><Error><Type>Sender</Type><Code>Throttling</Code>\
+                         <Message>Rate exceeded</Message></Error></ErrorResponse>";
+        let err = check_status(StatusCode::BAD_REQUEST, throttled, "ctx").unwrap_err();
+        assert!(format!("{err}").contains("Rate limited"));
+
+        let denied = "<ErrorResponse><Error><Code>AccessDenied</Code>\
+                      <Message>not authorized</Message></Error></ErrorResponse>";
+        let err = check_status(StatusCode::FORBIDDEN, denied, "ctx").unwrap_err();
+        assert!(format!("{err}").contains("AccessDenied not authorized"));
+        assert!(err.downcast_ref::<ZoneNotFound>().is_none());
+
+        let missing = "<ErrorResponse><Error><Code>NoSuchHostedZone</Code>\
+                       <Message>No hosted zone found</Message></Error></ErrorResponse>";
+        let err = check_status(StatusCode::NOT_FOUND, missing, "ctx").unwrap_err();
+        assert!(err.downcast_ref::<ZoneNotFound>().is_some());
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Validation);
+    }
+
+    #[test]
+    fn test_zone_and_name_normalization() {
+        assert_eq!(
+            hosted_zone_id("/hostedzone/Z0123456789ABC"),
+            "Z0123456789ABC"
+        );
+        assert_eq!(hosted_zone_id("Z0123456789ABC"), "Z0123456789ABC");
+        assert_eq!(fqdn("home.example.com"), "home.example.com.");
+        assert_eq!(fqdn("home.example.com."), "home.example.com.");
+    }
+}
@@ -0,0 +1,307 @@
+//! Last-known record state kept across restarts
+//!
+//! With `state_file` set, the daemon writes the address and record ID of every
+//! synced record to that file after it changes, and reads it back at startup.
+//! Restored records start out `Synced`, so if the detected address is still the
+//! one published, the initial sync makes no API request at all.
+//!
+//! The file is only a cache: a missing, unreadable or corrupt file is logged
+//! and ignored, and every record then starts `Unknown` as without it.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::daemon::{AppState, RecordState, RecordStatus};
+use crate::dns_provider::ProviderType;
+
+//==============================================================================
+// Saved State
+//==============================================================================
+
+/// Contents of `state_file`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedState {
+    /// Provider the records were synced with; a file written for another
+    /// provider is not restored
+    pub provider: String,
+    /// Synced AAAA records, keyed by record name
+    #[serde(default)]
+    pub records: BTreeMap<String, SavedRecord>,
+    /// Synced A records, keyed by record name
+    #[serde(default)]
+    pub a_records: BTreeMap<String, SavedRecord>,
+}
+
+/// A synced record as kept in `state_file`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedRecord {
+    /// Address the record was last synced to
+    pub ip: String,
+    /// ID of the record last written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record_id: Option<String>,
+    /// Time of the last successful sync (UTC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_sync: Option<DateTime<Utc>>,
+}
+
+impl SavedState {
+    /// Takes the synced records out of the daemon state
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The daemon state
+    /// * `provider` - The configured DNS provider
+    pub fn capture(state: &AppState, provider: ProviderType) -> Self {
+        Self {
+            provider: provider.name().to_string(),
+            records: saved_records(&state.records),
+            a_records: saved_records(&state.a_records),
+        }
+    }
+
+    /// Marks the saved records as synced in the daemon state
+    ///
+    /// Only records that are still configured are restored, and nothing is
+    /// if the file was written for another provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The daemon state, fresh from [`AppState::new`]
+    /// * `provider` - The configured DNS provider
+    /// * `records` - The configured AAAA record names
+    /// * `a_records` - The configured A record names (empty without `enable_ipv4`)
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of records restored
+    pub fn restore(
+        &self,
+        state: &mut AppState,
+        provider: ProviderType,
+        records: &[String],
+        a_records: &[String],
+    ) -> usize {
+        if self.provider != provider.name() {
+            info!(
+                "State file was written for provider {}; not restoring it",
+                self.provider
+            );
+            return 0;
+        }
+        let mut restored = 0;
+        for name in records {
+            if let Some(saved) = self.records.get(name) {
+                saved.restore(state.record_mut(name));
+                restored += 1;
+            }
+        }
+        for name in a_records {
+            if let Some(saved) = self.a_records.get(name) {
+                saved.restore(state.a_records.entry(name.clone()).or_default());
+                restored += 1;
+            }
+        }
+        restored
+    }
+}
+
+impl SavedRecord {
+    /// Sets a record's status to what was saved
+    ///
+    /// The monotonic sync time is set back by the wall-clock age of the saved
+    /// sync, so `max_sync_age` and `force_update_interval` keep counting from
+    /// the sync before the restart.
+    fn restore(&self, status: &mut RecordStatus) {
+        status.state = RecordState::Synced(self.ip.clone());
+        status.record_id = self.record_id.clone();
+        status.last_sync = self.last_sync;
+        status.last_sync_at = self.last_sync.map(|last_sync| {
+            let age = (Utc::now() - last_sync).to_std().unwrap_or_default();
+            let now = Instant::now();
+            now.checked_sub(age).unwrap_or(now)
+        });
+    }
+}
+
+/// Returns the synced records of a state map
+fn saved_records(records: &BTreeMap<String, RecordStatus>) -> BTreeMap<String, SavedRecord> {
+    records
+        .iter()
+        .filter_map(|(name, status)| match &status.state {
+            RecordState::Synced(ip) => Some((
+                name.clone(),
+                SavedRecord {
+                    ip: ip.clone(),
+                    record_id: status.record_id.clone(),
+                    last_sync: status.last_sync,
+                },
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+//==============================================================================
+// File Access
+//==============================================================================
+
+/// Reads `state_file`
+///
+/// # Arguments
+///
+/// * `path` - The state file
+///
+/// # Returns
+///
+/// Returns the saved state, or `None` if the file doesn't exist or can't be
+/// read or parsed (logged as a warning)
+pub fn load(path: &Path) -> Option<SavedState> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            warn!("State file {} not read: {}", path.display(), e);
+            return None;
+        }
+    };
+    match serde_json::from_str(&text) {
+        Ok(saved) => Some(saved),
+        Err(e) => {
+            warn!(
+                "State file {} is corrupt and was ignored: {}",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Writes `state_file`
+///
+/// The file is written next to its final path and renamed into place, so a
+/// crash mid-write never leaves a partial file behind.
+///
+/// # Arguments
+///
+/// * `path` - The state file
+/// * `saved` - The state to write
+///
+/// # Returns
+///
+/// Returns `Ok(())` or an error if the file cannot be written
+pub fn save(path: &Path, saved: &SavedState) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Invalid state_file path: {}", path.display()))?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let mut text = serde_json::to_string_pretty(saved).context("Failed to encode state")?;
+    text.push('\n');
+    std::fs::write(&tmp, text).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| {
+        let _ = std::fs::remove_file(&tmp);
+        format!("Failed to move state into {}", path.display())
+    })
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_state_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        assert_eq!(load(&path), None);
+
+        let mut state = AppState::new(&names(&["a.example.com", "b.example.com"]));
+        let status = state.record_mut("a.example.com");
+        status.mark_synced("2001:db8::1".to_string());
+        status.record_id = Some("rec-a".to_string());
+        state.record_mut("b.example.com").mark_error(
+            1,
+            crate::dns_provider::ErrorKind::Network,
+            None,
+        );
+        let saved = SavedState::capture(&state, ProviderType::Cloudflare);
+        assert_eq!(saved.records.len(), 1);
+        save(&path, &saved).unwrap();
+        assert_eq!(load(&path), Some(saved.clone()));
+
+        let mut restored = AppState::new(&names(&["a.example.com", "b.example.com"]));
+        let count = saved.restore(
+            &mut restored,
+            ProviderType::Cloudflare,
+            &names(&["a.example.com", "b.example.com"]),
+            &[],
+        );
+        assert_eq!(count, 1);
+        let status = &restored.records["a.example.com"];
+        assert_eq!(status.state, RecordState::Synced("2001:db8::1".to_string()));
+        assert_eq!(status.record_id.as_deref(), Some("rec-a"));
+        assert!(status.last_sync_at.is_some());
+        assert_eq!(
+            restored.records["b.example.com"].state,
+            RecordState::Unknown
+        );
+    }
+
+    #[test]
+    fn test_state_file_restore_skips_other_provider_and_removed_records() {
+        let mut state = AppState::new(&names(&["a.example.com"]));
+        state
+            .record_mut("a.example.com")
+            .mark_synced("2001:db8::1".to_string());
+        let saved = SavedState::capture(&state, ProviderType::Cloudflare);
+
+        let mut restored = AppState::new(&names(&["a.example.com"]));
+        let count = saved.restore(
+            &mut restored,
+            ProviderType::DuckDns,
+            &names(&["a.example.com"]),
+            &[],
+        );
+        assert_eq!(count, 0);
+        assert_eq!(
+            restored.records["a.example.com"].state,
+            RecordState::Unknown
+        );
+
+        let mut restored = AppState::new(&names(&["c.example.com"]));
+        let count = saved.restore(
+            &mut restored,
+            ProviderType::Cloudflare,
+            &names(&["c.example.com"]),
+            &[],
+        );
+        assert_eq!(count, 0);
+        assert!(!restored.records.contains_key("a.example.com"));
+    }
+
+    #[test]
+    fn test_state_file_corrupt_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        std::fs::write(&path, "{ not json").unwrap();
+        assert_eq!(load(&path), None);
+    }
+}
@@ -0,0 +1,97 @@
+//! systemd service notification for ipv6ddns
+//!
+//! When built with the `systemd` feature and started by systemd with
+//! `Type=notify`, the daemon reports `READY=1` once the initial sync has run,
+//! sends `WATCHDOG=1` pings from its main loop when `WatchdogSec=` is set, and
+//! reports `STOPPING=1` on SIGTERM. systemd is detected through `NOTIFY_SOCKET`;
+//! without the feature, or when not started by systemd, every function here is
+//! a no-op.
+//!
+//! Watchdog pings are sent from the main event loop itself, so a provider call
+//! that wedges the loop stops the pings and systemd restarts the service.
+
+use std::time::Duration;
+
+//==============================================================================
+// Notifications
+//==============================================================================
+
+/// Tells systemd the daemon has finished starting up (`READY=1`)
+pub fn notify_ready() {
+    #[cfg(feature = "systemd")]
+    notify(sd_notify::NotifyState::Ready);
+}
+
+/// Pings the systemd watchdog (`WATCHDOG=1`)
+pub fn notify_watchdog() {
+    #[cfg(feature = "systemd")]
+    notify(sd_notify::NotifyState::Watchdog);
+}
+
+/// Tells systemd the daemon is shutting down (`STOPPING=1`)
+pub fn notify_stopping() {
+    #[cfg(feature = "systemd")]
+    notify(sd_notify::NotifyState::Stopping);
+}
+
+/// Returns how often to ping the watchdog, if systemd enabled it
+///
+/// # Returns
+///
+/// Returns half of `WATCHDOG_USEC`, or `None` if the watchdog is disabled,
+/// the daemon was not started by systemd, or the `systemd` feature is off
+pub fn watchdog_interval() -> Option<Duration> {
+    #[cfg(feature = "systemd")]
+    {
+        let mut usec = 0;
+        if under_systemd() && sd_notify::watchdog_enabled(false, &mut usec) {
+            return Some(watchdog_ping_interval(usec));
+        }
+    }
+    None
+}
+
+//==============================================================================
+// Helpers
+//==============================================================================
+
+/// Returns whether systemd passed a notification socket to the daemon
+#[cfg(feature = "systemd")]
+fn under_systemd() -> bool {
+    std::env::var_os("NOTIFY_SOCKET").is_some_and(|v| !v.is_empty())
+}
+
+/// Sends a single state notification, logging (but ignoring) failures
+#[cfg(feature = "systemd")]
+fn notify(state: sd_notify::NotifyState<'_>) {
+    if !under_systemd() {
+        return;
+    }
+    if let Err(e) = sd_notify::notify(false, &[state]) {
+        tracing::debug!("sd_notify failed: {}", e);
+    }
+}
+
+/// Converts `WATCHDOG_USEC` into a ping interval
+///
+/// Pings are sent at half the timeout, as recommended by `sd_watchdog_enabled(3)`.
+#[cfg_attr(not(feature = "systemd"), allow(dead_code))]
+fn watchdog_ping_interval(usec: u64) -> Duration {
+    Duration::from_micros(usec / 2).max(Duration::from_millis(1))
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchdog_ping_interval() {
+        assert_eq!(watchdog_ping_interval(30_000_000), Duration::from_secs(15));
+        assert_eq!(watchdog_ping_interval(1), Duration::from_millis(1));
+        assert_eq!(watchdog_ping_interval(0), Duration::from_millis(1));
+    }
+}
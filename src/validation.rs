@@ -6,7 +6,11 @@
 //! # Functions
 //!
 //! - `validate_record_name`: Validates DNS record names according to RFC standards
+//! - `to_ascii_name`: Converts internationalized labels to their punycode form
 //! - `is_valid_ipv6`: Validates IPv6 addresses and filters out reserved ranges
+//!   according to an `Ipv6Filter`
+//! - `is_valid_ipv4`: Validates IPv4 addresses and filters out non-public ranges
+//! - `normalize_ipv6`: Strips a zone index and returns the canonical address text
 //!
 //! # DNS Record Name Validation
 //!
@@ -16,22 +20,42 @@
 //! - Wildcard records (*.example.com)
 //! - ACME challenge records (_acme-challenge.example.com)
 //! - FQDNs with trailing dots (example.com.)
+//! - Internationalized names (müller.example), validated in their ASCII
+//!   `xn--` form (RFC 3492 punycode)
 //!
 //! # IPv6 Address Validation
 //!
-//! The `is_valid_ipv6` function validates IPv6 addresses and filters out:
+//! The `is_valid_ipv6` function validates IPv6 addresses and, by default,
+//! filters out:
 //! - Unspecified address (::)
-//! - Loopback address (::1, unless allow_loopback is true)
+//! - Loopback address (::1)
 //! - Link-local addresses (fe80::/10)
 //! - Multicast addresses (ff00::/8)
 //! - Documentation addresses (2001:db8::/32)
 //!
-//! Unique-local addresses (fc00::/7) are allowed by design, since DDNS is often
-//! used on private networks.
+//! Unique-local addresses (fc00::/7) are allowed by default, since DDNS is
+//! often used on private networks. An `Ipv6Filter` can accept loopback,
+//! link-local and documentation addresses, or refuse unique-local ones; the
+//! unspecified and multicast addresses are always refused.
+
+use std::net::Ipv6Addr;
 
 use anyhow::{anyhow, Result};
 
 use crate::constants::{MAX_LABEL_LENGTH, MAX_RECORD_NAME_LENGTH};
+use crate::netlink::prefix_contains;
+
+/// Prefix of punycode-encoded labels (A-labels)
+const IDNA_PREFIX: &str = "xn--";
+
+// Punycode parameters (RFC 3492, section 5)
+const PUNYCODE_BASE: u32 = 36;
+const PUNYCODE_TMIN: u32 = 1;
+const PUNYCODE_TMAX: u32 = 26;
+const PUNYCODE_SKEW: u32 = 38;
+const PUNYCODE_DAMP: u32 = 700;
+const PUNYCODE_INITIAL_BIAS: u32 = 72;
+const PUNYCODE_INITIAL_N: u32 = 0x80;
 
 /// Validates that a string is a reasonable DNS record name.
 ///
@@ -40,6 +64,8 @@ use crate::constants::{MAX_LABEL_LENGTH, MAX_RECORD_NAME_LENGTH};
 /// - `_` in labels (e.g. `_acme-challenge`)
 /// - `*` as a whole label (e.g. `*.example.com`)
 /// - trailing dot (FQDN), which is ignored for validation
+/// - non-ASCII labels, which are validated in their punycode form (see
+///   [`to_ascii_name`])
 pub fn validate_record_name(record_name: &str) -> Result<()> {
     let trimmed = record_name.trim();
     if trimmed.is_empty() {
@@ -52,7 +78,8 @@ pub fn validate_record_name(record_name: &str) -> Result<()> {
         return Err(anyhow!("Record name cannot contain spaces"));
     }
 
-    let name = trimmed.strip_suffix('.').unwrap_or(trimmed);
+    let name = to_ascii_name(trimmed.strip_suffix('.').unwrap_or(trimmed))?;
+    let name = name.as_str();
     if name.is_empty() {
         return Err(anyhow!("Record name cannot be empty"));
     }
@@ -86,7 +113,7 @@ pub fn validate_record_name(record_name: &str) -> Result<()> {
             return Err(anyhow!("Record name label cannot start or end with hyphen"));
         }
         for ch in label.chars() {
-            if !ch.is_alphanumeric() && ch != '-' && ch != '_' {
+            if !ch.is_ascii_alphanumeric() && ch != '-' && ch != '_' {
                 return Err(anyhow!(
                     "Record name contains invalid character: '{}' (allowed: letters, digits, '-', '_', or wildcard labels)",
                     ch
@@ -98,50 +125,246 @@ pub fn validate_record_name(record_name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Converts the non-ASCII labels of a DNS name to punycode A-labels
+///
+/// Each label containing non-ASCII characters is lowercased and encoded as
+/// `xn--` followed by its RFC 3492 punycode form, as DNS providers expect:
+/// `müller.example` becomes `xn--mller-kva.example`. ASCII labels, including
+/// ones already in `xn--` form, are left unchanged.
+///
+/// No Unicode normalization is applied, so names should be written in their
+/// usual composed form (`ü`, not `u` followed by a combining diaeresis).
+///
+/// # Arguments
+///
+/// * `name` - The DNS name, possibly with Unicode labels
+///
+/// # Returns
+///
+/// Returns the ASCII form of the name, or an error if a label cannot be encoded
+pub fn to_ascii_name(name: &str) -> Result<String> {
+    if name.is_ascii() {
+        return Ok(name.to_string());
+    }
+    let labels = name
+        .split('.')
+        .map(|label| {
+            if label.is_ascii() {
+                return Ok(label.to_string());
+            }
+            punycode_encode(&label.to_lowercase())
+                .map(|encoded| format!("{}{}", IDNA_PREFIX, encoded))
+                .ok_or_else(|| anyhow!("Record name label cannot be encoded: '{}'", label))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(labels.join("."))
+}
+
+/// Encodes a label with punycode (RFC 3492, section 6.3), without the prefix
+///
+/// Returns `None` if the encoding overflows, which only happens for labels far
+/// longer than DNS allows.
+fn punycode_encode(label: &str) -> Option<String> {
+    let input: Vec<u32> = label.chars().map(u32::from).collect();
+    let mut output: String = label.chars().filter(char::is_ascii).collect();
+    let basic = output.len() as u32;
+    if basic > 0 {
+        output.push('-');
+    }
+
+    let mut n = PUNYCODE_INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = PUNYCODE_INITIAL_BIAS;
+    let mut handled = basic;
+    while (handled as usize) < input.len() {
+        // The next code point to insert is the smallest one not handled yet
+        let m = input.iter().copied().filter(|&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+        for &c in &input {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = PUNYCODE_BASE;
+                loop {
+                    let t = if k <= bias {
+                        PUNYCODE_TMIN
+                    } else if k >= bias + PUNYCODE_TMAX {
+                        PUNYCODE_TMAX
+                    } else {
+                        k - bias
+                    };
+                    if q < t {
+                        break;
+                    }
+                    output.push(punycode_digit(t + (q - t) % (PUNYCODE_BASE - t)));
+                    q = (q - t) / (PUNYCODE_BASE - t);
+                    k += PUNYCODE_BASE;
+                }
+                output.push(punycode_digit(q));
+                bias = punycode_adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta = delta.checked_add(1)?;
+        n += 1;
+    }
+    Some(output)
+}
+
+/// Bias adaptation function (RFC 3492, section 6.1)
+fn punycode_adapt(delta: u32, num_points: u32, first_time: bool) -> u32 {
+    let mut delta = if first_time {
+        delta / PUNYCODE_DAMP
+    } else {
+        delta / 2
+    };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((PUNYCODE_BASE - PUNYCODE_TMIN) * PUNYCODE_TMAX) / 2 {
+        delta /= PUNYCODE_BASE - PUNYCODE_TMIN;
+        k += PUNYCODE_BASE;
+    }
+    k + ((PUNYCODE_BASE - PUNYCODE_TMIN + 1) * delta) / (delta + PUNYCODE_SKEW)
+}
+
+/// Maps a digit value (0-35) to its punycode character (a-z, 0-9)
+fn punycode_digit(d: u32) -> char {
+    match d {
+        0..=25 => char::from(b'a' + d as u8),
+        _ => char::from(b'0' + (d - 26) as u8),
+    }
+}
+
+/// Reserved IPv6 ranges that may be published
+///
+/// The default accepts what DDNS is normally used for: global unicast and
+/// unique-local addresses. The unspecified address (::) and multicast
+/// addresses (ff00::/8) are never accepted, and neither is anything in
+/// `exclude_prefixes`, whatever its range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ipv6Filter {
+    /// Accept the loopback address (::1), for local testing (default: false)
+    pub allow_loopback: bool,
+    /// Accept link-local addresses (fe80::/10) (default: false)
+    pub allow_link_local: bool,
+    /// Accept unique-local addresses (fc00::/7) (default: true)
+    pub allow_unique_local: bool,
+    /// Accept documentation addresses (2001:db8::/32) (default: false)
+    pub allow_documentation: bool,
+    /// Prefixes (network, length) never to publish, e.g. internal-only
+    /// global space (default: none)
+    pub exclude_prefixes: Vec<(Ipv6Addr, u8)>,
+}
+
+impl Default for Ipv6Filter {
+    fn default() -> Self {
+        Self {
+            allow_loopback: false,
+            allow_link_local: false,
+            allow_unique_local: true,
+            allow_documentation: false,
+            exclude_prefixes: Vec::new(),
+        }
+    }
+}
+
+impl Ipv6Filter {
+    /// Returns whether `addr` may be published
+    pub fn allows(&self, addr: &Ipv6Addr) -> bool {
+        let segments = addr.segments();
+        if addr.is_unspecified() {
+            return false;
+        }
+        if self
+            .exclude_prefixes
+            .iter()
+            .any(|(network, len)| prefix_contains(*network, *len, *addr))
+        {
+            return false;
+        }
+        // Multicast addresses have first 8 bits as 11111111
+        if segments[0] & 0xff00 == 0xff00 {
+            return false;
+        }
+        if addr.is_loopback() {
+            return self.allow_loopback;
+        }
+        // Link-local addresses have first 10 bits as 1111111010
+        if segments[0] & 0xffc0 == 0xfe80 {
+            return self.allow_link_local;
+        }
+        // Unique-local addresses have first 7 bits as 1111110
+        if segments[0] & 0xfe00 == 0xfc00 {
+            return self.allow_unique_local;
+        }
+        if segments[0] == 0x2001 && segments[1] == 0x0db8 {
+            return self.allow_documentation;
+        }
+        true
+    }
+}
+
 /// Validates that a string is a properly formatted IPv6 address.
 ///
-/// This function checks that the address is syntactically valid AND filters out
-/// reserved/special IPv6 address ranges that are not suitable for DDNS:
+/// This function checks that the address is syntactically valid AND that the
+/// filter accepts its range. With the default filter, reserved/special IPv6
+/// address ranges that are not suitable for DDNS are filtered out:
 /// - Unspecified address (::)
 /// - Loopback address (::1)
 /// - Link-local addresses (fe80::/10)
 /// - Multicast addresses (ff00::/8)
 /// - Documentation addresses (2001:db8::/32)
 ///
-/// Note: unique-local addresses (fc00::/7) are allowed by design, since DDNS
+/// Note: unique-local addresses (fc00::/7) are allowed by default, since DDNS
 /// is often used on private networks.
-pub fn is_valid_ipv6(ip: &str, allow_loopback: bool) -> bool {
-    let addr = match ip.parse::<std::net::Ipv6Addr>() {
+pub fn is_valid_ipv6(ip: &str, filter: &Ipv6Filter) -> bool {
+    ip.parse::<Ipv6Addr>()
+        .is_ok_and(|addr| filter.allows(&addr))
+}
+
+/// Normalizes a detected IPv6 address to its canonical text form.
+///
+/// A zone index (`%eth0`, `%2`) is stripped first, since some detection
+/// sources (e.g. getifaddrs) append one and `Ipv6Addr` can't parse it. The
+/// result still has to pass `is_valid_ipv6` to be published.
+///
+/// Returns `None` if the address doesn't parse.
+pub fn normalize_ipv6(ip: &str) -> Option<String> {
+    let addr = ip.trim().split('%').next().unwrap_or_default();
+    addr.parse::<std::net::Ipv6Addr>()
+        .ok()
+        .map(|a| a.to_string())
+}
+
+/// Validates that a string is a publishable IPv4 address.
+///
+/// Filters out addresses that can't be reached from the internet:
+/// - Unspecified (0.0.0.0/8), broadcast, multicast and reserved (240.0.0.0/4)
+/// - Loopback (127.0.0.0/8) and link-local (169.254.0.0/16)
+/// - Documentation ranges (192.0.2.0/24, 198.51.100.0/24, 203.0.113.0/24)
+/// - Private (RFC 1918) and carrier-grade NAT (100.64.0.0/10) addresses,
+///   unless `allow_private` is true
+pub fn is_valid_ipv4(ip: &str, allow_private: bool) -> bool {
+    let addr = match ip.parse::<std::net::Ipv4Addr>() {
         Ok(a) => a,
         Err(_) => return false,
     };
+    let octets = addr.octets();
 
-    // Filter out unspecified address (::)
-    if addr.is_unspecified() {
-        return false;
-    }
-
-    // Filter out loopback address (::1)
-    if addr.is_loopback() && !allow_loopback {
+    if octets[0] == 0 || addr.is_broadcast() || addr.is_multicast() || octets[0] >= 240 {
         return false;
     }
-
-    let segments = addr.segments();
-
-    // Filter out link-local addresses (fe80::/10)
-    // Link-local addresses have first 10 bits as 1111111010
-    if segments[0] & 0xffc0 == 0xfe80 {
+    if addr.is_loopback() || addr.is_link_local() || addr.is_documentation() {
         return false;
     }
 
-    // Filter out multicast addresses (ff00::/8)
-    // Multicast addresses have first 8 bits as 11111111
-    if segments[0] & 0xff00 == 0xff00 {
-        return false;
-    }
-
-    // Filter out documentation addresses (2001:db8::/32)
-    if segments[0] == 0x2001 && segments[1] == 0x0db8 {
+    // Carrier-grade NAT shared address space (100.64.0.0/10)
+    let is_cgnat = octets[0] == 100 && octets[1] & 0xc0 == 64;
+    if (addr.is_private() || is_cgnat) && !allow_private {
         return false;
     }
 
@@ -164,6 +387,37 @@ mod tests {
         assert!(validate_record_name(&("a".repeat(63) + ".com")).is_ok());
     }
 
+    #[test]
+    fn test_to_ascii_name() {
+        assert_eq!(
+            to_ascii_name("müller.example").unwrap(),
+            "xn--mller-kva.example"
+        );
+        assert_eq!(
+            to_ascii_name("home.MÜNCHEN.example").unwrap(),
+            "home.xn--mnchen-3ya.example"
+        );
+        // RFC 3492 sample: a label without basic code points
+        assert_eq!(
+            to_ascii_name("他们为什么不说中文").unwrap(),
+            "xn--ihqwcrb4cv8a8dqg056pqjye"
+        );
+        // Already punycode, or plain ASCII: unchanged
+        assert_eq!(
+            to_ascii_name("xn--mller-kva.example").unwrap(),
+            "xn--mller-kva.example"
+        );
+        assert_eq!(
+            to_ascii_name("Home.example.com").unwrap(),
+            "Home.example.com"
+        );
+
+        assert!(validate_record_name("müller.example").is_ok());
+        assert!(validate_record_name("xn--mller-kva.example.").is_ok());
+        // Too long once encoded
+        assert!(validate_record_name(&format!("{}.example", "ü".repeat(60))).is_err());
+    }
+
     #[test]
     fn test_validate_record_name_invalid_cases() {
         assert!(validate_record_name("").is_err());
@@ -209,34 +463,142 @@ mod tests {
     #[test]
     fn test_is_valid_ipv6() {
         // Valid global unicast addresses
-        assert!(is_valid_ipv6("2606:4700:4700::1111", false));
-        assert!(is_valid_ipv6("2001:4860:4860::8888", false));
-        assert!(is_valid_ipv6("2a00:1450:4001:81b::200e", false));
+        assert!(is_valid_ipv6(
+            "2606:4700:4700::1111",
+            &Ipv6Filter::default()
+        ));
+        assert!(is_valid_ipv6(
+            "2001:4860:4860::8888",
+            &Ipv6Filter::default()
+        ));
+        assert!(is_valid_ipv6(
+            "2a00:1450:4001:81b::200e",
+            &Ipv6Filter::default()
+        ));
 
         // Unique-local addresses are allowed
-        assert!(is_valid_ipv6("fc00::1", false));
-        assert!(is_valid_ipv6("fd12:3456:789a::1", false));
+        assert!(is_valid_ipv6("fc00::1", &Ipv6Filter::default()));
+        assert!(is_valid_ipv6("fd12:3456:789a::1", &Ipv6Filter::default()));
         // Reserved addresses that should be rejected
-        assert!(!is_valid_ipv6("::", false)); // Unspecified
-        assert!(!is_valid_ipv6("::1", false)); // Loopback (default reject)
-        assert!(!is_valid_ipv6("fe80::1", false)); // Link-local
-        assert!(!is_valid_ipv6("fe80::dead:beef", false)); // Link-local
-        assert!(!is_valid_ipv6("ff00::1", false)); // Multicast
-        assert!(!is_valid_ipv6("ff02::1", false)); // Multicast
-        assert!(!is_valid_ipv6("2001:db8::1", false)); // Documentation
-        assert!(!is_valid_ipv6("2001:0db8::1", false)); // Documentation
+        assert!(!is_valid_ipv6("::", &Ipv6Filter::default())); // Unspecified
+        assert!(!is_valid_ipv6("::1", &Ipv6Filter::default())); // Loopback (default reject)
+        assert!(!is_valid_ipv6("fe80::1", &Ipv6Filter::default())); // Link-local
+        assert!(!is_valid_ipv6("fe80::dead:beef", &Ipv6Filter::default())); // Link-local
+        assert!(!is_valid_ipv6("ff00::1", &Ipv6Filter::default())); // Multicast
+        assert!(!is_valid_ipv6("ff02::1", &Ipv6Filter::default())); // Multicast
+        assert!(!is_valid_ipv6("2001:db8::1", &Ipv6Filter::default())); // Documentation
+        assert!(!is_valid_ipv6("2001:0db8::1", &Ipv6Filter::default())); // Documentation
 
         // Invalid formats
-        assert!(!is_valid_ipv6("192.168.1.1", false)); // IPv4
-        assert!(!is_valid_ipv6("invalid", false));
-        assert!(!is_valid_ipv6("", false));
-        assert!(!is_valid_ipv6("2001:db8::g", false));
+        assert!(!is_valid_ipv6("192.168.1.1", &Ipv6Filter::default())); // IPv4
+        assert!(!is_valid_ipv6("invalid", &Ipv6Filter::default()));
+        assert!(!is_valid_ipv6("", &Ipv6Filter::default()));
+        assert!(!is_valid_ipv6("2001:db8::g", &Ipv6Filter::default()));
+    }
+
+    #[test]
+    fn test_is_valid_ipv4() {
+        assert!(is_valid_ipv4("8.8.8.8", false));
+        assert!(is_valid_ipv4("100.63.255.255", false));
+        assert!(is_valid_ipv4("100.128.0.1", false));
+
+        for ip in [
+            "0.0.0.0",
+            "127.0.0.1",
+            "169.254.1.1",
+            "255.255.255.255",
+            "224.0.0.1",
+            "240.0.0.1",
+            "192.0.2.1",
+            "203.0.113.7",
+            "not-an-ip",
+            "2001:db8::1",
+        ] {
+            assert!(!is_valid_ipv4(ip, true), "{ip} accepted");
+        }
+
+        // Private and CGNAT space only with allow_private
+        for ip in [
+            "10.0.0.1",
+            "172.16.5.4",
+            "192.168.1.1",
+            "100.64.0.1",
+            "100.127.255.254",
+        ] {
+            assert!(!is_valid_ipv4(ip, false), "{ip} accepted");
+            assert!(is_valid_ipv4(ip, true), "{ip} rejected");
+        }
     }
 
     #[test]
     fn test_is_valid_ipv6_allow_loopback() {
-        assert!(is_valid_ipv6("::1", true));
-        assert!(!is_valid_ipv6("::", true));
+        let filter = Ipv6Filter {
+            allow_loopback: true,
+            ..Ipv6Filter::default()
+        };
+        assert!(is_valid_ipv6("::1", &filter));
+        assert!(!is_valid_ipv6("::", &filter));
+    }
+
+    #[test]
+    fn test_ipv6_filter_ranges() {
+        let default = Ipv6Filter::default();
+        assert!(default.allows(&"fd00::1".parse().unwrap()));
+        assert!(!default.allows(&"fe80::1".parse().unwrap()));
+        assert!(!default.allows(&"2001:db8::1".parse().unwrap()));
+
+        let lab = Ipv6Filter {
+            allow_documentation: true,
+            allow_link_local: true,
+            allow_unique_local: false,
+            ..Ipv6Filter::default()
+        };
+        assert!(is_valid_ipv6("2001:db8::1", &lab));
+        assert!(is_valid_ipv6("fe80::1", &lab));
+        assert!(!is_valid_ipv6("fd12:3456:789a::1", &lab));
+        assert!(!is_valid_ipv6("fc00::1", &lab));
+        assert!(!is_valid_ipv6("::1", &lab));
+        assert!(is_valid_ipv6("2606:4700:4700::1111", &lab));
+
+        // Never accepted
+        let all = Ipv6Filter {
+            allow_loopback: true,
+            allow_link_local: true,
+            allow_unique_local: true,
+            allow_documentation: true,
+            exclude_prefixes: Vec::new(),
+        };
+        assert!(!is_valid_ipv6("::", &all));
+        assert!(!is_valid_ipv6("ff02::1", &all));
+    }
+
+    #[test]
+    fn test_ipv6_filter_exclude_prefixes() {
+        let filter = Ipv6Filter {
+            exclude_prefixes: vec![("2a01:4f8:1234::".parse().unwrap(), 48)],
+            ..Ipv6Filter::default()
+        };
+        assert!(!is_valid_ipv6("2a01:4f8:1234::1", &filter));
+        assert!(!is_valid_ipv6("2a01:4f8:1234:ffff::1", &filter));
+        assert!(is_valid_ipv6("2a01:4f8:1235::1", &filter));
+        assert!(is_valid_ipv6("2a01:4f8:1233:ffff::1", &filter));
+
+        // Several exclusions, and exclusions beat the allow_* switches
+        let filter = Ipv6Filter {
+            allow_documentation: true,
+            exclude_prefixes: vec![
+                ("2a01:4f8:1234::".parse().unwrap(), 48),
+                ("2001:db8:1::".parse().unwrap(), 48),
+                ("fd00::".parse().unwrap(), 8),
+            ],
+            ..Ipv6Filter::default()
+        };
+        assert!(!is_valid_ipv6("2a01:4f8:1234::1", &filter));
+        assert!(!is_valid_ipv6("2001:db8:1::1", &filter));
+        assert!(!is_valid_ipv6("fd12::1", &filter));
+        assert!(is_valid_ipv6("2001:db8:2::1", &filter));
+        assert!(is_valid_ipv6("fc00::1", &filter));
+        assert!(is_valid_ipv6("2606:4700:4700::1111", &filter));
     }
 
     // Additional edge case tests for IPv6 validation
@@ -244,65 +606,90 @@ mod tests {
     #[test]
     fn test_ipv6_compression_variants() {
         // Valid compressed addresses (not in documentation range)
-        assert!(is_valid_ipv6("2001:4860::8888", false));
-        assert!(is_valid_ipv6("2001:4860:0:0:0:0:0:8888", false));
-        assert!(is_valid_ipv6("2001::", false));
+        assert!(is_valid_ipv6("2001:4860::8888", &Ipv6Filter::default()));
+        assert!(is_valid_ipv6(
+            "2001:4860:0:0:0:0:0:8888",
+            &Ipv6Filter::default()
+        ));
+        assert!(is_valid_ipv6("2001::", &Ipv6Filter::default()));
     }
 
     #[test]
     fn test_ipv6_with_port() {
         // IPv6 addresses with port notation should be rejected
-        assert!(!is_valid_ipv6("[2001:db8::1]:8080", false));
+        assert!(!is_valid_ipv6("[2001:db8::1]:8080", &Ipv6Filter::default()));
     }
 
     #[test]
     fn test_ipv6_zone_id() {
         // IPv6 addresses with zone ID should be rejected
-        assert!(!is_valid_ipv6("fe80::1%eth0", false));
+        assert!(!is_valid_ipv6("fe80::1%eth0", &Ipv6Filter::default()));
+    }
+
+    #[test]
+    fn test_normalize_ipv6_strips_zone_id() {
+        let stripped = normalize_ipv6("fe80::1%eth0").unwrap();
+        assert_eq!(stripped, "fe80::1");
+        // Still link-local once the zone is gone
+        assert!(!is_valid_ipv6(&stripped, &Ipv6Filter::default()));
+
+        assert_eq!(
+            normalize_ipv6("2606:4700:0:0::1%2").as_deref(),
+            Some("2606:4700::1")
+        );
+        assert_eq!(
+            normalize_ipv6(" 2606:4700::1 ").as_deref(),
+            Some("2606:4700::1")
+        );
+        assert_eq!(normalize_ipv6("%eth0"), None);
+        assert_eq!(normalize_ipv6("not-an-ip"), None);
     }
 
     #[test]
     fn test_ipv6_max_compression() {
-        assert!(!is_valid_ipv6("::", false)); // Fully compressed - unspecified, rejected
-        assert!(is_valid_ipv6("2001::", false)); // Trailing zeroes
-        assert!(!is_valid_ipv6("::1", false)); // Leading zeroes - loopback, rejected
+        assert!(!is_valid_ipv6("::", &Ipv6Filter::default())); // Fully compressed - unspecified, rejected
+        assert!(is_valid_ipv6("2001::", &Ipv6Filter::default())); // Trailing zeroes
+        assert!(!is_valid_ipv6("::1", &Ipv6Filter::default())); // Leading zeroes - loopback, rejected
     }
 
     #[test]
     fn test_ipv6_boundary_values() {
-        // Minimum valid IPv6 (all zeros)
-        assert!(!is_valid_ipv6("::", false)); // Unspecified, rejected
-                                              // Maximum valid IPv6 (all F's)
+        // Minimum valid IPv6 (all zeros): unspecified, rejected
+        assert!(!is_valid_ipv6("::", &Ipv6Filter::default()));
+        // Maximum valid IPv6 (all F's): multicast, rejected
         assert!(!is_valid_ipv6(
             "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
-            false
-        )); // Multicast-ish
+            &Ipv6Filter::default()
+        ));
     }
 
     #[test]
     fn test_ipv6_partial_compression() {
-        assert!(is_valid_ipv6("2001:4860:0:0:1:0:0:1", false));
-        assert!(is_valid_ipv6("2001:4860::1:0:0:1", false));
-        assert!(is_valid_ipv6("2001:4860:0:0:1::1", false));
+        assert!(is_valid_ipv6(
+            "2001:4860:0:0:1:0:0:1",
+            &Ipv6Filter::default()
+        ));
+        assert!(is_valid_ipv6("2001:4860::1:0:0:1", &Ipv6Filter::default()));
+        assert!(is_valid_ipv6("2001:4860:0:0:1::1", &Ipv6Filter::default()));
     }
 
     #[test]
     fn test_ipv6_multiple_double_colon() {
         // Only one :: is allowed
-        assert!(!is_valid_ipv6("2001::db8::1", false));
+        assert!(!is_valid_ipv6("2001::db8::1", &Ipv6Filter::default()));
     }
 
     #[test]
     fn test_ipv6_leading_trailing_colons() {
-        assert!(!is_valid_ipv6(":2001:db8::1", false));
-        assert!(!is_valid_ipv6("2001:db8::1:", false));
+        assert!(!is_valid_ipv6(":2001:db8::1", &Ipv6Filter::default()));
+        assert!(!is_valid_ipv6("2001:db8::1:", &Ipv6Filter::default()));
     }
 
     #[test]
     fn test_ipv6_invalid_characters() {
-        assert!(!is_valid_ipv6("2001:db8::g", false));
-        assert!(!is_valid_ipv6("2001:db8::1.2.3.4", false));
-        assert!(!is_valid_ipv6("2001:db8::12345", false)); // Too many digits
+        assert!(!is_valid_ipv6("2001:db8::g", &Ipv6Filter::default()));
+        assert!(!is_valid_ipv6("2001:db8::1.2.3.4", &Ipv6Filter::default()));
+        assert!(!is_valid_ipv6("2001:db8::12345", &Ipv6Filter::default())); // Too many digits
     }
 
     // Additional edge case tests for DNS record name validation
@@ -0,0 +1,750 @@
+//! Vultr API client for DNS operations
+//!
+//! This module provides a `DnsProvider` backed by the Vultr API v2. Requests
+//! carry the API key as a bearer token and address records below the domain:
+//!
+//! ```text
+//! GET https://api.vultr.com/v2/domains/<domain>/records?per_page=500
+//! Authorization: Bearer <api key>
+//! ```
+//!
+//! The zone ID holds the domain (e.g. `example.com`) and `api_token` the API
+//! key. Record lists are paginated with cursors: every page names the next one
+//! in `meta.links.next`, which is empty on the last page. Failures are
+//! answered with `{"error": "...", "status": 401}`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::Method;
+use serde::Deserialize;
+use tracing::{debug, warn};
+use zeroize::ZeroizeOnDrop;
+
+use crate::constants::{
+    HTTP_POOL_IDLE_TIMEOUT_SECS, HTTP_STATUS_SERVER_ERROR_MAX, HTTP_STATUS_SERVER_ERROR_MIN,
+    HTTP_STATUS_TOO_MANY_REQUESTS, VULTR_API_BASE, VULTR_MAX_RECORD_PAGES, VULTR_RECORDS_PER_PAGE,
+    VULTR_TTL_SECS,
+};
+use crate::dns_provider::{
+    relative_name, retry_after_header, DnsProvider, DnsRecord, ErrorKind, MultiRecordPolicy,
+    ProviderError, RecordType, UpsertOptions,
+};
+use crate::metrics::Metrics;
+
+//==============================================================================
+// Types
+//==============================================================================
+
+/// A DNS record as returned by Vultr
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+struct VultrRecord {
+    /// Record ID (a UUID)
+    id: String,
+    /// Record type
+    #[serde(rename = "type")]
+    record_type: String,
+    /// Record name relative to the domain ("" for the apex)
+    name: String,
+    /// Record value
+    data: String,
+    /// Time-to-live in seconds
+    #[serde(default)]
+    ttl: u64,
+}
+
+/// One page of a record list
+#[derive(Debug, Deserialize)]
+struct RecordList {
+    /// Records on this page
+    #[serde(default)]
+    records: Vec<VultrRecord>,
+    /// Paging details
+    #[serde(default)]
+    meta: Option<Meta>,
+}
+
+/// Paging details of a list response
+#[derive(Debug, Default, Deserialize)]
+struct Meta {
+    /// Cursors of the neighbouring pages
+    #[serde(default)]
+    links: Links,
+}
+
+/// Cursors of the neighbouring pages (empty when there is none)
+#[derive(Debug, Default, Deserialize)]
+struct Links {
+    /// Cursor of the next page
+    #[serde(default)]
+    next: String,
+}
+
+/// Response to a record creation
+#[derive(Debug, Deserialize)]
+struct CreatedRecord {
+    /// The created record
+    record: VultrRecord,
+}
+
+/// Error response body
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    /// Error description
+    error: String,
+}
+
+//==============================================================================
+// Client
+//==============================================================================
+
+/// Vultr API client for DNS operations
+///
+/// The API key is wrapped in `Zeroizing` to ensure it is securely cleared
+/// from memory when dropped.
+#[derive(ZeroizeOnDrop)]
+pub struct VultrClient {
+    /// API key, sent as a bearer token
+    #[zeroize(skip)]
+    api_key: zeroize::Zeroizing<String>,
+    /// HTTP client for making requests
+    #[zeroize(skip)]
+    client: reqwest::Client,
+    /// Base URL of the API, without a trailing slash
+    #[zeroize(skip)]
+    api_base: String,
+    /// Counters for API requests
+    #[zeroize(skip)]
+    metrics: Arc<Metrics>,
+}
+
+impl VultrClient {
+    /// Creates a new Vultr API client
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - Vultr API key
+    /// * `timeout` - HTTP request timeout duration
+    /// * `connect_timeout` - Time allowed to establish a connection
+    /// * `user_agent` - User-Agent header sent with every request
+    /// * `metrics` - Counters updated for every API request
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the client or an error if client creation fails
+    pub fn new(
+        api_key: &str,
+        timeout: Duration,
+        connect_timeout: Duration,
+        user_agent: &str,
+        metrics: Arc<Metrics>,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .user_agent(user_agent)
+            .pool_idle_timeout(Duration::from_secs(HTTP_POOL_IDLE_TIMEOUT_SECS))
+            .build()
+            .context("build reqwest client")?;
+
+        Ok(Self {
+            api_key: zeroize::Zeroizing::new(api_key.to_string()),
+            client,
+            api_base: VULTR_API_BASE.to_string(),
+            metrics,
+        })
+    }
+
+    /// Points the client at another API server (a mock server in tests)
+    #[cfg(test)]
+    fn with_api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sends an authenticated request and checks the response status
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method
+    /// * `url` - The request URL
+    /// * `body` - JSON body, if any
+    /// * `context` - Context message for errors
+    ///
+    /// # Returns
+    ///
+    /// Returns the response body, or an error for a failed request or a
+    /// non-success status
+    async fn send(
+        &self,
+        method: &'static str,
+        url: reqwest::Url,
+        body: Option<serde_json::Value>,
+        context: &str,
+    ) -> Result<String> {
+        debug!("{} {}", method, url);
+        let mut request = self
+            .client
+            .request(
+                Method::from_bytes(method.as_bytes()).context("Invalid HTTP method")?,
+                url,
+            )
+            .bearer_auth(self.api_key.as_str());
+        if let Some(body) = &body {
+            request = request.json(body);
+        }
+        let result = request.send().await;
+        self.metrics.record_provider_request(
+            method,
+            result.as_ref().ok().map(|resp| resp.status().as_u16()),
+        );
+        let resp = result.with_context(|| format!("{} request failed: {}", method, context))?;
+        let status = resp.status().as_u16();
+        let retry_after = retry_after_header(resp.headers());
+        let text = resp
+            .text()
+            .await
+            .with_context(|| format!("Failed to read Vultr response: {}", context))?;
+
+        if (200..300).contains(&status) {
+            return Ok(text);
+        }
+        if status == HTTP_STATUS_TOO_MANY_REQUESTS {
+            bail!(ProviderError::new(
+                ErrorKind::RateLimit,
+                format!(
+                    "Rate limited by Vultr (429): {}. \
+                     The daemon will automatically retry with exponential backoff.",
+                    context
+                )
+            )
+            .with_retry_after(retry_after));
+        }
+        if (HTTP_STATUS_SERVER_ERROR_MIN..=HTTP_STATUS_SERVER_ERROR_MAX).contains(&status) {
+            bail!(ProviderError::new(
+                ErrorKind::Server,
+                format!(
+                    "Vultr server error ({}): {}. \
+                     The daemon will automatically retry with exponential backoff.",
+                    status, context
+                )
+            ));
+        }
+        let message = serde_json::from_str::<ApiError>(&text)
+            .map(|e| e.error)
+            .unwrap_or_else(|_| "unexpected response".to_string());
+        bail!(ProviderError::new(
+            ErrorKind::from_status(status),
+            format!("API error ({}): {}: {}", status, context, message)
+        ))
+    }
+
+    /// Builds the URL of a path below the domain's records
+    fn records_url(&self, domain: &str, suffix: &str) -> Result<reqwest::Url> {
+        let url = format!("{}/domains/{}/records{}", self.api_base, domain, suffix);
+        reqwest::Url::parse(&url).with_context(|| format!("Invalid Vultr URL: {}", url))
+    }
+
+    /// Fetches the records of the given type and name
+    ///
+    /// Vultr can't filter record lists, so every page of the domain's records
+    /// is fetched, following the `next` cursors.
+    async fn list(
+        &self,
+        domain: &str,
+        record_name: &str,
+        record_type: RecordType,
+    ) -> Result<Vec<VultrRecord>> {
+        let name = subdomain(domain, record_name)?;
+        let ctx = format!("GET record '{}' in domain '{}'", record_name, domain);
+        let mut records = Vec::new();
+        let mut cursor = String::new();
+        for _ in 0..VULTR_MAX_RECORD_PAGES {
+            let mut url = self.records_url(domain, "")?;
+            url.query_pairs_mut()
+                .append_pair("per_page", &VULTR_RECORDS_PER_PAGE.to_string());
+            if !cursor.is_empty() {
+                url.query_pairs_mut().append_pair("cursor", &cursor);
+            }
+            let text = self.send("GET", url, None, &ctx).await?;
+            let page: RecordList = serde_json::from_str(&text).map_err(|e| {
+                ProviderError::new(
+                    ErrorKind::Server,
+                    format!("Failed to parse Vultr response: {}: {}", ctx, e),
+                )
+            })?;
+            records.extend(
+                page.records
+                    .into_iter()
+                    .filter(|r| matches_record(r, name, record_type)),
+            );
+            cursor = page.meta.unwrap_or_default().links.next;
+            if cursor.is_empty() {
+                return Ok(records);
+            }
+        }
+        bail!(
+            "Record list of domain '{}' has more than {} pages; giving up",
+            domain,
+            VULTR_MAX_RECORD_PAGES
+        )
+    }
+
+    /// Creates a record, returning its ID
+    async fn create(
+        &self,
+        domain: &str,
+        record_name: &str,
+        record_type: RecordType,
+        content: &str,
+    ) -> Result<String> {
+        let body = serde_json::json!({
+            "name": subdomain(domain, record_name)?,
+            "type": record_type.as_str(),
+            "data": content,
+            "ttl": VULTR_TTL_SECS,
+        });
+        let ctx = format!("Create record '{}' in domain '{}'", record_name, domain);
+        let url = self.records_url(domain, "")?;
+        let text = self.send("POST", url, Some(body), &ctx).await?;
+        let created: CreatedRecord = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse Vultr response: {}", ctx))?;
+        Ok(created.record.id)
+    }
+
+    /// Points an existing record at a new address
+    async fn patch(&self, domain: &str, id: &str, record_name: &str, content: &str) -> Result<()> {
+        let ctx = format!("Update record '{}' in domain '{}'", record_name, domain);
+        let url = self.records_url(domain, &format!("/{}", id))?;
+        let body = serde_json::json!({ "data": content });
+        self.send("PATCH", url, Some(body), &ctx).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DnsProvider for VultrClient {
+    async fn upsert_record(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+        content: &str,
+        options: UpsertOptions<'_>,
+    ) -> Result<DnsRecord> {
+        let domain = zone_id.trim();
+        let mut records = self.list(domain, record_name, record_type).await?;
+        let record = |id: String, ttl: u64, created: bool| DnsRecord {
+            id,
+            record_type: record_type.as_str().to_string(),
+            name: record_name.to_string(),
+            content: content.to_string(),
+            proxied: false,
+            ttl,
+            created,
+        };
+
+        if records.is_empty() {
+            if !options.allow_create {
+                bail!(
+                    "No {} record exists for '{}' and record creation is disabled. \
+                     Verify the record name is correct, then set allow_create = true \
+                     (or pass --allow-create) to create it.",
+                    record_type,
+                    record_name
+                );
+            }
+            let id = self
+                .create(domain, record_name, record_type, content)
+                .await?;
+            return Ok(record(id, VULTR_TTL_SECS, true));
+        }
+
+        let targets = match options.policy {
+            MultiRecordPolicy::Error if records.len() > 1 => {
+                warn!("Multiple {} records found for {}", record_type, record_name);
+                bail!(
+                    "Multiple {} records found for {}. Refusing to update.",
+                    record_type,
+                    record_name
+                );
+            }
+            MultiRecordPolicy::Error | MultiRecordPolicy::UpdateFirst => {
+                let pos = options
+                    .sticky_id
+                    .and_then(|id| records.iter().position(|r| r.id == id))
+                    .unwrap_or(0);
+                vec![records.swap_remove(pos)]
+            }
+            MultiRecordPolicy::UpdateAll => records,
+        };
+
+        for target in &targets {
+            if target.data == content {
+                debug!("Record already matches {}", content);
+                continue;
+            }
+            self.patch(domain, &target.id, record_name, content).await?;
+        }
+        let first = &targets[0];
+        Ok(record(first.id.clone(), first.ttl, false))
+    }
+
+    async fn delete_record(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+    ) -> Result<usize> {
+        let domain = zone_id.trim();
+        let records = self.list(domain, record_name, record_type).await?;
+        let ctx = format!("DELETE record '{}' in domain '{}'", record_name, domain);
+        for record in &records {
+            let url = self.records_url(domain, &format!("/{}", record.id))?;
+            self.send("DELETE", url, None, &ctx).await?;
+        }
+        Ok(records.len())
+    }
+
+    async fn get_records(
+        &self,
+        zone_id: &str,
+        record_name: &str,
+        record_type: RecordType,
+    ) -> Result<Vec<DnsRecord>> {
+        let records = self.list(zone_id.trim(), record_name, record_type).await?;
+        Ok(records
+            .into_iter()
+            .map(|record| DnsRecord {
+                id: record.id,
+                record_type: record.record_type,
+                name: record_name.to_string(),
+                content: record.data,
+                proxied: false,
+                ttl: record.ttl,
+                created: false,
+            })
+            .collect())
+    }
+}
+
+//==============================================================================
+// Helpers
+//==============================================================================
+
+/// Returns the part of a record name below the domain ("" for the apex)
+///
+/// # Arguments
+///
+/// * `domain` - The domain (zone ID), e.g. "example.com"
+/// * `record_name` - The record name, e.g. "home.example.com" or "@"
+///
+/// # Returns
+///
+/// Returns the subdomain, or an error if the record is not in the domain
+pub fn subdomain<'a>(domain: &str, record_name: &'a str) -> Result<&'a str> {
+    match relative_name(domain, record_name) {
+        Some(name) => Ok(name),
+        None => bail!(
+            "Record name '{}' is not in the Vultr domain '{}'",
+            record_name,
+            domain.trim().trim_end_matches('.')
+        ),
+    }
+}
+
+/// Returns whether a listed record has the given relative name and type
+fn matches_record(record: &VultrRecord, name: &str, record_type: RecordType) -> bool {
+    let record_name = match record.name.as_str() {
+        "@" => "",
+        other => other,
+    };
+    record
+        .record_type
+        .eq_ignore_ascii_case(record_type.as_str())
+        && record_name.eq_ignore_ascii_case(name)
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::CLOUDFLARE_USER_AGENT;
+    use crate::dns_provider::ReconcileFields;
+    use wiremock::matchers::{
+        body_json, header, method, path, query_param, query_param_is_missing,
+    };
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const NEW_IP: &str = "2001:db8::2";
+
+    fn mock_client(server: &MockServer) -> VultrClient {
+        VultrClient::new(
+            "0123456789ABCDEFGHIJ0123456789ABCDEF",
+            Duration::from_secs(5),
+            Duration::from_secs(5),
+            CLOUDFLARE_USER_AGENT,
+            Arc::new(Metrics::new()),
+        )
+        .unwrap()
+        .with_api_base(&server.uri())
+    }
+
+    fn options(policy: MultiRecordPolicy, allow_create: bool) -> UpsertOptions<'static> {
+        UpsertOptions {
+            policy,
+            allow_create,
+            reconcile: ReconcileFields::default(),
+            sticky_id: None,
+            stamp_comment: false,
+        }
+    }
+
+    fn mock_record(id: &str, name: &str, record_type: &str, data: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "type": record_type,
+            "name": name,
+            "data": data,
+            "priority": -1,
+            "ttl": 300,
+        })
+    }
+
+    /// Serves the records in pages of two, linked by cursors
+    async fn mock_list(server: &MockServer, records: Vec<serde_json::Value>) {
+        let pages: Vec<_> = records.chunks(2).map(|page| page.to_vec()).collect();
+        for (i, page) in pages.iter().enumerate() {
+            let next = if i + 1 < pages.len() {
+                format!("cursor{}", i + 1)
+            } else {
+                String::new()
+            };
+            let body = serde_json::json!({
+                "records": page,
+                "meta": {"total": records.len(), "links": {"next": next, "prev": ""}},
+            });
+            let mock = Mock::given(method("GET"))
+                .and(path("/domains/example.com/records"))
+                .and(header(
+                    "authorization",
+                    "Bearer 0123456789ABCDEFGHIJ0123456789ABCDEF",
+                ));
+            let mock = if i == 0 {
+                mock.and(query_param_is_missing("cursor"))
+            } else {
+                mock.and(query_param("cursor", format!("cursor{}", i).as_str()))
+            };
+            mock.respond_with(ResponseTemplate::new(200).set_body_json(body))
+                .expect(1)
+                .mount(server)
+                .await;
+        }
+        if pages.is_empty() {
+            Mock::given(method("GET"))
+                .and(path("/domains/example.com/records"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "records": [],
+                    "meta": {"total": 0, "links": {"next": "", "prev": ""}},
+                })))
+                .mount(server)
+                .await;
+        }
+    }
+
+    async fn mock_patch(server: &MockServer, id: &str, expected: u64) {
+        Mock::given(method("PATCH"))
+            .and(path(format!("/domains/example.com/records/{}", id)))
+            .and(body_json(serde_json::json!({"data": NEW_IP})))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(expected)
+            .mount(server)
+            .await;
+    }
+
+    #[test]
+    fn test_subdomain() {
+        assert_eq!(
+            subdomain("example.com", "home.example.com").unwrap(),
+            "home"
+        );
+        assert_eq!(subdomain("example.com", "@").unwrap(), "");
+        let err = subdomain("example.com", "home.example.org").unwrap_err();
+        assert!(format!("{err}").contains("Vultr domain"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_list_follows_cursors() {
+        let server = MockServer::start().await;
+        mock_list(
+            &server,
+            vec![
+                mock_record("a", "www", "AAAA", "2001:db8::a"),
+                mock_record("b", "home", "A", "192.0.2.1"),
+                mock_record("c", "Home", "AAAA", "2001:db8::1"),
+                mock_record("d", "", "AAAA", "2001:db8::d"),
+                mock_record("e", "home", "AAAA", "2001:db8::5"),
+            ],
+        )
+        .await;
+
+        let records = mock_client(&server)
+            .get_records("example.com", "home.example.com", RecordType::Aaaa)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = records.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["c", "e"]);
+        assert_eq!(records[0].name, "home.example.com");
+        assert_eq!(records[0].content, "2001:db8::1");
+    }
+
+    #[tokio::test]
+    async fn test_mock_upsert_patches_existing() {
+        let server = MockServer::start().await;
+        mock_list(
+            &server,
+            vec![mock_record("a", "home", "AAAA", "2001:db8::1")],
+        )
+        .await;
+        mock_patch(&server, "a", 1).await;
+
+        let record = mock_client(&server)
+            .upsert_record(
+                "example.com",
+                "home.example.com",
+                RecordType::Aaaa,
+                NEW_IP,
+                options(MultiRecordPolicy::Error, false),
+            )
+            .await
+            .unwrap();
+        assert_eq!(record.id, "a");
+        assert!(!record.created);
+    }
+
+    #[tokio::test]
+    async fn test_mock_upsert_policies() {
+        let records = || {
+            vec![
+                mock_record("a", "home", "AAAA", "2001:db8::1"),
+                mock_record("b", "home", "AAAA", NEW_IP),
+                mock_record("c", "home", "AAAA", "2001:db8::3"),
+            ]
+        };
+
+        let server = MockServer::start().await;
+        mock_list(&server, records()).await;
+        let err = mock_client(&server)
+            .upsert_record(
+                "example.com",
+                "home.example.com",
+                RecordType::Aaaa,
+                NEW_IP,
+                options(MultiRecordPolicy::Error, false),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Multiple AAAA records"));
+
+        let server = MockServer::start().await;
+        mock_list(&server, records()).await;
+        mock_patch(&server, "a", 1).await;
+        mock_patch(&server, "c", 0).await;
+        mock_client(&server)
+            .upsert_record(
+                "example.com",
+                "home.example.com",
+                RecordType::Aaaa,
+                NEW_IP,
+                options(MultiRecordPolicy::UpdateFirst, false),
+            )
+            .await
+            .unwrap();
+
+        let server = MockServer::start().await;
+        mock_list(&server, records()).await;
+        mock_patch(&server, "a", 1).await;
+        mock_patch(&server, "b", 0).await;
+        mock_patch(&server, "c", 1).await;
+        mock_client(&server)
+            .upsert_record(
+                "example.com",
+                "home.example.com",
+                RecordType::Aaaa,
+                NEW_IP,
+                options(MultiRecordPolicy::UpdateAll, false),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_create_when_absent() {
+        let server = MockServer::start().await;
+        mock_list(&server, Vec::new()).await;
+        Mock::given(method("POST"))
+            .and(path("/domains/example.com/records"))
+            .and(body_json(serde_json::json!({
+                "name": "home",
+                "type": "AAAA",
+                "data": NEW_IP,
+                "ttl": VULTR_TTL_SECS,
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "record": mock_record("new", "home", "AAAA", NEW_IP),
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = mock_client(&server);
+
+        let err = client
+            .upsert_record(
+                "example.com",
+                "home.example.com",
+                RecordType::Aaaa,
+                NEW_IP,
+                options(MultiRecordPolicy::Error, false),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("record creation is disabled"));
+
+        let record = client
+            .upsert_record(
+                "example.com",
+                "home.example.com",
+                RecordType::Aaaa,
+                NEW_IP,
+                options(MultiRecordPolicy::Error, true),
+            )
+            .await
+            .unwrap();
+        assert_eq!(record.id, "new");
+        assert!(record.created);
+    }
+
+    #[tokio::test]
+    async fn test_mock_auth_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "error": "Invalid API token.",
+                "status": 401,
+            })))
+            .mount(&server)
+            .await;
+
+        let err = mock_client(&server)
+            .get_records("example.com", "home.example.com", RecordType::Aaaa)
+            .await
+            .unwrap_err();
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Auth);
+        assert!(format!("{err}").contains("Invalid API token."));
+    }
+}
@@ -0,0 +1,188 @@
+//! Webhook notifications for ipv6ddns
+//!
+//! This module sends a small JSON notification to a user-configured URL when
+//! the published address changes. Notifications are best-effort: failures are
+//! logged but never mark the sync as failed or trigger backoff.
+//!
+//! # Payload
+//!
+//! ```text
+//! {
+//!   "record": "example.com",
+//!   "old_ip": "2001:db8::1",
+//!   "new_ip": "2001:db8::2",
+//!   "timestamp": "2026-01-19T12:00:00Z"
+//! }
+//! ```
+
+use std::time::Duration;
+
+use anyhow::{bail, Context as _, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::constants::CLOUDFLARE_USER_AGENT;
+
+//==============================================================================
+// Types
+//==============================================================================
+
+/// JSON body posted to the webhook URL on address change
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    /// DNS record name
+    pub record: String,
+    /// Previously published address (null on first sync)
+    pub old_ip: Option<String>,
+    /// Newly published address
+    pub new_ip: String,
+    /// Time of the change (UTC)
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Sends address-change notifications to a webhook URL
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    /// Target URL
+    url: String,
+    /// HTTP client for making requests
+    client: reqwest::Client,
+}
+
+//==============================================================================
+// Implementation
+//==============================================================================
+
+impl WebhookNotifier {
+    /// Creates a new webhook notifier
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The webhook URL (http or https)
+    /// * `timeout` - HTTP request timeout duration
+    /// * `connect_timeout` - Time allowed to establish a connection
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the notifier or an error if client creation fails
+    pub fn new(url: &str, timeout: Duration, connect_timeout: Duration) -> Result<Self> {
+        validate_webhook_url(url)?;
+        let client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .user_agent(CLOUDFLARE_USER_AGENT)
+            .build()
+            .context("build webhook client")?;
+
+        Ok(Self {
+            url: url.to_string(),
+            client,
+        })
+    }
+
+    /// Posts the payload to the webhook URL
+    ///
+    /// A network error is retried once; HTTP error responses are not retried.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the webhook accepted the notification, otherwise an error
+    pub async fn send(&self, payload: &WebhookPayload) -> Result<()> {
+        let mut attempt = 0;
+        let resp = loop {
+            attempt += 1;
+            match self.client.post(&self.url).json(payload).send().await {
+                Ok(resp) => break resp,
+                Err(e) if attempt < 2 => {
+                    debug!("Webhook request failed ({}), retrying once", e);
+                }
+                Err(e) => return Err(e).context("Webhook request failed"),
+            }
+        };
+
+        let status = resp.status();
+        if !status.is_success() {
+            bail!("Webhook returned HTTP {}", status);
+        }
+        Ok(())
+    }
+
+    /// Sends the notification in the background, logging any failure
+    pub fn spawn_send(&self, payload: WebhookPayload) {
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            match notifier.send(&payload).await {
+                Ok(()) => debug!("Webhook notification sent"),
+                Err(e) => warn!("Webhook notification failed: {:#}", e),
+            }
+        });
+    }
+}
+
+/// Validates that a webhook URL is an absolute http(s) URL
+///
+/// # Arguments
+///
+/// * `url` - The URL to validate
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the URL is usable, otherwise an error
+pub fn validate_webhook_url(url: &str) -> Result<()> {
+    let parsed =
+        reqwest::Url::parse(url).with_context(|| format!("Invalid webhook_url: {}", url))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        bail!(
+            "webhook_url must use http or https, got: {}",
+            parsed.scheme()
+        );
+    }
+    Ok(())
+}
+
+//==============================================================================
+// Tests
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_payload_serialization() {
+        let payload = WebhookPayload {
+            record: "example.com".to_string(),
+            old_ip: Some("2001:db8::1".to_string()),
+            new_ip: "2001:db8::2".to_string(),
+            timestamp: Utc::now(),
+        };
+
+        let json = serde_json::to_string(&payload).unwrap();
+        assert!(json.contains("\"record\":\"example.com\""));
+        assert!(json.contains("\"old_ip\":\"2001:db8::1\""));
+        assert!(json.contains("\"new_ip\":\"2001:db8::2\""));
+        assert!(json.contains("\"timestamp\":"));
+    }
+
+    #[test]
+    fn test_webhook_payload_first_sync() {
+        let payload = WebhookPayload {
+            record: "example.com".to_string(),
+            old_ip: None,
+            new_ip: "2001:db8::2".to_string(),
+            timestamp: Utc::now(),
+        };
+
+        let json = serde_json::to_string(&payload).unwrap();
+        assert!(json.contains("\"old_ip\":null"));
+    }
+
+    #[test]
+    fn test_validate_webhook_url() {
+        assert!(validate_webhook_url("https://hooks.example.com/ddns").is_ok());
+        assert!(validate_webhook_url("http://127.0.0.1:8080/notify").is_ok());
+        assert!(validate_webhook_url("ftp://example.com/").is_err());
+        assert!(validate_webhook_url("not a url").is_err());
+    }
+}